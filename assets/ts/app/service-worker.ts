@@ -13,12 +13,18 @@ interface BroadcastMessage {
     type: string;
     error?: string;
     changedCount?: number;
+    changedUrls?: string[];
 }
 
 // Bump cache name when we make major asset pipeline changes.
 // This forces a clean slate for users stuck with stale cached JS files.
-const CACHE_NAME = 'koshelf-cache-v2';
-const MANIFEST_URL = '/cache-manifest.json';
+const CACHE_PREFIX = 'koshelf-cache-v2';
+
+// URL path the site is served below: reader profile sites each live below `/u/<profile>` with
+// their own worker, so caches and manifest paths are kept per site root.
+const SITE_ROOT = sw.location.pathname.replace(/\/service-worker\.js$/, '');
+const CACHE_NAME = SITE_ROOT ? `${CACHE_PREFIX}:${SITE_ROOT}` : CACHE_PREFIX;
+const MANIFEST_URL = `${SITE_ROOT}/cache-manifest.json`;
 const BATCH_SIZE = 10;
 
 const SKIP_CACHE_PATTERNS = [
@@ -62,8 +68,9 @@ function shouldSkipCache(url: string): boolean {
     return SKIP_CACHE_PATTERNS.some((pattern) => pathname.endsWith(pattern));
 }
 
+// Manifest paths are relative to the site root
 function toFullUrl(urlPath: string): string {
-    return new URL(urlPath, sw.location.origin).href;
+    return new URL(SITE_ROOT + urlPath, sw.location.origin).href;
 }
 
 // Normalize URL for cache matching - handles /foo/index.html -> /foo/ mapping
@@ -179,7 +186,11 @@ async function updateChangedFiles(
     await storeManifest(newManifest);
 
     if (changedUrls.length > 0) {
-        broadcast({ type: 'CACHE_UPDATED', changedCount: changedUrls.length });
+        broadcast({
+            type: 'CACHE_UPDATED',
+            changedCount: changedUrls.length,
+            changedUrls: changedUrls.map((url) => SITE_ROOT + url),
+        });
     }
 
     console.log('[SW] Cache update complete');
@@ -207,10 +218,14 @@ sw.addEventListener('activate', (event) => {
     console.log('[SW] Activating...');
     event.waitUntil(
         (async () => {
-            // Clean up old cache versions
+            // Clean up old cache versions of this site root, leaving other profiles' caches alone
             const cacheNames = await caches.keys();
+            const isSameRoot = (name: string) =>
+                SITE_ROOT ? name.endsWith(`:${SITE_ROOT}`) : !name.includes(':');
             await Promise.all(
-                cacheNames.filter((name) => name !== CACHE_NAME).map((name) => caches.delete(name)),
+                cacheNames
+                    .filter((name) => name !== CACHE_NAME && isSameRoot(name))
+                    .map((name) => caches.delete(name)),
             );
             await sw.clients.claim();
         })(),
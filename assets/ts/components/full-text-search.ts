@@ -0,0 +1,200 @@
+// Full-text search over highlights, notes, review notes, subjects and descriptions.
+// Reads the index shards written to /assets/json/search/ and mirrors src/search.rs:
+// the same tokenizer, shard keys, ranking and snippets as the `/api/v1/search` endpoint.
+
+import { siteUrl } from '../shared/site-root.js';
+
+export type SearchField = 'highlight' | 'note' | 'review_note' | 'subject' | 'description';
+
+/** Translation keys of the field labels. */
+export const FIELD_LABEL_KEYS: Record<SearchField, string> = {
+    highlight: 'search-field.highlight',
+    note: 'search-field.note',
+    review_note: 'search-field.review-note',
+    subject: 'search-field.subject',
+    description: 'search-field.description',
+};
+
+export interface SearchDocument {
+    item_id: string;
+    title: string;
+    field: SearchField;
+    /** Detail page below the site root, with the anchor of the highlight or section */
+    href: string;
+    page?: number;
+    text: string;
+}
+
+export interface SearchHit extends SearchDocument {
+    snippet: string;
+}
+
+interface SearchManifest {
+    min_term_length: number;
+    documents: number;
+    /** Documents are stored in rank order, in chunks of this size */
+    documents_per_chunk: number;
+    shards: string[];
+}
+
+type SearchShard = Record<string, number[]>;
+
+const SNIPPET_BEFORE = 60;
+const SNIPPET_LENGTH = 180;
+
+const NON_WORD = /[^\p{Alphabetic}\p{N}]+/u;
+const WORD_CHAR = /[\p{Alphabetic}\p{N}]/u;
+
+/** Lowercase words of `text` with at least `minLength` characters. */
+export function tokenize(text: string, minLength = 2): string[] {
+    return text
+        .split(NON_WORD)
+        .filter((word) => [...word].length >= minLength)
+        .map((word) => word.toLowerCase());
+}
+
+/** Shard of a term: its first ASCII letter/digit, otherwise `u` + hex code point. */
+function shardKey(term: string): string {
+    const first = [...term][0] ?? '';
+    if (/^[a-z0-9]$/i.test(first)) return first;
+    return 'u' + (first.codePointAt(0) ?? 0).toString(16);
+}
+
+/** Excerpt of `text` around the first word starting with one of `terms`. */
+export function snippet(text: string, terms: string[]): string {
+    const chars = [...text];
+    const lower = chars.map((c) => [...c.toLowerCase()][0] ?? c);
+    const termChars = terms.map((term) => [...term]);
+
+    const isWordStart = (i: number): boolean => i === 0 || !WORD_CHAR.test(lower[i - 1]);
+    const matchesAt = (i: number): boolean =>
+        termChars.some((term) => term.every((c, j) => lower[i + j] === c));
+
+    let firstMatch = 0;
+    for (let i = 0; i < lower.length; i++) {
+        if (isWordStart(i) && matchesAt(i)) {
+            firstMatch = i;
+            break;
+        }
+    }
+
+    let start = Math.max(0, firstMatch - SNIPPET_BEFORE);
+    // Start at a word boundary unless that would drop the whole context
+    while (start > 0 && start < firstMatch && !isWordStart(start)) {
+        start++;
+    }
+    const end = Math.min(chars.length, start + SNIPPET_LENGTH);
+
+    let out = (start > 0 ? '…' : '') + chars.slice(start, end).join('');
+    out = out.trimEnd();
+    return end < chars.length ? out + '…' : out;
+}
+
+function escapeRegExp(value: string): string {
+    return value.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+}
+
+/** Fill `element` with `text`, marking the words that start with one of `terms`. */
+export function renderMarked(element: HTMLElement, text: string, terms: string[]): void {
+    element.textContent = '';
+    if (terms.length === 0) {
+        element.textContent = text;
+        return;
+    }
+
+    const pattern = new RegExp(
+        `(^|[^\\p{Alphabetic}\\p{N}])(${terms.map(escapeRegExp).join('|')})`,
+        'giu',
+    );
+    let last = 0;
+    for (const match of text.matchAll(pattern)) {
+        const start = (match.index ?? 0) + match[1].length;
+        element.append(text.slice(last, start));
+        const mark = document.createElement('mark');
+        mark.className = 'bg-primary-200/70 dark:bg-primary-500/40 text-inherit rounded px-0.5';
+        mark.textContent = match[2];
+        element.append(mark);
+        last = start + match[2].length;
+    }
+    element.append(text.slice(last));
+}
+
+export class FullTextSearch {
+    private manifest: Promise<SearchManifest | null> | null = null;
+    private chunks = new Map<number, Promise<SearchDocument[]>>();
+    private shards = new Map<string, Promise<SearchShard>>();
+
+    /** Terms of `query` as the index sees them. */
+    async terms(query: string): Promise<string[]> {
+        const manifest = await this.loadManifest();
+        return manifest ? tokenize(query, manifest.min_term_length) : [];
+    }
+
+    /** Documents matching every term of `query`, each term matching as a word prefix. */
+    async search(query: string, limit = 20): Promise<SearchHit[]> {
+        const manifest = await this.loadManifest();
+        if (!manifest) return [];
+        const terms = tokenize(query, manifest.min_term_length);
+        if (terms.length === 0) return [];
+
+        let matches: Set<number> | null = null;
+        for (const term of terms) {
+            const key = shardKey(term);
+            const shard = manifest.shards.includes(key) ? await this.loadShard(key) : {};
+            const docs = new Set<number>();
+            for (const [candidate, postings] of Object.entries(shard)) {
+                if (candidate.startsWith(term)) {
+                    postings.forEach((doc) => docs.add(doc));
+                }
+            }
+            const previous: Set<number> | null = matches;
+            matches = previous ? new Set([...previous].filter((doc) => docs.has(doc))) : docs;
+            if (matches.size === 0) return [];
+        }
+
+        // Document indices are in rank order: by field (highlights first), then library order
+        const hits = [...(matches ?? [])].sort((a, b) => a - b).slice(0, limit);
+        const size = manifest.documents_per_chunk;
+        const chunks = new Map<number, SearchDocument[]>();
+        for (const chunk of new Set(hits.map((i) => Math.floor(i / size)))) {
+            chunks.set(chunk, await this.loadChunk(chunk));
+        }
+        return hits
+            .map((i) => chunks.get(Math.floor(i / size))?.[i % size])
+            .filter((doc): doc is SearchDocument => doc !== undefined)
+            .map((doc) => ({ ...doc, snippet: snippet(doc.text, terms) }));
+    }
+
+    private loadManifest(): Promise<SearchManifest | null> {
+        if (!this.manifest) {
+            this.manifest = fetchJson<SearchManifest>('/assets/json/search/index.json').catch(
+                () => null,
+            );
+        }
+        return this.manifest;
+    }
+
+    private loadChunk(chunk: number): Promise<SearchDocument[]> {
+        let documents = this.chunks.get(chunk);
+        if (!documents) {
+            documents = fetchJson<SearchDocument[]>(`/assets/json/search/documents/${chunk}.json`);
+            this.chunks.set(chunk, documents);
+        }
+        return documents;
+    }
+
+    private loadShard(key: string): Promise<SearchShard> {
+        let shard = this.shards.get(key);
+        if (!shard) {
+            shard = fetchJson<SearchShard>(`/assets/json/search/terms/${key}.json`);
+            this.shards.set(key, shard);
+        }
+        return shard;
+    }
+}
+
+async function fetchJson<T>(path: string): Promise<T> {
+    const res = await fetch(siteUrl(path));
+    if (!res.ok) throw new Error(`Failed to load ${path}: ${res.status}`);
+    return (await res.json()) as T;
+}
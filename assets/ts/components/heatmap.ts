@@ -5,6 +5,7 @@
 
 import { translation } from '../shared/i18n.js';
 import { TooltipManager } from './tooltip-manager.js';
+import { siteUrl } from '../shared/site-root.js';
 
 interface ActivityEntry {
     date: string;
@@ -28,7 +29,7 @@ class ActivityHeatmap {
     private currentYear: number | null = null;
     private isInitialized = false;
     private resizeObserver: ResizeObserver | null = null;
-    private basePath = '/assets/json/statistics';
+    private basePath = siteUrl('/assets/json/statistics');
 
     // Initialize the heatmap module
     async init(): Promise<void> {
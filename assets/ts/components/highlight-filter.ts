@@ -0,0 +1,63 @@
+// Highlight Filter Module
+// Filters highlight cards on the item detail page by color and drawing style
+
+type FilterGroup = 'color' | 'drawer';
+
+const ACTIVE_CLASSES = [
+    'bg-primary-500/15',
+    'border-primary-500/60',
+    'text-primary-700',
+    'dark:text-primary-300',
+];
+
+export class HighlightFilter {
+    private chips: HTMLButtonElement[];
+    private cards: HTMLElement[];
+    private active: Record<FilterGroup, string | null> = { color: null, drawer: null };
+
+    constructor(container: HTMLElement) {
+        this.chips = Array.from(
+            container.querySelectorAll<HTMLButtonElement>('.highlight-filter-chip'),
+        );
+        this.cards = Array.from(document.querySelectorAll<HTMLElement>('[data-highlight-card]'));
+
+        this.chips.forEach((chip) => {
+            chip.addEventListener('click', () => this.select(chip));
+        });
+        this.render();
+    }
+
+    private select(chip: HTMLButtonElement): void {
+        const group = chip.dataset.filterGroup;
+        const value = chip.dataset.filterValue ?? null;
+
+        if (group === 'color' || group === 'drawer') {
+            // Clicking the active chip again clears that group
+            this.active[group] = this.active[group] === value ? null : value;
+        } else {
+            this.active = { color: null, drawer: null };
+        }
+        this.render();
+    }
+
+    private render(): void {
+        const noFilter = this.active.color === null && this.active.drawer === null;
+
+        this.chips.forEach((chip) => {
+            const group = chip.dataset.filterGroup;
+            const pressed =
+                group === 'color' || group === 'drawer'
+                    ? this.active[group] === chip.dataset.filterValue
+                    : noFilter;
+            chip.setAttribute('aria-pressed', String(pressed));
+            ACTIVE_CLASSES.forEach((cls) => chip.classList.toggle(cls, pressed));
+        });
+
+        this.cards.forEach((card) => {
+            const visible =
+                (this.active.color === null || card.dataset.color === this.active.color) &&
+                (this.active.drawer === null || card.dataset.drawer === this.active.drawer);
+            card.classList.toggle('hidden', !visible);
+        });
+    }
+}
@@ -7,6 +7,7 @@ import type { Calendar } from '@event-calendar/core';
 
 import { showModal, hideModal, setupModalCloseHandlers } from '../components/modal-utils.js';
 import { translation } from '../shared/i18n.js';
+import { siteUrl } from '../shared/site-root.js';
 
 type ContentFilter = 'all' | 'book' | 'comic';
 type ContentType = 'book' | 'comic';
@@ -30,6 +31,7 @@ interface EventExtendedProps extends Record<string, unknown> {
     end?: string;
     total_read_time: number;
     total_pages_read: number;
+    session_count?: number;
     book_title: string;
     authors: string[];
     item_path?: string;
@@ -45,6 +47,7 @@ interface RawEvent {
     end?: string;
     total_read_time: number;
     total_pages_read: number;
+    session_count?: number;
 }
 
 interface BookInfo {
@@ -145,7 +148,7 @@ if (document.readyState === 'loading') {
 // Load the list of available months
 async function loadAvailableMonths(): Promise<void> {
     try {
-        const response = await fetch('/assets/json/calendar/available_months.json');
+        const response = await fetch(siteUrl('/assets/json/calendar/available_months.json'));
         if (response.ok) {
             availableMonths = (await response.json()) as string[];
         } else {
@@ -215,7 +218,7 @@ async function fetchMonthData(targetMonth: string | null): Promise<MonthData> {
             return { events: [], books: {} }; // Return empty data instead of null
         }
 
-        const response = await fetch(`/assets/json/calendar/${targetMonth}.json`);
+        const response = await fetch(siteUrl(`/assets/json/calendar/${targetMonth}.json`));
         if (!response.ok) {
             console.error(`Failed to load calendar data for ${targetMonth}:`, response.status);
             return { events: [], books: {} };
@@ -466,6 +469,7 @@ function showEventModal(_title: string, event: EventExtendedProps): void {
     const authorEl = document.getElementById('modalAuthor');
     const readTimeEl = document.getElementById('modalReadTime');
     const pagesReadEl = document.getElementById('modalPagesRead');
+    const sessionsEl = document.getElementById('modalSessions');
 
     if (authorEl) {
         authorEl.textContent = event.authors?.length
@@ -478,6 +482,11 @@ function showEventModal(_title: string, event: EventExtendedProps): void {
     if (pagesReadEl) {
         pagesReadEl.textContent = String(event.total_pages_read);
     }
+    if (sessionsEl) {
+        const sessionCount = event.session_count ?? 0;
+        sessionsEl.textContent = translation.get('units-sessions', sessionCount);
+        sessionsEl.classList.toggle('hidden', sessionCount === 0);
+    }
 
     // View-item button setup
     const itemPath = event.item_path;
@@ -2,10 +2,41 @@
 // Uses the SectionToggle module for handling collapsible sections
 
 import { SectionToggle } from '../components/section-toggle.js';
+import { HighlightFilter } from '../components/highlight-filter.js';
+import { translation } from '../shared/i18n.js';
+
+/**
+ * Reveal the highlight, note or section a search result links to (`#annotation-3`),
+ * expanding its section if it is collapsed.
+ */
+async function revealAnchor(sectionToggle: SectionToggle): Promise<void> {
+    const id = decodeURIComponent(window.location.hash.slice(1));
+    const target = id ? document.getElementById(id) : null;
+    if (!target) return;
+
+    // Sections register once translations are loaded
+    await translation.init();
+    const sectionName = target.closest<HTMLElement>('section[data-name]')?.dataset.name;
+    if (sectionName && !sectionToggle.isVisible(sectionName)) {
+        sectionToggle.show(sectionName, { persist: false });
+    }
+
+    target.scrollIntoView({ block: 'start' });
+    target.classList.add('ring-2', 'ring-primary-500');
+    setTimeout(() => target.classList.remove('ring-2', 'ring-primary-500'), 2000);
+}
 
 document.addEventListener('DOMContentLoaded', () => {
     // Initialize section toggles using the module
-    new SectionToggle();
+    const sectionToggle = new SectionToggle();
+    void revealAnchor(sectionToggle);
+    window.addEventListener('hashchange', () => void revealAnchor(sectionToggle));
+
+    // Color/style filter for highlights (only rendered when highlights differ)
+    const highlightFilters = document.getElementById('highlightFilters');
+    if (highlightFilters) {
+        new HighlightFilter(highlightFilters);
+    }
 
     // Share dropdown toggle logic
     const shareDropdownButton = document.getElementById('shareDropdownButton');
@@ -4,6 +4,13 @@ import { SectionToggle } from '../components/section-toggle.js';
 import { translation } from '../shared/i18n.js';
 import { initBookCardTilt } from '../components/tilt-effect.js';
 import { StorageManager } from '../shared/storage-manager.js';
+import { siteUrl } from '../shared/site-root.js';
+import {
+    FIELD_LABEL_KEYS,
+    FullTextSearch,
+    renderMarked,
+    type SearchHit,
+} from '../components/full-text-search.js';
 
 const FILTER_ARIA_MAP = {
     all: 'filter.all-aria',
@@ -125,6 +132,7 @@ document.addEventListener('DOMContentLoaded', async () => {
         }
         lastSearchTerm = searchTerm;
         filterBooks(searchTerm, currentFilter);
+        scheduleFullTextSearch(searchTerm);
         // After filtering, expand/collapse sections based on visible books
         if (searchTerm) {
             // For each section, check if it has visible books
@@ -143,6 +151,73 @@ document.addEventListener('DOMContentLoaded', async () => {
         }
     }
 
+    // Full-text search over highlights, notes and descriptions, below the title matches
+    const fullTextSearch = new FullTextSearch();
+    const fullTextResults = document.getElementById('fullTextResults');
+    const fullTextList = document.getElementById('fullTextResultsList');
+    const fullTextCount = document.getElementById('fullTextCount');
+    let fullTextTimer: number | undefined;
+    let fullTextRequest = 0;
+
+    function scheduleFullTextSearch(searchTerm: string): void {
+        window.clearTimeout(fullTextTimer);
+        const request = ++fullTextRequest;
+        if (searchTerm.length < 2) {
+            renderFullTextResults([], []);
+            return;
+        }
+        fullTextTimer = window.setTimeout(async () => {
+            try {
+                const [hits, terms] = await Promise.all([
+                    fullTextSearch.search(searchTerm),
+                    fullTextSearch.terms(searchTerm),
+                ]);
+                // Ignore results of queries that were superseded while loading
+                if (request === fullTextRequest) renderFullTextResults(hits, terms);
+            } catch (error) {
+                console.error('Full-text search failed:', error);
+            }
+        }, 200);
+    }
+
+    function renderFullTextResults(hits: SearchHit[], terms: string[]): void {
+        if (!fullTextResults || !fullTextList) return;
+        fullTextList.replaceChildren(...hits.map((hit) => createFullTextResult(hit, terms)));
+        fullTextResults.classList.toggle('hidden', hits.length === 0);
+        if (fullTextCount) fullTextCount.textContent = String(hits.length);
+        if (hits.length > 0) {
+            document.getElementById('dynamicEmptyState')?.classList.add('hidden');
+        }
+    }
+
+    function createFullTextResult(hit: SearchHit, terms: string[]): HTMLElement {
+        const link = document.createElement('a');
+        link.href = siteUrl(hit.href);
+        link.className =
+            'block bg-white dark:bg-dark-850/50 border border-gray-200/70 dark:border-dark-700/70 rounded-lg p-4 hover:border-primary-500 dark:hover:border-primary-500 transition-colors';
+
+        const header = document.createElement('div');
+        header.className = 'flex items-center justify-between gap-3 text-sm mb-1';
+        const title = document.createElement('span');
+        title.className = 'font-semibold text-gray-900 dark:text-white truncate';
+        title.textContent = hit.title;
+        const meta = document.createElement('span');
+        meta.className = 'shrink-0 text-gray-500 dark:text-dark-400';
+        const label = translation.get(FIELD_LABEL_KEYS[hit.field]);
+        meta.textContent =
+            hit.page !== undefined
+                ? `${label} · ${translation.get('page-number', hit.page)}`
+                : label;
+        header.append(title, meta);
+
+        const text = document.createElement('p');
+        text.className = 'text-sm text-gray-700 dark:text-dark-300 leading-relaxed';
+        renderMarked(text, hit.snippet, terms);
+
+        link.append(header, text);
+        return link;
+    }
+
     // Attach input listeners to both inputs (if they exist)
     [searchInput, mobileSearchInput].forEach((inp) => {
         if (!inp) return;
@@ -260,7 +335,10 @@ document.addEventListener('DOMContentLoaded', async () => {
     function updateEmptyState(visibleCount: number): void {
         const dynamicEmptyState = document.getElementById('dynamicEmptyState');
 
-        if (visibleCount === 0) {
+        const hasFullTextResults =
+            document.getElementById('fullTextResults')?.classList.contains('hidden') === false;
+
+        if (visibleCount === 0 && !hasFullTextResults) {
             // Show the dynamic empty state (for search/filter results)
             dynamicEmptyState?.classList.remove('hidden');
         } else {
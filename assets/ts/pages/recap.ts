@@ -2,6 +2,7 @@ import { showModal, setupModalCloseHandlers } from '../components/modal-utils.js
 import { translation } from '../shared/i18n.js';
 import { initRecapCoverTilt } from '../components/tilt-effect.js';
 import { StorageManager } from '../shared/storage-manager.js';
+import { siteUrl } from '../shared/site-root.js';
 
 // Recap interactions: year dropdown + navigation
 document.addEventListener('DOMContentLoaded', async () => {
@@ -27,7 +28,7 @@ document.addEventListener('DOMContentLoaded', async () => {
             opt.addEventListener('click', () => {
                 const y = opt.getAttribute('data-year');
                 if (y) {
-                    window.location.href = `/recap/${y}/${scopePath}`;
+                    window.location.href = siteUrl(`/recap/${y}/${scopePath}`);
                 }
             });
         });
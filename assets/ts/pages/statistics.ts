@@ -8,6 +8,7 @@ import { SectionToggle } from '../components/section-toggle.js';
 // The statistics page also includes the reading heatmap.
 // Importing it here ensures it is bundled and initialized with the page.
 import '../components/heatmap.js';
+import { siteUrl } from '../shared/site-root.js';
 
 interface WeekData {
     read_time: number;
@@ -22,7 +23,7 @@ class StatisticsManager {
     private loadingIndicator: HTMLElement | null = null;
     private weekStats: HTMLElement | null = null;
     private isInitialized = false;
-    private statsJsonBasePath = '/assets/json/statistics';
+    private statsJsonBasePath = siteUrl('/assets/json/statistics');
 
     // Initialize the statistics module
     init(): void {
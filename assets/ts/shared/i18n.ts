@@ -5,6 +5,7 @@
 
 import { FluentBundle, FluentResource } from '@fluent/bundle';
 import type { FluentVariable } from '@fluent/bundle';
+import { siteUrl } from './site-root.js';
 
 let bundle: FluentBundle | null = null;
 let loadPromise: Promise<void> | null = null;
@@ -12,7 +13,7 @@ let loadPromise: Promise<void> | null = null;
 async function load(): Promise<void> {
     if (bundle) return;
     try {
-        const res = await fetch('/assets/json/locales.json');
+        const res = await fetch(siteUrl('/assets/json/locales.json'));
         const data = (await res.json()) as { language: string; resources: string[] };
 
         // Initialize bundle with the language from the server
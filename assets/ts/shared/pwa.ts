@@ -1,5 +1,6 @@
 // PWA utilities - service worker registration and update notifications
 import { StorageManager } from './storage-manager.js';
+import { siteUrl } from './site-root.js';
 
 const KEYS = StorageManager.KEYS;
 const RECOVERY_CONFIG = { maxRetries: 3, cooldownMs: 60000 };
@@ -14,6 +15,7 @@ let lastNotifiedVersion: string | null = null; // Track which version we last tr
 interface ServiceWorkerMessage {
     type: string;
     error?: string;
+    changedUrls?: string[];
 }
 
 if ('serviceWorker' in navigator) {
@@ -28,7 +30,7 @@ function parseStoredInt(value: string | null, fallback: number): number {
 
 async function initializeServiceWorker(): Promise<void> {
     try {
-        await navigator.serviceWorker.register('/service-worker.js');
+        await navigator.serviceWorker.register(siteUrl('/service-worker.js'));
     } catch (error) {
         console.error('[PWA] Service Worker registration failed:', error);
         recoveryReload();
@@ -44,7 +46,7 @@ async function initializeServiceWorker(): Promise<void> {
 
 function handleServiceWorkerMessage({ data }: MessageEvent<ServiceWorkerMessage>): void {
     if (data?.type === 'CACHE_UPDATED') {
-        showUpdateNotification();
+        handleCacheUpdated(data.changedUrls);
     } else if (data?.type === 'CRITICAL_ERROR') {
         console.error('[PWA] Service Worker critical error:', data.error);
         recoveryReload();
@@ -101,7 +103,7 @@ async function startVersionMonitoring(): Promise<void> {
     if (version) handleVersionChange(version);
 
     if (SERVER_MODE === 'internal') {
-        startLongPolling();
+        startEventStream();
     } else {
         startIntervalPolling();
     }
@@ -109,7 +111,7 @@ async function startVersionMonitoring(): Promise<void> {
 
 async function fetchVersion(): Promise<string | null> {
     try {
-        const response = await fetch('/version.txt', { cache: 'no-store' });
+        const response = await fetch(siteUrl('/version.txt'), { cache: 'no-store' });
         return response.ok ? (await response.text()).trim() : null;
     } catch {
         return null;
@@ -147,20 +149,21 @@ function requestCacheUpdate(): void {
     }
 }
 
-async function startLongPolling(): Promise<void> {
-    while (true) {
-        try {
-            const response = await fetch('/api/events/version', { cache: 'no-store' });
-
-            if (response.status === 200) {
-                handleVersionChange((await response.text()).trim());
-            }
-            // 204 = timeout, loop continues automatically
-        } catch {
-            // Connection lost - wait for server to come back
-            await waitForServerRecovery();
+// Server-Sent Events from the internal server: one `version` event on connect and after every rebuild
+function startEventStream(): void {
+    const source = new EventSource('/api/events/version');
+
+    source.addEventListener('version', (event) => {
+        handleVersionChange((event as MessageEvent<string>).data.trim());
+    });
+
+    source.onerror = () => {
+        // EventSource retries dropped connections by itself; it only gives up when the
+        // server answers with an error, so wait until the site is reachable again.
+        if (source.readyState === EventSource.CLOSED) {
+            waitForServerRecovery().then(startEventStream);
         }
-    }
+    };
 }
 
 async function waitForServerRecovery(): Promise<void> {
@@ -181,6 +184,24 @@ function startIntervalPolling(): void {
     }, POLL_INTERVAL_MS);
 }
 
+// Reload only if the page being viewed is one of the files that changed in the new build
+function handleCacheUpdated(changedUrls: string[] | undefined): void {
+    if (!changedUrls) {
+        showUpdateNotification();
+        return;
+    }
+
+    let currentPath = location.pathname;
+    if (currentPath.endsWith('/index.html')) {
+        currentPath = currentPath.slice(0, -10);
+    }
+
+    if (changedUrls.includes(currentPath)) {
+        console.log('[PWA] Current page changed, reloading');
+        location.reload();
+    }
+}
+
 function showUpdateNotification(): void {
     document.body.classList.add('update-available');
 }
@@ -0,0 +1,10 @@
+/**
+ * URL path the site is served below (empty at the web root).
+ * Reader profile sites live below `/u/<profile>`; the templates put the root on `<html>`.
+ */
+export const SITE_ROOT: string = document.documentElement.dataset.siteRoot ?? '';
+
+/** Absolute URL of a site path such as `/assets/json/locales.json`. */
+export function siteUrl(path: string): string {
+    return SITE_ROOT + path;
+}
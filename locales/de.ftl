@@ -21,6 +21,8 @@
 # -----------------------------------
 books = Bücher
 comics = Comics
+devices = Geräte
+choose-profile = Wer liest?
 statistics = Statistiken
 calendar = Kalender
 recap = Rückblick
@@ -79,6 +81,26 @@ unknown-author = Unbekannter Autor
 by = von
 book-overview = Buchübersicht
 comic-overview = Comicübersicht
+# OPDS catalog feed titles
+opds =
+    .all = Alle Titel
+    .authors = Autoren
+    .search = Suche
+# Autoren-, Reihen- und Genreseiten
+browse = Stöbern
+    .authors = Autoren
+    .series = Reihen
+    .subjects = Genres
+    .languages = Sprachen
+    .items = { $count ->
+        [one] { $count } Titel
+       *[other] { $count } Titel
+    }
+    .completions = { $count ->
+        [one] { $count } Lesung abgeschlossen
+       *[other] { $count } Lesungen abgeschlossen
+    }
+    .series-progress = Fortschritt der Reihe
 
 # -----------------------------------
 #            Book Details
@@ -89,6 +111,8 @@ series = Reihe
 genres = Genres
 language = Sprache
 book-identifiers = Buch-Identifikatoren
+stats-only-item = Nur in deiner Lesestatistik: Die Datei ist nicht mehr in der Bibliothek
+page-moved = Diese Seite ist umgezogen:
 my-review = Meine Rezension
 my-note = Meine Notiz
 highlights = Markierungen
@@ -106,6 +130,25 @@ page = Seite
 page-bookmark = Seiten-Lesezeichen
 bookmark-anchor = Lesezeichen-Anker
 highlights-quotes = Markierungen & Zitate
+highlight-style =
+    .lighten = Markierung
+    .underscore = Unterstrichen
+    .strikeout = Durchgestrichen
+    .invert = Invertiert
+highlight-color =
+    .red = Rot
+    .orange = Orange
+    .yellow = Gelb
+    .green = Grün
+    .olive = Oliv
+    .cyan = Cyan
+    .blue = Blau
+    .purple = Lila
+    .gray = Grau
+highlight-filter =
+    .aria-label = Markierungen nach Farbe und Stil filtern
+    .all = Alle
+highlight-edited = Bearbeitet
 additional-information = Zusätzliche Informationen
 reading-progress = Lesefortschritt
 page-number = Seite { $count }
@@ -151,6 +194,19 @@ average-pages-day = Ø Seiten/Tag
 most-pages-in-day = Meiste Seiten an einem Tag
 longest-daily-reading = Längste tägliche Lesezeit
 reading-completions = Abgeschlossene Lesungen
+reading-goals = Leseziele
+goal =
+    .yearly = Jahresziel
+    .monthly = Monatsziel
+    .books = Beendete Bücher
+    .hours = Gelesene Stunden
+    .pages = Gelesene Seiten
+    .ahead = Vor dem Zeitplan
+    .behind = Hinter dem Zeitplan
+    .reached = Ziel erreicht
+    .missed = Ziel verfehlt
+    .expected = { $count } bis heute erwartet
+    .projected = Auf Kurs für { $count }
 statistics-from-koreader = Statistiken aus KoReader Lese-Sitzungen
 reading-time = Lesezeit
 pages-read = Gelesene Seiten
@@ -213,6 +269,13 @@ next-month =
     .aria-label = Nächster Monat
 search =
     .aria-label = Suchen
+    .full-text = In Markierungen & Notizen
+search-field =
+    .highlight = Markierung
+    .note = Notiz
+    .review-note = Rezensionsnotiz
+    .subject = Thema
+    .description = Beschreibung
 close-search =
     .aria-label = Suche schließen
 close = Schließen
@@ -249,12 +312,47 @@ november = November
 december = Dezember
     .short = Dez
 
-# Weekday abbreviations (only Mon/Thu/Sun for GitHub-style heatmap visualization)
+# Weekday abbreviations (heatmap and reading patterns)
 weekday =
     .mon = Mo
+    .tue = Di
+    .wed = Mi
     .thu = Do
+    .fri = Fr
+    .sat = Sa
     .sun = So
 
+# Reading patterns by hour of the day and weekday (statistics page)
+reading-patterns = Lesemuster
+    .punch-card = Lesezeit nach Wochentag und Uhrzeit
+    .session-length = Durchschnittliche Sitzung nach Uhrzeit
+    .speed = Lesegeschwindigkeit nach Uhrzeit
+    .peak-hour = Aktivste Stunde
+    .busiest-day = Aktivster Tag
+    .fastest-hour = Schnellste Stunde
+    .rhythm = Leserhythmus
+chronotype =
+    .night-owl = Nachteule
+    .early-bird = Frühaufsteher
+    .day-reader = Tagleser
+    .night-share = { $count } % nachts gelesen
+    .morning-share = { $count } % frühmorgens gelesen
+
+# Per-book reading timeline (item detail page)
+reading-timeline = Leseverlauf
+    .progress = Fortschritt im Zeitverlauf
+    .speed = Seiten pro Stunde je Tag
+    .sessions = Lesesitzungen
+    .time-left = Verbleibende Lesezeit
+    .finish-date = Voraussichtliches Ende
+    .recent-speed = Aktuelles Tempo
+    .not-recent = In den letzten 30 Tagen nicht gelesen
+pages-left = { $count ->
+    [one] { $count } Seite übrig
+   *[other] { $count } Seiten übrig
+}
+pages-per-day = { $count } Seiten/Tag
+
 # Chrono date/time format strings (use %B for full month, %b for short, etc.)
 datetime =
     .full = %-d. %B %Y um %H:%M Uhr
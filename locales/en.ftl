@@ -21,6 +21,8 @@
 # -----------------------------------
 books = Books
 comics = Comics
+devices = Devices
+choose-profile = Who's reading?
 statistics = Statistics
 calendar = Calendar
 recap = Recap
@@ -79,6 +81,26 @@ unknown-author = Unknown Author
 by = by
 book-overview = Book Overview
 comic-overview = Comic Overview
+# OPDS catalog feed titles
+opds =
+    .all = All Items
+    .authors = Authors
+    .search = Search
+# Author, series and genre pages
+browse = Browse
+    .authors = Authors
+    .series = Series
+    .subjects = Genres
+    .languages = Languages
+    .items = { $count ->
+        [one] { $count } item
+       *[other] { $count } items
+    }
+    .completions = { $count ->
+        [one] { $count } completion
+       *[other] { $count } completions
+    }
+    .series-progress = Series Progress
 
 # -----------------------------------
 #            Book Details
@@ -89,6 +111,8 @@ series = Series
 genres = Genres
 language = Language
 book-identifiers = Book Identifiers
+stats-only-item = Only in your reading statistics: the file is no longer in the library
+page-moved = This page has moved:
 my-review = My Review
 my-note = My Note
 highlights = Highlights
@@ -106,6 +130,25 @@ page = Page
 page-bookmark = Page Bookmark
 bookmark-anchor = Bookmark anchor
 highlights-quotes = Highlights & Quotes
+highlight-style =
+    .lighten = Highlight
+    .underscore = Underline
+    .strikeout = Strikeout
+    .invert = Invert
+highlight-color =
+    .red = Red
+    .orange = Orange
+    .yellow = Yellow
+    .green = Green
+    .olive = Olive
+    .cyan = Cyan
+    .blue = Blue
+    .purple = Purple
+    .gray = Gray
+highlight-filter =
+    .aria-label = Filter highlights by color and style
+    .all = All
+highlight-edited = Edited
 additional-information = Additional Information
 reading-progress = Reading Progress
 page-number = Page { $count }
@@ -151,6 +194,19 @@ average-pages-day = Average Pages/Day
 most-pages-in-day = Most Pages in a Day
 longest-daily-reading = Longest Daily Reading
 reading-completions = Reading Completions
+reading-goals = Reading Goals
+goal =
+    .yearly = Yearly Goal
+    .monthly = Monthly Goal
+    .books = Books Finished
+    .hours = Hours Read
+    .pages = Pages Read
+    .ahead = Ahead of pace
+    .behind = Behind pace
+    .reached = Goal reached
+    .missed = Goal missed
+    .expected = { $count } expected by today
+    .projected = On pace for { $count }
 statistics-from-koreader = Statistics from KoReader reading sessions
 reading-time = Reading Time
 pages-read = Pages Read
@@ -213,6 +269,13 @@ next-month =
     .aria-label = Next month
 search =
     .aria-label = Search
+    .full-text = In Highlights & Notes
+search-field =
+    .highlight = Highlight
+    .note = Note
+    .review-note = Review Note
+    .subject = Subject
+    .description = Description
 close-search =
     .aria-label = Close search
 close = Close
@@ -249,12 +312,47 @@ november = November
 december = December
     .short = Dec
 
-# Weekday abbreviations (only Mon/Thu/Sun for GitHub-style heatmap visualization)
+# Weekday abbreviations (heatmap and reading patterns)
 weekday =
     .mon = Mon
+    .tue = Tue
+    .wed = Wed
     .thu = Thu
+    .fri = Fri
+    .sat = Sat
     .sun = Sun
 
+# Reading patterns by hour of the day and weekday (statistics page)
+reading-patterns = Reading Patterns
+    .punch-card = Reading Time by Weekday and Hour
+    .session-length = Average Session by Hour
+    .speed = Reading Speed by Hour
+    .peak-hour = Peak Hour
+    .busiest-day = Busiest Day
+    .fastest-hour = Fastest Hour
+    .rhythm = Reading Rhythm
+chronotype =
+    .night-owl = Night Owl
+    .early-bird = Early Bird
+    .day-reader = Daytime Reader
+    .night-share = { $count }% read at night
+    .morning-share = { $count }% read in the early morning
+
+# Per-book reading timeline (item detail page)
+reading-timeline = Reading Timeline
+    .progress = Progress Over Time
+    .speed = Pages per Hour by Day
+    .sessions = Reading Sessions
+    .time-left = Reading Time Left
+    .finish-date = Estimated Finish
+    .recent-speed = Recent Speed
+    .not-recent = Not read in the last 30 days
+pages-left = { $count ->
+    [one] { $count } page left
+   *[other] { $count } pages left
+}
+pages-per-day = { $count } pages/day
+
 # Chrono date/time format strings (use %B for full month, %b for short, etc.)
 datetime =
     .full = %B %-d, %Y at %-I:%M %p
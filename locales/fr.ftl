@@ -21,6 +21,8 @@
 # -----------------------------------
 books = Livres
 comics = Bandes dessinées
+devices = Appareils
+choose-profile = Qui lit ?
 statistics = Statistiques
 calendar = Calendrier
 recap = Récap
@@ -79,6 +81,26 @@ unknown-author = Auteur inconnu
 by = par
 book-overview = Aperçu du livre
 comic-overview = Aperçu de la bande dessinée
+# OPDS catalog feed titles
+opds =
+    .all = Tous les titres
+    .authors = Auteurs
+    .search = Recherche
+# Pages des auteurs, séries et genres
+browse = Parcourir
+    .authors = Auteurs
+    .series = Séries
+    .subjects = Genres
+    .languages = Langues
+    .items = { $count ->
+        [one] { $count } titre
+       *[other] { $count } titres
+    }
+    .completions = { $count ->
+        [one] { $count } lecture terminée
+       *[other] { $count } lectures terminées
+    }
+    .series-progress = Progression de la série
 
 # -----------------------------------
 #            Book Details
@@ -89,6 +111,8 @@ series = Série
 genres = Genres
 language = Langue
 book-identifiers = Identifiants du livre
+stats-only-item = Uniquement dans vos statistiques de lecture : le fichier n’est plus dans la bibliothèque
+page-moved = Cette page a été déplacée :
 my-review = Ma critique
 my-note = Ma note
 highlights = Surlignages
@@ -106,6 +130,25 @@ page = Page
 page-bookmark = Signet de page
 bookmark-anchor = Ancre du signet
 highlights-quotes = Surlignages & Citations
+highlight-style =
+    .lighten = Surlignage
+    .underscore = Souligné
+    .strikeout = Barré
+    .invert = Inversé
+highlight-color =
+    .red = Rouge
+    .orange = Orange
+    .yellow = Jaune
+    .green = Vert
+    .olive = Olive
+    .cyan = Cyan
+    .blue = Bleu
+    .purple = Violet
+    .gray = Gris
+highlight-filter =
+    .aria-label = Filtrer les surlignages par couleur et style
+    .all = Tous
+highlight-edited = Modifié
 additional-information = Informations supplémentaires
 reading-progress = Progression de lecture
 page-number = Page { $count }
@@ -151,6 +194,19 @@ average-pages-day = Pages moyennes/jour
 most-pages-in-day = Plus grand nombre de pages lues en un jour
 longest-daily-reading = Lecture quotidienne la plus longue
 reading-completions = Lectures terminées
+reading-goals = Objectifs de lecture
+goal =
+    .yearly = Objectif annuel
+    .monthly = Objectif mensuel
+    .books = Livres terminés
+    .hours = Heures de lecture
+    .pages = Pages lues
+    .ahead = En avance
+    .behind = En retard
+    .reached = Objectif atteint
+    .missed = Objectif manqué
+    .expected = { $count } attendus à ce jour
+    .projected = En route vers { $count }
 statistics-from-koreader = Statistiques des sessions KoReader
 reading-time = Temps de lecture
 pages-read = Pages lues
@@ -214,6 +270,13 @@ next-month =
     .aria-label = Mois suivant
 search =
     .aria-label = Rechercher
+    .full-text = Dans les surlignages et notes
+search-field =
+    .highlight = Surlignage
+    .note = Note
+    .review-note = Note de critique
+    .subject = Sujet
+    .description = Description
 close-search =
     .aria-label = Fermer la recherche
 close = Fermer
@@ -250,12 +313,47 @@ november = Novembre
 december = Décembre
     .short = Déc
 
-# Weekday abbreviations (Mon/Thu/Sun)
+# Weekday abbreviations (heatmap and reading patterns)
 weekday =
     .mon = Lun
+    .tue = Mar
+    .wed = Mer
     .thu = Jeu
+    .fri = Ven
+    .sat = Sam
     .sun = Dim
 
+# Reading patterns by hour of the day and weekday (statistics page)
+reading-patterns = Habitudes de lecture
+    .punch-card = Temps de lecture par jour et par heure
+    .session-length = Session moyenne par heure
+    .speed = Vitesse de lecture par heure
+    .peak-hour = Heure de pointe
+    .busiest-day = Jour le plus actif
+    .fastest-hour = Heure la plus rapide
+    .rhythm = Rythme de lecture
+chronotype =
+    .night-owl = Oiseau de nuit
+    .early-bird = Lève-tôt
+    .day-reader = Lecteur de jour
+    .night-share = { $count } % lu la nuit
+    .morning-share = { $count } % lu tôt le matin
+
+# Per-book reading timeline (item detail page)
+reading-timeline = Chronologie de lecture
+    .progress = Progression dans le temps
+    .speed = Pages par heure par jour
+    .sessions = Sessions de lecture
+    .time-left = Temps de lecture restant
+    .finish-date = Fin estimée
+    .recent-speed = Vitesse récente
+    .not-recent = Pas lu ces 30 derniers jours
+pages-left = { $count ->
+    [one] { $count } page restante
+   *[other] { $count } pages restantes
+}
+pages-per-day = { $count } pages/jour
+
 # Chrono date/time format strings
 datetime =
     .full = %-d %B %Y à %-H:%M
@@ -21,6 +21,8 @@
 # -----------------------------------
 books = Livros
 comics = Quadrinhos
+devices = Dispositivos
+choose-profile = Quem está lendo?
 statistics = Estatísticas
 calendar = Calendário
 recap = Retrospectiva
@@ -79,6 +81,26 @@ unknown-author = Autor Desconhecido
 by = por
 book-overview = Visão Geral
 comic-overview = Visão Geral da Banda Desenhada
+# OPDS catalog feed titles
+opds =
+    .all = Todos os títulos
+    .authors = Autores
+    .search = Pesquisa
+# Páginas de autores, séries e gêneros
+browse = Explorar
+    .authors = Autores
+    .series = Séries
+    .subjects = Gêneros
+    .languages = Idiomas
+    .items = { $count ->
+        [one] { $count } título
+       *[other] { $count } títulos
+    }
+    .completions = { $count ->
+        [one] { $count } leitura concluída
+       *[other] { $count } leituras concluídas
+    }
+    .series-progress = Progresso da Série
 
 # -----------------------------------
 #            Book Details
@@ -89,6 +111,8 @@ series = Série
 genres = Gêneros
 language = Idioma
 book-identifiers = Identificadores
+stats-only-item = Apenas nas suas estatísticas de leitura: o arquivo não está mais na biblioteca
+page-moved = Esta página foi movida:
 my-review = Minha Resenha
 my-note = Minha Nota
 highlights = Destaques
@@ -106,6 +130,25 @@ page = Página
 page-bookmark = Marcador de Página
 bookmark-anchor = Âncora do Marcador
 highlights-quotes = Destaques & Citações
+highlight-style =
+    .lighten = Destaque
+    .underscore = Sublinhado
+    .strikeout = Tachado
+    .invert = Invertido
+highlight-color =
+    .red = Vermelho
+    .orange = Laranja
+    .yellow = Amarelo
+    .green = Verde
+    .olive = Oliva
+    .cyan = Ciano
+    .blue = Azul
+    .purple = Roxo
+    .gray = Cinza
+highlight-filter =
+    .aria-label = Filtrar destaques por cor e estilo
+    .all = Todos
+highlight-edited = Editado
 additional-information = Informações Adicionais
 reading-progress = Progresso de Leitura
 page-number = Página { $count }
@@ -151,6 +194,19 @@ average-pages-day = Média de Páginas/Dia
 most-pages-in-day = Mais Páginas em um Dia
 longest-daily-reading = Maior Leitura Diária
 reading-completions = Leituras Concluídas
+reading-goals = Metas de Leitura
+goal =
+    .yearly = Meta Anual
+    .monthly = Meta Mensal
+    .books = Livros Concluídos
+    .hours = Horas Lidas
+    .pages = Páginas Lidas
+    .ahead = À frente do ritmo
+    .behind = Atrás do ritmo
+    .reached = Meta atingida
+    .missed = Meta não atingida
+    .expected = { $count } esperados até hoje
+    .projected = No ritmo para { $count }
 statistics-from-koreader = Estatísticas das sessões de leitura do KoReader
 reading-time = Tempo de Leitura
 pages-read = Páginas Lidas
@@ -213,6 +269,13 @@ next-month =
     .aria-label = Próximo mês
 search = 
     .aria-label = Buscar
+    .full-text = Em destaques e notas
+search-field =
+    .highlight = Destaque
+    .note = Nota
+    .review-note = Nota de avaliação
+    .subject = Assunto
+    .description = Descrição
 close-search = 
     .aria-label = Fechar busca
 close = Fechar
@@ -249,12 +312,47 @@ november = Novembro
 december = Dezembro
     .short = Dez
 
-# Weekday abbreviations (only Mon/Thu/Sun for GitHub-style heatmap visualization)
+# Weekday abbreviations (heatmap and reading patterns)
 weekday =
     .mon = Seg
+    .tue = Ter
+    .wed = Qua
     .thu = Qui
+    .fri = Sex
+    .sat = Sáb
     .sun = Dom
 
+# Reading patterns by hour of the day and weekday (statistics page)
+reading-patterns = Padrões de Leitura
+    .punch-card = Tempo de Leitura por Dia da Semana e Hora
+    .session-length = Sessão Média por Hora
+    .speed = Velocidade de Leitura por Hora
+    .peak-hour = Horário de Pico
+    .busiest-day = Dia Mais Ativo
+    .fastest-hour = Hora Mais Rápida
+    .rhythm = Ritmo de Leitura
+chronotype =
+    .night-owl = Coruja
+    .early-bird = Madrugador
+    .day-reader = Leitor Diurno
+    .night-share = { $count }% lido à noite
+    .morning-share = { $count }% lido de manhã cedo
+
+# Per-book reading timeline (item detail page)
+reading-timeline = Linha do Tempo de Leitura
+    .progress = Progresso ao Longo do Tempo
+    .speed = Páginas por Hora por Dia
+    .sessions = Sessões de Leitura
+    .time-left = Tempo de Leitura Restante
+    .finish-date = Término Estimado
+    .recent-speed = Velocidade Recente
+    .not-recent = Não lido nos últimos 30 dias
+pages-left = { $count ->
+    [one] { $count } página restante
+   *[other] { $count } páginas restantes
+}
+pages-per-day = { $count } páginas/dia
+
 # Chrono date/time format strings (use %B for full month, %b for short, etc.)
 datetime =
     .full = %d de %B de %Y às %H:%M
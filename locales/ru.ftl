@@ -21,6 +21,8 @@
 # -----------------------------------
 books = Книги
 comics = Комиксы
+devices = Устройства
+choose-profile = Кто читает?
 statistics = Статистика
 calendar = Календарь
 recap = Итоги
@@ -87,6 +89,30 @@ unknown-author = Неизвестный автор
 by = автор
 book-overview = Обзор книги
 comic-overview = Обзор комикса
+# OPDS catalog feed titles
+opds =
+    .all = Все книги
+    .authors = Авторы
+    .search = Поиск
+# Страницы авторов, серий и жанров
+browse = Обзор
+    .authors = Авторы
+    .series = Серии
+    .subjects = Жанры
+    .languages = Языки
+    .items = { $count ->
+        [one] { $count } книга
+        [few] { $count } книги
+        [many] { $count } книг
+       *[other] { $count } книг
+    }
+    .completions = { $count ->
+        [one] { $count } прочтение
+        [few] { $count } прочтения
+        [many] { $count } прочтений
+       *[other] { $count } прочтений
+    }
+    .series-progress = Прогресс серии
 
 # -----------------------------------
 #            Book Details
@@ -97,6 +123,8 @@ series = Серия
 genres = Жанры
 language = Язык
 book-identifiers = Идентификаторы книги
+stats-only-item = Только в статистике чтения: файла больше нет в библиотеке
+page-moved = Эта страница перемещена:
 my-review = Мой отзыв
 my-note = Моя заметка
 highlights = Выделения
@@ -118,6 +146,25 @@ page = Страница
 page-bookmark = Закладка страницы
 bookmark-anchor = Якорь закладки
 highlights-quotes = Выделения и цитаты
+highlight-style =
+    .lighten = Выделение
+    .underscore = Подчёркивание
+    .strikeout = Зачёркивание
+    .invert = Инверсия
+highlight-color =
+    .red = Красный
+    .orange = Оранжевый
+    .yellow = Жёлтый
+    .green = Зелёный
+    .olive = Оливковый
+    .cyan = Голубой
+    .blue = Синий
+    .purple = Фиолетовый
+    .gray = Серый
+highlight-filter =
+    .aria-label = Фильтровать выделения по цвету и стилю
+    .all = Все
+highlight-edited = Изменено
 additional-information = Дополнительная информация
 reading-progress = Прогресс чтения
 page-number = Страница { $count }
@@ -169,6 +216,19 @@ average-pages-day = Средние страницы/день
 most-pages-in-day = Больше всего страниц за день
 longest-daily-reading = Самое длинное чтение за день
 reading-completions = Завершённые чтения
+reading-goals = Цели чтения
+goal =
+    .yearly = Цель на год
+    .monthly = Цель на месяц
+    .books = Прочитано книг
+    .hours = Часов чтения
+    .pages = Прочитано страниц
+    .ahead = Опережение графика
+    .behind = Отставание от графика
+    .reached = Цель достигнута
+    .missed = Цель не достигнута
+    .expected = Ожидалось к сегодняшнему дню: { $count }
+    .projected = Прогноз: { $count }
 statistics-from-koreader = Статистика из сессий чтения KoReader
 reading-time = Время чтения
 pages-read = Прочитано страниц
@@ -237,6 +297,13 @@ next-month =
     .aria-label = Следующий месяц
 search =
     .aria-label = Поиск
+    .full-text = В выделениях и заметках
+search-field =
+    .highlight = Выделение
+    .note = Заметка
+    .review-note = Заметка к отзыву
+    .subject = Тема
+    .description = Описание
 close-search =
     .aria-label = Закрыть поиск
 close = Закрыть
@@ -273,12 +340,49 @@ november = Ноябрь
 december = Декабрь
     .short = Дек
 
-# Weekday abbreviations (only Mon/Thu/Sun for GitHub-style heatmap visualization)
+# Weekday abbreviations (heatmap and reading patterns)
 weekday =
     .mon = Пн
+    .tue = Вт
+    .wed = Ср
     .thu = Чт
+    .fri = Пт
+    .sat = Сб
     .sun = Вс
 
+# Reading patterns by hour of the day and weekday (statistics page)
+reading-patterns = Привычки чтения
+    .punch-card = Время чтения по дням недели и часам
+    .session-length = Средняя сессия по часам
+    .speed = Скорость чтения по часам
+    .peak-hour = Пиковый час
+    .busiest-day = Самый активный день
+    .fastest-hour = Самый быстрый час
+    .rhythm = Ритм чтения
+chronotype =
+    .night-owl = Сова
+    .early-bird = Жаворонок
+    .day-reader = Дневной читатель
+    .night-share = { $count }% чтения ночью
+    .morning-share = { $count }% чтения рано утром
+
+# Per-book reading timeline (item detail page)
+reading-timeline = Хронология чтения
+    .progress = Прогресс во времени
+    .speed = Страниц в час по дням
+    .sessions = Сессии чтения
+    .time-left = Осталось читать
+    .finish-date = Ожидаемое завершение
+    .recent-speed = Текущая скорость
+    .not-recent = Не читалось последние 30 дней
+pages-left = { $count ->
+    [one] Осталась { $count } страница
+    [few] Осталось { $count } страницы
+    [many] Осталось { $count } страниц
+   *[other] Осталось { $count } страниц
+}
+pages-per-day = { $count } стр./день
+
 # Chrono date/time format strings (use %B for full month, %b for short, etc.)
 datetime =
     .full = %-d %B %Y в %H:%M
@@ -21,6 +21,8 @@
 # -----------------------------------
 books = Книги
 comics = Комікси
+devices = Пристрої
+choose-profile = Хто читає?
 statistics = Статистика
 calendar = Календар
 recap = Підсумки
@@ -87,6 +89,30 @@ unknown-author = Невідомий автор
 by = автор
 book-overview = Огляд книги
 comic-overview = Огляд коміксу
+# OPDS catalog feed titles
+opds =
+    .all = Усі книги
+    .authors = Автори
+    .search = Пошук
+# Сторінки авторів, серій і жанрів
+browse = Огляд
+    .authors = Автори
+    .series = Серії
+    .subjects = Жанри
+    .languages = Мови
+    .items = { $count ->
+        [one] { $count } книга
+        [few] { $count } книги
+        [many] { $count } книг
+       *[other] { $count } книг
+    }
+    .completions = { $count ->
+        [one] { $count } прочитання
+        [few] { $count } прочитання
+        [many] { $count } прочитань
+       *[other] { $count } прочитань
+    }
+    .series-progress = Прогрес серії
 
 # -----------------------------------
 #            Book Details
@@ -97,6 +123,8 @@ series = Серія
 genres = Жанри
 language = Мова
 book-identifiers = Ідентифікатори книги
+stats-only-item = Лише в статистиці читання: файлу більше немає в бібліотеці
+page-moved = Цю сторінку переміщено:
 my-review = Мій відгук
 my-note = Моя нотатка
 highlights = Виділення
@@ -118,6 +146,25 @@ page = Сторінка
 page-bookmark = Закладка сторінки
 bookmark-anchor = Якір закладки
 highlights-quotes = Виділення та цитати
+highlight-style =
+    .lighten = Виділення
+    .underscore = Підкреслення
+    .strikeout = Закреслення
+    .invert = Інверсія
+highlight-color =
+    .red = Червоний
+    .orange = Помаранчевий
+    .yellow = Жовтий
+    .green = Зелений
+    .olive = Оливковий
+    .cyan = Блакитний
+    .blue = Синій
+    .purple = Фіолетовий
+    .gray = Сірий
+highlight-filter =
+    .aria-label = Фільтрувати виділення за кольором і стилем
+    .all = Усі
+highlight-edited = Змінено
 additional-information = Додаткова інформація
 reading-progress = Прогрес читання
 page-number = Сторінка { $count }
@@ -169,6 +216,19 @@ average-pages-day = Середні сторінки/день
 most-pages-in-day = Найбільше сторінок за день
 longest-daily-reading = Найдовше денне читання
 reading-completions = Завершені читання
+reading-goals = Цілі читання
+goal =
+    .yearly = Ціль на рік
+    .monthly = Ціль на місяць
+    .books = Прочитано книг
+    .hours = Годин читання
+    .pages = Прочитано сторінок
+    .ahead = Випередження графіка
+    .behind = Відставання від графіка
+    .reached = Ціль досягнуто
+    .missed = Ціль не досягнуто
+    .expected = Очікувалося до сьогодні: { $count }
+    .projected = Прогноз: { $count }
 statistics-from-koreader = Статистика з сесій читання KoReader
 reading-time = Час читання
 pages-read = Прочитано сторінок
@@ -237,6 +297,13 @@ next-month =
     .aria-label = Наступний місяць
 search =
     .aria-label = Пошук
+    .full-text = У виділеннях і нотатках
+search-field =
+    .highlight = Виділення
+    .note = Нотатка
+    .review-note = Нотатка до відгуку
+    .subject = Тема
+    .description = Опис
 close-search =
     .aria-label = Закрити пошук
 close = Закрити
@@ -273,12 +340,49 @@ november = Листопад
 december = Грудень
     .short = Гру
 
-# Weekday abbreviations (only Mon/Thu/Sun for GitHub-style heatmap visualization)
+# Weekday abbreviations (heatmap and reading patterns)
 weekday =
     .mon = Пн
+    .tue = Вт
+    .wed = Ср
     .thu = Чт
+    .fri = Пт
+    .sat = Сб
     .sun = Нд
 
+# Reading patterns by hour of the day and weekday (statistics page)
+reading-patterns = Звички читання
+    .punch-card = Час читання за днями тижня та годинами
+    .session-length = Середня сесія за годинами
+    .speed = Швидкість читання за годинами
+    .peak-hour = Пікова година
+    .busiest-day = Найактивніший день
+    .fastest-hour = Найшвидша година
+    .rhythm = Ритм читання
+chronotype =
+    .night-owl = Сова
+    .early-bird = Жайворонок
+    .day-reader = Денний читач
+    .night-share = { $count }% читання вночі
+    .morning-share = { $count }% читання рано вранці
+
+# Per-book reading timeline (item detail page)
+reading-timeline = Хронологія читання
+    .progress = Прогрес у часі
+    .speed = Сторінок на годину за днями
+    .sessions = Сесії читання
+    .time-left = Залишилось читати
+    .finish-date = Орієнтовне завершення
+    .recent-speed = Поточна швидкість
+    .not-recent = Не читалося останні 30 днів
+pages-left = { $count ->
+    [one] Залишилась { $count } сторінка
+    [few] Залишилось { $count } сторінки
+    [many] Залишилось { $count } сторінок
+   *[other] Залишилось { $count } сторінок
+}
+pages-per-day = { $count } стор./день
+
 # Chrono date/time format strings (use %B for full month, %b for short, etc.)
 datetime =
     .full = %-d %B %Y о %H:%M
@@ -1,12 +1,29 @@
-use crate::cli::{Cli, parse_time_to_seconds};
-use crate::config::SiteConfig;
-use crate::library::{FileWatcher, MetadataLocation};
-use crate::server::{WebServer, create_version_notifier};
+use crate::cli::{
+    Cli, Command, ExportAnnotationsArgs, InspectArgs, InspectCommand, parse_time_to_seconds,
+};
+use crate::config::{ReaderProfile, SiteConfig};
+use crate::export::ExportItem;
+use crate::i18n::Translations;
+use crate::koreader::completion::{CompletionConfig, ProgressionReport, ReadCompletionDetector};
+use crate::koreader::session::DEFAULT_SESSION_GAP_SECONDS;
+use crate::koreader::{
+    KosyncStore, StatisticsCalculator, StatisticsParser, StatisticsSource, calculate_partial_md5,
+};
+use crate::library::{
+    FileWatcher, LibraryState, MetadataLocation, SharedLibraryState, scan_library,
+};
+use crate::models::{BookStatus, ContentType, KoReaderMetadata, LibraryItem, LibraryItemFormat};
+use crate::server::{ApiConfig, KosyncState, WebServer, create_version_notifier};
 use crate::site_generator::SiteGenerator;
+use crate::site_generator::utils::format_duration;
 use crate::time_config::TimeConfig;
+use crate::utils::generate_slug;
 use anyhow::{Context, Result};
 use log::{info, error};
+use serde::Serialize;
 use tempfile::TempDir;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
 
 enum RunMode {
@@ -54,14 +71,381 @@ fn metadata_location(cli: &Cli) -> MetadataLocation {
     }
 }
 
+/// Longest pause within a reading session in seconds (`--session-gap`).
+fn session_gap(cli: &Cli) -> Result<i64> {
+    Ok(parse_time_to_seconds(&cli.session_gap)?.map_or(DEFAULT_SESSION_GAP_SECONDS, i64::from))
+}
+
+/// Resolve the configured reader profiles, falling back to the global metadata location,
+/// time zone and language.
+fn reader_profiles(cli: &Cli) -> Result<Vec<ReaderProfile>> {
+    cli.profiles
+        .iter()
+        .map(|profile| {
+            let metadata_location = if let Some(path) = &profile.docsettings_path {
+                MetadataLocation::DocSettings(path.clone())
+            } else if let Some(path) = &profile.hashdocsettings_path {
+                MetadataLocation::HashDocSettings(path.clone())
+            } else {
+                metadata_location(cli)
+            };
+            let timezone = profile.timezone.clone().or_else(|| cli.timezone.clone());
+            Ok(ReaderProfile {
+                name: profile.name.clone(),
+                slug: generate_slug(&profile.name),
+                metadata_location,
+                statistics_db_paths: profile.statistics_db.clone(),
+                statistics_db_devices: profile.statistics_db_devices.clone(),
+                time_config: TimeConfig::from_cli(&timezone, &cli.day_start_time)
+                    .with_context(|| format!("Invalid time zone for profile {:?}", profile.name))?,
+                language: profile
+                    .language
+                    .clone()
+                    .unwrap_or_else(|| cli.language.clone()),
+            })
+        })
+        .collect()
+}
+
+/// Settings of the server API: the root site's, with those of every reader profile site.
+fn api_config(config: &SiteConfig) -> ApiConfig {
+    let site_api = |site: &SiteConfig| ApiConfig {
+        time_config: site.time_config.clone(),
+        language: site.language.clone(),
+        site_dir: site.output_dir.clone(),
+        goals: site.goals,
+        profiles: Vec::new(),
+    };
+    ApiConfig {
+        profiles: config
+            .profiles
+            .iter()
+            .map(|profile| (profile.slug.clone(), site_api(&config.for_profile(profile))))
+            .collect(),
+        ..site_api(config)
+    }
+}
+
+/// Scan the library and export the highlights of all items with KoReader metadata.
+async fn export_annotations(cli: &Cli, args: &ExportAnnotationsArgs) -> Result<()> {
+    let scan = scan_library(
+        &cli.library_path,
+        &metadata_location(cli),
+        cli.id_scheme,
+        None,
+    )
+    .await?;
+    let items = ExportItem::collect(&scan.items);
+    let summary = args.format.exporter().export(&items, &args.output)?;
+    info!(
+        "Exported {} highlights of {} items to {:?}",
+        summary.highlights, summary.items, args.output
+    );
+    Ok(())
+}
+
+/// What `koshelf inspect` prints for a book.
+#[derive(Serialize)]
+struct InspectReport<'a> {
+    id: &'a str,
+    path: &'a Path,
+    format: Option<LibraryItemFormat>,
+    content_type: ContentType,
+    partial_md5: Option<String>,
+    status: BookStatus,
+    progress_percentage: Option<f64>,
+    /// Size of the embedded cover image; the image itself is left out
+    cover_bytes: Option<usize>,
+    book_info: serde_json::Value,
+    koreader_metadata: Option<&'a KoReaderMetadata>,
+}
+
+/// What `koshelf inspect completions` prints for a book.
+#[derive(Serialize)]
+struct CompletionsReport<'a> {
+    id: &'a str,
+    path: &'a Path,
+    content_type: ContentType,
+    partial_md5: &'a str,
+    status: BookStatus,
+    /// Page count recorded in the statistics database
+    total_pages: Option<i64>,
+    thresholds: &'a CompletionConfig,
+    trust_status: bool,
+    completions: usize,
+    progressions: Vec<ProgressionReport>,
+}
+
+/// Scan a single book or comic file.
+async fn scan_single_item(cli: &Cli, book: &Path) -> Result<LibraryItem> {
+    let scan = scan_library(
+        &[book.to_path_buf()],
+        &metadata_location(cli),
+        cli.id_scheme,
+        None,
+    )
+    .await?;
+    match scan.items.into_iter().next() {
+        Some(item) => Ok(item),
+        None => anyhow::bail!("Not a supported book or comic file: {:?}", book),
+    }
+}
+
+/// Print the parsed metadata of a single book as JSON, or its completion diagnostics.
+async fn inspect(cli: &Cli, args: &InspectArgs) -> Result<()> {
+    let Some(book) = args.book() else {
+        anyhow::bail!("No book file given");
+    };
+    if let Some(InspectCommand::Completions(_)) = args.command {
+        return inspect_completions(cli, book).await;
+    }
+
+    let mut item = scan_single_item(cli, book).await?;
+    let cover_bytes = item.book_info.cover_data.take().map(|data| data.len());
+
+    let mut book_info = serde_json::to_value(&item.book_info)?;
+    if let Some(fields) = book_info.as_object_mut() {
+        fields.remove("cover_data");
+    }
+    let report = InspectReport {
+        id: &item.id,
+        path: book,
+        format: item.format(),
+        content_type: item.content_type(),
+        partial_md5: calculate_partial_md5(book).ok(),
+        status: item.status(),
+        progress_percentage: item.progress_percentage(),
+        cover_bytes,
+        book_info,
+        koreader_metadata: item.koreader_metadata.as_ref(),
+    };
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// Print each reading progression of a book found in the statistics and why it was or was
+/// not counted as a completion, as JSON.
+async fn inspect_completions(cli: &Cli, book: &Path) -> Result<()> {
+    let time_config = TimeConfig::from_cli(&cli.timezone, &cli.day_start_time)?;
+    let min_time_per_day = match &cli.min_time_per_day {
+        Some(t) => parse_time_to_seconds(t)?,
+        None => None,
+    };
+    let item = scan_single_item(cli, book).await?;
+    let md5 = match item
+        .koreader_metadata
+        .as_ref()
+        .and_then(|m| m.partial_md5_checksum.clone())
+    {
+        Some(md5) => md5,
+        None => calculate_partial_md5(book)?,
+    };
+
+    let sources: Vec<_> = cli
+        .statistics_db
+        .iter()
+        .map(|path| StatisticsSource {
+            path: path.clone(),
+            device: cli.statistics_db_devices.get(path).cloned(),
+        })
+        .collect();
+    let mut data = StatisticsParser::parse_sources(&sources)?;
+    if cli.min_pages_per_day.is_some() || min_time_per_day.is_some() {
+        StatisticsCalculator::filter_stats(
+            &mut data,
+            &time_config,
+            cli.min_pages_per_day,
+            min_time_per_day,
+        );
+    }
+    StatisticsCalculator::populate_sessions(&mut data, session_gap(cli)?);
+    let Some(stat_book) = data.stats_by_md5.get(&md5) else {
+        anyhow::bail!(
+            "No reading statistics found for {:?} (partial MD5 {})",
+            book,
+            md5
+        );
+    };
+
+    let content_type = item.content_type();
+    let thresholds = cli.completion.for_content_type(Some(content_type));
+    let marked_complete = item.status() == BookStatus::Complete;
+    let detector =
+        ReadCompletionDetector::with_config_and_time(thresholds.clone(), time_config.clone())
+            .trusting_status(cli.completion.trust_status);
+    let progressions = detector.explain(stat_book, &data, marked_complete);
+
+    let report = CompletionsReport {
+        id: &item.id,
+        path: book,
+        content_type,
+        partial_md5: &md5,
+        status: item.status(),
+        total_pages: stat_book.pages,
+        thresholds,
+        trust_status: cli.completion.trust_status,
+        completions: progressions.iter().filter(|p| p.counted).count(),
+        progressions,
+    };
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// Print a summary of the reading statistics, filtered like the statistics page.
+async fn print_stats(cli: &Cli) -> Result<()> {
+    let time_config = TimeConfig::from_cli(&cli.timezone, &cli.day_start_time)?;
+    let min_time_per_day = match &cli.min_time_per_day {
+        Some(t) => parse_time_to_seconds(t)?,
+        None => None,
+    };
+    let translations = Translations::load(&cli.language)?;
+
+    let sources: Vec<_> = cli
+        .statistics_db
+        .iter()
+        .map(|path| StatisticsSource {
+            path: path.clone(),
+            device: cli.statistics_db_devices.get(path).cloned(),
+        })
+        .collect();
+    let mut data = StatisticsParser::parse_sources(&sources)?;
+    if cli.min_pages_per_day.is_some() || min_time_per_day.is_some() {
+        StatisticsCalculator::filter_stats(
+            &mut data,
+            &time_config,
+            cli.min_pages_per_day,
+            min_time_per_day,
+        );
+    }
+    let mut finished_md5s = HashSet::new();
+    if !cli.library_path.is_empty() {
+        let scan = scan_library(
+            &cli.library_path,
+            &metadata_location(cli),
+            cli.id_scheme,
+            None,
+        )
+        .await?;
+        if !cli.include_all_stats {
+            StatisticsCalculator::filter_to_library(&mut data, &scan.library_md5s);
+        }
+        let mut md5_to_content_type = HashMap::new();
+        for item in &scan.items {
+            if let Some(md5) = item
+                .koreader_metadata
+                .as_ref()
+                .and_then(|m| m.partial_md5_checksum.clone())
+            {
+                if item.status() == BookStatus::Complete {
+                    finished_md5s.insert(md5.clone());
+                }
+                md5_to_content_type.insert(md5, item.content_type());
+            }
+        }
+        data.tag_content_types(&md5_to_content_type);
+    }
+    StatisticsCalculator::populate_sessions(&mut data, session_gap(cli)?);
+    StatisticsCalculator::populate_completions(
+        &mut data,
+        &time_config,
+        &cli.completion,
+        &finished_md5s,
+    );
+    let stats = StatisticsParser::calculate_stats(&mut data, &time_config);
+
+    let duration = |seconds: i64| format_duration(seconds, &translations);
+    let streak = |days: i64, start: &Option<String>, end: &Option<String>| {
+        // Fluent's bidi isolation marks only make sense in HTML
+        let days = translations
+            .get_with_num("units-days", days)
+            .replace(['\u{2068}', '\u{2069}'], "");
+        match (start, end) {
+            (Some(start), Some(end)) => format!("{} ({} – {})", days, start, end),
+            _ => days,
+        }
+    };
+    let today = time_config.today_date().format("%Y-%m-%d").to_string();
+    let this_week = stats
+        .weeks
+        .iter()
+        .find(|week| week.start_date <= today && today <= week.end_date);
+
+    let rows = [
+        ("total-read-time", duration(stats.total_read_time)),
+        ("total-pages-read", stats.total_page_reads.to_string()),
+        (
+            "longest-daily-reading",
+            duration(stats.longest_read_time_in_day),
+        ),
+        ("most-pages-in-day", stats.most_pages_in_day.to_string()),
+        (
+            "session.average",
+            duration(stats.average_session_duration.unwrap_or(0)),
+        ),
+        (
+            "session.longest",
+            duration(stats.longest_session_duration.unwrap_or(0)),
+        ),
+        ("reading-completions", stats.total_completions.to_string()),
+        (
+            "streak.current",
+            streak(
+                stats.current_streak.days,
+                &stats.current_streak.start_date,
+                &stats.current_streak.end_date,
+            ),
+        ),
+        (
+            "streak.longest",
+            streak(
+                stats.longest_streak.days,
+                &stats.longest_streak.start_date,
+                &stats.longest_streak.end_date,
+            ),
+        ),
+        (
+            "weekly-reading-time",
+            duration(this_week.map_or(0, |week| week.read_time)),
+        ),
+        (
+            "weekly-pages-read",
+            this_week.map_or(0, |week| week.pages_read).to_string(),
+        ),
+    ];
+    let rows: Vec<_> = rows
+        .into_iter()
+        .map(|(key, value)| (translations.get(key), value))
+        .collect();
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    println!("{}", translations.get("reading-statistics"));
+    for (label, value) in rows {
+        println!("  {:<width$}  {}", label, value);
+    }
+    Ok(())
+}
+
 pub async fn run(cli: Cli) -> Result<()> {
     info!("Starting KOShelf...");
     cli.validate()?;
 
+    match &cli.command {
+        Some(Command::Export(args)) => return export_annotations(&cli, args).await,
+        Some(Command::Inspect(args)) => return inspect(&cli, args).await,
+        Some(Command::Stats) => return print_stats(&cli).await,
+        _ => {}
+    }
+
     let heatmap_scale_max = parse_time_to_seconds(&cli.heatmap_scale_max)?;
     let min_time_per_day = if let Some(ref t) = cli.min_time_per_day {
         parse_time_to_seconds(t)?
-    } else { None };
+    } else {
+        None
+    };
 
     let time_config = TimeConfig::from_cli(&cli.timezone, &cli.day_start_time)?;
     let plan = plan_output(&cli)?;
@@ -71,47 +455,81 @@ pub async fn run(cli: Cli) -> Result<()> {
         site_title: cli.title.clone(),
         include_unread: cli.include_unread,
         library_paths: cli.library_path.clone(),
+        library_options: cli.library_options.clone(),
         metadata_location: metadata_location(&cli),
-        statistics_db_path: cli.statistics_db.clone(),
+        statistics_db_paths: cli.statistics_db.clone(),
+        statistics_db_devices: cli.statistics_db_devices.clone(),
         heatmap_scale_max,
         time_config: time_config.clone(),
         min_pages_per_day: cli.min_pages_per_day,
         min_time_per_day,
+        session_gap: session_gap(&cli)?,
         include_all_stats: cli.include_all_stats,
         is_internal_server: matches!(plan.mode, RunMode::Serve),
         language: cli.language.clone(),
+        id_scheme: cli.id_scheme,
+        kosync_db_path: cli.kosync_db.clone(),
+        site_root: String::new(),
+        profiles: reader_profiles(&cli)?,
+        goals: cli.goals,
+        completion: cli.completion.clone(),
     };
 
     let site_generator = SiteGenerator::new(config.clone());
-    site_generator.generate().await?;
-    
-    let scanner = crate::library::scanner::Scanner::new(config.clone());
-    let library_items = Arc::new(scanner.scan().await?);
+    let snapshot = site_generator.generate().await?;
 
     match plan.mode {
         RunMode::StaticExport => Ok(()),
         RunMode::WatchStatic => {
-            let file_watcher = FileWatcher::new(config, None);
-            file_watcher.run().await.map_err(|e| { error!("{}", e); e })?;
+            let file_watcher = FileWatcher::new(config, None, None);
+            file_watcher.run().await.map_err(|e| {
+                error!("{}", e);
+                e
+            })?;
             Ok(())
         }
         RunMode::Serve => {
+            // Open the sync database before the watcher starts, so a new one is watched too
+            let kosync = match &cli.kosync_db {
+                Some(path) => Some(KosyncState::new(
+                    KosyncStore::open(path)?,
+                    cli.kosync_registration,
+                )),
+                None => None,
+            };
             let version_notifier = create_version_notifier();
-            let file_watcher = FileWatcher::new(config.clone(), Some(version_notifier.clone()));
-            
-            // Pegamos o primeiro caminho da biblioteca fornecido via CLI
-            // No seu caso: /Users/helder/Documents/Projetos/koshelf/Livros
+            version_notifier.notify(snapshot.version.clone());
+            let library_state: SharedLibraryState = Arc::new(LibraryState::new(snapshot));
+            let file_watcher = FileWatcher::new(
+                config.clone(),
+                Some(version_notifier.clone()),
+                Some(library_state.clone()),
+            );
+
+            // Raw files and KOReader settings are served from the first library path
             let library_path = cli.library_path.first().cloned().unwrap_or_default();
 
             let web_server = WebServer::new(
-                plan.output_dir, 
-                cli.port, 
-                version_notifier, 
-                library_items,
-                library_path // Este é o 5º argumento
+                plan.output_dir,
+                cli.port,
+                version_notifier,
+                library_state,
+                library_path,
+                api_config(&config),
+                kosync,
             );
 
             info!("Server mode active. Port: {}", cli.port);
+            if cli.kosync_db.is_some() {
+                info!(
+                    "KOReader progress sync enabled (registration {})",
+                    if cli.kosync_registration {
+                        "open"
+                    } else {
+                        "closed"
+                    }
+                );
+            }
 
             tokio::select! {
                 res = file_watcher.run() => { if let Err(e) = res { error!("Watcher: {}", e); } }
@@ -120,4 +538,4 @@ pub async fn run(cli: Cli) -> Result<()> {
             Ok(())
         }
     }
-}
\ No newline at end of file
+}
@@ -1,88 +1,254 @@
+use crate::config::{
+    CompletionSettings, GoalsConfig, ItemIdScheme, LibraryPathOptions, ProfileOptions,
+};
+use crate::config_file::FileConfig;
+use crate::export::ExportTarget;
+use crate::utils::generate_slug;
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 use regex::Regex;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// KoShelf CLI arguments.
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
-    /// Path(s) to folders containing ebooks (EPUB, FB2, MOBI) and/or comics (CBZ, CBR) with KoReader metadata.
+    /// Path(s) to folders containing ebooks (EPUB, FB2, MOBI, PDF, DjVu) and/or comics (CBZ, CBR) with KoReader metadata.
     /// Can be specified multiple times. (optional if statistics_db is provided)
-    #[arg(short = 'i', visible_short_alias = 'b', long, alias = "books-path", display_order = 1, action = clap::ArgAction::Append)]
+    #[arg(short = 'i', visible_short_alias = 'b', long, alias = "books-path", global = true, display_order = 1, action = clap::ArgAction::Append)]
     pub library_path: Vec<PathBuf>,
 
     /// Path to KOReader's docsettings folder (for users who store metadata separately). Requires --books-path. Mutually exclusive with --hashdocsettings-path.
-    #[arg(long, display_order = 2)]
+    #[arg(long, global = true, display_order = 2)]
     pub docsettings_path: Option<PathBuf>,
 
     /// Path to KOReader's hashdocsettings folder (for users who store metadata by hash). Requires --books-path. Mutually exclusive with --docsettings-path.
-    #[arg(long, display_order = 3)]
+    #[arg(long, global = true, display_order = 3)]
     pub hashdocsettings_path: Option<PathBuf>,
 
-    /// Path to the statistics.sqlite3 file for additional reading stats (optional if books_path is provided)
-    #[arg(short, long, display_order = 4)]
-    pub statistics_db: Option<PathBuf>,
+    /// Path to the statistics.sqlite3 file for additional reading stats (optional if books_path is provided).
+    /// Can be specified multiple times to merge statistics from several devices.
+    #[arg(short, long, global = true, display_order = 4, action = clap::ArgAction::Append)]
+    pub statistics_db: Vec<PathBuf>,
 
-    /// Output directory for the generated static site (if not provided, starts web server with file watching)
+    /// Output directory for the generated static site (if not provided, starts web server with file watching).
+    /// Flag-only invocation; prefer the `generate` and `watch` subcommands
     #[arg(short, long, display_order = 5)]
     pub output: Option<PathBuf>,
 
-    /// Port for web server mode (default: 3000)
+    /// Port for web server mode (default: 3000). Flag-only invocation; prefer `serve --port`
     #[arg(short, long, default_value = "3000", display_order = 6)]
     pub port: u16,
 
-    /// Enable file watching with static output (requires --output)
+    /// Enable file watching with static output (requires --output). Flag-only invocation; prefer `watch`
     #[arg(short, long, default_value = "false", display_order = 7)]
     pub watch: bool,
 
     /// Site title
-    #[arg(short, long, default_value = "KoShelf", display_order = 8)]
+    #[arg(
+        short,
+        long,
+        default_value = "KoShelf",
+        global = true,
+        display_order = 8
+    )]
     pub title: String,
 
     /// Include unread books (EPUBs without KoReader metadata) in the generated site
-    #[arg(long, default_value = "false", display_order = 9)]
+    #[arg(long, default_value = "false", global = true, display_order = 9)]
     pub include_unread: bool,
 
     /// Maximum value for heatmap color intensity scaling (e.g., "auto", "1h", "1h30m", "45min"). Values above this will still be shown but use the highest color intensity.
-    #[arg(long, default_value = "auto", display_order = 10)]
+    #[arg(long, default_value = "auto", global = true, display_order = 10)]
     pub heatmap_scale_max: String,
 
     /// Timezone to interpret timestamps (IANA name, e.g., "Australia/Sydney"). Defaults to system local timezone.
-    #[arg(long, display_order = 11)]
+    #[arg(long, global = true, display_order = 11)]
     pub timezone: Option<String>,
 
     /// Logical day start time (HH:MM). Defaults to 00:00.
-    #[arg(long, value_name = "HH:MM", display_order = 12)]
+    #[arg(long, value_name = "HH:MM", global = true, display_order = 12)]
     pub day_start_time: Option<String>,
 
     /// Minimum pages read per day to be counted in statistics (optional)
-    #[arg(long, display_order = 13)]
+    #[arg(long, global = true, display_order = 13)]
     pub min_pages_per_day: Option<u32>,
 
     /// Minimum reading time per day to be counted in statistics (e.g., "15m", "1h"). (optional)
-    #[arg(long, display_order = 14)]
+    #[arg(long, global = true, display_order = 14)]
     pub min_time_per_day: Option<String>,
 
+    /// Longest pause between two page turns that still counts as the same reading session (e.g., "5m", "90s")
+    #[arg(long, default_value = "5m", global = true, display_order = 15)]
+    pub session_gap: String,
+
     /// Include statistics for all books in the database, not just those in --books-path.
     /// By default, when --books-path is provided, statistics are filtered to only include
     /// books present in that directory. Use this flag to include all statistics.
-    #[arg(long, default_value = "false", display_order = 15)]
+    #[arg(long, default_value = "false", global = true, display_order = 16)]
     pub include_all_stats: bool,
 
     /// Language for UI translations. Use full locale (e.g., en_US, de_DE) for correct date formatting. Use --list-languages to see available options
-    #[arg(long, short = 'l', default_value = "en_US", display_order = 16)]
+    #[arg(
+        long,
+        short = 'l',
+        default_value = "en_US",
+        global = true,
+        display_order = 17
+    )]
     pub language: String,
 
     /// List all supported languages and exit
-    #[arg(long, display_order = 17)]
+    #[arg(long, display_order = 18)]
     pub list_languages: bool,
 
     /// Print GitHub repository URL
-    #[arg(long, display_order = 18)]
+    #[arg(long, display_order = 19)]
     pub github: bool,
+
+    /// Path to a koshelf.toml configuration file. Defaults to $XDG_CONFIG_HOME/koshelf/koshelf.toml if it exists.
+    /// Options given on the command line override values from the file.
+    #[arg(short = 'c', long, global = true, display_order = 20)]
+    pub config: Option<PathBuf>,
+
+    /// How item IDs in URLs are derived: "hash" (title slug plus partial MD5, collision-free)
+    /// or "title" (title slug only). Old title-slug URLs redirect to the new ones.
+    #[arg(long, value_enum, default_value_t = ItemIdScheme::Hash, global = true, display_order = 21)]
+    pub id_scheme: ItemIdScheme,
+
+    /// Path to a SQLite database for the built-in KOReader progress sync server (created if missing).
+    /// In web server mode, devices can use KoShelf as their "Progress sync" server; synced progress
+    /// is shown for matching items in every mode.
+    #[arg(long, global = true, display_order = 22)]
+    pub kosync_db: Option<PathBuf>,
+
+    /// Allow KOReader devices to register new progress sync accounts (requires --kosync-db)
+    #[arg(long, default_value = "false", display_order = 23)]
+    pub kosync_registration: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Per-library-path options (only settable from the configuration file)
+    #[arg(skip)]
+    pub library_options: HashMap<PathBuf, LibraryPathOptions>,
+
+    /// Device names of statistics databases (only settable from the configuration file)
+    #[arg(skip)]
+    pub statistics_db_devices: HashMap<PathBuf, String>,
+
+    /// Reader profiles (only settable from the configuration file)
+    #[arg(skip)]
+    pub profiles: Vec<ProfileOptions>,
+
+    /// Reading goals (only settable from the configuration file)
+    #[arg(skip)]
+    pub goals: GoalsConfig,
+
+    /// Read completion detection thresholds (only settable from the configuration file)
+    #[arg(skip)]
+    pub completion: CompletionSettings,
+}
+
+/// Subcommands. Without one, the legacy flags decide the mode: `--output` generates the
+/// site (and `--watch` keeps regenerating it), otherwise the web server is started.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Generate the static site once
+    Generate(GenerateArgs),
+    /// Generate the static site and regenerate it whenever the library or statistics change
+    Watch(GenerateArgs),
+    /// Start the web server, regenerating the site on changes
+    Serve(ServeArgs),
+    /// Export highlights and notes to Obsidian, Logseq, Readwise (CSV) or Anki (TSV)
+    #[command(alias = "export-annotations")]
+    Export(ExportAnnotationsArgs),
+    /// Print the parsed metadata of a single book as JSON, or explain its detected completions
+    Inspect(InspectArgs),
+    /// Print a reading statistics summary to the terminal
+    Stats,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GenerateArgs {
+    /// Output directory for the generated static site (defaults to `output` from the configuration file)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeArgs {
+    /// Port for the web server (default: 3000)
+    #[arg(short, long)]
+    pub port: Option<u16>,
+
+    /// Allow KOReader devices to register new progress sync accounts (requires --kosync-db)
+    #[arg(long, default_value = "false")]
+    pub kosync_registration: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct ExportAnnotationsArgs {
+    /// Export target
+    #[arg(short, long, value_enum)]
+    pub format: ExportTarget,
+
+    /// Obsidian vault or Logseq graph folder; CSV/TSV file (or folder to write it to) for Readwise and Anki.
+    /// Re-exporting into the same vault or graph only adds new highlights.
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
+pub struct InspectArgs {
+    #[command(subcommand)]
+    pub command: Option<InspectCommand>,
+
+    /// Book or comic file to inspect. Its KOReader metadata is looked up like during a library
+    /// scan, honouring --docsettings-path and --hashdocsettings-path.
+    pub book: Option<PathBuf>,
+}
+
+impl InspectArgs {
+    /// The inspected book file, whichever form of the command was used.
+    pub fn book(&self) -> Option<&PathBuf> {
+        match &self.command {
+            Some(InspectCommand::Completions(args)) => Some(&args.book),
+            None => self.book.as_ref(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum InspectCommand {
+    /// Print each reading progression of a book found in the statistics and why it was or
+    /// was not counted as a completion (requires --statistics-db)
+    Completions(InspectBookArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct InspectBookArgs {
+    /// Book or comic file to inspect
+    pub book: PathBuf,
+}
+
+/// Flags of the flag-only invocation that have a subcommand-specific replacement.
+const LEGACY_MODE_FLAGS: [(&str, &str); 4] = [
+    (
+        "output",
+        "--output (use `generate --output` or `watch --output`)",
+    ),
+    ("port", "--port (use `serve --port`)"),
+    ("watch", "--watch (use the `watch` subcommand)"),
+    (
+        "kosync_registration",
+        "--kosync-registration (use `serve --kosync-registration`)",
+    ),
+];
+
 /// Parse time format strings like "1h", "1h30m", "45min", "30s" into seconds.
 ///
 /// Special case: "auto" returns `Ok(None)`.
@@ -119,12 +285,155 @@ pub fn parse_time_to_seconds(time_str: &str) -> Result<Option<u32>> {
 }
 
 impl Cli {
+    /// Validate reader profiles: they share the library, need distinct names, and each
+    /// profile's metadata folders and statistics databases must exist.
+    fn validate_profiles(&self) -> Result<()> {
+        if self.profiles.is_empty() {
+            return Ok(());
+        }
+        if self.library_path.is_empty() {
+            anyhow::bail!("Reader profiles require --library-path to be provided");
+        }
+
+        let mut slugs = std::collections::HashSet::new();
+        for profile in &self.profiles {
+            if profile.name.trim().is_empty() {
+                anyhow::bail!("Reader profiles need a name");
+            }
+            if !slugs.insert(generate_slug(&profile.name)) {
+                anyhow::bail!("Duplicate reader profile name: {:?}", profile.name);
+            }
+            if profile.docsettings_path.is_some() && profile.hashdocsettings_path.is_some() {
+                anyhow::bail!(
+                    "Profile {:?}: docsettings_path and hashdocsettings_path are mutually exclusive",
+                    profile.name
+                );
+            }
+            for path in profile
+                .docsettings_path
+                .iter()
+                .chain(&profile.hashdocsettings_path)
+            {
+                if !path.is_dir() {
+                    anyhow::bail!(
+                        "Profile {:?}: metadata path is not a directory: {:?}",
+                        profile.name,
+                        path
+                    );
+                }
+            }
+            if let Some(language) = &profile.language
+                && !language.contains(['_', '-'])
+            {
+                anyhow::bail!(
+                    "Profile {:?}: language needs a full locale (e.g. de_DE), got {:?}",
+                    profile.name,
+                    language
+                );
+            }
+            for stats_path in &profile.statistics_db {
+                if !stats_path.exists() {
+                    anyhow::bail!(
+                        "Profile {:?}: statistics database does not exist: {:?}",
+                        profile.name,
+                        stats_path
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the process arguments and merge in the configuration file, if any.
+    pub fn parse_with_config() -> Result<Self> {
+        Self::from_matches_with_config(&Self::command().get_matches())
+    }
+
+    /// Build the CLI from already parsed matches, merging in the configuration file
+    /// given by `--config` or found in the default location.
+    pub fn from_matches_with_config(matches: &ArgMatches) -> Result<Self> {
+        let mut cli = Self::from_arg_matches(matches)?;
+
+        if cli.command.is_some() {
+            for (id, flag) in LEGACY_MODE_FLAGS {
+                if matches.value_source(id) == Some(ValueSource::CommandLine) {
+                    anyhow::bail!("{} cannot be combined with a subcommand", flag);
+                }
+            }
+        }
+
+        if let Some(path) = cli.config.clone().or_else(FileConfig::discover) {
+            log::info!("Loading configuration file: {:?}", path);
+            let file_config = FileConfig::load(&path)?;
+            let base_dir = path
+                .parent()
+                .map(|dir| dir.to_path_buf())
+                .unwrap_or_default();
+            file_config.apply(&mut cli, matches, &base_dir);
+        }
+        cli.apply_command();
+
+        Ok(cli)
+    }
+
+    /// Map the site subcommands onto the flags of the flag-only invocation, so both
+    /// select the run mode the same way. Values from the configuration file are used
+    /// where the subcommand leaves them unset.
+    fn apply_command(&mut self) {
+        match &self.command {
+            Some(Command::Generate(args)) | Some(Command::Watch(args)) => {
+                if args.output.is_some() {
+                    self.output = args.output.clone();
+                }
+                self.watch = matches!(self.command, Some(Command::Watch(_)));
+                self.kosync_registration = false;
+            }
+            Some(Command::Serve(args)) => {
+                self.output = None;
+                self.watch = false;
+                if let Some(port) = args.port {
+                    self.port = port;
+                }
+                self.kosync_registration |= args.kosync_registration;
+            }
+            _ => {}
+        }
+    }
+
     /// Validate CLI inputs that are independent of runtime mode.
     pub fn validate(&self) -> Result<()> {
-        // Require at least one of library_path or statistics_db
-        if self.library_path.is_empty() && self.statistics_db.is_none() {
-            anyhow::bail!("Either --library-path or --statistics-db (or both) must be provided");
+        match &self.command {
+            Some(Command::Generate(_)) | Some(Command::Watch(_)) if self.output.is_none() => {
+                anyhow::bail!(
+                    "--output must be provided (or set `output` in the configuration file)"
+                );
+            }
+            Some(Command::Export(_)) if self.library_path.is_empty() => {
+                anyhow::bail!("Exporting annotations requires --library-path to be provided");
+            }
+            Some(Command::Inspect(args)) if args.book().is_none_or(|book| !book.is_file()) => {
+                anyhow::bail!("Book file does not exist: {:?}", args.book());
+            }
+            Some(Command::Inspect(InspectArgs {
+                command: Some(InspectCommand::Completions(_)),
+                ..
+            })) if self.statistics_db.is_empty() => {
+                anyhow::bail!("Inspecting completions requires --statistics-db to be provided");
+            }
+            // Inspecting a single book needs no library
+            Some(Command::Inspect(_)) => {}
+            Some(Command::Stats) if self.statistics_db.is_empty() => {
+                anyhow::bail!("--statistics-db must be provided");
+            }
+            // Require at least one of library_path or statistics_db
+            _ if self.library_path.is_empty() && self.statistics_db.is_empty() => {
+                anyhow::bail!(
+                    "Either --library-path or --statistics-db (or both) must be provided"
+                );
+            }
+            _ => {}
         }
+        let inspect = matches!(self.command, Some(Command::Inspect(_)));
 
         // Validate library paths if provided
         for library_path in &self.library_path {
@@ -148,11 +457,11 @@ impl Cli {
             );
         }
 
-        if self.docsettings_path.is_some() && self.library_path.is_empty() {
+        if self.docsettings_path.is_some() && self.library_path.is_empty() && !inspect {
             anyhow::bail!("--docsettings-path requires --library-path to be provided");
         }
 
-        if self.hashdocsettings_path.is_some() && self.library_path.is_empty() {
+        if self.hashdocsettings_path.is_some() && self.library_path.is_empty() && !inspect {
             anyhow::bail!("--hashdocsettings-path requires --library-path to be provided");
         }
 
@@ -185,18 +494,26 @@ impl Cli {
             }
         }
 
-        // Validate port option
-        if self.output.is_some() && self.port != 3000 {
+        // Validate port option (subcommands only accept it for `serve`)
+        if self.command.is_none() && self.output.is_some() && self.port != 3000 {
             anyhow::bail!("--port can only be used in web server mode (without --output)");
         }
 
-        // Validate statistics database if provided
-        if let Some(ref stats_path) = self.statistics_db
-            && !stats_path.exists()
-        {
-            anyhow::bail!("Statistics database does not exist: {:?}", stats_path);
+        // Validate progress sync options
+        if self.kosync_registration && self.kosync_db.is_none() {
+            anyhow::bail!("--kosync-registration requires --kosync-db to be provided");
         }
 
+        // Validate statistics databases if provided
+        for stats_path in &self.statistics_db {
+            if !stats_path.exists() {
+                anyhow::bail!("Statistics database does not exist: {:?}", stats_path);
+            }
+        }
+
+        self.validate_profiles()?;
+        self.completion.validate()?;
+
         // Validate heatmap scale max
         parse_time_to_seconds(&self.heatmap_scale_max).with_context(|| {
             format!(
@@ -211,6 +528,10 @@ impl Cli {
                 .with_context(|| format!("Invalid min-time-per-day format: {}", min_time_str))?;
         }
 
+        // Validate session gap
+        parse_time_to_seconds(&self.session_gap)
+            .with_context(|| format!("Invalid session-gap format: {}", self.session_gap))?;
+
         Ok(())
     }
 }
@@ -1,8 +1,132 @@
 //! Site configuration module - bundles generator/watcher configuration.
 
+use crate::koreader::completion::CompletionConfig;
 use crate::library::MetadataLocation;
+use crate::models::ContentType;
 use crate::time_config::TimeConfig;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How the IDs used in item URLs (`/books/<id>/`) and cover file names are derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemIdScheme {
+    /// Title slug plus the first characters of the item's partial MD5 (`collected-poems-3f2a9c1b`)
+    #[default]
+    Hash,
+    /// Title slug only, as in earlier versions (`collected-poems`)
+    Title,
+}
+
+/// Reading goals for one kind of period (only settable from the configuration file).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoalTargets {
+    /// Books and comics finished (optional)
+    pub books: Option<u32>,
+    /// Hours read (optional)
+    pub hours: Option<u32>,
+    /// Pages read (optional)
+    pub pages: Option<u32>,
+}
+
+impl GoalTargets {
+    pub fn is_empty(&self) -> bool {
+        self.books.is_none() && self.hours.is_none() && self.pages.is_none()
+    }
+}
+
+/// Yearly and monthly reading goals (`[goals.yearly]` and `[goals.monthly]`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoalsConfig {
+    #[serde(default)]
+    pub yearly: GoalTargets,
+    #[serde(default)]
+    pub monthly: GoalTargets,
+}
+
+impl GoalsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.yearly.is_empty() && self.monthly.is_empty()
+    }
+}
+
+/// Read completion detection (`[completion]`, only settable from the configuration file).
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompletionSettings {
+    /// Count items KOReader marks as finished even when no reading progression meets the
+    /// thresholds
+    #[serde(default)]
+    pub trust_status: bool,
+    /// Thresholds for books (`[completion.books]`)
+    #[serde(default)]
+    pub books: CompletionConfig,
+    /// Thresholds for comics (`[completion.comics]`)
+    #[serde(default)]
+    pub comics: CompletionConfig,
+}
+
+impl CompletionSettings {
+    /// Thresholds for an item of `content_type`; statistics entries outside the library use
+    /// the book thresholds.
+    pub fn for_content_type(&self, content_type: Option<ContentType>) -> &CompletionConfig {
+        match content_type {
+            Some(ContentType::Comic) => &self.comics,
+            Some(ContentType::Book) | None => &self.books,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.books
+            .validate()
+            .context("Invalid [completion.books]")?;
+        self.comics
+            .validate()
+            .context("Invalid [completion.comics]")
+    }
+}
+
+/// Options that apply to a single library path.
+#[derive(Clone, Debug, Default)]
+pub struct LibraryPathOptions {
+    /// Overrides the global `include_unread` setting for this path (optional)
+    pub include_unread: Option<bool>,
+}
+
+/// A reader profile as written in the configuration file (only settable from the file).
+///
+/// Options left out fall back to the global ones, except the statistics databases: a
+/// profile without any has no statistics pages.
+#[derive(Clone, Debug, Default)]
+pub struct ProfileOptions {
+    pub name: String,
+    pub docsettings_path: Option<PathBuf>,
+    pub hashdocsettings_path: Option<PathBuf>,
+    pub statistics_db: Vec<PathBuf>,
+    /// Device names of the profile's statistics databases, keyed by database path
+    pub statistics_db_devices: HashMap<PathBuf, String>,
+    pub timezone: Option<String>,
+    pub language: Option<String>,
+}
+
+/// A reader of the shared library with their own KoReader metadata and statistics.
+///
+/// Each profile gets its own site below `/u/<slug>/`.
+#[derive(Clone, Debug)]
+pub struct ReaderProfile {
+    /// Display name
+    pub name: String,
+    /// URL-safe name used in the profile's path
+    pub slug: String,
+    pub metadata_location: MetadataLocation,
+    pub statistics_db_paths: Vec<PathBuf>,
+    pub statistics_db_devices: HashMap<PathBuf, String>,
+    pub time_config: TimeConfig,
+    pub language: String,
+}
 
 /// Configuration for site generation and file watching.
 #[derive(Clone)]
@@ -15,10 +139,14 @@ pub struct SiteConfig {
     pub include_unread: bool,
     /// Paths to library directories (books and/or comics)
     pub library_paths: Vec<PathBuf>,
+    /// Per-library-path overrides, keyed by library path
+    pub library_options: HashMap<PathBuf, LibraryPathOptions>,
     /// Where to look for KoReader metadata
     pub metadata_location: MetadataLocation,
-    /// Path to the statistics database (optional)
-    pub statistics_db_path: Option<PathBuf>,
+    /// Paths to the statistics databases (empty if statistics are disabled)
+    pub statistics_db_paths: Vec<PathBuf>,
+    /// Device names of statistics databases, keyed by database path
+    pub statistics_db_devices: HashMap<PathBuf, String>,
     /// Maximum value for heatmap scale (optional)
     pub heatmap_scale_max: Option<u32>,
     /// Time zone configuration
@@ -27,10 +155,73 @@ pub struct SiteConfig {
     pub min_pages_per_day: Option<u32>,
     /// Minimum time per day in seconds for statistics filtering (optional)
     pub min_time_per_day: Option<u32>,
+    /// Longest pause in seconds between two page turns of the same reading session
+    pub session_gap: i64,
     /// Whether to include all stats or filter to library books only
     pub include_all_stats: bool,
-    /// Whether running with internal web server (enables long-polling)
+    /// Whether running with internal web server (enables live version events over SSE)
     pub is_internal_server: bool,
     /// Language for UI translations (e.g., "en_US", "de_DE")
     pub language: String,
+    /// How item IDs are derived
+    pub id_scheme: ItemIdScheme,
+    /// Path to the progress sync database, whose progress is merged into items (optional)
+    pub kosync_db_path: Option<PathBuf>,
+    /// URL path the site is served below, without trailing slash (empty at the web root)
+    pub site_root: String,
+    /// Reader profiles; when set, one site per profile is generated below `u/` (optional)
+    pub profiles: Vec<ReaderProfile>,
+    /// Reading goals tracked on the statistics and recap pages
+    pub goals: GoalsConfig,
+    /// Read completion detection thresholds
+    pub completion: CompletionSettings,
+}
+
+impl SiteConfig {
+    /// Whether statistics pages should be generated.
+    pub fn has_statistics_db(&self) -> bool {
+        !self.statistics_db_paths.is_empty()
+    }
+
+    /// Configuration of a profile's site: the shared library and options, with the profile's
+    /// metadata, statistics, time zone and language, written to `u/<slug>/`.
+    pub fn for_profile(&self, profile: &ReaderProfile) -> SiteConfig {
+        SiteConfig {
+            output_dir: self.output_dir.join("u").join(&profile.slug),
+            site_title: format!("{} · {}", self.site_title, profile.name),
+            metadata_location: profile.metadata_location.clone(),
+            statistics_db_paths: profile.statistics_db_paths.clone(),
+            statistics_db_devices: profile.statistics_db_devices.clone(),
+            time_config: profile.time_config.clone(),
+            language: profile.language.clone(),
+            site_root: format!("{}/u/{}", self.site_root, profile.slug),
+            profiles: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// URL prefix and output directory of every generated site with library pages: the
+    /// profile sites when profiles are configured, otherwise this site.
+    pub fn site_dirs(&self) -> Vec<(String, PathBuf)> {
+        if self.profiles.is_empty() {
+            return vec![(self.site_root.clone(), self.output_dir.clone())];
+        }
+        self.profiles
+            .iter()
+            .map(|profile| {
+                let site = self.for_profile(profile);
+                (site.site_root, site.output_dir)
+            })
+            .collect()
+    }
+
+    /// Resolve `include_unread` for an item, honoring per-library-path overrides.
+    pub fn include_unread_for(&self, file_path: &Path) -> bool {
+        self.library_options
+            .iter()
+            .filter(|(library_path, _)| file_path.starts_with(library_path))
+            .max_by_key(|(library_path, _)| library_path.components().count())
+            .and_then(|(_, options)| options.include_unread)
+            .unwrap_or(self.include_unread)
+    }
 }
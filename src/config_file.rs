@@ -0,0 +1,332 @@
+//! Configuration file support - loads `koshelf.toml` and merges it into the CLI arguments.
+//!
+//! Every CLI option can also be set in the file. Values given explicitly on the command
+//! line always win over values from the file. Relative paths in the file are resolved
+//! against the directory containing the file.
+
+use crate::cli::Cli;
+use crate::config::{
+    CompletionSettings, GoalsConfig, ItemIdScheme, LibraryPathOptions, ProfileOptions,
+};
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name looked up in the XDG config directory when `--config` is not given.
+pub const CONFIG_FILE_NAME: &str = "koshelf.toml";
+
+/// Contents of a `koshelf.toml` configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    /// Library folders, either plain paths or tables with per-path options
+    #[serde(default, alias = "library")]
+    pub library_path: Vec<LibraryPathEntry>,
+    pub docsettings_path: Option<PathBuf>,
+    pub hashdocsettings_path: Option<PathBuf>,
+    /// One or more statistics databases, either plain paths or tables with a device name
+    #[serde(default)]
+    pub statistics_db: Option<OneOrMany<StatisticsDbEntry>>,
+    pub output: Option<PathBuf>,
+    pub port: Option<u16>,
+    pub watch: Option<bool>,
+    pub title: Option<String>,
+    pub include_unread: Option<bool>,
+    pub heatmap_scale_max: Option<String>,
+    pub timezone: Option<String>,
+    pub day_start_time: Option<String>,
+    pub min_pages_per_day: Option<u32>,
+    pub min_time_per_day: Option<String>,
+    pub session_gap: Option<String>,
+    pub include_all_stats: Option<bool>,
+    pub language: Option<String>,
+    pub id_scheme: Option<ItemIdScheme>,
+    pub kosync_db: Option<PathBuf>,
+    pub kosync_registration: Option<bool>,
+    /// Reader profiles, each with their own metadata and statistics
+    #[serde(default, rename = "profile", alias = "profiles")]
+    pub profiles: Vec<ProfileTable>,
+    /// Yearly and monthly reading goals
+    #[serde(default)]
+    pub goals: GoalsConfig,
+    /// Read completion detection thresholds
+    #[serde(default)]
+    pub completion: CompletionSettings,
+}
+
+/// A `[[profile]]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileTable {
+    pub name: String,
+    pub docsettings_path: Option<PathBuf>,
+    pub hashdocsettings_path: Option<PathBuf>,
+    #[serde(default)]
+    pub statistics_db: Option<OneOrMany<StatisticsDbEntry>>,
+    pub timezone: Option<String>,
+    pub language: Option<String>,
+}
+
+/// A library folder entry: `"~/Books"` or `{ path = "~/Books", include_unread = true }`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum LibraryPathEntry {
+    Path(PathBuf),
+    Table(LibraryPathTable),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LibraryPathTable {
+    pub path: PathBuf,
+    /// Overrides the global `include_unread` for items in this folder
+    pub include_unread: Option<bool>,
+}
+
+/// A statistics database entry: `"~/Kobo/statistics.sqlite3"` or
+/// `{ path = "~/Kobo/statistics.sqlite3", device = "Kobo" }`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StatisticsDbEntry {
+    Path(PathBuf),
+    Table(StatisticsDbTable),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatisticsDbTable {
+    pub path: PathBuf,
+    /// Device name shown in the statistics device filter
+    pub device: Option<String>,
+}
+
+/// Accepts either a single value or a list of values.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+impl FileConfig {
+    /// Parse a configuration file from disk.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("Invalid config file: {:?}", path))
+    }
+
+    /// Parse configuration from a TOML string.
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Locate the default config file (`$XDG_CONFIG_HOME/koshelf/koshelf.toml`,
+    /// falling back to `~/.config/koshelf/koshelf.toml`). Returns `None` if it does not exist.
+    pub fn discover() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        let path = config_home.join("koshelf").join(CONFIG_FILE_NAME);
+        path.is_file().then_some(path)
+    }
+
+    /// Merge file values into `cli`. Options that were passed on the command line are kept.
+    pub fn apply(self, cli: &mut Cli, matches: &ArgMatches, base_dir: &Path) {
+        let resolve = |path: PathBuf| resolve_path(base_dir, path);
+
+        // Per-path options are kept even when the paths themselves come from the CLI,
+        // so `-i ~/Books` still picks up `{ path = "~/Books", include_unread = true }`.
+        let mut library_paths = Vec::new();
+        for entry in self.library_path {
+            match entry {
+                LibraryPathEntry::Path(path) => library_paths.push(resolve(path)),
+                LibraryPathEntry::Table(table) => {
+                    let path = resolve(table.path);
+                    let options = LibraryPathOptions {
+                        include_unread: table.include_unread,
+                    };
+                    cli.library_options.insert(path.clone(), options);
+                    library_paths.push(path);
+                }
+            }
+        }
+        if !set_on_cli(matches, "library_path") {
+            cli.library_path = library_paths;
+        }
+
+        // Device names are kept for CLI paths too, like the per-library-path options above.
+        let statistics_paths = resolve_statistics_entries(
+            self.statistics_db,
+            &mut cli.statistics_db_devices,
+            &resolve,
+        );
+        if !set_on_cli(matches, "statistics_db") && !statistics_paths.is_empty() {
+            cli.statistics_db = statistics_paths;
+        }
+
+        cli.goals = self.goals;
+        cli.completion = self.completion;
+
+        for profile in self.profiles {
+            let mut options = ProfileOptions {
+                name: profile.name,
+                docsettings_path: profile.docsettings_path.map(resolve),
+                hashdocsettings_path: profile.hashdocsettings_path.map(resolve),
+                timezone: profile.timezone,
+                language: profile.language,
+                ..Default::default()
+            };
+            options.statistics_db = resolve_statistics_entries(
+                profile.statistics_db,
+                &mut options.statistics_db_devices,
+                &resolve,
+            );
+            cli.profiles.push(options);
+        }
+
+        // Metadata location flags are mutually exclusive, so either one on the CLI overrides both.
+        if !set_on_cli(matches, "docsettings_path") && !set_on_cli(matches, "hashdocsettings_path")
+        {
+            cli.docsettings_path = self.docsettings_path.map(resolve);
+            cli.hashdocsettings_path = self.hashdocsettings_path.map(resolve);
+        }
+
+        merge(
+            &mut cli.output,
+            self.output.map(resolve).map(Some),
+            matches,
+            "output",
+        );
+        merge(&mut cli.port, self.port, matches, "port");
+        merge(&mut cli.watch, self.watch, matches, "watch");
+        merge(&mut cli.title, self.title, matches, "title");
+        merge(
+            &mut cli.include_unread,
+            self.include_unread,
+            matches,
+            "include_unread",
+        );
+        merge(
+            &mut cli.heatmap_scale_max,
+            self.heatmap_scale_max,
+            matches,
+            "heatmap_scale_max",
+        );
+        merge(
+            &mut cli.timezone,
+            self.timezone.map(Some),
+            matches,
+            "timezone",
+        );
+        merge(
+            &mut cli.day_start_time,
+            self.day_start_time.map(Some),
+            matches,
+            "day_start_time",
+        );
+        merge(
+            &mut cli.min_pages_per_day,
+            self.min_pages_per_day.map(Some),
+            matches,
+            "min_pages_per_day",
+        );
+        merge(
+            &mut cli.min_time_per_day,
+            self.min_time_per_day.map(Some),
+            matches,
+            "min_time_per_day",
+        );
+        merge(
+            &mut cli.session_gap,
+            self.session_gap,
+            matches,
+            "session_gap",
+        );
+        merge(
+            &mut cli.include_all_stats,
+            self.include_all_stats,
+            matches,
+            "include_all_stats",
+        );
+        merge(&mut cli.language, self.language, matches, "language");
+        merge(&mut cli.id_scheme, self.id_scheme, matches, "id_scheme");
+        merge(
+            &mut cli.kosync_db,
+            self.kosync_db.map(resolve).map(Some),
+            matches,
+            "kosync_db",
+        );
+        merge(
+            &mut cli.kosync_registration,
+            self.kosync_registration,
+            matches,
+            "kosync_registration",
+        );
+    }
+}
+
+/// Resolve statistics database entries, recording device names in `devices`.
+fn resolve_statistics_entries(
+    entries: Option<OneOrMany<StatisticsDbEntry>>,
+    devices: &mut HashMap<PathBuf, String>,
+    resolve: &impl Fn(PathBuf) -> PathBuf,
+) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for entry in entries.map(OneOrMany::into_vec).unwrap_or_default() {
+        match entry {
+            StatisticsDbEntry::Path(path) => paths.push(resolve(path)),
+            StatisticsDbEntry::Table(table) => {
+                let path = resolve(table.path);
+                if let Some(device) = table.device.filter(|d| !d.trim().is_empty()) {
+                    devices.insert(path.clone(), device.trim().to_string());
+                }
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+fn set_on_cli(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+fn merge<T>(target: &mut T, file_value: Option<T>, matches: &ArgMatches, id: &str) {
+    if let Some(value) = file_value
+        && !set_on_cli(matches, id)
+    {
+        *target = value;
+    }
+}
+
+/// Expand a leading `~` and resolve relative paths against the config file's directory.
+fn resolve_path(base_dir: &Path, path: PathBuf) -> PathBuf {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(rest),
+            None => path,
+        },
+        Err(_) => path,
+    };
+    if path.is_relative() {
+        base_dir.join(path)
+    } else {
+        path
+    }
+}
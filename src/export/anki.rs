@@ -0,0 +1,70 @@
+//! Anki TSV export: one Basic card per highlight, with the highlight on the front and its
+//! note and source on the back.
+//!
+//! The file header tells Anki (2.1.54+) the note type and columns. Cards carry a GUID derived
+//! from the highlight, so importing a newer export updates cards instead of duplicating them.
+
+use super::{AnnotationExporter, ExportItem, ExportSummary, output_file};
+use crate::utils::generate_slug;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+const HEADER: &str = "#separator:tab\n#html:true\n#notetype:Basic\n#guid column:1\n#tags column:4\n#columns:GUID\tFront\tBack\tTags\n";
+
+pub struct AnkiExporter;
+
+impl AnnotationExporter for AnkiExporter {
+    fn export(&self, items: &[ExportItem], output: &Path) -> Result<ExportSummary> {
+        let path = output_file(output, "koshelf-anki.tsv");
+        fs::write(&path, render_tsv(items))
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(ExportSummary {
+            items: items.len(),
+            highlights: items.iter().map(|export| export.highlights.len()).sum(),
+        })
+    }
+}
+
+fn render_tsv(items: &[ExportItem]) -> String {
+    let mut tsv = HEADER.to_string();
+    for export in items {
+        let tags = format!("koshelf koshelf::{}", generate_slug(export.title()));
+        let mut source = html(export.title());
+        if !export.item.book_info.authors.is_empty() {
+            source.push_str(&format!(" — {}", html(&export.authors())));
+        }
+        for highlight in &export.highlights {
+            let mut back = String::new();
+            if let Some(note) = highlight.note() {
+                back.push_str(&html(note));
+                back.push_str("<br><br>");
+            }
+            back.push_str(&format!("<small>{}", source));
+            if let Some(location) = highlight.location() {
+                back.push_str(&format!("<br>{}", html(&location)));
+            }
+            back.push_str("</small>");
+
+            let guid = format!("koshelf-{}", &highlight.digest[..16]);
+            tsv.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                guid,
+                html(highlight.text()),
+                back,
+                tags
+            ));
+        }
+    }
+    tsv
+}
+
+/// Escape text for an HTML field on a single TSV line.
+fn html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\t', " ")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
@@ -0,0 +1,155 @@
+//! Logseq graph export: one page per item below `pages/`.
+//!
+//! Highlights are quote blocks with an `id::` property (a UUID derived from the highlight),
+//! notes are child blocks. Re-exporting into the same graph refreshes the page properties
+//! and appends only highlights whose ID is not on the page yet.
+
+use super::{AnnotationExporter, ExportHighlight, ExportItem, ExportSummary, note_names};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static BLOCK_UUID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s+id:: ([0-9a-f-]{36})\s*$").unwrap());
+
+pub struct LogseqExporter;
+
+impl AnnotationExporter for LogseqExporter {
+    fn export(&self, items: &[ExportItem], output: &Path) -> Result<ExportSummary> {
+        let pages_dir = output.join("pages");
+        fs::create_dir_all(&pages_dir)
+            .with_context(|| format!("Failed to create pages folder {:?}", pages_dir))?;
+
+        let mut summary = ExportSummary::default();
+        for (export, name) in items.iter().zip(note_names(items)) {
+            let path = pages_dir.join(format!("{}.md", name));
+            let existing = match fs::read_to_string(&path) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+            };
+            let (page, added) = render_page(export, existing.as_deref());
+            fs::write(&path, page).with_context(|| format!("Failed to write {:?}", path))?;
+            summary.items += 1;
+            summary.highlights += added;
+        }
+        Ok(summary)
+    }
+}
+
+/// Render the page of an item, merged into the `existing` page if there is one. Returns the
+/// page and the number of highlights added to it.
+fn render_page(export: &ExportItem, existing: Option<&str>) -> (String, usize) {
+    // Page properties are the lines before the first block
+    let blocks = existing
+        .map(|existing| match existing.find("\n- ") {
+            Some(start) => existing[start + 1..].trim_end(),
+            None if existing.starts_with("- ") => existing.trim_end(),
+            None => "",
+        })
+        .unwrap_or_default();
+
+    let present: HashSet<&str> = BLOCK_UUID
+        .captures_iter(blocks)
+        .filter_map(|c| c.get(1))
+        .map(|m| m.as_str())
+        .collect();
+    let new_blocks: Vec<String> = export
+        .highlights
+        .iter()
+        .filter(|h| !present.contains(block_uuid(h).as_str()))
+        .map(block)
+        .collect();
+
+    let mut page = properties(export);
+    page.push('\n');
+    if !blocks.is_empty() {
+        page.push_str(blocks);
+        page.push('\n');
+    }
+    for block in &new_blocks {
+        page.push_str(block);
+    }
+    (page, new_blocks.len())
+}
+
+/// Block UUID of a highlight: its digest in UUID form (version 3, as it is MD5-based).
+fn block_uuid(highlight: &ExportHighlight) -> String {
+    let d = &highlight.digest;
+    let variant = (u8::from_str_radix(&d[16..17], 16).unwrap_or(0) & 0x3) | 0x8;
+    format!(
+        "{}-{}-3{}-{:x}{}-{}",
+        &d[0..8],
+        &d[8..12],
+        &d[13..16],
+        variant,
+        &d[17..20],
+        &d[20..32]
+    )
+}
+
+fn block(highlight: &ExportHighlight) -> String {
+    let mut lines: Vec<String> = highlight
+        .text()
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { "- > " } else { "  > " };
+            format!("{}{}", prefix, line.trim_end())
+        })
+        .collect();
+    if let Some(location) = highlight.location() {
+        lines.push(format!("  > — {}", location));
+    }
+    lines.push(format!("  id:: {}", block_uuid(highlight)));
+    if let Some(note) = highlight.note() {
+        for (i, line) in note.lines().enumerate() {
+            let prefix = if i == 0 { "\t- " } else { "\t  " };
+            lines.push(format!("{}{}", prefix, line.trim_end()));
+        }
+    }
+    let mut block = lines.join("\n");
+    block.push('\n');
+    block
+}
+
+fn properties(export: &ExportItem) -> String {
+    let item = export.item;
+    let mut properties = vec![format!("title:: {}", property_value(export.title()))];
+    if !item.book_info.authors.is_empty() {
+        let authors: Vec<String> = item
+            .book_info
+            .authors
+            .iter()
+            .map(|author| format!("[[{}]]", property_value(author)))
+            .collect();
+        properties.push(format!("author:: {}", authors.join(", ")));
+    }
+    // Not `series_display()`: a `#` would turn the number into a tag
+    if let Some(series) = item.series() {
+        properties.push(format!("series:: {}", property_value(series)));
+    }
+    if let Some(number) = item.series_number() {
+        properties.push(format!("series-number:: {}", property_value(number)));
+    }
+    properties.push(format!("status:: {}", item.status()));
+    properties.push(format!(
+        "progress:: {}%",
+        item.progress_percentage_display()
+    ));
+    if let Some(rating) = item.rating() {
+        properties.push(format!("rating:: {}", rating));
+    }
+    properties.push("tags:: koshelf".to_string());
+    let mut properties = properties.join("\n");
+    properties.push('\n');
+    properties
+}
+
+/// Property values are single-line.
+fn property_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,223 @@
+//! Annotation export to note-taking and flashcard apps (`koshelf export-annotations`).
+//!
+//! Each target implements [`AnnotationExporter`]:
+//! - `obsidian`: One note per item in an Obsidian vault, with YAML frontmatter
+//! - `logseq`: One page per item in a Logseq graph
+//! - `readwise`: Readwise-compatible CSV
+//! - `anki`: Anki-importable TSV, one card per highlight
+//!
+//! Every highlight gets an identifier derived from the item and the highlighted range, so
+//! re-exports update what an earlier export wrote instead of duplicating it.
+
+mod anki;
+mod logseq;
+mod obsidian;
+mod readwise;
+
+use crate::models::{Annotation, LibraryItem};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub use anki::AnkiExporter;
+pub use logseq::LogseqExporter;
+pub use obsidian::ObsidianExporter;
+pub use readwise::ReadwiseExporter;
+
+/// Where `koshelf export-annotations` writes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportTarget {
+    /// Obsidian vault folder (one note per item)
+    Obsidian,
+    /// Logseq graph folder (one page per item below `pages/`)
+    Logseq,
+    /// Readwise CSV file
+    Readwise,
+    /// Anki TSV file
+    Anki,
+}
+
+impl ExportTarget {
+    pub fn exporter(self) -> Box<dyn AnnotationExporter> {
+        match self {
+            Self::Obsidian => Box::new(ObsidianExporter),
+            Self::Logseq => Box::new(LogseqExporter),
+            Self::Readwise => Box::new(ReadwiseExporter),
+            Self::Anki => Box::new(AnkiExporter),
+        }
+    }
+}
+
+/// An export target.
+pub trait AnnotationExporter {
+    /// Write the highlights of `items` to `output` (a folder or a file, depending on the target).
+    fn export(&self, items: &[ExportItem], output: &Path) -> Result<ExportSummary>;
+}
+
+/// What an export wrote.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// Items with at least one highlight
+    pub items: usize,
+    /// Highlights written (for merging targets: only those not present before)
+    pub highlights: usize,
+}
+
+/// A highlight to export.
+pub struct ExportHighlight<'a> {
+    pub annotation: &'a Annotation,
+    /// Hex digest identifying the highlight across exports. It depends on the item and the
+    /// highlighted range only, so editing the note keeps it.
+    pub digest: String,
+}
+
+impl ExportHighlight<'_> {
+    /// Highlighted text (never empty).
+    pub fn text(&self) -> &str {
+        self.annotation.text.as_deref().unwrap_or_default().trim()
+    }
+
+    /// Note attached to the highlight, if any.
+    pub fn note(&self) -> Option<&str> {
+        self.annotation
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty())
+    }
+
+    /// Where the highlight is, e.g. "Chapter 3 · p. 42".
+    pub fn location(&self) -> Option<String> {
+        let chapter = self
+            .annotation
+            .chapter
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty());
+        let page = self.annotation.pageno.map(|page| format!("p. {}", page));
+        match (chapter, page) {
+            (Some(chapter), Some(page)) => Some(format!("{} · {}", chapter, page)),
+            (Some(chapter), None) => Some(chapter.to_string()),
+            (None, page) => page,
+        }
+    }
+}
+
+/// An item with its highlights.
+pub struct ExportItem<'a> {
+    pub item: &'a LibraryItem,
+    pub highlights: Vec<ExportHighlight<'a>>,
+}
+
+impl<'a> ExportItem<'a> {
+    /// Items that have highlights with text, ordered by title. Highlights keep KoReader's
+    /// order (by position in the book).
+    pub fn collect(items: &'a [LibraryItem]) -> Vec<Self> {
+        let mut export_items: Vec<Self> = items
+            .iter()
+            .filter_map(|item| {
+                let book_key = item
+                    .koreader_metadata
+                    .as_ref()
+                    .and_then(|m| m.partial_md5_checksum.as_deref())
+                    .unwrap_or(&item.id);
+                let highlights: Vec<_> = item
+                    .annotations()
+                    .iter()
+                    .filter(|a| {
+                        a.is_highlight() && a.text.as_deref().is_some_and(|t| !t.trim().is_empty())
+                    })
+                    .map(|annotation| ExportHighlight {
+                        annotation,
+                        digest: highlight_digest(book_key, annotation),
+                    })
+                    .collect();
+                (!highlights.is_empty()).then_some(ExportItem { item, highlights })
+            })
+            .collect();
+        export_items.sort_by(|a, b| {
+            a.item
+                .book_info
+                .title
+                .to_lowercase()
+                .cmp(&b.item.book_info.title.to_lowercase())
+                .then_with(|| a.item.id.cmp(&b.item.id))
+        });
+        export_items
+    }
+
+    pub fn title(&self) -> &str {
+        &self.item.book_info.title
+    }
+
+    pub fn authors(&self) -> String {
+        self.item.book_info.authors.join(", ")
+    }
+}
+
+/// Digest of a highlight: the item plus the highlighted range, falling back to the page,
+/// time and text for annotations without a range.
+fn highlight_digest(book_key: &str, annotation: &Annotation) -> String {
+    let location = match (&annotation.pos0, &annotation.pos1) {
+        (Some(pos0), Some(pos1)) => format!("{}\u{0}{}", pos0, pos1),
+        _ => format!(
+            "{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}",
+            annotation.page, annotation.pageno, annotation.datetime, annotation.text
+        ),
+    };
+    format!(
+        "{:x}",
+        md5::compute(format!("{}\u{0}{}", book_key, location))
+    )
+}
+
+/// File name (without extension) for an item's note: the title without characters that
+/// file systems or note links reject.
+fn note_name(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let name = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = name.trim_end_matches('.').trim();
+    if name.is_empty() {
+        "Untitled".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Note names for all items. Items that share a title all get their ID appended, so a
+/// note keeps its name no matter which other items are exported alongside it.
+fn note_names(items: &[ExportItem]) -> Vec<String> {
+    let names: Vec<String> = items.iter().map(|e| note_name(e.title())).collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for name in &names {
+        *counts.entry(name.to_lowercase()).or_default() += 1;
+    }
+    items
+        .iter()
+        .zip(names)
+        .map(|(export, name)| {
+            if counts[&name.to_lowercase()] > 1 {
+                format!("{} ({})", name, export.item.id)
+            } else {
+                name
+            }
+        })
+        .collect()
+}
+
+/// File to write a single-file export to: `output` itself, or `default_name` inside it if
+/// `output` is an existing folder.
+fn output_file(output: &Path, default_name: &str) -> PathBuf {
+    if output.is_dir() {
+        output.join(default_name)
+    } else {
+        output.to_path_buf()
+    }
+}
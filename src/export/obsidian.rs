@@ -0,0 +1,186 @@
+//! Obsidian vault export: one Markdown note per item with YAML frontmatter.
+//!
+//! Highlights are quote blocks followed by a `^kos-…` block ID, so they can be linked from
+//! other notes. Re-exporting into the same vault refreshes the frontmatter and appends only
+//! highlights whose block ID is not in the note yet; everything else in the note is kept.
+
+use super::{AnnotationExporter, ExportHighlight, ExportItem, ExportSummary, note_names};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static BLOCK_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\^(kos-[0-9a-f]+)[ \t]*$").unwrap());
+
+pub struct ObsidianExporter;
+
+impl AnnotationExporter for ObsidianExporter {
+    fn export(&self, items: &[ExportItem], output: &Path) -> Result<ExportSummary> {
+        fs::create_dir_all(output)
+            .with_context(|| format!("Failed to create vault folder {:?}", output))?;
+
+        let mut summary = ExportSummary::default();
+        for (export, name) in items.iter().zip(note_names(items)) {
+            let path = output.join(format!("{}.md", name));
+            let existing = match fs::read_to_string(&path) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+            };
+            let (note, added) = render_note(export, existing.as_deref());
+            fs::write(&path, note).with_context(|| format!("Failed to write {:?}", path))?;
+            summary.items += 1;
+            summary.highlights += added;
+        }
+        Ok(summary)
+    }
+}
+
+/// Render the note of an item, merged into the `existing` note if there is one. Returns the
+/// note and the number of highlights added to it.
+fn render_note(export: &ExportItem, existing: Option<&str>) -> (String, usize) {
+    let (frontmatter, body) = match existing {
+        Some(existing) => {
+            let (old_frontmatter, body) = split_frontmatter(existing);
+            (
+                frontmatter(export, old_frontmatter),
+                body.trim_start_matches('\n').trim_end().to_string(),
+            )
+        }
+        None => (
+            frontmatter(export, None),
+            format!("# {}\n\n## Highlights", export.title()),
+        ),
+    };
+
+    let present: HashSet<&str> = BLOCK_ID
+        .captures_iter(&body)
+        .filter_map(|c| c.get(1))
+        .map(|m| m.as_str())
+        .collect();
+    let new_blocks: Vec<String> = export
+        .highlights
+        .iter()
+        .filter(|h| !present.contains(block_id(h).as_str()))
+        .map(block)
+        .collect();
+
+    let mut note = format!("{}\n{}\n", frontmatter, body);
+    for block in &new_blocks {
+        note.push('\n');
+        note.push_str(block);
+    }
+    (note, new_blocks.len())
+}
+
+/// Obsidian block ID of a highlight.
+fn block_id(highlight: &ExportHighlight) -> String {
+    format!("kos-{}", &highlight.digest[..12])
+}
+
+fn block(highlight: &ExportHighlight) -> String {
+    let mut lines: Vec<String> = quote_lines(highlight.text());
+    if let Some(note) = highlight.note() {
+        lines.push(">".to_string());
+        let mut note_lines = quote_lines(note);
+        note_lines[0] = format!("> **Note:** {}", &note_lines[0][2..]);
+        lines.extend(note_lines);
+    }
+    if let Some(location) = highlight.location() {
+        lines.push(">".to_string());
+        lines.push(format!("> — {}", location));
+    }
+    format!("{}\n\n^{}\n", lines.join("\n"), block_id(highlight))
+}
+
+fn quote_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| format!("> {}", line.trim_end()).trim_end().to_string())
+        .collect()
+}
+
+/// Split a note into its frontmatter (without the `---` fences) and body.
+fn split_frontmatter(note: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = note.strip_prefix("---\n")
+        && let Some(end) = rest.find("\n---\n")
+    {
+        return (Some(&rest[..end]), &rest[end + 5..]);
+    }
+    (None, note)
+}
+
+/// YAML frontmatter of an item. Properties of an `old` frontmatter that KoShelf does not
+/// write (e.g. added by the user) are kept.
+fn frontmatter(export: &ExportItem, old: Option<&str>) -> String {
+    let item = export.item;
+    let mut properties = vec![format!("title: {}", yaml_string(export.title()))];
+    if !item.book_info.authors.is_empty() {
+        properties.push(yaml_list("authors", &item.book_info.authors));
+    }
+    if let Some(series) = item.series_display() {
+        properties.push(format!("series: {}", yaml_string(&series)));
+    }
+    if let Some(language) = item.language() {
+        properties.push(format!("language: {}", yaml_string(language)));
+    }
+    properties.push(format!("status: {}", item.status()));
+    properties.push(format!("progress: {}", item.progress_percentage_display()));
+    if let Some(rating) = item.rating() {
+        properties.push(format!("rating: {}", rating));
+    }
+    properties.push(format!("highlights: {}", export.highlights.len()));
+    properties.push(yaml_list("tags", &["koshelf".to_string()]));
+
+    let ours: HashSet<&str> = properties
+        .iter()
+        .filter_map(|p| p.split(':').next())
+        .collect();
+    let kept = old
+        .map(|old| top_level_entries(old))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(key, _)| !ours.contains(key))
+        .map(|(_, entry)| entry);
+    let properties: Vec<String> = properties.iter().cloned().chain(kept).collect();
+
+    format!("---\n{}\n---\n", properties.join("\n"))
+}
+
+/// Top-level entries of a YAML mapping as (key, lines), including nested lines.
+fn top_level_entries(yaml: &str) -> Vec<(&str, String)> {
+    let mut entries: Vec<(&str, String)> = Vec::new();
+    for line in yaml.lines() {
+        let nested = line.starts_with([' ', '\t', '-']) || line.trim().is_empty();
+        match (nested, entries.last_mut()) {
+            (true, Some((_, entry))) => {
+                entry.push('\n');
+                entry.push_str(line);
+            }
+            (true, None) => {}
+            (false, _) => {
+                let key = line.split(':').next().unwrap_or(line).trim();
+                entries.push((key, line.to_string()));
+            }
+        }
+    }
+    entries
+}
+
+fn yaml_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn yaml_list(key: &str, values: &[String]) -> String {
+    let mut list = format!("{}:", key);
+    for value in values {
+        list.push_str(&format!("\n  - {}", yaml_string(value)));
+    }
+    list
+}
@@ -0,0 +1,70 @@
+//! Readwise CSV export (https://readwise.io/import_bulk).
+//!
+//! The file is written from scratch on every export; Readwise skips highlights it already
+//! has when importing.
+
+use super::{AnnotationExporter, ExportItem, ExportSummary, output_file};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+const HEADER: [&str; 8] = [
+    "Highlight",
+    "Title",
+    "Author",
+    "URL",
+    "Note",
+    "Location",
+    "Location Type",
+    "Date",
+];
+
+pub struct ReadwiseExporter;
+
+impl AnnotationExporter for ReadwiseExporter {
+    fn export(&self, items: &[ExportItem], output: &Path) -> Result<ExportSummary> {
+        let path = output_file(output, "koshelf-readwise.csv");
+        fs::write(&path, render_csv(items))
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(ExportSummary {
+            items: items.len(),
+            highlights: items.iter().map(|export| export.highlights.len()).sum(),
+        })
+    }
+}
+
+fn render_csv(items: &[ExportItem]) -> String {
+    let mut csv = csv_row(HEADER.iter().map(|h| h.to_string()));
+    for export in items {
+        for highlight in &export.highlights {
+            let annotation = highlight.annotation;
+            let page = annotation.pageno.map(|page| page.to_string());
+            csv.push_str(&csv_row([
+                highlight.text().to_string(),
+                export.title().to_string(),
+                export.authors(),
+                String::new(),
+                highlight.note().unwrap_or_default().to_string(),
+                page.clone().unwrap_or_default(),
+                page.map(|_| "page".to_string()).unwrap_or_default(),
+                annotation.datetime.clone().unwrap_or_default(),
+            ]));
+        }
+    }
+    csv
+}
+
+/// A CSV record (RFC 4180): fields with separators, quotes or line breaks are quoted.
+fn csv_row(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
@@ -92,7 +92,7 @@ impl Translations {
     /// Frontend will use these to create its own Bundle.
     /// Comments and blank lines are stripped to reduce payload size.
     pub fn to_json_string(&self) -> String {
-        let bcp47_language = self.language.replace('_', "-");
+        let bcp47_language = self.html_lang();
         // Strip comments and blank lines from FTL content
         let stripped_resources: Vec<String> = self
             .resources_content
@@ -176,6 +176,11 @@ impl Translations {
         chrono::Locale::from_str(&self.language).unwrap_or(chrono::Locale::en_US)
     }
 
+    /// The language as a BCP 47 tag (e.g. `de-DE`), for `lang` attributes.
+    pub fn html_lang(&self) -> String {
+        self.language.replace('_', "-")
+    }
+
     #[cfg(test)]
     pub fn language(&self) -> &str {
         &self.language
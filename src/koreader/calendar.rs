@@ -32,7 +32,7 @@ impl CalendarGenerator {
         //
         // Prefer MD5 from KOReader metadata, but fall back to calculating a compatible partial MD5
         // from the file. This enables calendar → detail linking even for items without metadata.
-        let md5_to_book_info: HashMap<String, (String, String, ContentType)> = books
+        let md5_to_book_info: HashMap<String, (String, Option<String>, ContentType)> = books
             .iter()
             .filter_map(|b| {
                 let md5 = b
@@ -40,7 +40,10 @@ impl CalendarGenerator {
                     .as_ref()
                     .and_then(|m| m.partial_md5_checksum.as_ref())
                     .cloned()
-                    .or_else(|| calculate_partial_md5(&b.file_path).ok());
+                    .or_else(|| {
+                        b.file_path()
+                            .and_then(|path| calculate_partial_md5(path).ok())
+                    });
 
                 md5.map(|md5| {
                     (
@@ -50,7 +53,8 @@ impl CalendarGenerator {
                                 ContentType::Book => format!("/books/{}/", b.id),
                                 ContentType::Comic => format!("/comics/{}/", b.id),
                             },
-                            format!("/assets/covers/{}.webp", b.id),
+                            // Items only known from the statistics have no cover
+                            (!b.is_stats_only()).then(|| format!("/assets/covers/{}.webp", b.id)),
                             b.content_type(),
                         ),
                     )
@@ -77,7 +81,7 @@ impl CalendarGenerator {
                 let authors = Self::parse_authors(&stat_book.authors);
                 let (item_path, item_cover, content_type_from_library) = md5_to_book_info
                     .get(&stat_book.md5.to_lowercase())
-                    .map(|(path, cover, ct)| (Some(path.clone()), Some(cover.clone()), Some(*ct)))
+                    .map(|(path, cover, ct)| (Some(path.clone()), cover.clone(), Some(*ct)))
                     .unwrap_or((None, None, None));
 
                 let content_type = content_type_from_library
@@ -115,12 +119,27 @@ impl CalendarGenerator {
             }
             days.sort();
 
+            // Reading sessions of this book by the day they started on
+            let session_days: Vec<NaiveDate> = stats_data
+                .sessions
+                .iter()
+                .filter(|s| s.id_book == book_id)
+                .filter_map(|s| {
+                    NaiveDate::parse_from_str(
+                        &Self::timestamp_to_date_string(s.start_time, time_config),
+                        "%Y-%m-%d",
+                    )
+                    .ok()
+                })
+                .collect();
+
             // Process reading days and create calendar events
             Self::create_calendar_events_from_reading_days(
                 &mut calendar_events,
                 &calendar_item_id,
                 &days,
                 &sessions_by_day,
+                &session_days,
             );
         }
 
@@ -249,6 +268,7 @@ impl CalendarGenerator {
         calendar_item_id: &str,
         days: &[NaiveDate],
         sessions_by_day: &HashMap<NaiveDate, Vec<&PageStat>>,
+        session_days: &[NaiveDate],
     ) {
         if days.is_empty() {
             return;
@@ -276,6 +296,7 @@ impl CalendarGenerator {
                     span_start,
                     prev_day,
                     &span_sessions,
+                    session_days,
                 );
 
                 // Start new streak
@@ -292,6 +313,7 @@ impl CalendarGenerator {
             span_start,
             prev_day,
             &span_sessions,
+            session_days,
         );
     }
 
@@ -302,9 +324,14 @@ impl CalendarGenerator {
         start_date: NaiveDate,
         end_date: NaiveDate,
         sessions: &[&PageStat],
+        session_days: &[NaiveDate],
     ) {
         let total_read_time: i64 = sessions.iter().map(|s| s.duration).sum();
         let total_pages_read = sessions.len() as i64;
+        let session_count = session_days
+            .iter()
+            .filter(|day| (start_date..=end_date).contains(day))
+            .count() as i64;
 
         let end_exclusive = if start_date == end_date {
             None
@@ -321,6 +348,7 @@ impl CalendarGenerator {
             end_exclusive,
             total_read_time,
             total_pages_read,
+            session_count,
             calendar_item_id.to_string(),
         );
 
@@ -31,16 +31,24 @@
 //!
 //! - **True re-reads**: When a user finishes a book, then starts again from the beginning
 //!   and reads through again, two separate completions are detected.
+//!
+//! ## Configuration
+//!
+//! Thresholds are set per content type in the `[completion.books]` and `[completion.comics]`
+//! tables of the configuration file. With `trust_status`, an item KOReader marks as finished
+//! counts its latest progression as a completion even when no progression meets the thresholds.
 
-use log::{debug, info};
-use std::collections::{HashMap, HashSet};
+use anyhow::Result;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-use super::session;
 use crate::models::*;
 use crate::time_config::TimeConfig;
 
 /// Configuration for read completion detection
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct CompletionConfig {
     /// Minimum percentage of book that must be read to count as completion (0.0 - 1.0)
     pub min_completion_percentage: f64,
@@ -61,6 +69,20 @@ impl Default for CompletionConfig {
 }
 
 impl CompletionConfig {
+    /// Check that all thresholds are fractions between 0.0 and 1.0.
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("min_completion_percentage", self.min_completion_percentage),
+            ("min_early_percentage", self.min_early_percentage),
+            ("min_late_percentage", self.min_late_percentage),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                anyhow::bail!("{} must be between 0.0 and 1.0, got {}", name, value);
+            }
+        }
+        Ok(())
+    }
+
     /// Calculate the early page threshold (pages considered "beginning" of the book)
     fn early_threshold(&self, total_pages: i64) -> i64 {
         (total_pages as f64 * self.min_early_percentage) as i64
@@ -113,40 +135,106 @@ impl ReadingProgression {
         self.stats.is_empty()
     }
 
+    /// Measure this progression against the completion thresholds
+    fn check(&self, total_pages: i64, config: &CompletionConfig) -> CompletionCheck {
+        let early_threshold = config.early_threshold(total_pages);
+        let late_threshold = config.late_threshold(total_pages);
+        CompletionCheck {
+            coverage: self.pages_visited.len() as f64 / total_pages as f64,
+            has_early_pages: self
+                .pages_visited
+                .iter()
+                .any(|&page| page <= early_threshold),
+            has_late_pages: self
+                .pages_visited
+                .iter()
+                .any(|&page| page >= late_threshold),
+        }
+    }
+
     /// Check if this progression qualifies as a valid completion
     fn is_valid_completion(&self, total_pages: i64, config: &CompletionConfig) -> bool {
-        if total_pages <= 0 {
-            return false;
-        }
+        total_pages > 0 && self.check(total_pages, config).passes(config)
+    }
+}
 
-        // Check minimum completion percentage
-        let pages_covered = self.pages_visited.len() as i64;
-        let completion_percentage = pages_covered as f64 / total_pages as f64;
-        if completion_percentage < config.min_completion_percentage {
-            return false;
-        }
+/// How a reading progression measures up against the completion thresholds
+#[derive(Debug, Clone, Copy)]
+struct CompletionCheck {
+    /// Share of the book's pages visited (0.0 - 1.0)
+    coverage: f64,
+    /// Whether pages from the beginning of the book were read
+    has_early_pages: bool,
+    /// Whether pages from the end of the book were read
+    has_late_pages: bool,
+}
 
-        // Check if we read from early and late parts of the book
-        let early_threshold = config.early_threshold(total_pages);
-        let late_threshold = config.late_threshold(total_pages);
+impl CompletionCheck {
+    fn passes(&self, config: &CompletionConfig) -> bool {
+        self.coverage >= config.min_completion_percentage
+            && self.has_early_pages
+            && self.has_late_pages
+    }
 
-        let has_early_pages = self
-            .pages_visited
-            .iter()
-            .any(|&page| page <= early_threshold);
-        let has_late_pages = self
-            .pages_visited
-            .iter()
-            .any(|&page| page >= late_threshold);
+    /// Human-readable explanation of the outcome, listing every failed threshold
+    fn reason(&self, total_pages: i64, config: &CompletionConfig) -> String {
+        let mut failed = Vec::new();
+        if self.coverage < config.min_completion_percentage {
+            failed.push(format!(
+                "covers {:.1}% of the pages, needs {:.0}%",
+                self.coverage * 100.0,
+                config.min_completion_percentage * 100.0
+            ));
+        }
+        if !self.has_early_pages {
+            failed.push(format!(
+                "no pages read up to page {} (first {:.0}%)",
+                config.early_threshold(total_pages),
+                config.min_early_percentage * 100.0
+            ));
+        }
+        if !self.has_late_pages {
+            failed.push(format!(
+                "no pages read from page {} on (last {:.0}%)",
+                config.late_threshold(total_pages),
+                config.min_late_percentage * 100.0
+            ));
+        }
 
-        has_early_pages && has_late_pages
+        if failed.is_empty() {
+            format!(
+                "covers {:.1}% of the pages, including the beginning and the end",
+                self.coverage * 100.0
+            )
+        } else {
+            failed.join("; ")
+        }
     }
 }
 
+/// A detected reading progression and whether it was counted as a completion, as printed
+/// by `koshelf inspect completions`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressionReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub reading_time: i64,
+    pub session_count: i64,
+    pub pages_visited: usize,
+    pub coverage_percentage: f64,
+    pub has_early_pages: bool,
+    pub has_late_pages: bool,
+    pub counted: bool,
+    /// Why the progression was or was not counted
+    pub reason: String,
+}
+
 /// Main detector for reading completions
 pub struct ReadCompletionDetector {
     config: CompletionConfig,
     time_config: TimeConfig,
+    /// Count the latest progression of books KOReader marks as finished
+    trust_status: bool,
 }
 
 impl ReadCompletionDetector {
@@ -154,16 +242,24 @@ impl ReadCompletionDetector {
         Self {
             config,
             time_config,
+            trust_status: false,
         }
     }
 
-    /// Detect reading completions for a single book
-    pub fn detect_completions(&self, book: &StatBook, page_stats: &[PageStat]) -> BookCompletions {
-        debug!(
-            "Detecting completions for book: {} (pages: {:?})",
-            book.title, book.pages
-        );
+    /// Treat KOReader's "finished" status as authoritative: a finished book without a
+    /// detected completion counts its latest progression.
+    pub fn trusting_status(mut self, trust_status: bool) -> Self {
+        self.trust_status = trust_status;
+        self
+    }
 
+    /// Sorted reading progressions of a book and its page count, or `None` without usable
+    /// page stats.
+    fn progressions(
+        &self,
+        book: &StatBook,
+        page_stats: &[PageStat],
+    ) -> Option<(Vec<ReadingProgression>, i64)> {
         // Filter page stats for this book only and with valid durations
         let book_stats: Vec<PageStat> = page_stats
             .iter()
@@ -173,13 +269,13 @@ impl ReadCompletionDetector {
 
         if book_stats.is_empty() {
             debug!("No valid page stats found for book {}", book.title);
-            return BookCompletions::new(Vec::new());
+            return None;
         }
 
         let total_pages = book.pages.unwrap_or(0);
         if total_pages <= 0 {
             debug!("Book {} has no valid page count", book.title);
-            return BookCompletions::new(Vec::new());
+            return None;
         }
 
         // Sort stats by start time
@@ -194,42 +290,70 @@ impl ReadCompletionDetector {
             book.title
         );
 
+        Some((progressions, total_pages))
+    }
+
+    /// Index of the progression counted because the book is marked as finished: the latest
+    /// one, when status is trusted and no progression meets the thresholds.
+    fn trusted_progression(
+        &self,
+        progressions: &[ReadingProgression],
+        total_pages: i64,
+        marked_complete: bool,
+    ) -> Option<usize> {
+        let any_valid = progressions
+            .iter()
+            .any(|p| p.is_valid_completion(total_pages, &self.config));
+        (self.trust_status && marked_complete && !any_valid)
+            .then(|| progressions.len().checked_sub(1))
+            .flatten()
+    }
+
+    /// Detect reading completions for a single book. `marked_complete` is whether KOReader
+    /// marks the book as finished.
+    pub fn detect_completions(
+        &self,
+        book: &StatBook,
+        stats_data: &StatisticsData,
+        marked_complete: bool,
+    ) -> BookCompletions {
+        debug!(
+            "Detecting completions for book: {} (pages: {:?})",
+            book.title, book.pages
+        );
+        let Some((progressions, total_pages)) = self.progressions(book, &stats_data.page_stats)
+        else {
+            return BookCompletions::new(Vec::new());
+        };
+
         // Evaluate each progression for completion
-        let mut completions = Vec::new();
-        let early_threshold = self.config.early_threshold(total_pages);
-        let late_threshold = self.config.late_threshold(total_pages);
-        // Track best progression: (coverage%, pages, has_early, has_late)
-        let mut best_progression: Option<(f64, usize, bool, bool)> = None;
-        
-        for progression in &progressions {
-            let pages = progression.pages_visited.len();
-            let coverage = pages as f64 / total_pages as f64;
-            if best_progression.map_or(true, |(best, _, _, _)| coverage > best) {
-                let has_early = progression.pages_visited.iter().any(|&p| p <= early_threshold);
-                let has_late = progression.pages_visited.iter().any(|&p| p >= late_threshold);
-                best_progression = Some((coverage, pages, has_early, has_late));
-            }
-            
-            if let Some(completion) = self.evaluate_progression(progression, total_pages) {
-                completions.push(completion);
-            }
-        }
+        let mut completions: Vec<ReadCompletion> = progressions
+            .iter()
+            .filter_map(|progression| {
+                self.evaluate_progression(progression, total_pages, &stats_data.sessions)
+            })
+            .collect();
 
-        // Log helpful info about why no completions were found
-        if completions.is_empty() && !progressions.is_empty() {
-            if let Some((coverage, pages, has_early, has_late)) = best_progression {
+        if let Some(i) = self.trusted_progression(&progressions, total_pages, marked_complete) {
+            debug!(
+                "'{}' is marked as finished, counting its latest progression",
+                book.title
+            );
+            completions.push(self.completion_from(&progressions[i], &stats_data.sessions));
+        } else if completions.is_empty() {
+            // Log helpful info about why no completions were found
+            if let Some(best) = progressions
+                .iter()
+                .map(|p| p.check(total_pages, &self.config))
+                .max_by(|a, b| a.coverage.total_cmp(&b.coverage))
+            {
                 debug!(
-                    "No valid completions for '{}': best progression covered {:.1}% ({}/{} pages, need {:.0}%), early pages: {}, late pages: {}",
+                    "No valid completions for '{}': best progression {}",
                     book.title,
-                    coverage * 100.0,
-                    pages,
-                    total_pages,
-                    self.config.min_completion_percentage * 100.0,
-                    if has_early { "✓" } else { "✗" },
-                    if has_late { "✓" } else { "✗" },
+                    best.reason(total_pages, &self.config)
                 );
             }
-        } else if !completions.is_empty() {
+        } else {
             debug!(
                 "Detected {} completion(s) for '{}'",
                 completions.len(),
@@ -239,11 +363,54 @@ impl ReadCompletionDetector {
         BookCompletions::new(completions)
     }
 
+    /// Explain for each reading progression of a book whether and why it counts as a
+    /// completion.
+    pub fn explain(
+        &self,
+        book: &StatBook,
+        stats_data: &StatisticsData,
+        marked_complete: bool,
+    ) -> Vec<ProgressionReport> {
+        let Some((progressions, total_pages)) = self.progressions(book, &stats_data.page_stats)
+        else {
+            return Vec::new();
+        };
+        let trusted = self.trusted_progression(&progressions, total_pages, marked_complete);
+
+        progressions
+            .iter()
+            .enumerate()
+            .map(|(i, progression)| {
+                let check = progression.check(total_pages, &self.config);
+                let mut reason = check.reason(total_pages, &self.config);
+                let counted = if trusted == Some(i) {
+                    reason.push_str("; counted because KOReader marks the book as finished");
+                    true
+                } else {
+                    check.passes(&self.config)
+                };
+                let completion = self.completion_from(progression, &stats_data.sessions);
+                ProgressionReport {
+                    start_date: completion.start_date,
+                    end_date: completion.end_date,
+                    reading_time: completion.reading_time,
+                    session_count: completion.session_count,
+                    pages_visited: progression.pages_visited.len(),
+                    coverage_percentage: check.coverage * 100.0,
+                    has_early_pages: check.has_early_pages,
+                    has_late_pages: check.has_late_pages,
+                    counted,
+                    reason,
+                }
+            })
+            .collect()
+    }
+
     /// Group page stats into reading progressions based on re-read detection.
     /// A split occurs when:
     /// 1. Reading restarts from early pages (within min_early_percentage)
     /// 2. The remaining stats from that point would form a valid completion on their own
-    /// 
+    ///
     /// This handles both abandoned reads (split off incomplete portion) and true re-reads.
     fn group_into_progressions(
         &self,
@@ -266,8 +433,11 @@ impl ReadCompletionDetector {
             let restart_threshold = (total_pages as f64 * 0.05) as i64; // First 5% of book
             let prev_page = if i > 0 { sorted_stats[i - 1].page } else { 0 };
             let is_jumping_back = prev_page > early_page_threshold;
-            let already_started_reading = current_progression.pages_visited.iter().any(|&p| p <= early_page_threshold);
-            
+            let already_started_reading = current_progression
+                .pages_visited
+                .iter()
+                .any(|&p| p <= early_page_threshold);
+
             let is_likely_restart = !current_progression.is_empty()
                 && stat.page <= restart_threshold
                 && is_jumping_back
@@ -309,57 +479,49 @@ impl ReadCompletionDetector {
         &self,
         progression: &ReadingProgression,
         total_pages: i64,
+        sessions: &[ReadingSession],
     ) -> Option<ReadCompletion> {
         // Use shared validation logic with logging enabled for final evaluation
         if !progression.is_valid_completion(total_pages, &self.config) {
             return None;
         }
 
-        let pages_covered = progression.pages_visited.len() as i64;
+        let completion = self.completion_from(progression, sessions);
+        debug!(
+            "Valid completion found: {:.1}% completion, {} sessions, {} pages",
+            (completion.pages_read as f64 / total_pages as f64) * 100.0,
+            completion.session_count,
+            completion.pages_read
+        );
+        Some(completion)
+    }
 
-        // Calculate session count
-        let session_count = session::session_count(&progression.stats);
+    /// The completion a progression stands for, with the reading sessions overlapping it
+    fn completion_from(
+        &self,
+        progression: &ReadingProgression,
+        sessions: &[ReadingSession],
+    ) -> ReadCompletion {
+        let id_book = progression.stats.first().map(|stat| stat.id_book);
+        let session_count = sessions
+            .iter()
+            .filter(|s| {
+                Some(s.id_book) == id_book
+                    && s.start_time <= progression.end_time
+                    && s.end_time >= progression.start_time
+            })
+            .count() as i64;
 
         // Convert timestamps to dates
         let start_date = self.time_config.format_date(progression.start_time);
         let end_date = self.time_config.format_date(progression.end_time);
 
-        debug!(
-            "Valid completion found: {:.1}% completion, {} sessions, {} pages",
-            (pages_covered as f64 / total_pages as f64) * 100.0,
-            session_count,
-            pages_covered
-        );
-
-        Some(ReadCompletion::new(
+        ReadCompletion::new(
             start_date,
             end_date,
             progression.total_reading_time,
             session_count,
-            pages_covered,
-        ))
-    }
-
-    /// Detect completions for all books in the statistics data
-    pub fn detect_all_completions(
-        &self,
-        stats_data: &StatisticsData,
-    ) -> HashMap<String, BookCompletions> {
-        let mut all_completions = HashMap::new();
-
-        for book in &stats_data.books {
-            let completions = self.detect_completions(book, &stats_data.page_stats);
-            if completions.has_completions() {
-                all_completions.insert(book.md5.clone(), completions);
-            }
-        }
-
-        info!(
-            "Detected completions for {} books out of {}",
-            all_completions.len(),
-            stats_data.books.len()
-        );
-
-        all_completions
+            progression.pages_visited.len() as i64,
+        )
     }
 }
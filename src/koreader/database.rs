@@ -4,10 +4,19 @@ use crate::time_config::TimeConfig;
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
 use rusqlite::{Connection, OpenFlags};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+/// A statistics database and the name of the device it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatisticsSource {
+    pub path: PathBuf,
+    /// Device name page stats from this database are tagged with (optional)
+    pub device: Option<String>,
+}
+
 /// Parser for KoReader statistics database
 pub struct StatisticsParser;
 
@@ -58,6 +67,7 @@ impl StatisticsParser {
             books,
             page_stats,
             stats_by_md5,
+            sessions: Vec::new(),
         };
 
         info!(
@@ -69,9 +79,150 @@ impl StatisticsParser {
         Ok(stats_data)
     }
 
+    /// Parse several statistics databases (e.g. one per device) and merge them into one.
+    ///
+    /// Books are matched by MD5. Book IDs from later databases are remapped so that
+    /// page stats of the same book share one ID across all databases.
+    pub fn parse_many<P: AsRef<Path>>(paths: &[P]) -> Result<StatisticsData> {
+        let sources: Vec<StatisticsSource> = paths
+            .iter()
+            .map(|path| StatisticsSource {
+                path: path.as_ref().to_path_buf(),
+                device: None,
+            })
+            .collect();
+        Self::parse_sources(&sources)
+    }
+
+    /// Parse and merge several statistics databases, tagging each page stat with the name
+    /// of the device its database belongs to.
+    ///
+    /// Page stats that appear in more than one database (same book, page and start time,
+    /// as in copies of one database synced to several places) are only kept once, with the
+    /// device of the first database that contains them.
+    pub fn parse_sources(sources: &[StatisticsSource]) -> Result<StatisticsData> {
+        let mut merged: Option<StatisticsData> = None;
+        for source in sources {
+            let mut data = Self::parse(&source.path)?;
+            if let Some(device) = &source.device {
+                for stat in &mut data.page_stats {
+                    stat.device = Some(device.clone());
+                }
+            }
+            merged = Some(match merged {
+                None => data,
+                Some(mut merged) => {
+                    Self::merge_into(&mut merged, data);
+                    merged
+                }
+            });
+        }
+
+        let merged = merged.context("No statistics database provided")?;
+        if sources.len() > 1 {
+            info!(
+                "Merged {} statistics databases into {} books and {} page stats",
+                sources.len(),
+                merged.books.len(),
+                merged.page_stats.len()
+            );
+        }
+        Ok(merged)
+    }
+
+    /// Merge `other` into `target`, matching books by MD5 and skipping page stats that
+    /// `target` already contains.
+    ///
+    /// Reading time and page totals of books found in both only grow by the page stats
+    /// that were actually added, so merging a copy of a database changes nothing.
+    fn merge_into(target: &mut StatisticsData, other: StatisticsData) {
+        let mut next_id = target.books.iter().map(|b| b.id).max().unwrap_or(0) + 1;
+        let mut id_map: HashMap<i64, i64> = HashMap::new();
+        let mut shared_ids: HashSet<i64> = HashSet::new();
+
+        for book in other.books {
+            if let Some(existing) = target.books.iter_mut().find(|b| b.md5 == book.md5) {
+                id_map.insert(book.id, existing.id);
+                shared_ids.insert(existing.id);
+                existing.last_open = existing.last_open.max(book.last_open);
+                existing.highlights = existing.highlights.max(book.highlights);
+                existing.notes = existing.notes.max(book.notes);
+                if existing.pages.is_none() {
+                    existing.pages = book.pages;
+                }
+                if existing.series.is_none() {
+                    existing.series = book.series;
+                }
+                if existing.language.is_none() {
+                    existing.language = book.language;
+                }
+            } else {
+                id_map.insert(book.id, next_id);
+                target.books.push(StatBook {
+                    id: next_id,
+                    ..book
+                });
+                next_id += 1;
+            }
+        }
+
+        let mut seen: HashSet<(i64, i64, i64)> = target
+            .page_stats
+            .iter()
+            .map(|stat| (stat.id_book, stat.page, stat.start_time))
+            .collect();
+        let mut read_pages: HashSet<(i64, i64)> = target
+            .page_stats
+            .iter()
+            .map(|stat| (stat.id_book, stat.page))
+            .collect();
+        // Reading time and newly read pages added per shared book
+        let mut added: HashMap<i64, (i64, i64)> = HashMap::new();
+        let mut duplicates = 0;
+
+        for stat in other.page_stats {
+            let Some(&id_book) = id_map.get(&stat.id_book) else {
+                continue;
+            };
+            if !seen.insert((id_book, stat.page, stat.start_time)) {
+                duplicates += 1;
+                continue;
+            }
+            let (time, pages) = added.entry(id_book).or_default();
+            *time += stat.duration;
+            if read_pages.insert((id_book, stat.page)) {
+                *pages += 1;
+            }
+            target.page_stats.push(PageStat { id_book, ..stat });
+        }
+
+        for book in target
+            .books
+            .iter_mut()
+            .filter(|b| shared_ids.contains(&b.id))
+        {
+            let (time, pages) = added.get(&book.id).copied().unwrap_or_default();
+            book.total_read_time = Some(book.total_read_time.unwrap_or(0) + time);
+            book.total_read_pages = Some(book.total_read_pages.unwrap_or(0) + pages);
+        }
+
+        if duplicates > 0 {
+            info!(
+                "Skipped {} page stats already present in another statistics database",
+                duplicates
+            );
+        }
+
+        target.stats_by_md5 = target
+            .books
+            .iter()
+            .map(|book| (book.md5.clone(), book.clone()))
+            .collect();
+    }
+
     /// Parse book entries from the database
     fn parse_books(conn: &Connection) -> Result<Vec<StatBook>> {
-        let mut stmt = conn.prepare("SELECT id, title, authors, notes, last_open, highlights, pages, md5, total_read_time, total_read_pages FROM book")?;
+        let mut stmt = conn.prepare("SELECT id, title, authors, notes, last_open, highlights, pages, md5, total_read_time, total_read_pages, series, language FROM book")?;
 
         let book_iter = stmt.query_map([], |row| {
             Ok(StatBook {
@@ -82,6 +233,8 @@ impl StatisticsParser {
                 last_open: row.get(4)?,
                 highlights: row.get(5)?,
                 pages: row.get(6)?,
+                series: recorded_value(row.get(10)?),
+                language: recorded_value(row.get(11)?),
                 md5: row.get(7)?,
                 content_type: None,
                 total_read_time: row.get(8)?,
@@ -103,10 +256,81 @@ impl StatisticsParser {
 
     /// Parse page stat entries from the database
     fn parse_page_stats(conn: &Connection) -> Result<Vec<PageStat>> {
-        // Query the rescaled view so that page numbers are already expressed in the current
-        // pagination of each document. The `page_stat` view has the same four columns we
-        // actually use (id_book, page, start_time, duration). See KOReader's Lua code for
-        // the precise definition.
+        if !Self::has_table(conn, "page_stat_data")? {
+            debug!("No page_stat_data table found, reading the page_stat view instead");
+            return Self::parse_page_stat_view(conn);
+        }
+
+        // Read the raw rows together with the page count each one was recorded at and the
+        // current page count of the book, and rescale them ourselves. KOReader's `page_stat`
+        // view does the same in SQL, but expands rows through a `numbers` table of 1,000 rows,
+        // which truncates the rescaling for very long books and comics.
+        let mut stmt = conn.prepare(
+            "SELECT page_stat_data.id_book, page_stat_data.page, page_stat_data.start_time, \
+             page_stat_data.duration, page_stat_data.total_pages, book.pages \
+             FROM page_stat_data JOIN book ON book.id = page_stat_data.id_book",
+        )?;
+
+        let row_iter = stmt.query_map([], |row| {
+            Ok((
+                PageStat {
+                    id_book: row.get(0)?,
+                    page: row.get(1)?,
+                    start_time: row.get(2)?,
+                    duration: row.get(3)?,
+                    device: None,
+                },
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+            ))
+        })?;
+
+        let mut page_stats = Vec::new();
+        for row in row_iter {
+            match row {
+                Ok((stat, total_pages, pages)) => {
+                    Self::push_rescaled(&mut page_stats, stat, total_pages, pages)
+                }
+                Err(e) => warn!("Failed to parse page stat entry: {}", e),
+            }
+        }
+
+        Ok(page_stats)
+    }
+
+    /// Express a page stat recorded at `total_pages` in the current pagination (`pages`) of
+    /// its book, the same way KOReader's `page_stat` view does: a page maps onto the range
+    /// of current pages it covers, and its duration is split evenly across them.
+    ///
+    /// Rows without a usable page count are kept as recorded.
+    pub(crate) fn push_rescaled(
+        page_stats: &mut Vec<PageStat>,
+        stat: PageStat,
+        total_pages: Option<i64>,
+        pages: Option<i64>,
+    ) {
+        let (Some(total_pages), Some(pages)) = (total_pages, pages) else {
+            page_stats.push(stat);
+            return;
+        };
+        if total_pages <= 0 || pages <= 0 || total_pages == pages {
+            page_stats.push(stat);
+            return;
+        }
+
+        let first_page = ((stat.page - 1) * pages) / total_pages + 1;
+        let last_page = first_page.max((stat.page * pages) / total_pages);
+        let duration = stat.duration / (last_page - first_page + 1);
+        page_stats.extend((first_page..=last_page).map(|page| PageStat {
+            page,
+            duration,
+            device: stat.device.clone(),
+            ..stat
+        }));
+    }
+
+    /// Parse page stats from the rescaled `page_stat` view (or table, in old databases)
+    fn parse_page_stat_view(conn: &Connection) -> Result<Vec<PageStat>> {
         let mut stmt = conn.prepare("SELECT id_book, page, start_time, duration FROM page_stat")?;
 
         let stat_iter = stmt.query_map([], |row| {
@@ -115,6 +339,7 @@ impl StatisticsParser {
                 page: row.get(1)?,
                 start_time: row.get(2)?,
                 duration: row.get(3)?,
+                device: None,
             })
         })?;
 
@@ -129,6 +354,16 @@ impl StatisticsParser {
         Ok(page_stats)
     }
 
+    /// Whether the database has a table with the given name
+    fn has_table(conn: &Connection, name: &str) -> Result<bool> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
     /// Calculate reading statistics based on the parsed data
     pub fn calculate_stats(
         stats_data: &mut StatisticsData,
@@ -137,3 +372,10 @@ impl StatisticsParser {
         StatisticsCalculator::calculate_stats(stats_data, time_config)
     }
 }
+
+/// A text column of the `book` table, unless empty or KOReader's "N/A" placeholder.
+fn recorded_value(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && v != "N/A")
+}
@@ -0,0 +1,127 @@
+//! Reading goal tracking: progress, pace and projections for yearly and monthly goals.
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::config::{GoalTargets, GoalsConfig};
+use crate::models::{GoalMetric, GoalPeriod, GoalProgress, ReadingStats, StatisticsData};
+
+pub struct GoalCalculator;
+
+impl GoalCalculator {
+    /// Progress of the yearly and monthly goals for the periods containing `today`.
+    ///
+    /// `stats_data` must have its completions populated and `reading_stats` must have been
+    /// calculated from it.
+    pub fn current(
+        goals: &GoalsConfig,
+        stats_data: &StatisticsData,
+        reading_stats: &ReadingStats,
+        today: NaiveDate,
+    ) -> Vec<GoalProgress> {
+        let mut progress = Self::for_year(goals, stats_data, reading_stats, today.year(), today);
+        if let Some(start) = NaiveDate::from_ymd_opt(today.year(), today.month(), 1) {
+            let end = start
+                .checked_add_months(chrono::Months::new(1))
+                .and_then(|next| next.pred_opt())
+                .unwrap_or(start);
+            progress.extend(Self::for_period(
+                &goals.monthly,
+                GoalPeriod::Month,
+                (start, end),
+                stats_data,
+                reading_stats,
+                today,
+            ));
+        }
+        progress
+    }
+
+    /// Progress of the yearly goals in `year`.
+    pub fn for_year(
+        goals: &GoalsConfig,
+        stats_data: &StatisticsData,
+        reading_stats: &ReadingStats,
+        year: i32,
+        today: NaiveDate,
+    ) -> Vec<GoalProgress> {
+        let (Some(start), Some(end)) = (
+            NaiveDate::from_ymd_opt(year, 1, 1),
+            NaiveDate::from_ymd_opt(year, 12, 31),
+        ) else {
+            return Vec::new();
+        };
+        Self::for_period(
+            &goals.yearly,
+            GoalPeriod::Year,
+            (start, end),
+            stats_data,
+            reading_stats,
+            today,
+        )
+    }
+
+    fn for_period(
+        targets: &GoalTargets,
+        period: GoalPeriod,
+        (start, end): (NaiveDate, NaiveDate),
+        stats_data: &StatisticsData,
+        reading_stats: &ReadingStats,
+        today: NaiveDate,
+    ) -> Vec<GoalProgress> {
+        if targets.is_empty() {
+            return Vec::new();
+        }
+        let first = start.format("%Y-%m-%d").to_string();
+        let last = end.format("%Y-%m-%d").to_string();
+        let in_period = |date: &str| first.as_str() <= date && date <= last.as_str();
+
+        let completions = stats_data
+            .books
+            .iter()
+            .filter_map(|book| book.completions.as_ref())
+            .flat_map(|completions| &completions.entries)
+            .filter(|completion| in_period(&completion.end_date))
+            .count();
+        let (seconds, pages) = reading_stats
+            .daily_activity
+            .iter()
+            .filter(|day| in_period(&day.date))
+            .fold((0, 0), |(seconds, pages), day| {
+                (seconds + day.read_time, pages + day.pages_read)
+            });
+
+        let total_days = (end - start).num_days() + 1;
+        let elapsed_days = ((today - start).num_days() + 1).clamp(0, total_days);
+        let period_key = match period {
+            GoalPeriod::Year => start.format("%Y").to_string(),
+            GoalPeriod::Month => start.format("%Y-%m").to_string(),
+        };
+
+        [
+            (GoalMetric::Books, targets.books, completions as f64),
+            (GoalMetric::Hours, targets.hours, seconds as f64 / 3600.0),
+            (GoalMetric::Pages, targets.pages, pages as f64),
+        ]
+        .into_iter()
+        .filter_map(|(metric, target, actual)| {
+            let target = target.filter(|target| *target > 0)?;
+            let elapsed = elapsed_days as f64 / total_days as f64;
+            let expected = target as f64 * elapsed;
+            Some(GoalProgress {
+                period,
+                period_key: period_key.clone(),
+                metric,
+                target,
+                actual,
+                percentage: actual / target as f64 * 100.0,
+                expected,
+                projected: if elapsed > 0.0 { actual / elapsed } else { 0.0 },
+                elapsed_days,
+                total_days,
+                reached: actual >= target as f64,
+                on_track: actual >= expected,
+            })
+        })
+        .collect()
+    }
+}
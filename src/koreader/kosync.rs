@@ -0,0 +1,208 @@
+//! Local store for the KOReader progress sync protocol (kosync).
+//!
+//! Devices identify documents by the partial MD5 that [`super::calculate_partial_md5`]
+//! computes, so synced progress can be matched against library items directly.
+
+use crate::models::{BookStatus, KoReaderMetadata, LibraryItem, Summary};
+use anyhow::{Context, Result};
+use log::info;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Reading position of one document as last reported by a device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedProgress {
+    /// Partial MD5 of the document
+    pub document: String,
+    /// Opaque position (an xpointer for reflowable documents, a page number otherwise)
+    pub progress: String,
+    /// Progress as a fraction between 0 and 1
+    pub percentage: f64,
+    pub device: String,
+    pub device_id: String,
+    /// Unix timestamp (seconds) of the update
+    pub timestamp: i64,
+}
+
+impl SyncedProgress {
+    /// Apply this progress to an item unless its KoReader metadata is newer.
+    ///
+    /// `metadata_modified` is the modification time of the item's metadata file in
+    /// nanoseconds since the Unix epoch. Items without metadata get a minimal metadata
+    /// record marking them as being read. Returns whether the item changed.
+    pub fn apply_to(&self, item: &mut LibraryItem, metadata_modified: Option<u64>) -> bool {
+        let synced_nanos = u64::try_from(self.timestamp)
+            .unwrap_or(0)
+            .saturating_mul(1_000_000_000);
+
+        let metadata = match item.koreader_metadata.as_mut() {
+            Some(metadata) => {
+                if metadata_modified.is_some_and(|modified| modified >= synced_nanos) {
+                    return false;
+                }
+                metadata
+            }
+            None => item.koreader_metadata.insert(KoReaderMetadata {
+                annotations: Vec::new(),
+                doc_pages: None,
+                doc_path: None,
+                doc_props: None,
+                partial_md5_checksum: Some(self.document.clone()),
+                percent_finished: None,
+                stats: None,
+                summary: None,
+                text_lang: None,
+            }),
+        };
+
+        let percentage = self.percentage.clamp(0.0, 1.0);
+        if metadata.percent_finished == Some(percentage) {
+            return false;
+        }
+        metadata.percent_finished = Some(percentage);
+
+        // Progress from a device means the item is being read, unless KoReader says otherwise
+        let summary = metadata.summary.get_or_insert(Summary {
+            modified: None,
+            note: None,
+            rating: None,
+            status: BookStatus::Unknown,
+        });
+        if summary.status == BookStatus::Unknown {
+            summary.status = BookStatus::Reading;
+        }
+        true
+    }
+}
+
+/// SQLite store of sync users and their reading progress.
+pub struct KosyncStore {
+    conn: Connection,
+}
+
+impl KosyncStore {
+    /// Open the store, creating the database and its tables if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open sync database: {:?}", path))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS users (
+                username TEXT PRIMARY KEY NOT NULL,
+                userkey TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS progress (
+                username TEXT NOT NULL,
+                document TEXT NOT NULL,
+                progress TEXT NOT NULL,
+                percentage REAL NOT NULL,
+                device TEXT NOT NULL,
+                device_id TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                PRIMARY KEY (username, document)
+            );",
+        )
+        .with_context(|| format!("Failed to initialize sync database: {:?}", path))?;
+        info!("Opened sync database: {:?}", path);
+        Ok(Self { conn })
+    }
+
+    /// Register a user. Returns false if the username is taken.
+    ///
+    /// The key is what the device sends as password: KOReader already hashes it with MD5.
+    pub fn create_user(&self, username: &str, userkey: &str) -> Result<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO users (username, userkey) VALUES (?1, ?2)",
+            params![username, userkey],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    /// Whether the username exists and the key matches.
+    pub fn authorize(&self, username: &str, userkey: &str) -> Result<bool> {
+        let stored: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT userkey FROM users WHERE username = ?1",
+                params![username],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(stored.is_some_and(|stored| stored == userkey))
+    }
+
+    /// Store the progress of a document for a user, replacing the previous one.
+    pub fn update_progress(&self, username: &str, progress: &SyncedProgress) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO progress
+                (username, document, progress, percentage, device, device_id, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                username,
+                progress.document,
+                progress.progress,
+                progress.percentage,
+                progress.device,
+                progress.device_id,
+                progress.timestamp
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Last progress a user synced for a document.
+    pub fn get_progress(&self, username: &str, document: &str) -> Result<Option<SyncedProgress>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT document, progress, percentage, device, device_id, timestamp
+                 FROM progress WHERE username = ?1 AND document = ?2",
+                params![username, document],
+                Self::progress_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Most recent progress per document across all users, keyed by partial MD5.
+    pub fn latest_progress(&self) -> Result<HashMap<String, SyncedProgress>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT document, progress, percentage, device, device_id, timestamp
+             FROM progress ORDER BY timestamp",
+        )?;
+        let rows = stmt.query_map([], Self::progress_from_row)?;
+
+        let mut latest = HashMap::new();
+        for row in rows {
+            let progress = row?;
+            latest.insert(progress.document.clone(), progress);
+        }
+        Ok(latest)
+    }
+
+    /// Read the latest progress per document from a database without creating it.
+    pub fn read_latest_progress<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<HashMap<String, SyncedProgress>> {
+        let path = path.as_ref();
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open sync database: {:?}", path))?;
+        Self { conn }.latest_progress()
+    }
+
+    fn progress_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SyncedProgress> {
+        Ok(SyncedProgress {
+            document: row.get(0)?,
+            progress: row.get(1)?,
+            percentage: row.get(2)?,
+            device: row.get(3)?,
+            device_id: row.get(4)?,
+            timestamp: row.get(5)?,
+        })
+    }
+}
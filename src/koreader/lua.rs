@@ -2,6 +2,7 @@ use crate::models::*;
 use anyhow::{Context, Result, anyhow};
 use log::{debug, warn};
 use mlua::{Lua, Table, Value};
+use std::cmp::Ordering;
 use std::fs;
 use std::path::Path;
 
@@ -76,6 +77,14 @@ impl LuaParser {
             }
         }
 
+        // Files written before KoReader 2024.07 only have the legacy tables; some have both
+        for legacy in self.parse_legacy_annotations(table)? {
+            if !annotations.iter().any(|a| is_same_annotation(a, &legacy)) {
+                annotations.push(legacy);
+            }
+        }
+        annotations.sort_by(reading_order);
+
         Ok(annotations)
     }
 
@@ -84,10 +93,122 @@ impl LuaParser {
             chapter: self.get_optional_string(&table, "chapter")?,
             datetime: self.get_optional_string(&table, "datetime")?,
             pageno: self.get_optional_u32(&table, "pageno")?,
-            pos0: self.get_optional_string(&table, "pos0")?,
-            pos1: self.get_optional_string(&table, "pos1")?,
+            pos0: self.get_optional_position(&table, "pos0")?,
+            pos1: self.get_optional_position(&table, "pos1")?,
             text: self.get_optional_string(&table, "text")?,
             note: self.get_optional_string(&table, "note")?,
+            color: self.get_optional_string(&table, "color")?,
+            drawer: self
+                .get_optional_string(&table, "drawer")?
+                .map(|d| HighlightDrawer::from_koreader(&d)),
+            datetime_updated: self.get_optional_string(&table, "datetime_updated")?,
+            page: self.get_optional_location(&table, "page")?,
+        })
+    }
+
+    /// Build annotations from the legacy `highlight` (keyed by page number) and `bookmarks`
+    /// tables. Highlights are linked to their bookmark by creation time, which is how
+    /// KoReader pairs them when it migrates these tables.
+    fn parse_legacy_annotations(&self, table: &Table) -> Result<Vec<Annotation>> {
+        let mut highlights: Vec<(u32, Table)> = Vec::new();
+        if let Ok(Value::Table(highlight_table)) = table.get("highlight") {
+            for pair in highlight_table.pairs::<Value, Value>() {
+                let Ok((page, Value::Table(page_highlights))) = pair else {
+                    continue;
+                };
+                let page = match page {
+                    Value::Integer(i) => i as u32,
+                    Value::Number(n) => n as u32,
+                    _ => continue,
+                };
+                for highlight in page_highlights.sequence_values::<Table>().flatten() {
+                    highlights.push((page, highlight));
+                }
+            }
+        }
+        // `pairs` has no defined order
+        highlights.sort_by_key(|(page, _)| *page);
+
+        let mut paired = vec![false; highlights.len()];
+        let mut legacy = Vec::new();
+
+        if let Ok(Value::Table(bookmarks)) = table.get("bookmarks") {
+            for bookmark in bookmarks.sequence_values::<Table>().flatten() {
+                let datetime = self.get_optional_string(&bookmark, "datetime")?;
+                let page = self.get_optional_location(&bookmark, "page")?;
+                let highlighted = matches!(bookmark.get("highlighted"), Ok(Value::Boolean(true)));
+                let notes = self.get_optional_string(&bookmark, "notes")?;
+                let note = self
+                    .get_optional_string(&bookmark, "text")?
+                    .filter(|text| !is_auto_bookmark_text(text, notes.as_deref(), &datetime));
+
+                let highlight = highlighted
+                    .then(|| {
+                        highlights.iter().enumerate().position(|(i, (_, hl))| {
+                            !paired[i]
+                                && self.get_optional_string(hl, "datetime").ok().flatten()
+                                    == datetime
+                        })
+                    })
+                    .flatten();
+
+                let annotation = if let Some(index) = highlight {
+                    paired[index] = true;
+                    let (pageno, hl) = &highlights[index];
+                    let mut annotation = self.parse_legacy_highlight(*pageno, hl)?;
+                    annotation.note = note;
+                    annotation.page = page.or(annotation.page);
+                    annotation
+                } else {
+                    // Page bookmark, or a highlight whose entry in `highlight` is gone
+                    Annotation {
+                        chapter: self.get_optional_string(&bookmark, "chapter")?,
+                        datetime,
+                        pageno: page.as_deref().and_then(|p| p.parse().ok()),
+                        pos0: self.get_optional_position(&bookmark, "pos0")?,
+                        pos1: self.get_optional_position(&bookmark, "pos1")?,
+                        text: if highlighted { notes } else { None },
+                        note,
+                        color: None,
+                        drawer: None,
+                        datetime_updated: None,
+                        page,
+                    }
+                };
+                legacy.push(annotation);
+            }
+        }
+
+        for (index, (pageno, hl)) in highlights.iter().enumerate() {
+            if !paired[index] {
+                legacy.push(self.parse_legacy_highlight(*pageno, hl)?);
+            }
+        }
+
+        // Legacy bookmarks are stored newest first
+        legacy.sort_by(|a, b| {
+            a.pageno
+                .cmp(&b.pageno)
+                .then_with(|| a.datetime.cmp(&b.datetime))
+        });
+        Ok(legacy)
+    }
+
+    fn parse_legacy_highlight(&self, pageno: u32, table: &Table) -> Result<Annotation> {
+        Ok(Annotation {
+            chapter: self.get_optional_string(table, "chapter")?,
+            datetime: self.get_optional_string(table, "datetime")?,
+            pageno: Some(pageno),
+            pos0: self.get_optional_position(table, "pos0")?,
+            pos1: self.get_optional_position(table, "pos1")?,
+            text: self.get_optional_string(table, "text")?,
+            note: None,
+            color: self.get_optional_string(table, "color")?,
+            drawer: self
+                .get_optional_string(table, "drawer")?
+                .map(|d| HighlightDrawer::from_koreader(&d)),
+            datetime_updated: None,
+            page: None,
         })
     }
 
@@ -166,6 +287,36 @@ impl LuaParser {
         }
     }
 
+    /// Highlight positions are xpointer strings for reflowable documents and
+    /// `{ page, x, y }` tables for PDF/DjVu, which are flattened to `page:x,y`.
+    fn get_optional_position(&self, table: &Table, key: &str) -> Result<Option<String>> {
+        match table.get(key) {
+            Ok(Value::Table(position)) => {
+                let coordinate = |name: &str| match position.get(name) {
+                    Ok(Value::Integer(i)) => Some(i.to_string()),
+                    Ok(Value::Number(n)) => Some(n.to_string()),
+                    _ => None,
+                };
+                Ok(Some(format!(
+                    "{}:{},{}",
+                    coordinate("page").unwrap_or_default(),
+                    coordinate("x").unwrap_or_default(),
+                    coordinate("y").unwrap_or_default()
+                )))
+            }
+            _ => self.get_optional_string(table, key),
+        }
+    }
+
+    /// Locations are xpointer strings for reflowable documents and page numbers otherwise.
+    fn get_optional_location(&self, table: &Table, key: &str) -> Result<Option<String>> {
+        match table.get(key) {
+            Ok(Value::Integer(i)) => Ok(Some(i.to_string())),
+            Ok(Value::Number(n)) => Ok(Some(n.to_string())),
+            _ => self.get_optional_string(table, key),
+        }
+    }
+
     fn get_optional_u32(&self, table: &Table, key: &str) -> Result<Option<u32>> {
         match table.get(key) {
             Ok(Value::Integer(i)) => Ok(Some(i as u32)),
@@ -192,3 +343,67 @@ impl LuaParser {
         }
     }
 }
+
+/// Whether two annotations describe the same highlight or bookmark.
+fn is_same_annotation(a: &Annotation, b: &Annotation) -> bool {
+    a.datetime == b.datetime && a.pos0 == b.pos0 && (a.pos0.is_some() || a.page == b.page)
+}
+
+/// Order annotations as they appear in the book: by page, then by position within the page.
+/// Like the legacy tables, annotations without a page number come first.
+fn reading_order(a: &Annotation, b: &Annotation) -> Ordering {
+    a.pageno
+        .cmp(&b.pageno)
+        .then_with(|| match (&a.pos0, &b.pos0) {
+            (Some(a), Some(b)) => compare_positions(a, b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Compare xpointers (`/body/DocFragment[10]/p[2]/text().15`) and PDF positions
+/// (`9:10,20`) so that numeric parts are ordered by value rather than lexically.
+fn compare_positions(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.iter().take_while(|c| c.is_ascii_digit()).count();
+                let b_len = b.iter().take_while(|c| c.is_ascii_digit()).count();
+                let (a_num, b_num) = (trim_zeros(&a[..a_len]), trim_zeros(&b[..b_len]));
+                let ordering = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                (a, b) = (&a[a_len..], &b[b_len..]);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                (a, b) = (&a[1..], &b[1..]);
+            }
+        }
+    }
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits
+        .iter()
+        .position(|&d| d != b'0')
+        .unwrap_or(digits.len());
+    &digits[start..]
+}
+
+/// Legacy bookmarks store an auto-generated description (e.g. `Page 12 "quote" @ 2020-01-01
+/// 10:00:00`) in `text` until the user edits it into a note.
+fn is_auto_bookmark_text(text: &str, notes: Option<&str>, datetime: &Option<String>) -> bool {
+    let text = text.trim();
+    text.is_empty()
+        || Some(text) == notes.map(str::trim)
+        || datetime
+            .as_ref()
+            .is_some_and(|dt| text.ends_with(&format!("@ {}", dt)))
+}
@@ -3,13 +3,21 @@
 pub mod calendar;
 pub mod completion;
 pub mod database;
+pub mod goals;
+pub mod kosync;
 pub mod lua;
 pub mod partial_md5;
+pub mod patterns;
 pub mod session;
 pub mod statistics;
+pub mod timeline;
 
 pub use calendar::CalendarGenerator;
-pub use database::StatisticsParser;
+pub use database::{StatisticsParser, StatisticsSource};
+pub use goals::GoalCalculator;
+pub use kosync::{KosyncStore, SyncedProgress};
 pub use lua::LuaParser;
 pub use partial_md5::calculate_partial_md5;
+pub use patterns::PatternCalculator;
 pub use statistics::{BookStatistics, StatisticsCalculator};
+pub use timeline::TimelineCalculator;
@@ -0,0 +1,126 @@
+//! Reading patterns: when during the day and the week reading happens, and how it goes.
+
+use chrono::{Datelike, Timelike};
+
+use crate::models::{Chronotype, HourPattern, ReadingPatterns, StatisticsData, WeekdayPattern};
+use crate::time_config::TimeConfig;
+
+/// Hours counted as night reading (22:00 to 04:00)
+const NIGHT_HOURS: [u32; 6] = [22, 23, 0, 1, 2, 3];
+/// Hours counted as early morning reading (05:00 to 09:00)
+const MORNING_HOURS: [u32; 4] = [5, 6, 7, 8];
+/// Share of the reading time in the night or morning hours that makes a night owl or early bird
+const CHRONOTYPE_MIN_SHARE: f64 = 0.25;
+/// Reading time an hour needs before its reading speed is reported (in seconds)
+const MIN_SPEED_READ_TIME: i64 = 600;
+
+pub struct PatternCalculator;
+
+impl PatternCalculator {
+    /// Hour-of-day and weekday patterns of the page stats and sessions in `stats_data`.
+    ///
+    /// `stats_data.sessions` must have been populated for the session lengths by hour.
+    pub fn calculate(stats_data: &StatisticsData, time_config: &TimeConfig) -> ReadingPatterns {
+        let mut punch_card = vec![vec![0i64; 24]; 7];
+        let mut hour_pages = [0i64; 24];
+        let mut weekday_pages = [0i64; 7];
+
+        for stat in stats_data.page_stats.iter().filter(|s| s.duration > 0) {
+            let hour = time_config.local_datetime(stat.start_time).hour() as usize;
+            let weekday = time_config
+                .date_for_timestamp(stat.start_time)
+                .weekday()
+                .num_days_from_monday() as usize;
+            punch_card[weekday][hour] += stat.duration;
+            hour_pages[hour] += 1;
+            weekday_pages[weekday] += 1;
+        }
+
+        let mut hour_sessions: Vec<Vec<i64>> = vec![Vec::new(); 24];
+        for reading_session in &stats_data.sessions {
+            let hour = time_config
+                .local_datetime(reading_session.start_time)
+                .hour() as usize;
+            hour_sessions[hour].push(reading_session.duration);
+        }
+
+        let hours: Vec<HourPattern> = (0..24)
+            .map(|hour| {
+                let read_time: i64 = punch_card.iter().map(|day| day[hour]).sum();
+                let sessions = &hour_sessions[hour];
+                HourPattern {
+                    hour: hour as u32,
+                    read_time,
+                    pages_read: hour_pages[hour],
+                    session_count: sessions.len() as i64,
+                    average_session_duration: (!sessions.is_empty())
+                        .then(|| sessions.iter().sum::<i64>() / sessions.len() as i64),
+                    pages_per_hour: (read_time >= MIN_SPEED_READ_TIME)
+                        .then(|| hour_pages[hour] as f64 * 3600.0 / read_time as f64),
+                }
+            })
+            .collect();
+
+        let weekdays: Vec<WeekdayPattern> = (0..7)
+            .map(|weekday| WeekdayPattern {
+                weekday: weekday as u32,
+                read_time: punch_card[weekday].iter().sum(),
+                pages_read: weekday_pages[weekday],
+            })
+            .collect();
+
+        let total_read_time: i64 = hours.iter().map(|h| h.read_time).sum();
+        let share = |range: &[u32]| {
+            if total_read_time == 0 {
+                return 0.0;
+            }
+            let read_time: i64 = range.iter().map(|&h| hours[h as usize].read_time).sum();
+            read_time as f64 / total_read_time as f64
+        };
+        let night_share = share(&NIGHT_HOURS);
+        let morning_share = share(&MORNING_HOURS);
+
+        ReadingPatterns {
+            peak_hour: Self::busiest(hours.iter().map(|h| (h.hour, h.read_time))),
+            peak_weekday: Self::busiest(weekdays.iter().map(|w| (w.weekday, w.read_time))),
+            fastest_hour: hours
+                .iter()
+                .filter_map(|h| h.pages_per_hour.map(|speed| (h.hour, speed)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(hour, _)| hour),
+            chronotype: Self::chronotype(total_read_time, night_share, morning_share),
+            night_share,
+            morning_share,
+            punch_card,
+            hours,
+            weekdays,
+        }
+    }
+
+    /// Key with the largest non-zero value; the first one wins ties.
+    fn busiest(values: impl Iterator<Item = (u32, i64)>) -> Option<u32> {
+        values
+            .filter(|(_, value)| *value > 0)
+            .fold(None, |best: Option<(u32, i64)>, (key, value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((key, value)),
+            })
+            .map(|(key, _)| key)
+    }
+
+    fn chronotype(
+        total_read_time: i64,
+        night_share: f64,
+        morning_share: f64,
+    ) -> Option<Chronotype> {
+        if total_read_time == 0 {
+            None
+        } else if night_share >= CHRONOTYPE_MIN_SHARE && night_share > morning_share {
+            Some(Chronotype::NightOwl)
+        } else if morning_share >= CHRONOTYPE_MIN_SHARE && morning_share > night_share {
+            Some(Chronotype::EarlyBird)
+        } else {
+            Some(Chronotype::DayReader)
+        }
+    }
+}
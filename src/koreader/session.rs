@@ -1,72 +1,62 @@
 use std::collections::HashMap;
 
-use crate::models::PageStat;
+use crate::models::{PageStat, ReadingSession};
 
 /// Default time gap that separates two reading events into different sessions (in seconds)
-const DEFAULT_SESSION_GAP_SECONDS: i64 = 300; // 5 minutes
+pub const DEFAULT_SESSION_GAP_SECONDS: i64 = 300; // 5 minutes
 
-/// Calculate the duration (in seconds) of each reading session for a single book.
-/// Two consecutive page reads belong to the same session when the gap between
-/// them is less than or equal to the default gap (5 minutes).
-pub fn session_durations(stats: &[PageStat]) -> Vec<i64> {
-    if stats.is_empty() {
-        return Vec::new();
-    }
-
-    let mut sorted = stats.to_vec();
-    sorted.sort_by_key(|s| s.start_time);
-
-    let mut durations = Vec::new();
-    let mut current = sorted[0].duration;
-    let mut last_end = sorted[0].start_time + sorted[0].duration;
-
-    for stat in &sorted[1..] {
-        if stat.start_time - last_end <= DEFAULT_SESSION_GAP_SECONDS {
-            current += stat.duration;
-        } else {
-            durations.push(current);
-            current = stat.duration;
-        }
-        last_end = stat.start_time + stat.duration;
-    }
-
-    durations.push(current);
-    durations
-}
-
-/// Convenience helper that only returns the number of sessions.
-#[inline]
-pub fn session_count(stats: &[PageStat]) -> i64 {
-    session_durations(stats).len() as i64
-}
-
-/// Aggregate session durations across *all* books contained in `page_stats`.
-/// The returned vector contains the duration (in seconds) of **every** session
-/// across the dataset.
-pub fn aggregate_session_durations(page_stats: &[PageStat]) -> Vec<i64> {
-    let mut by_book: HashMap<i64, Vec<PageStat>> = HashMap::new();
+/// Group page stats into reading sessions, ordered by start time.
+/// Two consecutive page reads of a book belong to the same session when they were read on
+/// the same device and the pause between them is less than or equal to `gap_seconds`.
+pub fn build_sessions(page_stats: &[PageStat], gap_seconds: i64) -> Vec<ReadingSession> {
+    let mut by_book: HashMap<i64, Vec<&PageStat>> = HashMap::new();
     for stat in page_stats.iter().filter(|s| s.duration > 0) {
-        by_book.entry(stat.id_book).or_default().push(stat.clone());
+        by_book.entry(stat.id_book).or_default().push(stat);
     }
 
-    let mut all = Vec::new();
-    for stats in by_book.values() {
-        let mut durations = session_durations(stats);
-        all.append(&mut durations);
+    let mut sessions = Vec::new();
+    for (id_book, mut stats) in by_book {
+        stats.sort_by_key(|s| s.start_time);
+
+        let mut current: Option<ReadingSession> = None;
+        for stat in stats {
+            if let Some(session) = current.as_mut()
+                && session.device == stat.device
+                && stat.start_time - session.end_time <= gap_seconds
+            {
+                session.end_time = session.end_time.max(stat.start_time + stat.duration);
+                session.pages += 1;
+                session.duration += stat.duration;
+                continue;
+            }
+
+            sessions.extend(current.replace(ReadingSession {
+                id_book,
+                start_time: stat.start_time,
+                end_time: stat.start_time + stat.duration,
+                pages: 1,
+                duration: stat.duration,
+                device: stat.device.clone(),
+            }));
+        }
+        sessions.extend(current);
     }
 
-    all
+    sessions.sort_by_key(|s| (s.start_time, s.id_book));
+    sessions
 }
 
 /// Compute (average_session_duration, longest_session_duration) from the provided
-/// `page_stats` slice. Returns `(None, None)` if no valid sessions exist.
-pub fn session_metrics(page_stats: &[PageStat]) -> (Option<i64>, Option<i64>) {
-    let sessions = aggregate_session_durations(page_stats);
-    if sessions.is_empty() {
+/// sessions. Returns `(None, None)` if there are none.
+pub fn session_metrics<'a>(
+    sessions: impl IntoIterator<Item = &'a ReadingSession>,
+) -> (Option<i64>, Option<i64>) {
+    let durations: Vec<i64> = sessions.into_iter().map(|s| s.duration).collect();
+    if durations.is_empty() {
         return (None, None);
     }
-    let total: i64 = sessions.iter().sum();
-    let average = Some(total / sessions.len() as i64);
-    let longest = sessions.iter().max().copied();
+    let total: i64 = durations.iter().sum();
+    let average = Some(total / durations.len() as i64);
+    let longest = durations.iter().max().copied();
     (average, longest)
 }
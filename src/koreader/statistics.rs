@@ -1,9 +1,11 @@
 use chrono::{Datelike, Duration, NaiveDate};
-use log::debug;
+use log::{debug, info};
 use std::collections::{HashMap, HashSet};
 
-use super::completion::{CompletionConfig, ReadCompletionDetector};
+use super::completion::ReadCompletionDetector;
+use super::patterns::PatternCalculator;
 use super::session;
+use crate::config::CompletionSettings;
 use crate::i18n::Translations;
 use crate::models::*;
 use crate::time_config::TimeConfig;
@@ -12,39 +14,30 @@ use crate::time_config::TimeConfig;
 pub trait BookStatistics {
     fn calculate_session_stats(
         &self,
-        page_stats: &[PageStat],
+        sessions: &[ReadingSession],
         time_config: &TimeConfig,
         translations: &Translations,
     ) -> BookSessionStats;
 }
 
 impl BookStatistics for StatBook {
-    /// Calculate additional statistics for this book from page stats
+    /// Calculate additional statistics for this book from its reading sessions
     fn calculate_session_stats(
         &self,
-        page_stats: &[PageStat],
+        sessions: &[ReadingSession],
         time_config: &TimeConfig,
         translations: &Translations,
     ) -> BookSessionStats {
-        let book_sessions: Vec<&PageStat> = page_stats
-            .iter()
-            .filter(|stat| stat.id_book == self.id && stat.duration > 0)
-            .collect();
+        let book_sessions: Vec<&ReadingSession> =
+            sessions.iter().filter(|s| s.id_book == self.id).collect();
 
-        // Calculate session statistics using shared helper
-        let book_stats: Vec<PageStat> = book_sessions.iter().cloned().cloned().collect();
-        let durations = session::session_durations(&book_stats);
-        let session_count = durations.len() as i64;
-        let longest_session_duration = durations.iter().max().copied();
-        let average_session_duration = if !durations.is_empty() {
-            Some(durations.iter().sum::<i64>() / session_count)
-        } else {
-            None
-        };
+        let session_count = book_sessions.len() as i64;
+        let (average_session_duration, longest_session_duration) =
+            session::session_metrics(book_sessions.iter().copied());
 
         let last_read_date = book_sessions
             .iter()
-            .map(|s| s.start_time)
+            .map(|s| s.end_time)
             .max()
             .map(|timestamp| {
                 let date = time_config.date_for_timestamp(timestamp);
@@ -101,8 +94,9 @@ impl StatisticsCalculator {
         let mut daily_read_time: HashMap<String, i64> = HashMap::new();
         let mut daily_page_reads: HashMap<String, i64> = HashMap::new();
 
-        // For weekly stats, we'll organize by ISO week and track page stats for session calculation
-        let mut weekly_stats: HashMap<(i32, u32), (i64, i64, Vec<PageStat>)> = HashMap::new(); // (year, week) -> (read_time, pages_read, page_stats)
+        // For weekly stats, we'll organize by ISO week
+        let mut weekly_stats: HashMap<(i32, u32), (i64, i64, Vec<&ReadingSession>)> =
+            HashMap::new(); // (year, week) -> (read_time, pages_read, sessions)
 
         // Process all page stats
         for stat in &stats_data.page_stats {
@@ -130,7 +124,17 @@ impl StatisticsCalculator {
             let entry = weekly_stats.entry(key).or_insert((0, 0, Vec::new()));
             entry.0 += stat.duration;
             entry.1 += 1;
-            entry.2.push(stat.clone());
+        }
+
+        // Sessions count towards the week they started in
+        for reading_session in &stats_data.sessions {
+            let date = time_config.date_for_timestamp(reading_session.start_time);
+            let key = (date.year(), date.iso_week().week());
+            weekly_stats
+                .entry(key)
+                .or_insert((0, 0, Vec::new()))
+                .2
+                .push(reading_session);
         }
 
         // Find max values for daily stats
@@ -139,7 +143,7 @@ impl StatisticsCalculator {
 
         // Calculate overall session statistics
         let (average_session_duration, longest_session_duration) =
-            session::session_metrics(&stats_data.page_stats);
+            session::session_metrics(&stats_data.sessions);
 
         // Calculate completion statistics
         let (total_completions, books_completed, most_completions) =
@@ -155,6 +159,9 @@ impl StatisticsCalculator {
         let (longest_streak, current_streak) =
             Self::calculate_streaks(&daily_activity, time_config);
 
+        // Hour-of-day and weekday patterns
+        let patterns = PatternCalculator::calculate(stats_data, time_config);
+
         ReadingStats {
             total_read_time,
             total_page_reads,
@@ -169,15 +176,16 @@ impl StatisticsCalculator {
             current_streak,
             weeks,
             daily_activity,
+            patterns,
         }
     }
 
     /// Build weekly statistics from raw weekly data
     fn build_weekly_stats(
-        weekly_stats: HashMap<(i32, u32), (i64, i64, Vec<PageStat>)>,
+        weekly_stats: HashMap<(i32, u32), (i64, i64, Vec<&ReadingSession>)>,
     ) -> Vec<WeeklyStats> {
         let mut weeks = Vec::new();
-        for ((year, week), (read_time, pages_read, page_stats)) in weekly_stats {
+        for ((year, week), (read_time, pages_read, sessions)) in weekly_stats {
             // Calculate the start date of the week (Monday)
             // This is a simplified calculation
             let start_date_approx = NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon)
@@ -190,7 +198,7 @@ impl StatisticsCalculator {
 
             // Calculate session statistics for this week
             let (average_session_duration, longest_session_duration) =
-                session::session_metrics(&page_stats);
+                session::session_metrics(sessions);
 
             let weekly_stat = WeeklyStats {
                 start_date: start_date_approx.format("%Y-%m-%d").to_string(),
@@ -211,8 +219,6 @@ impl StatisticsCalculator {
         weeks
     }
 
-    // session_metrics moved to session_calculator
-
     /// Build daily activity data from daily stats maps
     fn build_daily_activity(
         daily_read_time: HashMap<String, i64>,
@@ -331,13 +337,48 @@ impl StatisticsCalculator {
         (longest_streak_info, current_streak_info)
     }
 
-    /// Populate completion data for all books in the statistics data
-    pub fn populate_completions(stats_data: &mut StatisticsData, time_config: &TimeConfig) {
-        let detector = ReadCompletionDetector::with_config_and_time(
-            CompletionConfig::default(),
-            time_config.clone(),
+    /// Build the reading sessions of the statistics data, splitting sessions at pauses longer
+    /// than `gap_seconds`. Run after filtering the page stats.
+    pub fn populate_sessions(stats_data: &mut StatisticsData, gap_seconds: i64) {
+        stats_data.sessions = session::build_sessions(&stats_data.page_stats, gap_seconds);
+    }
+
+    /// Populate completion data for all books in the statistics data, using the thresholds of
+    /// each book's content type (tag content types first). `finished_md5s` holds the books
+    /// KOReader marks as finished, counted with `trust_status`.
+    pub fn populate_completions(
+        stats_data: &mut StatisticsData,
+        time_config: &TimeConfig,
+        settings: &CompletionSettings,
+        finished_md5s: &HashSet<String>,
+    ) {
+        let detector = |content_type| {
+            ReadCompletionDetector::with_config_and_time(
+                settings.for_content_type(content_type).clone(),
+                time_config.clone(),
+            )
+            .trusting_status(settings.trust_status)
+        };
+        let book_detector = detector(Some(ContentType::Book));
+        let comic_detector = detector(Some(ContentType::Comic));
+
+        let mut all_completions: HashMap<String, BookCompletions> = HashMap::new();
+        for book in &stats_data.books {
+            let detector = match book.content_type {
+                Some(ContentType::Comic) => &comic_detector,
+                _ => &book_detector,
+            };
+            let completions =
+                detector.detect_completions(book, stats_data, finished_md5s.contains(&book.md5));
+            if completions.has_completions() {
+                all_completions.insert(book.md5.clone(), completions);
+            }
+        }
+        info!(
+            "Detected completions for {} books out of {}",
+            all_completions.len(),
+            stats_data.books.len()
         );
-        let all_completions = detector.detect_all_completions(stats_data);
 
         // Update each book with its completion data
         for book in &mut stats_data.books {
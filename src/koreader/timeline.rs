@@ -0,0 +1,165 @@
+//! Per-book reading timeline: daily progress, sessions, pace and time-to-finish estimate.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{Duration, NaiveDate};
+
+use crate::models::{
+    BookStatus, BookTimeline, FinishEstimate, LibraryItem, StatBook, StatisticsData, TimelineDay,
+    TimelineSession,
+};
+use crate::time_config::TimeConfig;
+
+/// Number of most recent sessions the reading speed of the estimate is based on
+const PACE_SESSIONS: usize = 10;
+/// Number of days (up to today) the daily pace of the estimate is based on
+const PACE_DAYS: i64 = 30;
+
+pub struct TimelineCalculator;
+
+impl TimelineCalculator {
+    /// Timeline of `stat_book`, the statistics entry of `item`.
+    ///
+    /// `stats_data.sessions` must have been populated. The finish estimate is only made for
+    /// items that are being read and have a known page count.
+    pub fn for_item(
+        item: &LibraryItem,
+        stat_book: &StatBook,
+        stats_data: &StatisticsData,
+        time_config: &TimeConfig,
+        today: NaiveDate,
+    ) -> BookTimeline {
+        let total_pages = item
+            .doc_pages()
+            .map(i64::from)
+            .or(stat_book.pages)
+            .filter(|&pages| pages > 0);
+
+        let timeline = Self::for_book(stat_book.id, total_pages, stats_data, time_config);
+        let remaining_pages = match (item.status(), total_pages) {
+            (BookStatus::Reading, Some(pages)) => {
+                let progress = item.progress_percentage().unwrap_or(0.0).clamp(0.0, 1.0);
+                Some((pages as f64 * (1.0 - progress)).round() as i64)
+            }
+            _ => None,
+        };
+
+        BookTimeline {
+            estimate: remaining_pages
+                .filter(|&pages| pages > 0)
+                .and_then(|pages| Self::estimate(&timeline, pages, time_config, today)),
+            ..timeline
+        }
+    }
+
+    /// Reading days and sessions of the book with statistics id `id_book`.
+    pub fn for_book(
+        id_book: i64,
+        total_pages: Option<i64>,
+        stats_data: &StatisticsData,
+        time_config: &TimeConfig,
+    ) -> BookTimeline {
+        // date -> (read_time, pages_read, pages visited that day)
+        let mut by_day: BTreeMap<NaiveDate, (i64, i64, Vec<i64>)> = BTreeMap::new();
+        for stat in &stats_data.page_stats {
+            if stat.id_book != id_book || stat.duration <= 0 {
+                continue;
+            }
+            let entry = by_day
+                .entry(time_config.date_for_timestamp(stat.start_time))
+                .or_default();
+            entry.0 += stat.duration;
+            entry.1 += 1;
+            entry.2.push(stat.page);
+        }
+
+        let mut covered: HashSet<i64> = HashSet::new();
+        let days = by_day
+            .into_iter()
+            .map(|(date, (read_time, pages_read, pages))| {
+                covered.extend(pages);
+                let pages_covered = covered.len() as i64;
+                TimelineDay {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    read_time,
+                    pages_read,
+                    pages_covered,
+                    progress: total_pages
+                        .map(|total| (pages_covered as f64 / total as f64).min(1.0)),
+                    pages_per_hour: pages_per_hour(pages_read, read_time),
+                }
+            })
+            .collect();
+
+        let mut sessions: Vec<TimelineSession> = stats_data
+            .sessions
+            .iter()
+            .filter(|s| s.id_book == id_book)
+            .map(|s| TimelineSession {
+                date: time_config.format_date(s.start_time),
+                start: time_config
+                    .local_datetime(s.start_time)
+                    .format("%H:%M")
+                    .to_string(),
+                end: time_config
+                    .local_datetime(s.end_time)
+                    .format("%H:%M")
+                    .to_string(),
+                start_time: s.start_time,
+                end_time: s.end_time,
+                duration: s.duration,
+                pages: s.pages,
+                pages_per_hour: pages_per_hour(s.pages, s.duration),
+                device: s.device.clone(),
+            })
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.start_time));
+
+        BookTimeline {
+            days,
+            sessions,
+            estimate: None,
+        }
+    }
+
+    /// Time to finish `remaining_pages` at the pace of the recent sessions and days.
+    fn estimate(
+        timeline: &BookTimeline,
+        remaining_pages: i64,
+        time_config: &TimeConfig,
+        today: NaiveDate,
+    ) -> Option<FinishEstimate> {
+        let recent = &timeline.sessions[..timeline.sessions.len().min(PACE_SESSIONS)];
+        let pages: i64 = recent.iter().map(|s| s.pages).sum();
+        let duration: i64 = recent.iter().map(|s| s.duration).sum();
+        let pages_per_hour = pages_per_hour(pages, duration)?;
+
+        let window_start = today - Duration::days(PACE_DAYS - 1);
+        let recent_pages: i64 = timeline
+            .sessions
+            .iter()
+            .filter(|s| time_config.date_for_timestamp(s.start_time) >= window_start)
+            .map(|s| s.pages)
+            .sum();
+        let pages_per_day = (recent_pages as f64 / PACE_DAYS as f64 * 10.0).round() / 10.0;
+        let days_to_finish =
+            (pages_per_day > 0.0).then(|| (remaining_pages as f64 / pages_per_day).ceil() as i64);
+
+        Some(FinishEstimate {
+            remaining_pages,
+            pages_per_hour,
+            remaining_read_time: (remaining_pages as f64 * 3600.0 / pages_per_hour).round() as i64,
+            pages_per_day,
+            days_to_finish,
+            finish_date: days_to_finish.map(|days| {
+                (today + Duration::days(days))
+                    .format("%Y-%m-%d")
+                    .to_string()
+            }),
+        })
+    }
+}
+
+fn pages_per_hour(pages: i64, seconds: i64) -> Option<f64> {
+    (seconds > 0 && pages > 0).then(|| pages as f64 * 3600.0 / seconds as f64)
+}
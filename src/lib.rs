@@ -6,11 +6,15 @@
 pub mod app;
 pub mod cli;
 pub mod config;
+pub mod config_file;
+pub mod export;
 pub mod i18n;
 pub mod koreader;
 pub mod library;
 pub mod models;
+pub mod opds;
 pub mod parsers;
+pub mod search;
 pub mod server;
 pub mod share;
 pub mod site_generator;
@@ -0,0 +1,66 @@
+//! Per-item scan cache used to skip reparsing unchanged library files.
+
+use crate::models::LibraryItem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Modification time of a file in nanoseconds since the Unix epoch.
+pub fn modified_nanos(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    u64::try_from(nanos).ok()
+}
+
+/// Size and modification time of a library file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub modified: u64,
+}
+
+impl FileFingerprint {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            modified: modified_nanos(path)?,
+        })
+    }
+}
+
+/// Everything a parsed `LibraryItem` depends on: the book file and its KoReader metadata file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemFingerprint {
+    pub file: FileFingerprint,
+    pub metadata_path: Option<PathBuf>,
+    pub metadata_modified: Option<u64>,
+}
+
+/// A previously scanned item together with the fingerprint it was parsed from.
+///
+/// Cover bytes are not stored; the converted cover already lives in the output directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedItem {
+    pub fingerprint: ItemFingerprint,
+    pub item: LibraryItem,
+    pub md5: Option<String>,
+    pub has_cover: bool,
+}
+
+impl CachedItem {
+    pub fn new(fingerprint: ItemFingerprint, item: &LibraryItem, md5: Option<String>) -> Self {
+        let mut item = item.clone();
+        let has_cover = item.book_info.cover_data.take().is_some();
+        Self {
+            fingerprint,
+            item,
+            md5,
+            has_cover,
+        }
+    }
+}
+
+/// Scan results keyed by book file path.
+pub type ScanCache = HashMap<PathBuf, CachedItem>;
@@ -1,7 +1,11 @@
 //! Library scanning and file watching.
 
+pub mod cache;
 pub mod scanner;
+pub mod state;
 pub mod watcher;
 
-pub use scanner::{MetadataLocation, scan_library};
+pub use cache::{CachedItem, ScanCache};
+pub use scanner::{LibraryScan, MetadataLocation, scan_library};
+pub use state::{LibrarySnapshot, LibraryState, SharedLibraryState};
 pub use watcher::FileWatcher;
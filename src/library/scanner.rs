@@ -3,16 +3,24 @@ use indicatif::{ProgressBar, ProgressStyle};
 use log::{info, warn};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
 
+use super::cache::{CachedItem, FileFingerprint, ItemFingerprint, ScanCache, modified_nanos};
+use crate::config::{ItemIdScheme, SiteConfig};
+use crate::koreader::session::DEFAULT_SESSION_GAP_SECONDS;
 use crate::koreader::{LuaParser, calculate_partial_md5};
-use crate::models::{BookInfo, KoReaderMetadata, LibraryItem, LibraryItemFormat};
-use crate::parsers::{ComicParser, EpubParser, Fb2Parser, MobiParser};
-use crate::utils::generate_book_id;
-use crate::config::SiteConfig;
+use crate::models::{BookInfo, ItemSource, KoReaderMetadata, LibraryItem, LibraryItemFormat};
+use crate::parsers::{ComicParser, DjvuParser, EpubParser, Fb2Parser, MobiParser, PdfParser};
 use crate::time_config::TimeConfig;
+use crate::utils::unique_item_id;
+
+/// Bounds for the number of parallel scan workers.
+const MIN_SCAN_WORKERS: usize = 4;
+const MAX_SCAN_WORKERS: usize = 16;
 
 /// Configuration for where to find KOReader metadata
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum MetadataLocation {
     /// Default: metadata stored in .sdr folder next to each book
     #[default]
@@ -39,20 +47,19 @@ fn build_docsettings_index(docsettings_path: &PathBuf) -> Result<HashMap<String,
             && let Some(dir_name) = path.file_name().and_then(|s| s.to_str())
             && let Some(book_stem) = dir_name.strip_suffix(".sdr")
         {
-            let epub_metadata_path = path.join("metadata.epub.lua");
-            let fb2_metadata_path = path.join("metadata.fb2.lua");
+            let found = LibraryItemFormat::ALL
+                .iter()
+                .flat_map(|format| format.extensions())
+                .find_map(|ext| {
+                    let metadata_path = path.join(format!("metadata.{}.lua", ext));
+                    metadata_path.exists().then_some((ext, metadata_path))
+                });
 
-            if epub_metadata_path.exists() {
-                let book_filename = format!("{}.epub", book_stem);
-                match index.entry(book_filename.clone()) {
-                    std::collections::hash_map::Entry::Occupied(_) => duplicates.push(book_filename),
-                    std::collections::hash_map::Entry::Vacant(entry) => { entry.insert(epub_metadata_path); }
-                }
-            } else if fb2_metadata_path.exists() {
-                let book_filename = format!("{}.fb2", book_stem);
+            if let Some((ext, metadata_path)) = found {
+                let book_filename = format!("{}.{}", book_stem, ext);
                 match index.entry(book_filename.clone()) {
                     std::collections::hash_map::Entry::Occupied(_) => duplicates.push(book_filename),
-                    std::collections::hash_map::Entry::Vacant(entry) => { entry.insert(fb2_metadata_path); }
+                    std::collections::hash_map::Entry::Vacant(entry) => { entry.insert(metadata_path); }
                 }
             }
         }
@@ -100,13 +107,53 @@ pub struct Scanner {
     metadata_location: MetadataLocation,
     docsettings_index: Option<HashMap<String, PathBuf>>,
     hashdocsettings_index: Option<HashMap<String, PathBuf>>,
+}
+
+/// Parsers owned by a single scan worker. `mlua::Lua` is not `Send`, so each worker
+/// thread creates its own set instead of sharing one.
+struct ItemParsers {
     epub_parser: EpubParser,
     fb2_parser: Fb2Parser,
     comic_parser: ComicParser,
     mobi_parser: MobiParser,
+    pdf_parser: PdfParser,
+    djvu_parser: DjvuParser,
     lua_parser: LuaParser,
 }
 
+impl ItemParsers {
+    fn new() -> Self {
+        Self {
+            epub_parser: EpubParser::new(),
+            fb2_parser: Fb2Parser::new(),
+            comic_parser: ComicParser::new(),
+            mobi_parser: MobiParser::new(),
+            pdf_parser: PdfParser::new(),
+            djvu_parser: DjvuParser::new(),
+            lua_parser: LuaParser::new(),
+        }
+    }
+
+    async fn parse_book_info(&self, format: LibraryItemFormat, path: &Path) -> Result<BookInfo> {
+        match format {
+            LibraryItemFormat::Epub => self.epub_parser.parse(path).await,
+            LibraryItemFormat::Fb2 => self.fb2_parser.parse(path).await,
+            LibraryItemFormat::Cbz | LibraryItemFormat::Cbr => self.comic_parser.parse(path).await,
+            LibraryItemFormat::Mobi => self.mobi_parser.parse(path).await,
+            LibraryItemFormat::Pdf => self.pdf_parser.parse(path).await,
+            LibraryItemFormat::Djvu => self.djvu_parser.parse(path).await,
+        }
+    }
+
+    async fn parse_koreader_metadata(
+        &self,
+        metadata_path: Option<PathBuf>,
+    ) -> Option<KoReaderMetadata> {
+        let path = metadata_path?;
+        self.lua_parser.parse(&path).await.ok()
+    }
+}
+
 impl Scanner {
     pub fn new(config: SiteConfig) -> Self {
         let metadata_location = config.metadata_location.clone();
@@ -124,136 +171,292 @@ impl Scanner {
             metadata_location,
             docsettings_index,
             hashdocsettings_index,
-            epub_parser: EpubParser::new(),
-            fb2_parser: Fb2Parser::new(),
-            comic_parser: ComicParser::new(),
-            mobi_parser: MobiParser::new(),
-            lua_parser: LuaParser::new(),
         }
     }
 
     pub async fn scan(&self) -> Result<Vec<LibraryItem>> {
-        let (books, _) = scan_library(&self.config.library_paths, &self.metadata_location).await?;
-        Ok(books)
+        let scan = scan_library(
+            &self.config.library_paths,
+            &self.metadata_location,
+            self.config.id_scheme,
+            None,
+        )
+        .await?;
+        Ok(scan.items)
     }
 
-    async fn parse_book_info(&self, format: LibraryItemFormat, path: &Path) -> Result<BookInfo> {
-        match format {
-            LibraryItemFormat::Epub => self.epub_parser.parse(path).await,
-            LibraryItemFormat::Fb2 => self.fb2_parser.parse(path).await,
-            LibraryItemFormat::Cbz | LibraryItemFormat::Cbr => self.comic_parser.parse(path).await,
-            LibraryItemFormat::Mobi => self.mobi_parser.parse(path).await,
-        }
-    }
-
-    fn locate_metadata_path_and_md5(&self, path: &Path, format: LibraryItemFormat) -> (Option<PathBuf>, Option<String>) {
+    fn locate_metadata_path_and_md5(
+        &self,
+        path: &Path,
+        format: LibraryItemFormat,
+        known_md5: Option<String>,
+    ) -> (Option<PathBuf>, Option<String>) {
         let mut book_md5: Option<String> = None;
         let metadata_path = match &self.metadata_location {
             MetadataLocation::InBookFolder => {
                 let book_stem = path.file_stem().unwrap().to_str().unwrap();
                 let sdr_path = path.parent().unwrap().join(format!("{}.sdr", book_stem));
-                let metadata_file = sdr_path.join(format.metadata_filename());
+                let metadata_file = sdr_path.join(format.metadata_filename(path));
                 metadata_file.exists().then_some(metadata_file)
             }
             MetadataLocation::DocSettings(_) => {
                 if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                    self.docsettings_index.as_ref().and_then(|idx| idx.get(filename).cloned())
-                } else { None }
-            }
-            MetadataLocation::HashDocSettings(_) => {
-                match calculate_partial_md5(path) {
-                    Ok(hash) => {
-                        book_md5 = Some(hash.clone());
-                        self.hashdocsettings_index.as_ref().and_then(|idx| idx.get(&hash.to_lowercase()).cloned())
-                    }
-                    Err(_) => None
+                    self.docsettings_index
+                        .as_ref()
+                        .and_then(|idx| idx.get(filename).cloned())
+                } else {
+                    None
                 }
             }
+            MetadataLocation::HashDocSettings(_) => match known_md5
+                .map(Ok)
+                .unwrap_or_else(|| calculate_partial_md5(path))
+            {
+                Ok(hash) => {
+                    book_md5 = Some(hash.clone());
+                    self.hashdocsettings_index
+                        .as_ref()
+                        .and_then(|idx| idx.get(&hash.to_lowercase()).cloned())
+                }
+                Err(_) => None,
+            },
         };
         (metadata_path, book_md5)
     }
 
-    async fn parse_koreader_metadata(&self, metadata_path: Option<PathBuf>) -> Option<KoReaderMetadata> {
-        let path = metadata_path?;
-        self.lua_parser.parse(&path).await.ok()
+    fn item_md5(
+        &self,
+        path: &Path,
+        koreader_metadata: &Option<KoReaderMetadata>,
+        book_md5: Option<String>,
+    ) -> Option<String> {
+        if let Some(metadata) = koreader_metadata
+            && let Some(md5) = metadata.partial_md5_checksum.as_ref()
+        {
+            return Some(md5.clone());
+        }
+        book_md5.or_else(|| calculate_partial_md5(path).ok())
     }
 
-    fn collect_md5_for_item(&self, library_md5s: &mut HashSet<String>, path: &Path, koreader_metadata: &Option<KoReaderMetadata>, book_md5: &Option<String>) {
-        if let Some(metadata) = koreader_metadata {
-            if let Some(md5) = metadata.partial_md5_checksum.as_ref() {
-                library_md5s.insert(md5.clone());
-                return;
-            }
-        }
-        if let Some(md5) = book_md5 {
-            library_md5s.insert(md5.clone());
-        } else if let Ok(md5) = calculate_partial_md5(path) {
-            library_md5s.insert(md5);
+    /// Scan a single library file, reusing `cached` if neither the file nor its
+    /// metadata changed, and its book info if only the metadata changed. Returns `None` for
+    /// files that cannot be parsed.
+    async fn scan_item(
+        &self,
+        parsers: &ItemParsers,
+        job: ScanJob,
+    ) -> Option<(LibraryItem, CachedItem, bool)> {
+        let ScanJob {
+            path,
+            format,
+            cached,
+            ..
+        } = job;
+        let file = FileFingerprint::of(&path)?;
+
+        // The cached MD5 is still valid if the book file itself is unchanged
+        let cached = cached.filter(|c| c.fingerprint.file == file);
+        let known_md5 = cached.as_ref().and_then(|c| c.md5.clone());
+        let (metadata_path, book_md5) = self.locate_metadata_path_and_md5(&path, format, known_md5);
+        let fingerprint = ItemFingerprint {
+            file,
+            metadata_modified: metadata_path.as_deref().and_then(modified_nanos),
+            metadata_path,
+        };
+
+        if let Some(cached) = &cached
+            && cached.fingerprint == fingerprint
+        {
+            return Some((cached.item.clone(), cached.clone(), false));
         }
+
+        // If only the metadata changed, the book file does not need to be parsed again. The
+        // cached item carries no cover bytes, so remember whether it had a cover.
+        let (book_info, had_cover) = match cached {
+            Some(cached) => (cached.item.book_info, cached.has_cover),
+            None => (parsers.parse_book_info(format, &path).await.ok()?, false),
+        };
+        let koreader_metadata = parsers
+            .parse_koreader_metadata(fingerprint.metadata_path.clone())
+            .await;
+        let md5 = self.item_md5(&path, &koreader_metadata, book_md5);
+
+        let item = LibraryItem {
+            // Assigned by `scan_library` once all items are known
+            id: String::new(),
+            book_info,
+            koreader_metadata,
+            source: ItemSource::File { path, format },
+        };
+        let mut entry = CachedItem::new(fingerprint, &item, md5);
+        entry.has_cover |= had_cover;
+        Some((item, entry, true))
     }
 }
 
+/// A library file waiting to be scanned by a worker.
+struct ScanJob {
+    /// Position of the file in walk order, used to restore deterministic ordering
+    index: usize,
+    path: PathBuf,
+    format: LibraryItemFormat,
+    cached: Option<CachedItem>,
+}
+
+/// Number of scan worker threads: one per CPU, within sensible bounds. Parsing is mostly
+/// I/O bound on network shares, so use at least a few workers even on small machines.
+fn scan_worker_count(jobs: usize) -> usize {
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    cpus.clamp(MIN_SCAN_WORKERS, MAX_SCAN_WORKERS)
+        .min(jobs)
+        .max(1)
+}
+
+/// Result of a library scan.
+pub struct LibraryScan {
+    pub items: Vec<LibraryItem>,
+    /// Partial MD5 hashes of all items (for statistics filtering)
+    pub library_md5s: HashSet<String>,
+    /// Cache entries for every scanned item, to be passed to the next scan
+    pub cache: ScanCache,
+    /// Number of items that were parsed rather than taken from the cache
+    pub parsed: usize,
+}
+
+/// Scan all library paths. Items whose book file and metadata file are unchanged since
+/// `previous` was built are taken from it instead of being parsed again.
 pub async fn scan_library(
     library_paths: &[PathBuf],
     metadata_location: &MetadataLocation,
-) -> Result<(Vec<LibraryItem>, HashSet<String>)> {
-    
+    id_scheme: ItemIdScheme,
+    previous: Option<&ScanCache>,
+) -> Result<LibraryScan> {
     // CORREÇÃO FINAL DOS TIPOS:
     // min_pages/min_time corrigidos para None (Option<u32>)
     // language corrigido para String::new() para satisfazer a struct
     let scanner = Scanner::new(SiteConfig {
         library_paths: library_paths.to_vec(),
+        library_options: HashMap::new(),
         metadata_location: metadata_location.clone(),
         output_dir: PathBuf::new(),
         site_title: String::new(),
         include_unread: false,
-        statistics_db_path: None,
+        statistics_db_paths: Vec::new(),
+        statistics_db_devices: HashMap::new(),
         heatmap_scale_max: Some(0),
         // TimeConfig::new(Option<Tz>, u16)
-        time_config: TimeConfig::new(None, 0), 
-        min_pages_per_day: None, 
-        min_time_per_day: None,  
+        time_config: TimeConfig::new(None, 0),
+        min_pages_per_day: None,
+        min_time_per_day: None,
         include_all_stats: false,
         is_internal_server: false,
-        language: String::new(), 
+        language: String::new(),
+        id_scheme,
+        kosync_db_path: None,
+        site_root: String::new(),
+        profiles: Vec::new(),
+        goals: Default::default(),
+        completion: Default::default(),
+        session_gap: DEFAULT_SESSION_GAP_SECONDS,
     });
 
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.cyan} {msg}").unwrap());
-    spinner.set_message("Scanning library...");
-    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
-
-    let mut books = Vec::new();
-    let mut library_md5s = HashSet::new();
+    let scanner = Arc::new(scanner);
 
+    // Collect all candidate files first so the work can be spread over the workers
+    let mut jobs = Vec::new();
     for library_path in library_paths {
-        for entry in walkdir::WalkDir::new(library_path) {
+        for entry in walkdir::WalkDir::new(library_path).sort_by_file_name() {
             let entry = entry?;
             let path = entry.path();
-            let format = match LibraryItemFormat::from_path(path) {
-                Some(f) => f,
-                None => continue,
+            let Some(format) = LibraryItemFormat::from_path(path) else {
+                continue;
             };
-            let book_info = match scanner.parse_book_info(format, path).await {
-                Ok(info) => info,
-                Err(_) => continue,
-            };
-            let (metadata_path, book_md5) = scanner.locate_metadata_path_and_md5(path, format);
-            let koreader_metadata = scanner.parse_koreader_metadata(metadata_path).await;
-            
-            // Lógica de coleta de MD5 integrada
-            scanner.collect_md5_for_item(&mut library_md5s, path, &koreader_metadata, &book_md5);
-            
-            books.push(LibraryItem {
-                id: generate_book_id(&book_info.title),
-                book_info,
-                koreader_metadata,
-                file_path: path.to_path_buf(),
+            jobs.push(ScanJob {
+                index: jobs.len(),
+                path: path.to_path_buf(),
                 format,
+                cached: previous.and_then(|c| c.get(path)).cloned(),
             });
-            spinner.set_message(format!("Scanning library... {} items found", books.len()));
         }
     }
-    spinner.finish_and_clear();
-    Ok((books, library_md5s))
-}
\ No newline at end of file
+
+    let total = jobs.len();
+    let progress = ProgressBar::new(total as u64);
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template("{msg} {bar:30.cyan/blue} {pos}/{len} ({per_sec})")
+            .unwrap()
+            .progress_chars("━╸─"),
+    );
+    progress.set_message("Scanning library:");
+
+    // Workers are plain threads, each with its own parsers (and Lua state). They drive the
+    // async parsers through the runtime handle and send results back tagged with their index.
+    let runtime = tokio::runtime::Handle::current();
+    let queue = Arc::new(Mutex::new(jobs.into_iter()));
+    let (result_tx, mut result_rx) = mpsc::unbounded_channel();
+    let workers: Vec<_> = (0..scan_worker_count(total))
+        .map(|_| {
+            let scanner = Arc::clone(&scanner);
+            let queue = Arc::clone(&queue);
+            let result_tx = result_tx.clone();
+            let runtime = runtime.clone();
+            std::thread::spawn(move || {
+                let parsers = ItemParsers::new();
+                loop {
+                    let Some(job) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    let index = job.index;
+                    let result = runtime.block_on(scanner.scan_item(&parsers, job));
+                    if result_tx.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(result_tx);
+
+    let mut results: Vec<Option<(LibraryItem, CachedItem, bool)>> = vec![None; total];
+    while let Some((index, result)) = result_rx.recv().await {
+        results[index] = result;
+        progress.inc(1);
+    }
+    for worker in workers {
+        if worker.join().is_err() {
+            bail!("Library scan worker panicked");
+        }
+    }
+    progress.finish_and_clear();
+
+    let mut books = Vec::with_capacity(total);
+    let mut library_md5s = HashSet::new();
+    let mut cache = ScanCache::new();
+    let mut parsed = 0;
+    // IDs are assigned in walk order: the first item keeps the plain ID on every scan
+    let mut taken_ids = HashSet::new();
+    for (mut item, mut entry, was_parsed) in results.into_iter().flatten() {
+        item.id = unique_item_id(&item, entry.md5.as_deref(), id_scheme, &mut taken_ids);
+        entry.item.id = item.id.clone();
+        if let Some(md5) = &entry.md5 {
+            library_md5s.insert(md5.clone());
+        }
+        if was_parsed {
+            parsed += 1;
+        }
+        if let Some(path) = item.file_path() {
+            cache.insert(path.to_path_buf(), entry);
+        }
+        books.push(item);
+    }
+
+    Ok(LibraryScan {
+        items: books,
+        library_md5s,
+        cache,
+        parsed,
+    })
+}
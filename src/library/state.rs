@@ -0,0 +1,64 @@
+//! Shared, swappable library state for server mode.
+//!
+//! The site generator produces a [`LibrarySnapshot`] on every run. The file watcher
+//! publishes each new snapshot here so API handlers always serve the same data the
+//! rendered HTML was generated from.
+
+use crate::models::{LibraryItem, StatisticsData};
+use crate::search::SearchIndex;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Library items and statistics from a single site generation run.
+#[derive(Default)]
+pub struct LibrarySnapshot {
+    /// Build version (matches version.txt and cache-manifest.json)
+    pub version: String,
+    /// All items included in the generated site
+    pub items: Vec<LibraryItem>,
+    /// Statistics data, with completions populated (if a statistics database is configured)
+    pub stats_data: Option<StatisticsData>,
+    /// Full-text search index over `items`
+    pub search_index: SearchIndex,
+    /// Snapshots of the reader profile sites by profile slug. When profiles are configured,
+    /// `items` holds the shared OPDS catalog instead of a site's items.
+    pub profiles: BTreeMap<String, Arc<LibrarySnapshot>>,
+}
+
+/// Holds the latest snapshot; readers get a cheap `Arc` clone that stays valid after a swap.
+#[derive(Default)]
+pub struct LibraryState {
+    current: RwLock<Arc<LibrarySnapshot>>,
+}
+
+impl LibraryState {
+    /// Create a state holding the given snapshot.
+    pub fn new(snapshot: LibrarySnapshot) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(snapshot)),
+        }
+    }
+
+    /// Get the current snapshot.
+    pub fn load(&self) -> Arc<LibrarySnapshot> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    /// Get the current snapshot of a reader profile's site, or of the root site for `None`.
+    /// Unknown profiles get an empty snapshot.
+    pub fn load_profile(&self, profile: Option<&str>) -> Arc<LibrarySnapshot> {
+        let snapshot = self.load();
+        match profile {
+            Some(slug) => snapshot.profiles.get(slug).cloned().unwrap_or_default(),
+            None => snapshot,
+        }
+    }
+
+    /// Replace the current snapshot after a rebuild.
+    pub fn store(&self, snapshot: LibrarySnapshot) {
+        *self.current.write().unwrap() = Arc::new(snapshot);
+    }
+}
+
+/// Arc-wrapped library state for sharing between the watcher and the web server.
+pub type SharedLibraryState = Arc<LibraryState>;
@@ -1,4 +1,5 @@
 use super::scanner::MetadataLocation;
+use super::state::SharedLibraryState;
 use crate::config::SiteConfig;
 use crate::models::LibraryItemFormat;
 use crate::server::version::SharedVersionNotifier;
@@ -6,6 +7,7 @@ use crate::site_generator::SiteGenerator;
 use anyhow::Result;
 use log::{debug, info, warn};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
@@ -13,6 +15,7 @@ use tokio::time::sleep;
 pub struct FileWatcher {
     config: SiteConfig,
     version_notifier: Option<SharedVersionNotifier>,
+    library_state: Option<SharedLibraryState>,
 }
 
 impl std::ops::Deref for FileWatcher {
@@ -23,10 +26,15 @@ impl std::ops::Deref for FileWatcher {
 }
 
 impl FileWatcher {
-    pub fn new(config: SiteConfig, version_notifier: Option<SharedVersionNotifier>) -> Self {
+    pub fn new(
+        config: SiteConfig,
+        version_notifier: Option<SharedVersionNotifier>,
+        library_state: Option<SharedLibraryState>,
+    ) -> Self {
         Self {
             config,
             version_notifier,
+            library_state,
         }
     }
 
@@ -57,29 +65,31 @@ impl FileWatcher {
         }
 
         // Watch external metadata locations if configured
-        match &self.metadata_location {
-            MetadataLocation::DocSettings(path) => {
-                watcher.watch(path, RecursiveMode::Recursive)?;
-                info!("File watcher started for docsettings directory: {:?}", path);
-            }
-            MetadataLocation::HashDocSettings(path) => {
-                watcher.watch(path, RecursiveMode::Recursive)?;
-                info!(
-                    "File watcher started for hashdocsettings directory: {:?}",
-                    path
-                );
-            }
-            MetadataLocation::InBookFolder => {
-                // Already watching books_path recursively
+        for metadata_location in self.metadata_locations() {
+            match metadata_location {
+                MetadataLocation::DocSettings(path) => {
+                    watcher.watch(path, RecursiveMode::Recursive)?;
+                    info!("File watcher started for docsettings directory: {:?}", path);
+                }
+                MetadataLocation::HashDocSettings(path) => {
+                    watcher.watch(path, RecursiveMode::Recursive)?;
+                    info!(
+                        "File watcher started for hashdocsettings directory: {:?}",
+                        path
+                    );
+                }
+                MetadataLocation::InBookFolder => {
+                    // Already watching books_path recursively
+                }
             }
         }
 
-        // Also watch the statistics database if provided
-        if let Some(ref stats_path) = self.statistics_db_path
-            && stats_path.exists()
-        {
+        // Also watch the statistics databases if provided
+        for stats_path in self.all_statistics_db_paths() {
             // Watch the parent directory of the statistics database
-            if let Some(parent) = stats_path.parent() {
+            if stats_path.exists()
+                && let Some(parent) = stats_path.parent()
+            {
                 watcher.watch(parent, RecursiveMode::NonRecursive)?;
                 info!(
                     "File watcher started for statistics database: {:?}",
@@ -88,9 +98,19 @@ impl FileWatcher {
             }
         }
 
+        // Watch the progress sync database, so progress from devices shows up after a rebuild
+        if let Some(ref kosync_path) = self.kosync_db_path
+            && kosync_path.exists()
+            && let Some(parent) = kosync_path.parent()
+        {
+            watcher.watch(parent, RecursiveMode::NonRecursive)?;
+            info!("File watcher started for sync database: {:?}", kosync_path);
+        }
+
         // Clone the config and version notifier for the rebuild task
         let config_clone = self.config.clone();
         let version_notifier_clone = self.version_notifier.clone();
+        let library_state_clone = self.library_state.clone();
 
         // Spawn delayed rebuild task
         // NOTE: Site generation uses non-Send types (e.g. mlua::Lua, Rc-based translations),
@@ -113,12 +133,18 @@ impl FileWatcher {
                     let site_generator = SiteGenerator::new(config_clone.clone());
 
                     match site_generator.generate().await {
-                        Ok(_) => {
+                        Ok(snapshot) => {
                             info!("Delayed site rebuild completed successfully");
 
-                            // Notify long-polling clients that a new version is available
+                            // Publish the new data before announcing the version, so clients
+                            // reacting to the event already see it through the API
+                            let version = snapshot.version.clone();
+                            if let Some(ref state) = library_state_clone {
+                                state.store(snapshot);
+                            }
+
+                            // Notify SSE clients that a new version is available
                             if let Some(ref notifier) = version_notifier_clone {
-                                let version = chrono::Local::now().to_rfc3339();
                                 notifier.notify(version);
                             }
                         }
@@ -143,6 +169,26 @@ impl FileWatcher {
         Ok(())
     }
 
+    /// Metadata locations of the site and of its reader profiles, without duplicates.
+    fn metadata_locations(&self) -> Vec<&MetadataLocation> {
+        let mut locations = vec![&self.metadata_location];
+        for profile in &self.profiles {
+            if !locations.contains(&&profile.metadata_location) {
+                locations.push(&profile.metadata_location);
+            }
+        }
+        locations
+    }
+
+    /// Statistics databases of the site and of its reader profiles.
+    fn all_statistics_db_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.statistics_db_paths.iter().chain(
+            self.profiles
+                .iter()
+                .flat_map(|profile| &profile.statistics_db_paths),
+        )
+    }
+
     fn should_process_event(&self, event: &Event) -> bool {
         match &event.kind {
             // Only process actual file changes, not access events
@@ -176,7 +222,7 @@ impl FileWatcher {
         event.paths.iter().any(|path| {
             let filename = path.file_name().and_then(|s| s.to_str());
 
-            // Check for library items using LibraryItemFormat (handles .epub, .fb2, .fb2.zip, .cbz, .cbr)
+            // Check for library items using LibraryItemFormat (handles .epub, .fb2, .fb2.zip, .mobi, .pdf, .djvu, .djv, .cbz, .cbr)
             if LibraryItemFormat::from_path(path).is_some() {
                 return true;
             }
@@ -196,9 +242,12 @@ impl FileWatcher {
             }
 
             // Check for statistics database files
-            if let Some(ref stats_path) = self.statistics_db_path
-                && path == stats_path
-            {
+            if self.all_statistics_db_paths().any(|p| p == path) {
+                return true;
+            }
+
+            // Check for the progress sync database
+            if self.kosync_db_path.as_ref() == Some(path) {
                 return true;
             }
 
@@ -235,11 +284,14 @@ impl FileWatcher {
             }
 
             // Check statistics database
-            if let Some(ref stats_path) = self.statistics_db_path
-                && path == stats_path
-            {
+            if self.all_statistics_db_paths().any(|p| p == path) {
                 info!("Statistics database {}: {:?}", action, path);
             }
+
+            // Check progress sync database
+            if self.kosync_db_path.as_ref() == Some(path) {
+                info!("Sync database {}: {:?}", action, path);
+            }
         }
     }
 }
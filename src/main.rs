@@ -1,5 +1,4 @@
 use anyhow::Result;
-use clap::Parser;
 use koshelf::{Cli, run};
 
 #[tokio::main]
@@ -8,7 +7,7 @@ async fn main() -> Result<()> {
         .filter_level(log::LevelFilter::Info)
         .parse_default_env()
         .init();
-    let cli = Cli::parse();
+    let cli = Cli::parse_with_config()?;
 
     // Handle --github flag
     if cli.github {
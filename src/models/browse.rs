@@ -0,0 +1,201 @@
+use super::{BookStatus, LibraryItem, StatisticsData};
+use crate::utils::generate_slug;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// What the library can be browsed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowseKind {
+    Author,
+    Series,
+    Subject,
+    Language,
+}
+
+impl BrowseKind {
+    pub const ALL: [BrowseKind; 4] = [
+        BrowseKind::Author,
+        BrowseKind::Series,
+        BrowseKind::Subject,
+        BrowseKind::Language,
+    ];
+
+    /// Path segment of the kind's pages (e.g. `authors` for `/authors/<slug>/`).
+    pub fn path(&self) -> &'static str {
+        match self {
+            BrowseKind::Author => "authors",
+            BrowseKind::Series => "series",
+            BrowseKind::Subject => "subjects",
+            BrowseKind::Language => "languages",
+        }
+    }
+
+    /// Translation key of the kind's title.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            BrowseKind::Author => "browse.authors",
+            BrowseKind::Series => "browse.series",
+            BrowseKind::Subject => "browse.subjects",
+            BrowseKind::Language => "browse.languages",
+        }
+    }
+
+    /// Names of the item's authors, series, subjects or language.
+    pub fn names_of<'a>(&self, item: &'a LibraryItem) -> Vec<&'a String> {
+        match self {
+            BrowseKind::Author => item.book_info.authors.iter().collect(),
+            BrowseKind::Series => item.series().into_iter().collect(),
+            BrowseKind::Subject => item.subjects().iter().collect(),
+            BrowseKind::Language => item.language().into_iter().collect(),
+        }
+    }
+}
+
+/// Items grouped by the slug of their names, with the first spelling seen as the group's
+/// name. Names with the same slug (e.g. differing only in case) share a group.
+pub fn group_by_slug(
+    items: &[LibraryItem],
+    kind: BrowseKind,
+) -> BTreeMap<String, (String, Vec<&LibraryItem>)> {
+    let mut groups: BTreeMap<String, (String, Vec<&LibraryItem>)> = BTreeMap::new();
+    for item in items {
+        for name in kind.names_of(item) {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let (_, members) = groups
+                .entry(generate_slug(name))
+                .or_insert_with(|| (name.to_string(), Vec::new()));
+            if !members.iter().any(|member| std::ptr::eq(*member, item)) {
+                members.push(item);
+            }
+        }
+    }
+    groups
+}
+
+pub fn compare_titles(a: &LibraryItem, b: &LibraryItem) -> Ordering {
+    a.book_info
+        .title
+        .to_lowercase()
+        .cmp(&b.book_info.title.to_lowercase())
+}
+
+/// Order by series number (numerically where possible), then by title.
+pub fn compare_series_positions(a: &LibraryItem, b: &LibraryItem) -> Ordering {
+    let position = |item: &LibraryItem| {
+        item.series_number()
+            .and_then(|n| n.trim().parse::<f64>().ok())
+            .unwrap_or(f64::MAX)
+    };
+    position(a)
+        .total_cmp(&position(b))
+        .then_with(|| compare_titles(a, b))
+}
+
+/// An item on an author, series or subject page.
+#[derive(Debug, Clone)]
+pub struct BrowseEntry {
+    pub item: LibraryItem,
+    /// Reading time from the statistics database, in seconds
+    pub read_time: i64,
+    pub completions: usize,
+}
+
+impl BrowseEntry {
+    /// Whether the item was finished (its status is complete).
+    pub fn is_finished(&self) -> bool {
+        self.item.status() == BookStatus::Complete
+    }
+
+    /// Whether the item was never opened in KOReader.
+    pub fn is_unread(&self) -> bool {
+        self.item.koreader_metadata.is_none()
+    }
+}
+
+/// The items of one author, series or subject, with their aggregated reading statistics.
+#[derive(Debug, Clone)]
+pub struct BrowseGroup {
+    pub kind: BrowseKind,
+    pub name: String,
+    pub slug: String,
+    /// Ordered by series number for series, by title otherwise
+    pub entries: Vec<BrowseEntry>,
+    /// Total reading time of the entries, in seconds
+    pub read_time: i64,
+    pub completions: usize,
+}
+
+impl BrowseGroup {
+    /// Groups of `kind` ordered by name, with reading time and completions taken from
+    /// `stats` (matched by the items' partial MD5).
+    pub fn collect(
+        kind: BrowseKind,
+        items: &[LibraryItem],
+        stats: Option<&StatisticsData>,
+    ) -> Vec<BrowseGroup> {
+        let mut groups: Vec<BrowseGroup> = group_by_slug(items, kind)
+            .into_iter()
+            .map(|(slug, (name, mut members))| {
+                if kind == BrowseKind::Series {
+                    members.sort_by(|a, b| compare_series_positions(a, b));
+                } else {
+                    members.sort_by(|a, b| compare_titles(a, b));
+                }
+
+                let entries: Vec<BrowseEntry> = members
+                    .into_iter()
+                    .map(|item| {
+                        let stat = stats.and_then(|stats| {
+                            item.koreader_metadata
+                                .as_ref()
+                                .and_then(|m| m.partial_md5_checksum.as_ref())
+                                .and_then(|md5| stats.stats_by_md5.get(md5))
+                        });
+                        BrowseEntry {
+                            item: item.clone(),
+                            read_time: stat.and_then(|s| s.total_read_time).unwrap_or(0),
+                            completions: stat
+                                .and_then(|s| s.completions.as_ref())
+                                .map_or(0, |c| c.total_completions),
+                        }
+                    })
+                    .collect();
+
+                BrowseGroup {
+                    kind,
+                    name,
+                    slug,
+                    read_time: entries.iter().map(|e| e.read_time).sum(),
+                    completions: entries.iter().map(|e| e.completions).sum(),
+                    entries,
+                }
+            })
+            .collect();
+        groups.sort_by_key(|group| group.name.to_lowercase());
+        groups
+    }
+
+    /// Number of finished entries.
+    pub fn finished_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_finished()).count()
+    }
+
+    /// Number of entries never opened in KOReader.
+    pub fn unread_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_unread()).count()
+    }
+
+    /// Share of finished entries, in percent.
+    pub fn finished_percentage(&self) -> usize {
+        if self.entries.is_empty() {
+            0
+        } else {
+            self.finished_count() * 100 / self.entries.len()
+        }
+    }
+}
@@ -12,6 +12,8 @@ pub struct CalendarEvent {
     pub end: Option<String>, // ISO date: yyyy-mm-dd (optional, for single-day events)
     pub total_read_time: i64, // Total seconds read for this item
     pub total_pages_read: i64, // Total pages read for this item
+    #[serde(default)]
+    pub session_count: i64, // Reading sessions started within the event span
     pub item_id: String, // Reference to item metadata
 }
 
@@ -51,6 +53,7 @@ impl CalendarEvent {
         end_date: Option<String>,
         total_read_time: i64,
         total_pages_read: i64,
+        session_count: i64,
         item_id: String,
     ) -> Self {
         Self {
@@ -58,6 +61,7 @@ impl CalendarEvent {
             end: end_date,
             total_read_time,
             total_pages_read,
+            session_count,
             item_id,
         }
     }
@@ -0,0 +1,92 @@
+use serde::Serialize;
+
+/// Period a reading goal applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalPeriod {
+    Year,
+    Month,
+}
+
+/// What a reading goal counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalMetric {
+    /// Completed books and comics (re-reads count again)
+    Books,
+    Hours,
+    Pages,
+}
+
+impl GoalMetric {
+    /// Translation key of the goal's label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            GoalMetric::Books => "goal.books",
+            GoalMetric::Hours => "goal.hours",
+            GoalMetric::Pages => "goal.pages",
+        }
+    }
+}
+
+impl GoalPeriod {
+    /// Translation key of the period's label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            GoalPeriod::Year => "goal.yearly",
+            GoalPeriod::Month => "goal.monthly",
+        }
+    }
+}
+
+/// Progress towards a single reading goal.
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalProgress {
+    pub period: GoalPeriod,
+    /// `YYYY` for yearly goals, `YYYY-MM` for monthly goals
+    pub period_key: String,
+    pub metric: GoalMetric,
+    pub target: u32,
+    /// Amount reached so far (hours may be fractional)
+    pub actual: f64,
+    /// Share of the target reached, in percent (may exceed 100)
+    pub percentage: f64,
+    /// Amount that would have been reached by today at an even pace
+    pub expected: f64,
+    /// Amount at the end of the period if the current pace continues
+    pub projected: f64,
+    /// Days of the period that have passed, including today
+    pub elapsed_days: i64,
+    pub total_days: i64,
+    pub reached: bool,
+    /// Whether the actual amount is at least the expected one
+    pub on_track: bool,
+}
+
+impl GoalProgress {
+    pub fn actual_display(&self) -> i64 {
+        self.actual.floor() as i64
+    }
+
+    pub fn expected_display(&self) -> i64 {
+        self.expected.round() as i64
+    }
+
+    pub fn projected_display(&self) -> i64 {
+        self.projected.round() as i64
+    }
+
+    pub fn percentage_display(&self) -> i64 {
+        self.percentage.floor() as i64
+    }
+
+    /// Percentage for progress bars, capped at 100.
+    pub fn bar_percentage(&self) -> u32 {
+        self.percentage.clamp(0.0, 100.0).round() as u32
+    }
+
+    /// Whether the period is over, so pace and projection no longer apply.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_days >= self.total_days
+    }
+}
@@ -27,8 +27,86 @@ pub struct Annotation {
     pub pos1: Option<String>,
     pub text: Option<String>, // Optional: highlights have text, bookmarks don't
     pub note: Option<String>,
+    /// Highlight color name as written by KoReader (e.g. "yellow", "red")
+    pub color: Option<String>,
+    pub drawer: Option<HighlightDrawer>,
+    pub datetime_updated: Option<String>,
+    /// Location of the annotation: an xpointer for reflowable documents, a page number otherwise
+    pub page: Option<String>,
 }
 
+/// How KoReader draws a highlight (the annotation's `drawer` field).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightDrawer {
+    Lighten,
+    Underscore,
+    Strikeout,
+    Invert,
+}
+
+impl HighlightDrawer {
+    pub const ALL: [Self; 4] = [
+        Self::Lighten,
+        Self::Underscore,
+        Self::Strikeout,
+        Self::Invert,
+    ];
+
+    /// Parse KoReader's drawer name. Unknown drawers are shown as regular highlights.
+    pub fn from_koreader(drawer: &str) -> Self {
+        match drawer {
+            "underscore" => Self::Underscore,
+            "strikeout" => Self::Strikeout,
+            "invert" => Self::Invert,
+            _ => Self::Lighten,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lighten => "lighten",
+            Self::Underscore => "underscore",
+            Self::Strikeout => "strikeout",
+            Self::Invert => "invert",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Lighten => "Highlight",
+            Self::Underscore => "Underline",
+            Self::Strikeout => "Strikeout",
+            Self::Invert => "Invert",
+        }
+    }
+
+    pub fn translation_key(&self) -> &'static str {
+        match self {
+            Self::Lighten => "highlight-style.lighten",
+            Self::Underscore => "highlight-style.underscore",
+            Self::Strikeout => "highlight-style.strikeout",
+            Self::Invert => "highlight-style.invert",
+        }
+    }
+}
+
+/// KoReader's highlight color palette: name, translation key and display color.
+pub const HIGHLIGHT_COLORS: [(&str, &str, &str); 9] = [
+    ("red", "highlight-color.red", "#ff3300"),
+    ("orange", "highlight-color.orange", "#ff8800"),
+    ("yellow", "highlight-color.yellow", "#ffff33"),
+    ("green", "highlight-color.green", "#00aa66"),
+    ("olive", "highlight-color.olive", "#88ff77"),
+    ("cyan", "highlight-color.cyan", "#00ffee"),
+    ("blue", "highlight-color.blue", "#0066ff"),
+    ("purple", "highlight-color.purple", "#ee00ff"),
+    ("gray", "highlight-color.gray", "#808080"),
+];
+
+/// Accent used for highlights without a (known) color.
+const DEFAULT_HIGHLIGHT_HEX: &str = "#f59e0b";
+
 impl Annotation {
     pub fn formatted_datetime(&self, translations: &crate::i18n::Translations) -> Option<String> {
         self.datetime.as_ref().and_then(|dt| {
@@ -45,6 +123,71 @@ impl Annotation {
         })
     }
 
+    /// Returns true if the annotation was changed after it was created.
+    pub fn is_edited(&self) -> bool {
+        self.datetime_updated.is_some() && self.datetime_updated != self.datetime
+    }
+
+    /// Formatted edit time, only if the annotation was changed after it was created.
+    pub fn formatted_datetime_updated(
+        &self,
+        translations: &crate::i18n::Translations,
+    ) -> Option<String> {
+        if !self.is_edited() {
+            return None;
+        }
+        Self {
+            datetime: self.datetime_updated.clone(),
+            ..self.clone()
+        }
+        .formatted_datetime(translations)
+    }
+
+    /// Drawing style, defaulting to KoReader's regular highlight.
+    pub fn drawer_style(&self) -> HighlightDrawer {
+        self.drawer.unwrap_or(HighlightDrawer::Lighten)
+    }
+
+    fn palette_entry(&self) -> Option<&'static (&'static str, &'static str, &'static str)> {
+        let color = self.color.as_deref()?.to_lowercase();
+        HIGHLIGHT_COLORS.iter().find(|(name, _, _)| *name == color)
+    }
+
+    /// Normalized color name for filtering, `None` for missing or unknown colors.
+    pub fn color_name(&self) -> Option<&'static str> {
+        self.palette_entry().map(|(name, _, _)| *name)
+    }
+
+    pub fn color_translation_key(&self) -> Option<&'static str> {
+        self.palette_entry().map(|(_, key, _)| *key)
+    }
+
+    pub fn color_hex(&self) -> &'static str {
+        self.palette_entry()
+            .map(|(_, _, hex)| *hex)
+            .unwrap_or(DEFAULT_HIGHLIGHT_HEX)
+    }
+
+    /// Inline CSS that renders the quote the way KoReader draws it.
+    pub fn text_style(&self) -> String {
+        let hex = self.color_hex();
+        match self.drawer_style() {
+            // 8-digit hex: the color at ~35% opacity
+            HighlightDrawer::Lighten => format!(
+                "background-color: {hex}59; box-decoration-break: clone; -webkit-box-decoration-break: clone;"
+            ),
+            HighlightDrawer::Underscore => format!(
+                "text-decoration: underline; text-decoration-color: {hex}; text-decoration-thickness: 2px; text-underline-offset: 4px;"
+            ),
+            HighlightDrawer::Strikeout => format!(
+                "text-decoration: line-through; text-decoration-color: {hex}; text-decoration-thickness: 2px;"
+            ),
+            HighlightDrawer::Invert => format!(
+                "background-color: {hex}; color: #111827; box-decoration-break: clone; -webkit-box-decoration-break: clone;"
+            ),
+        }
+    }
+
     /// Returns true if this annotation is a bookmark (no pos0/pos1), false if it's a highlight/quote
     pub fn is_bookmark(&self) -> bool {
         self.pos0.is_none() && self.pos1.is_none()
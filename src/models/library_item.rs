@@ -3,7 +3,8 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use super::koreader_metadata::KoReaderMetadata;
+use super::koreader_metadata::{KoReaderMetadata, Stats, Summary};
+use super::statistics::StatBook;
 
 /// Content type classification (broad category)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -29,11 +30,24 @@ pub enum LibraryItemFormat {
     Epub,
     Fb2,
     Mobi,
+    Pdf,
+    Djvu,
     Cbz,
     Cbr,
 }
 
 impl LibraryItemFormat {
+    /// All formats, in the order their metadata files are looked up.
+    pub const ALL: [Self; 7] = [
+        Self::Epub,
+        Self::Fb2,
+        Self::Mobi,
+        Self::Pdf,
+        Self::Djvu,
+        Self::Cbz,
+        Self::Cbr,
+    ];
+
     pub fn from_path(path: &Path) -> Option<Self> {
         let filename = path.file_name()?.to_str()?.to_lowercase();
 
@@ -46,6 +60,8 @@ impl LibraryItemFormat {
             "epub" => Some(Self::Epub),
             "fb2" => Some(Self::Fb2),
             "mobi" => Some(Self::Mobi),
+            "pdf" => Some(Self::Pdf),
+            "djvu" | "djv" => Some(Self::Djvu),
             "cbz" => Some(Self::Cbz),
             #[cfg(not(windows))]
             "cbr" => Some(Self::Cbr),
@@ -53,30 +69,54 @@ impl LibraryItemFormat {
         }
     }
 
-    pub fn metadata_filename(&self) -> &'static str {
+    /// File extensions of the format (without the leading dot), the usual one first.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Epub => &["epub"],
+            Self::Fb2 => &["fb2"],
+            Self::Mobi => &["mobi"],
+            Self::Pdf => &["pdf"],
+            Self::Djvu => &["djvu", "djv"],
+            Self::Cbz => &["cbz"],
+            Self::Cbr => &["cbr"],
+        }
+    }
+
+    /// MIME type used when serving files of the format.
+    pub fn mime_type(&self) -> &'static str {
         match self {
-            Self::Epub => "metadata.epub.lua",
-            Self::Fb2 => "metadata.fb2.lua",
-            Self::Mobi => "metadata.mobi.lua",
-            Self::Cbz => "metadata.cbz.lua",
-            Self::Cbr => "metadata.cbr.lua",
+            Self::Epub => "application/epub+zip",
+            Self::Fb2 => "application/x-fictionbook+xml",
+            Self::Mobi => "application/x-mobipocket-ebook",
+            Self::Pdf => "application/pdf",
+            Self::Djvu => "image/vnd.djvu",
+            Self::Cbz => "application/vnd.comicbook+zip",
+            Self::Cbr => "application/vnd.comicbook-rar",
         }
     }
 
+    /// Name of the KoReader metadata file of a book: KoReader names it after the book's
+    /// extension (`metadata.djv.lua` for `book.djv`).
+    pub fn metadata_filename(&self, path: &Path) -> String {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .filter(|ext| self.extensions().contains(&ext.as_str()))
+            .unwrap_or_else(|| self.extensions()[0].to_string());
+        format!("metadata.{}.lua", ext)
+    }
+
     pub fn is_metadata_file(filename: &str) -> bool {
-        matches!(
-            filename,
-            "metadata.epub.lua"
-                | "metadata.fb2.lua"
-                | "metadata.mobi.lua"
-                | "metadata.cbz.lua"
-                | "metadata.cbr.lua"
-        )
+        filename
+            .strip_prefix("metadata.")
+            .and_then(|rest| rest.strip_suffix(".lua"))
+            .is_some_and(|ext| Self::ALL.iter().any(|f| f.extensions().contains(&ext)))
     }
 
     pub fn content_type(&self) -> ContentType {
         match self {
-            Self::Epub | Self::Fb2 | Self::Mobi => ContentType::Book,
+            Self::Epub | Self::Fb2 | Self::Mobi | Self::Pdf | Self::Djvu => ContentType::Book,
             Self::Cbz | Self::Cbr => ContentType::Comic,
         }
     }
@@ -135,16 +175,122 @@ impl Identifier {
     }
 }
 
+/// Where a library item comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemSource {
+    /// A book or comic file in the library
+    File {
+        path: PathBuf,
+        format: LibraryItemFormat,
+    },
+    /// A book only known from the statistics database, e.g. because its file was removed
+    /// from the library
+    Statistics { content_type: ContentType },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LibraryItem {
     pub id: String,
     pub book_info: BookInfo,
     pub koreader_metadata: Option<KoReaderMetadata>,
-    pub file_path: PathBuf,
-    pub format: LibraryItemFormat,
+    pub source: ItemSource,
 }
 
 impl LibraryItem {
+    /// Entry for a book only known from the statistics database, so it can still get a
+    /// detail page and be linked to.
+    pub fn from_stat_book(id: String, stat_book: &StatBook) -> Self {
+        let content_type = stat_book.content_type.unwrap_or(ContentType::Book);
+        // KOReader may record the series with its index appended ("Dune #2")
+        let (series, series_number) = match stat_book.series.as_deref() {
+            Some(series) => match series.rsplit_once(" #") {
+                Some((name, number)) if !name.trim().is_empty() && !number.trim().is_empty() => (
+                    Some(name.trim().to_string()),
+                    Some(number.trim().to_string()),
+                ),
+                _ => (Some(series.to_string()), None),
+            },
+            None => (None, None),
+        };
+        let pages = stat_book.pages.and_then(|pages| u32::try_from(pages).ok());
+        let finished = stat_book
+            .completions
+            .as_ref()
+            .is_some_and(|c| c.total_completions > 0);
+
+        Self {
+            id,
+            book_info: BookInfo {
+                title: stat_book.title.clone(),
+                // Multiple authors are separated by newlines
+                authors: stat_book
+                    .authors
+                    .lines()
+                    .map(str::trim)
+                    .filter(|author| !author.is_empty() && *author != "N/A")
+                    .map(String::from)
+                    .collect(),
+                description: None,
+                language: stat_book.language.clone(),
+                publisher: None,
+                identifiers: Vec::new(),
+                subjects: Vec::new(),
+                series,
+                series_number,
+                pages,
+                cover_data: None,
+                cover_mime_type: None,
+            },
+            koreader_metadata: Some(KoReaderMetadata {
+                annotations: Vec::new(),
+                doc_pages: pages,
+                doc_path: None,
+                doc_props: None,
+                partial_md5_checksum: Some(stat_book.md5.clone()),
+                percent_finished: finished.then_some(1.0),
+                stats: Some(Stats {
+                    authors: Some(stat_book.authors.clone()),
+                    highlights: stat_book.highlights.and_then(|n| u32::try_from(n).ok()),
+                    language: stat_book.language.clone(),
+                    notes: stat_book.notes.and_then(|n| u32::try_from(n).ok()),
+                    pages,
+                    series: stat_book.series.clone(),
+                    title: Some(stat_book.title.clone()),
+                }),
+                summary: finished.then_some(Summary {
+                    modified: None,
+                    note: None,
+                    rating: None,
+                    status: BookStatus::Complete,
+                }),
+                text_lang: None,
+            }),
+            source: ItemSource::Statistics { content_type },
+        }
+    }
+
+    /// Path of the item's file; `None` for items only known from the statistics database.
+    pub fn file_path(&self) -> Option<&Path> {
+        match &self.source {
+            ItemSource::File { path, .. } => Some(path),
+            ItemSource::Statistics { .. } => None,
+        }
+    }
+
+    pub fn format(&self) -> Option<LibraryItemFormat> {
+        match self.source {
+            ItemSource::File { format, .. } => Some(format),
+            ItemSource::Statistics { .. } => None,
+        }
+    }
+
+    /// Whether the item was built from the statistics database alone (see
+    /// [`Self::from_stat_book`]) rather than scanned from a library file.
+    pub fn is_stats_only(&self) -> bool {
+        matches!(self.source, ItemSource::Statistics { .. })
+    }
+
     pub fn status(&self) -> BookStatus {
         self.koreader_metadata
             .as_ref()
@@ -218,6 +364,39 @@ impl LibraryItem {
             .unwrap_or(0)
     }
 
+    /// Distinct highlight colors used in this item, in KoReader palette order.
+    pub fn highlight_colors(&self) -> Vec<&'static (&'static str, &'static str, &'static str)> {
+        let used: HashSet<&str> = self
+            .annotations()
+            .iter()
+            .filter(|a| a.is_highlight())
+            .filter_map(|a| a.color_name())
+            .collect();
+        super::koreader_metadata::HIGHLIGHT_COLORS
+            .iter()
+            .filter(|(name, _, _)| used.contains(name))
+            .collect()
+    }
+
+    /// Distinct highlight styles used in this item.
+    pub fn highlight_drawers(&self) -> Vec<super::koreader_metadata::HighlightDrawer> {
+        let used: HashSet<_> = self
+            .annotations()
+            .iter()
+            .filter(|a| a.is_highlight())
+            .map(|a| a.drawer_style())
+            .collect();
+        super::koreader_metadata::HighlightDrawer::ALL
+            .into_iter()
+            .filter(|drawer| used.contains(drawer))
+            .collect()
+    }
+
+    /// Whether the highlights differ enough in color or style to be worth filtering.
+    pub fn has_highlight_filters(&self) -> bool {
+        self.highlight_colors().len() > 1 || self.highlight_drawers().len() > 1
+    }
+
     pub fn doc_pages(&self) -> Option<u32> {
         self.koreader_metadata
             .as_ref()
@@ -334,7 +513,10 @@ impl LibraryItem {
     }
 
     pub fn content_type(&self) -> ContentType {
-        self.format.content_type()
+        match self.source {
+            ItemSource::File { format, .. } => format.content_type(),
+            ItemSource::Statistics { content_type } => content_type,
+        }
     }
 
     pub fn is_comic(&self) -> bool {
@@ -381,4 +563,4 @@ impl std::fmt::Display for BookStatus {
             BookStatus::Unknown => write!(f, "unknown"),
         }
     }
-}
\ No newline at end of file
+}
@@ -1,12 +1,16 @@
+pub mod browse;
 pub mod calendar;
 pub mod completions;
+pub mod goals;
 pub mod koreader_metadata;
 pub mod library_item;
 pub mod recap;
 pub mod statistics;
 
+pub use browse::*;
 pub use calendar::*;
 pub use completions::*;
+pub use goals::*;
 pub use koreader_metadata::*;
 pub use library_item::*;
 pub use recap::*;
@@ -18,6 +18,12 @@ pub struct StatBook {
     pub last_open: Option<i64>,
     pub highlights: Option<i64>,
     pub pages: Option<i64>,
+    /// Series as recorded by KOReader (without the "N/A" placeholder)
+    #[serde(default, skip_serializing)]
+    pub series: Option<String>,
+    /// Language as recorded by KOReader (without the "N/A" placeholder)
+    #[serde(default, skip_serializing)]
+    pub language: Option<String>,
     #[serde(skip_serializing)]
     pub md5: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -45,6 +51,27 @@ pub struct PageStat {
     pub page: i64,
     pub start_time: i64,
     pub duration: i64,
+    /// Name of the device whose statistics database the entry came from (when configured)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+}
+
+/// A stretch of continuous reading of one book on one device: consecutive page stats with
+/// pauses no longer than the session gap.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadingSession {
+    pub id_book: i64,
+    /// Unix timestamp of the first page turn
+    pub start_time: i64,
+    /// Unix timestamp at which the last page was left
+    pub end_time: i64,
+    /// Pages read; a page read twice counts twice, like in the page stats
+    pub pages: i64,
+    /// Seconds spent on the pages (pauses between them are not counted)
+    pub duration: i64,
+    /// Name of the device the session was read on (when configured)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
 }
 
 /// Main container for KoReader statistics data
@@ -53,6 +80,8 @@ pub struct StatisticsData {
     pub books: Vec<StatBook>,
     pub page_stats: Vec<PageStat>,
     pub stats_by_md5: HashMap<String, StatBook>,
+    /// Reading sessions built from `page_stats` by `StatisticsCalculator::populate_sessions`
+    pub sessions: Vec<ReadingSession>,
 }
 
 impl StatisticsData {
@@ -93,6 +122,13 @@ impl StatisticsData {
             .cloned()
             .collect();
 
+        let sessions: Vec<ReadingSession> = self
+            .sessions
+            .iter()
+            .filter(|session| ids_to_keep.contains(&session.id_book))
+            .cloned()
+            .collect();
+
         let mut stats_by_md5: HashMap<String, StatBook> = HashMap::new();
         for b in &books {
             stats_by_md5.insert(b.md5.clone(), b.clone());
@@ -102,6 +138,162 @@ impl StatisticsData {
             books,
             page_stats,
             stats_by_md5,
+            sessions,
+        }
+    }
+
+    /// Names of the devices page stats are tagged with, sorted alphabetically.
+    pub fn devices(&self) -> Vec<String> {
+        let devices: std::collections::BTreeSet<&String> = self
+            .page_stats
+            .iter()
+            .filter_map(|ps| ps.device.as_ref())
+            .collect();
+        devices.into_iter().cloned().collect()
+    }
+
+    /// Return a cloned `StatisticsData` containing only page stats read on the given device,
+    /// and the books those page stats belong to.
+    pub fn filtered_by_device(&self, device: &str) -> Self {
+        let page_stats: Vec<PageStat> = self
+            .page_stats
+            .iter()
+            .filter(|ps| ps.device.as_deref() == Some(device))
+            .cloned()
+            .collect();
+
+        let ids_to_keep: std::collections::HashSet<i64> =
+            page_stats.iter().map(|ps| ps.id_book).collect();
+        let books: Vec<StatBook> = self
+            .books
+            .iter()
+            .filter(|b| ids_to_keep.contains(&b.id))
+            .cloned()
+            .collect();
+
+        let stats_by_md5 = books.iter().map(|b| (b.md5.clone(), b.clone())).collect();
+
+        // Sessions never span devices, so they split cleanly
+        let sessions = self
+            .sessions
+            .iter()
+            .filter(|session| session.device.as_deref() == Some(device))
+            .cloned()
+            .collect();
+
+        Self {
+            books,
+            page_stats,
+            stats_by_md5,
+            sessions,
+        }
+    }
+}
+
+/// Reading history of one book, for the timeline on its detail page
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookTimeline {
+    /// Reading days, oldest first
+    pub days: Vec<TimelineDay>,
+    /// Reading sessions, newest first
+    pub sessions: Vec<TimelineSession>,
+    /// Time-to-finish estimate, for books still being read
+    pub estimate: Option<FinishEstimate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineDay {
+    pub date: String,   // ISO format yyyy-mm-dd
+    pub read_time: i64, // seconds
+    pub pages_read: i64,
+    /// Distinct pages visited up to and including this day
+    pub pages_covered: i64,
+    /// `pages_covered` relative to the page count of the book (0.0 to 1.0), when known
+    pub progress: Option<f64>,
+    pub pages_per_hour: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineSession {
+    pub date: String, // ISO format yyyy-mm-dd
+    /// Local start and end time as HH:MM
+    pub start: String,
+    pub end: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration: i64, // seconds
+    pub pages: i64,
+    pub pages_per_hour: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+}
+
+/// Estimated time to finish a book at the recent reading pace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinishEstimate {
+    pub remaining_pages: i64,
+    /// Reading speed over the most recent sessions
+    pub pages_per_hour: f64,
+    /// Reading time the remaining pages take at that speed (seconds)
+    pub remaining_read_time: i64,
+    /// Pages of this book read per day over the last weeks, to one decimal
+    pub pages_per_day: f64,
+    /// Days until the book is finished at that pace; `None` when it wasn't read lately
+    pub days_to_finish: Option<i64>,
+    pub finish_date: Option<String>, // ISO format yyyy-mm-dd
+}
+
+impl BookTimeline {
+    /// Number of sessions listed on the detail page; `details.json` has all of them
+    const LISTED_SESSIONS: usize = 20;
+
+    /// Most recent sessions for the session list
+    pub fn listed_sessions(&self) -> &[TimelineSession] {
+        &self.sessions[..self.sessions.len().min(Self::LISTED_SESSIONS)]
+    }
+
+    /// Points of the cumulative progress line in a 100 × 40 SVG view box.
+    ///
+    /// Days are placed by date; without a page count, progress is relative to the pages
+    /// covered by the last day.
+    pub fn progress_points(&self) -> String {
+        let dates: Vec<Option<NaiveDate>> = self
+            .days
+            .iter()
+            .map(|d| NaiveDate::parse_from_str(&d.date, "%Y-%m-%d").ok())
+            .collect();
+        let (Some(Some(first)), Some(Some(last))) = (dates.first(), dates.last()) else {
+            return String::new();
+        };
+        let span = (*last - *first).num_days().max(1) as f64;
+        let max_covered = self.days.last().map_or(1, |d| d.pages_covered.max(1)) as f64;
+
+        let mut points = vec!["0,40".to_string()];
+        for (day, date) in self.days.iter().zip(&dates) {
+            let Some(date) = date else { continue };
+            let x = if self.days.len() == 1 {
+                100.0
+            } else {
+                (*date - *first).num_days() as f64 / span * 100.0
+            };
+            let progress = day
+                .progress
+                .unwrap_or(day.pages_covered as f64 / max_covered);
+            points.push(format!("{:.1},{:.1}", x, 40.0 - progress.min(1.0) * 40.0));
+        }
+        points.join(" ")
+    }
+
+    /// Height of the reading speed bar of `day`, in percent of the fastest day
+    pub fn speed_bar_percent(&self, day: &TimelineDay) -> u32 {
+        let max = self
+            .days
+            .iter()
+            .filter_map(|d| d.pages_per_hour)
+            .fold(0.0, f64::max);
+        match day.pages_per_hour {
+            Some(speed) if max > 0.0 => (speed / max * 100.0).round() as u32,
+            _ => 0,
         }
     }
 }
@@ -194,6 +386,10 @@ pub struct ReadingStats {
 
     // Daily activity data for heatmap
     pub daily_activity: Vec<DailyStats>,
+
+    // Hour-of-day and weekday reading patterns
+    #[serde(default)]
+    pub patterns: ReadingPatterns,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -215,3 +411,139 @@ pub struct DailyStats {
     pub read_time: i64, // seconds
     pub pages_read: i64,
 }
+
+/// Reading habits by hour of the day and weekday.
+///
+/// Hours are local clock hours; weekdays (Monday = 0) follow the logical reading day, so a page
+/// read at 01:00 with a 03:00 day start counts towards the previous weekday.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReadingPatterns {
+    /// Seconds read per weekday and hour: `punch_card[weekday][hour]`
+    pub punch_card: Vec<Vec<i64>>,
+    pub hours: Vec<HourPattern>,
+    pub weekdays: Vec<WeekdayPattern>,
+    /// Hour with the most reading time
+    pub peak_hour: Option<u32>,
+    /// Weekday with the most reading time
+    pub peak_weekday: Option<u32>,
+    /// Hour with the highest reading speed
+    pub fastest_hour: Option<u32>,
+    pub chronotype: Option<Chronotype>,
+    /// Share of the reading time between 22:00 and 04:00
+    pub night_share: f64,
+    /// Share of the reading time between 05:00 and 09:00
+    pub morning_share: f64,
+}
+
+/// Reading done in one hour of the day, summed over all days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourPattern {
+    pub hour: u32,
+    pub read_time: i64, // seconds
+    pub pages_read: i64,
+    /// Sessions started in this hour
+    pub session_count: i64,
+    pub average_session_duration: Option<i64>, // seconds
+    /// Pages per hour, once at least ten minutes were read in this hour
+    pub pages_per_hour: Option<f64>,
+}
+
+/// Reading done on one weekday, summed over all weeks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekdayPattern {
+    pub weekday: u32,   // 0 = Monday
+    pub read_time: i64, // seconds
+    pub pages_read: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Chronotype {
+    NightOwl,
+    EarlyBird,
+    DayReader,
+}
+
+impl Chronotype {
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            Chronotype::NightOwl => "chronotype.night-owl",
+            Chronotype::EarlyBird => "chronotype.early-bird",
+            Chronotype::DayReader => "chronotype.day-reader",
+        }
+    }
+
+    /// Translation key of the share of reading that earned the chronotype, if any
+    pub fn detail_key(&self) -> Option<&'static str> {
+        match self {
+            Chronotype::NightOwl => Some("chronotype.night-share"),
+            Chronotype::EarlyBird => Some("chronotype.morning-share"),
+            Chronotype::DayReader => None,
+        }
+    }
+}
+
+impl WeekdayPattern {
+    /// Translation key of the abbreviated weekday name
+    pub fn label_key(weekday: &u32) -> &'static str {
+        match weekday {
+            0 => "weekday.mon",
+            1 => "weekday.tue",
+            2 => "weekday.wed",
+            3 => "weekday.thu",
+            4 => "weekday.fri",
+            5 => "weekday.sat",
+            _ => "weekday.sun",
+        }
+    }
+}
+
+impl ReadingPatterns {
+    /// Night share for night owls, morning share for early birds, in percent
+    pub fn chronotype_percent(&self) -> u32 {
+        let share = match self.chronotype {
+            Some(Chronotype::NightOwl) => self.night_share,
+            Some(Chronotype::EarlyBird) => self.morning_share,
+            _ => 0.0,
+        };
+        (share * 100.0).round() as u32
+    }
+
+    /// Seconds read on `weekday` during `hour`
+    pub fn cell(&self, weekday: &u32, hour: &u32) -> i64 {
+        self.punch_card
+            .get(*weekday as usize)
+            .and_then(|hours| hours.get(*hour as usize))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Punch card intensity from 0 (no reading) to 4, relative to the busiest cell
+    pub fn cell_level(&self, weekday: &u32, hour: &u32) -> u8 {
+        let max = self.punch_card.iter().flatten().copied().max().unwrap_or(0);
+        let value = self.cell(weekday, hour);
+        if value <= 0 || max <= 0 {
+            return 0;
+        }
+        (1 + (value * 4 - 1) / max).min(4) as u8
+    }
+
+    /// Height of the average session bar of `hour`, in percent of the longest one
+    pub fn session_bar_percent(&self, hour: &u32) -> u32 {
+        let value = |h: &HourPattern| h.average_session_duration.unwrap_or(0) as f64;
+        self.bar_percent(hour, value)
+    }
+
+    /// Height of the reading speed bar of `hour`, in percent of the fastest one
+    pub fn speed_bar_percent(&self, hour: &u32) -> u32 {
+        self.bar_percent(hour, |h| h.pages_per_hour.unwrap_or(0.0))
+    }
+
+    fn bar_percent(&self, hour: &u32, value: impl Fn(&HourPattern) -> f64) -> u32 {
+        let max = self.hours.iter().map(&value).fold(0.0, f64::max);
+        match self.hours.get(*hour as usize) {
+            Some(h) if max > 0.0 => (value(h) / max * 100.0).round() as u32,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! OPDS catalog feeds (OPDS 1.2 Atom and OPDS 2.0 JSON).
+//!
+//! A feed is built once as an [`OpdsFeed`] and rendered in both formats. Hrefs of feeds are
+//! stored without extension (`/opds/authors/jane-doe`); each renderer appends its own.
+
+use crate::models::{ItemSource, LibraryItem};
+use crate::templates::OpdsAtomTemplate;
+use crate::utils::{generate_slug, strip_tags};
+use anyhow::Result;
+use askama::Template;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+/// Base path of all catalog feeds.
+pub const OPDS_ROOT: &str = "/opds";
+
+pub const ATOM_NAVIGATION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+pub const ATOM_ACQUISITION_TYPE: &str =
+    "application/atom+xml;profile=opds-catalog;kind=acquisition";
+pub const OPDS_JSON_TYPE: &str = "application/opds+json";
+pub const OPENSEARCH_TYPE: &str = "application/opensearchdescription+xml";
+
+/// Whether a feed lists other feeds or publications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpdsFeedKind {
+    Navigation,
+    Acquisition,
+}
+
+/// Link from a navigation feed to a sub-feed.
+#[derive(Debug, Clone)]
+pub struct OpdsNavEntry {
+    pub title: String,
+    /// Feed href without extension
+    pub href: String,
+    pub count: usize,
+    /// Kind of the linked feed
+    pub kind: OpdsFeedKind,
+}
+
+/// Author of a publication, linked to the author's feed.
+#[derive(Debug, Clone)]
+pub struct OpdsAuthor {
+    pub name: String,
+    pub href: String,
+}
+
+/// A publication entry of an acquisition feed.
+#[derive(Debug, Clone)]
+pub struct OpdsEntry {
+    pub id: String,
+    pub title: String,
+    pub authors: Vec<OpdsAuthor>,
+    pub summary: Option<String>,
+    pub language: Option<String>,
+    pub publisher: Option<String>,
+    /// Identifiers as URNs where possible (`urn:isbn:...`)
+    pub identifiers: Vec<String>,
+    pub subjects: Vec<String>,
+    pub series: Option<String>,
+    pub series_number: Option<String>,
+    pub cover_href: Option<String>,
+    /// Download link below `/raw` and the MIME type of the file
+    pub acquisition: Option<(String, String)>,
+    /// Detail page of the item
+    pub html_href: String,
+}
+
+/// A navigation or acquisition feed.
+#[derive(Debug, Clone)]
+pub struct OpdsFeed {
+    /// Feed href without extension, also used to build the feed id
+    pub href: String,
+    pub title: String,
+    /// RFC 3339 timestamp
+    pub updated: String,
+    pub kind: OpdsFeedKind,
+    pub navigation: Vec<OpdsNavEntry>,
+    pub entries: Vec<OpdsEntry>,
+    /// Whether to advertise the OpenSearch endpoint (server mode only)
+    pub searchable: bool,
+}
+
+/// A generated site whose detail pages and covers entries link to.
+#[derive(Debug, Clone)]
+pub struct OpdsSite {
+    /// URL prefix of the site (empty for the root site, `/u/<profile>` for a reader profile)
+    pub root: String,
+    /// Output directory of the site
+    pub dir: PathBuf,
+}
+
+/// Where the links of publication entries point to.
+#[derive(Debug, Clone)]
+pub struct OpdsLinks {
+    /// Library folder served below `/raw` (server mode serves the first library path)
+    pub raw_root: Option<PathBuf>,
+    /// Sites with detail pages: the root site, or the reader profile sites sharing the
+    /// catalog. Entries link to the first one that has a page for the item.
+    pub sites: Vec<OpdsSite>,
+}
+
+impl OpdsLinks {
+    /// Build the entry of an item.
+    pub fn entry(&self, item: &LibraryItem) -> OpdsEntry {
+        let section = if item.is_comic() { "comics" } else { "books" };
+        let page = format!("{}/{}", section, item.id);
+        let site = self
+            .sites
+            .iter()
+            .find(|site| site.dir.join(&page).join("index.html").exists())
+            .or(self.sites.first());
+        let site_root = site.map_or("", |site| site.root.as_str());
+        let cover_href = site
+            .is_some_and(|site| {
+                site.dir
+                    .join("assets")
+                    .join("covers")
+                    .join(format!("{}.webp", item.id))
+                    .exists()
+            })
+            .then(|| format!("{}/assets/covers/{}.webp", site_root, item.id));
+
+        let identifiers = item
+            .identifiers()
+            .iter()
+            .map(|identifier| {
+                let scheme = identifier.scheme.to_lowercase();
+                match scheme.as_str() {
+                    "isbn" | "uuid" | "doi" => format!("urn:{}:{}", scheme, identifier.value),
+                    _ => identifier.value.clone(),
+                }
+            })
+            .collect();
+
+        OpdsEntry {
+            id: format!("urn:koshelf:item:{}", item.id),
+            title: item.book_info.title.clone(),
+            authors: item
+                .book_info
+                .authors
+                .iter()
+                .map(|name| OpdsAuthor {
+                    name: name.clone(),
+                    href: group_href("authors", name),
+                })
+                .collect(),
+            summary: item
+                .book_info
+                .description
+                .as_deref()
+                .map(strip_tags)
+                .filter(|s| !s.is_empty()),
+            language: item.language().cloned(),
+            publisher: item.publisher().cloned(),
+            identifiers,
+            subjects: item.subjects().clone(),
+            series: item.series().cloned(),
+            series_number: item.series_number().cloned(),
+            cover_href,
+            acquisition: match &item.source {
+                ItemSource::File { path, format } => self.raw_href(path).map(|href| {
+                    let mime = if path.to_string_lossy().to_lowercase().ends_with(".zip") {
+                        "application/x-zip-compressed-fb2"
+                    } else {
+                        format.mime_type()
+                    };
+                    (href, mime.to_string())
+                }),
+                ItemSource::Statistics { .. } => None,
+            },
+            html_href: format!("{}/{}/", site_root, page),
+        }
+    }
+
+    /// URL of a library file below `/raw`, if it lives in the served library folder.
+    fn raw_href(&self, file_path: &Path) -> Option<String> {
+        let relative = file_path.strip_prefix(self.raw_root.as_ref()?).ok()?;
+        let segments: Vec<String> = relative
+            .components()
+            .map(|c| encode_path_segment(&c.as_os_str().to_string_lossy()))
+            .collect();
+        Some(format!("/raw/{}", segments.join("/")))
+    }
+}
+
+/// Href of the feed of an author, series or subject.
+pub fn group_href(group: &str, name: &str) -> String {
+    format!("{}/{}/{}", OPDS_ROOT, group, generate_slug(name))
+}
+
+/// Items whose title, authors, series or subjects contain every word of `query`.
+pub fn search<'a>(items: &'a [LibraryItem], query: &str) -> Vec<&'a LibraryItem> {
+    let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    items
+        .iter()
+        .filter(|item| {
+            let mut haystack = item.book_info.title.to_lowercase();
+            for field in item
+                .book_info
+                .authors
+                .iter()
+                .chain(item.series())
+                .chain(item.subjects())
+            {
+                haystack.push(' ');
+                haystack.push_str(&field.to_lowercase());
+            }
+            words.iter().all(|word| haystack.contains(word.as_str()))
+        })
+        .collect()
+}
+
+impl OpdsFeed {
+    /// Render as an OPDS 1.2 Atom document.
+    pub fn to_atom(&self) -> Result<String> {
+        Ok(OpdsAtomTemplate { feed: self }.render()?)
+    }
+
+    /// Render as an OPDS 2.0 JSON document.
+    pub fn to_json(&self) -> Value {
+        let mut links = vec![
+            json!({ "rel": "self", "href": format!("{}.json", self.href), "type": OPDS_JSON_TYPE }),
+            json!({ "rel": "start", "href": format!("{}/index.json", OPDS_ROOT), "type": OPDS_JSON_TYPE }),
+        ];
+        if self.searchable {
+            links.push(json!({
+                "rel": "search",
+                "href": format!("{}/search.json{{?query}}", OPDS_ROOT),
+                "type": OPDS_JSON_TYPE,
+                "templated": true,
+            }));
+        }
+
+        let mut feed = json!({
+            "metadata": {
+                "title": self.title,
+                "modified": self.updated,
+            },
+            "links": links,
+        });
+
+        match self.kind {
+            OpdsFeedKind::Navigation => {
+                feed["navigation"] = self
+                    .navigation
+                    .iter()
+                    .map(|nav| {
+                        json!({
+                            "title": nav.title,
+                            "href": format!("{}.json", nav.href),
+                            "type": OPDS_JSON_TYPE,
+                            "rel": "subsection",
+                            "properties": { "numberOfItems": nav.count },
+                        })
+                    })
+                    .collect();
+            }
+            OpdsFeedKind::Acquisition => {
+                feed["metadata"]["numberOfItems"] = json!(self.entries.len());
+                feed["publications"] = self.entries.iter().map(publication_json).collect();
+            }
+        }
+        feed
+    }
+}
+
+fn publication_json(entry: &OpdsEntry) -> Value {
+    let mut metadata = json!({
+        "@type": "http://schema.org/Book",
+        "identifier": entry.identifiers.first().cloned().unwrap_or_else(|| entry.id.clone()),
+        "title": entry.title,
+        "author": entry.authors.iter().map(|author| json!({
+            "name": author.name,
+            "links": [{ "href": format!("{}.json", author.href), "type": OPDS_JSON_TYPE }],
+        })).collect::<Vec<_>>(),
+        "subject": entry.subjects.iter().map(|subject| json!({
+            "name": subject,
+            "links": [{ "href": format!("{}.json", group_href("subjects", subject)), "type": OPDS_JSON_TYPE }],
+        })).collect::<Vec<_>>(),
+    });
+    if let Some(ref language) = entry.language {
+        metadata["language"] = json!(language);
+    }
+    if let Some(ref publisher) = entry.publisher {
+        metadata["publisher"] = json!(publisher);
+    }
+    if let Some(ref summary) = entry.summary {
+        metadata["description"] = json!(summary);
+    }
+    if let Some(ref series) = entry.series {
+        let mut series_json = json!({
+            "name": series,
+            "links": [{ "href": format!("{}.json", group_href("series", series)), "type": OPDS_JSON_TYPE }],
+        });
+        if let Some(position) = entry
+            .series_number
+            .as_deref()
+            .and_then(|n| n.parse::<f64>().ok())
+        {
+            series_json["position"] = json!(position);
+        }
+        metadata["belongsTo"] = json!({ "series": series_json });
+    }
+
+    let mut links =
+        vec![json!({ "rel": "alternate", "href": entry.html_href, "type": "text/html" })];
+    if let Some((ref href, ref mime)) = entry.acquisition {
+        links.insert(
+            0,
+            json!({ "rel": "http://opds-spec.org/acquisition", "href": href, "type": mime }),
+        );
+    }
+
+    let mut publication = json!({ "metadata": metadata, "links": links });
+    if let Some(ref cover) = entry.cover_href {
+        publication["images"] = json!([{ "href": cover, "type": "image/webp" }]);
+    }
+    publication
+}
+
+/// OpenSearch description document pointing at the Atom search feed.
+pub fn opensearch_description(site_title: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>{title}</ShortName>
+  <Description>{title}</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <OutputEncoding>UTF-8</OutputEncoding>
+  <Url type="{atom}" template="{root}/search.xml?q={{searchTerms}}"/>
+  <Url type="{json}" template="{root}/search.json?query={{searchTerms}}"/>
+</OpenSearchDescription>
+"#,
+        title = escape_xml(site_title),
+        atom = ATOM_ACQUISITION_TYPE,
+        json = OPDS_JSON_TYPE,
+        root = OPDS_ROOT,
+    )
+}
+
+fn escape_xml(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Percent-encode a URL path segment (RFC 3986 unreserved characters are kept).
+fn encode_path_segment(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
@@ -0,0 +1,300 @@
+//! DjVu metadata extraction from the IFF container.
+//!
+//! Reads the page count from the document structure and the `(metadata ...)` block of
+//! uncompressed annotation chunks (`ANTa`). Only chunk headers and the chunks that are used
+//! are read; everything else is skipped over. BZZ-compressed annotations (`ANTz`) and IW44
+//! image data are not decoded, so such files get their title from the filename and no cover;
+//! KoReader's own metadata still applies on top.
+
+use crate::models::BookInfo;
+use anyhow::{Context, Result, anyhow};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Nesting limit for FORM chunks.
+const MAX_DEPTH: usize = 8;
+/// Longest annotation chunk that is read.
+const MAX_ANNOTATION_LEN: u64 = 1024 * 1024;
+
+pub struct DjvuParser;
+
+impl Default for DjvuParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Facts collected while walking the chunk tree.
+#[derive(Debug, Default)]
+struct DjvuStructure {
+    page_forms: u32,
+    /// File count from the DIRM chunk of multi-page documents
+    dirm_files: Option<u32>,
+    bundled: bool,
+    annotations: Vec<String>,
+    compressed_annotations: bool,
+}
+
+impl DjvuParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn parse(&self, djvu_path: &Path) -> Result<BookInfo> {
+        let path = djvu_path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::parse_sync(&path))
+            .await
+            .with_context(|| "Task join error")?
+    }
+
+    fn parse_sync(djvu_path: &PathBuf) -> Result<BookInfo> {
+        debug!("Opening DjVu: {:?}", djvu_path);
+        let mut file = File::open(djvu_path)
+            .with_context(|| format!("Failed to read DjVu file: {:?}", djvu_path))?;
+
+        let fallback_title = djvu_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "Unknown Title".to_string());
+
+        let structure = match Self::read_structure(&mut file) {
+            Ok(structure) => structure,
+            Err(e) => {
+                warn!("Failed to parse DjVu structure for {:?}: {}", djvu_path, e);
+                return Ok(Self::book_info_from_filename(&fallback_title));
+            }
+        };
+        if structure.compressed_annotations && structure.annotations.is_empty() {
+            debug!(
+                "{:?} only has compressed annotations, metadata is not read",
+                djvu_path
+            );
+        }
+
+        let mut metadata: HashMap<String, String> = HashMap::new();
+        for annotation in &structure.annotations {
+            for (key, value) in Self::parse_metadata_block(annotation) {
+                metadata.entry(key).or_insert(value);
+            }
+        }
+        let field = |key: &str| {
+            metadata
+                .get(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let pages = if structure.page_forms > 0 {
+            Some(structure.page_forms)
+        } else if structure.bundled {
+            None
+        } else {
+            // Indirect documents keep their pages in separate files listed in DIRM
+            structure.dirm_files.filter(|&n| n > 0)
+        };
+
+        let mut info = Self::book_info_from_filename(&field("title").unwrap_or(fallback_title));
+        info.authors = field("author")
+            .map(|a| {
+                a.split([';', '&'])
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        info.description = field("subject").or_else(|| field("note"));
+        info.publisher = field("publisher");
+        info.subjects = field("keywords")
+            .map(|k| {
+                k.split([',', ';'])
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        info.pages = pages;
+        Ok(info)
+    }
+
+    fn book_info_from_filename(title: &str) -> BookInfo {
+        BookInfo {
+            title: title.to_string(),
+            authors: Vec::new(),
+            description: None,
+            language: None,
+            publisher: None,
+            identifiers: Vec::new(),
+            subjects: Vec::new(),
+            series: None,
+            series_number: None,
+            pages: None,
+            cover_data: None,
+            cover_mime_type: None,
+        }
+    }
+
+    fn read_structure<R: Read + Seek>(reader: &mut R) -> Result<DjvuStructure> {
+        // Files start with the "AT&T" magic followed by a single FORM chunk
+        let mut magic = [0; 4];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|_| anyhow!("Missing AT&T magic"))?;
+        if &magic != b"AT&T" {
+            return Err(anyhow!("Missing AT&T magic"));
+        }
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let (id, start, end) =
+            Self::read_chunk(reader, 4, file_end)?.ok_or_else(|| anyhow!("Truncated file"))?;
+        if &id != b"FORM" || end - start < 4 {
+            return Err(anyhow!("Top-level chunk is not a FORM"));
+        }
+
+        let mut structure = DjvuStructure::default();
+        match &Self::read_bytes(reader, start, 4)?[..] {
+            b"DJVU" => {
+                structure.page_forms = 1;
+                Self::walk(reader, start + 4, end, &mut structure, 1, false)?;
+            }
+            b"DJVM" => Self::walk(reader, start + 4, end, &mut structure, 1, true)?,
+            other => {
+                return Err(anyhow!(
+                    "Unsupported DjVu form type {:?}",
+                    String::from_utf8_lossy(other)
+                ));
+            }
+        }
+        Ok(structure)
+    }
+
+    /// Read the header of the chunk at `offset`. Returns the chunk id and the start and end
+    /// of its data, cut off at `end`.
+    fn read_chunk<R: Read + Seek>(
+        reader: &mut R,
+        offset: u64,
+        end: u64,
+    ) -> io::Result<Option<([u8; 4], u64, u64)>> {
+        if offset + 8 > end {
+            return Ok(None);
+        }
+        let header = Self::read_bytes(reader, offset, 8)?;
+        let length = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let start = offset + 8;
+        let data_end = (start + u64::from(length)).min(end);
+        Ok(Some((
+            [header[0], header[1], header[2], header[3]],
+            start,
+            data_end,
+        )))
+    }
+
+    fn read_bytes<R: Read + Seek>(reader: &mut R, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        reader.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn walk<R: Read + Seek>(
+        reader: &mut R,
+        start: u64,
+        end: u64,
+        structure: &mut DjvuStructure,
+        depth: usize,
+        top_level: bool,
+    ) -> io::Result<()> {
+        if depth > MAX_DEPTH {
+            return Ok(());
+        }
+        let mut offset = start;
+        while let Some((id, data_start, data_end)) = Self::read_chunk(reader, offset, end)? {
+            let len = data_end - data_start;
+            match &id {
+                b"FORM" if len >= 4 => {
+                    if top_level && Self::read_bytes(reader, data_start, 4)? == b"DJVU" {
+                        structure.page_forms += 1;
+                    }
+                    Self::walk(
+                        reader,
+                        data_start + 4,
+                        data_end,
+                        structure,
+                        depth + 1,
+                        false,
+                    )?;
+                }
+                b"DIRM" if top_level && len >= 3 => {
+                    let head = Self::read_bytes(reader, data_start, 3)?;
+                    structure.bundled = head[0] & 0x80 != 0;
+                    structure.dirm_files = Some(u32::from(u16::from_be_bytes([head[1], head[2]])));
+                }
+                b"ANTa" => {
+                    let annotation =
+                        Self::read_bytes(reader, data_start, len.min(MAX_ANNOTATION_LEN))?;
+                    structure
+                        .annotations
+                        .push(String::from_utf8_lossy(&annotation).into_owned());
+                }
+                b"ANTz" => structure.compressed_annotations = true,
+                _ => {}
+            }
+            // Chunks are padded to an even offset
+            offset = data_end + (data_end % 2);
+        }
+        Ok(())
+    }
+
+    /// Extract `(key "value")` pairs from the `(metadata ...)` expression of an annotation.
+    fn parse_metadata_block(annotation: &str) -> Vec<(String, String)> {
+        let Some(start) = annotation.find("(metadata") else {
+            return Vec::new();
+        };
+        let chars: Vec<char> = annotation[start + "(metadata".len()..].chars().collect();
+        let mut pairs = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                ')' => break,
+                '(' => {
+                    i += 1;
+                    let key_start = i;
+                    while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ')' {
+                        i += 1;
+                    }
+                    let key: String = chars[key_start..i].iter().collect();
+                    while i < chars.len() && chars[i].is_whitespace() {
+                        i += 1;
+                    }
+                    let mut value = String::new();
+                    if i < chars.len() && chars[i] == '"' {
+                        i += 1;
+                        while i < chars.len() && chars[i] != '"' {
+                            if chars[i] == '\\' && i + 1 < chars.len() {
+                                i += 1;
+                                value.push(match chars[i] {
+                                    'n' => '\n',
+                                    't' => '\t',
+                                    other => other,
+                                });
+                            } else {
+                                value.push(chars[i]);
+                            }
+                            i += 1;
+                        }
+                    }
+                    // Skip to the end of this pair
+                    while i < chars.len() && chars[i] != ')' {
+                        i += 1;
+                    }
+                    pairs.push((key.to_lowercase(), value));
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+        pairs
+    }
+}
@@ -1,11 +1,15 @@
 //! Format-specific parsers for extracting metadata from ebooks and comics.
 
 pub mod comic;
+pub mod djvu;
 pub mod epub;
 pub mod fb2;
 pub mod mobi;
+pub mod pdf;
 
 pub use comic::ComicParser;
+pub use djvu::DjvuParser;
 pub use epub::EpubParser;
 pub use fb2::Fb2Parser;
 pub use mobi::MobiParser;
+pub use pdf::PdfParser;
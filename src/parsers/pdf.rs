@@ -0,0 +1,1428 @@
+//! PDF metadata extraction: Info dictionary, XMP packet, page count and first-page cover.
+//!
+//! This is a small, tolerant reader rather than a full PDF implementation. Objects are
+//! located through the cross-reference tables or streams, so only the trailer, the
+//! cross-reference data and the objects that are needed are read from the file. Damaged
+//! files without usable cross-reference data are read whole and scanned for `N G obj`
+//! headers instead.
+
+use crate::models::BookInfo;
+use anyhow::{Context, Result};
+use flate2::read::ZlibDecoder;
+use log::{debug, warn};
+use regex::bytes::Regex;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Upper bound for a decoded stream, to avoid decompression bombs.
+const MAX_DECODED_STREAM: u64 = 64 * 1024 * 1024;
+/// Depth limit for page tree and reference chains.
+const MAX_DEPTH: usize = 32;
+/// Bytes at the end of the file searched for `startxref`.
+const XREF_TAIL_WINDOW: u64 = 2048;
+/// Bytes first read for an object; more are read for larger ones, up to the maximum.
+const OBJECT_WINDOW: usize = 4096;
+const MAX_OBJECT_WINDOW: usize = 4 * 1024 * 1024;
+
+static OBJ_HEADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\r\n\s])(\d{1,10})\s+(\d{1,5})\s+obj\b").unwrap());
+static TRAILER_REF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/(Info|Root)\s*(\d{1,10})\s+(\d{1,5})\s+R").unwrap());
+
+pub struct PdfParser;
+
+impl Default for PdfParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn parse(&self, pdf_path: &Path) -> Result<BookInfo> {
+        let path = pdf_path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::parse_sync(&path))
+            .await
+            .with_context(|| "Task join error")?
+    }
+
+    fn parse_sync(pdf_path: &PathBuf) -> Result<BookInfo> {
+        debug!("Opening PDF: {:?}", pdf_path);
+        let mut header = Vec::new();
+        File::open(pdf_path)
+            .and_then(|file| file.take(1024).read_to_end(&mut header))
+            .with_context(|| format!("Failed to read PDF file: {:?}", pdf_path))?;
+
+        let fallback_title = pdf_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "Unknown Title".to_string());
+
+        if Self::find_bytes(&header, b"%PDF").is_none() {
+            warn!("{:?} does not look like a PDF file", pdf_path);
+            return Ok(Self::book_info_from_filename(&fallback_title));
+        }
+
+        let document = PdfDocument::open(pdf_path)
+            .with_context(|| format!("Failed to read PDF file: {:?}", pdf_path))?;
+        let info = document.info();
+        let xmp = document
+            .xmp()
+            .map(|xml| XmpMetadata::parse(&xml))
+            .unwrap_or_default();
+
+        let info_text = |key: &str| {
+            info.as_ref()
+                .and_then(|dict| dict.get(key))
+                .and_then(|obj| document.resolve(obj).as_text())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+
+        let title = xmp
+            .title
+            .clone()
+            .or_else(|| info_text("Title"))
+            .unwrap_or(fallback_title);
+
+        let authors = if xmp.creators.is_empty() {
+            info_text("Author")
+                .map(|a| Self::split_people_list(&a))
+                .unwrap_or_default()
+        } else {
+            xmp.creators.clone()
+        };
+
+        // Info /Subject corresponds to dc:description, Info /Keywords to dc:subject
+        let description = xmp.description.clone().or_else(|| info_text("Subject"));
+        let subjects = if xmp.subjects.is_empty() {
+            info_text("Keywords")
+                .map(|k| Self::split_keyword_list(&k))
+                .unwrap_or_default()
+        } else {
+            xmp.subjects.clone()
+        };
+
+        let pages = document.page_count();
+        let cover = document.first_page_cover();
+        if cover.is_none() {
+            debug!("No usable first-page image found in {:?}", pdf_path);
+        }
+        let (cover_data, cover_mime_type) = match cover {
+            Some((bytes, mime)) => (Some(bytes), Some(mime.to_string())),
+            None => (None, None),
+        };
+
+        Ok(BookInfo {
+            title,
+            authors,
+            description,
+            language: xmp.language,
+            publisher: xmp.publisher,
+            identifiers: Vec::new(),
+            subjects,
+            series: None,
+            series_number: None,
+            pages,
+            cover_data,
+            cover_mime_type,
+        })
+    }
+
+    fn book_info_from_filename(title: &str) -> BookInfo {
+        BookInfo {
+            title: title.to_string(),
+            authors: Vec::new(),
+            description: None,
+            language: None,
+            publisher: None,
+            identifiers: Vec::new(),
+            subjects: Vec::new(),
+            series: None,
+            series_number: None,
+            pages: None,
+            cover_data: None,
+            cover_mime_type: None,
+        }
+    }
+
+    /// Info /Author is free text; producers separate multiple people with `;` or `&`.
+    fn split_people_list(raw: &str) -> Vec<String> {
+        raw.split([';', '&'])
+            .flat_map(|part| part.split(" and "))
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    fn split_keyword_list(raw: &str) -> Vec<String> {
+        raw.split([',', ';'])
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PdfObject {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Vec<u8>),
+    Name(String),
+    Array(Vec<PdfObject>),
+    Dict(HashMap<String, PdfObject>),
+    Ref(u32),
+    /// Stream dictionary and its raw (still encoded) data
+    Stream(HashMap<String, PdfObject>, Vec<u8>),
+}
+
+impl PdfObject {
+    fn as_dict(&self) -> Option<&HashMap<String, PdfObject>> {
+        match self {
+            Self::Dict(dict) | Self::Stream(dict, _) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            Self::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Decode a PDF text string: UTF-16BE or UTF-8 with BOM, otherwise PDFDocEncoding
+    /// (approximated as Latin-1).
+    fn as_text(&self) -> Option<String> {
+        let Self::String(bytes) = self else {
+            return None;
+        };
+        if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+            let units: Vec<u16> = utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            return Some(String::from_utf16_lossy(&units));
+        }
+        if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            return Some(String::from_utf8_lossy(utf8).into_owned());
+        }
+        Some(bytes.iter().map(|&b| b as char).collect())
+    }
+}
+
+/// Lexer/parser for PDF object syntax over a byte slice.
+struct ObjectParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+impl<'a> ObjectParser<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while let Some(c) = self.peek() {
+                    if c == b'\r' || c == b'\n' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn starts_with(&self, token: &[u8]) -> bool {
+        self.data[self.pos.min(self.data.len())..].starts_with(token)
+    }
+
+    fn regular_token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) || is_delimiter(b) {
+                break;
+            }
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Option<PdfObject> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match self.peek()? {
+            b'/' => {
+                self.pos += 1;
+                Some(PdfObject::Name(self.parse_name()))
+            }
+            b'(' => {
+                self.pos += 1;
+                Some(PdfObject::String(self.parse_literal_string()))
+            }
+            b'<' if self.starts_with(b"<<") => {
+                self.pos += 2;
+                self.parse_dict(depth).map(PdfObject::Dict)
+            }
+            b'<' => {
+                self.pos += 1;
+                Some(PdfObject::String(self.parse_hex_string()))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b']' => {
+                            self.pos += 1;
+                            break;
+                        }
+                        _ => items.push(self.parse_object(depth + 1)?),
+                    }
+                }
+                Some(PdfObject::Array(items))
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => self.parse_number_or_ref(),
+            _ => match self.regular_token() {
+                b"true" => Some(PdfObject::Bool(true)),
+                b"false" => Some(PdfObject::Bool(false)),
+                b"null" => Some(PdfObject::Null),
+                _ => None,
+            },
+        }
+    }
+
+    fn parse_name(&mut self) -> String {
+        let raw = self.regular_token();
+        let mut name = Vec::with_capacity(raw.len());
+        let mut i = 0;
+        while i < raw.len() {
+            if raw[i] == b'#'
+                && let Some(hex) = raw.get(i + 1..i + 3)
+                && let Ok(s) = std::str::from_utf8(hex)
+                && let Ok(byte) = u8::from_str_radix(s, 16)
+            {
+                name.push(byte);
+                i += 3;
+            } else {
+                name.push(raw[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    fn parse_literal_string(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut nesting = 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    nesting += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    nesting -= 1;
+                    if nesting == 0 {
+                        break;
+                    }
+                    out.push(b);
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(d - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        // Line continuation
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other),
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
+
+    fn parse_hex_string(&mut self) -> Vec<u8> {
+        let mut digits = Vec::new();
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            if b == b'>' {
+                break;
+            }
+            if b.is_ascii_hexdigit() {
+                digits.push(b);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(b'0');
+        }
+        digits
+            .chunks_exact(2)
+            .filter_map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|s| u8::from_str_radix(s, 16).ok())
+            })
+            .collect()
+    }
+
+    fn parse_dict(&mut self, depth: usize) -> Option<HashMap<String, PdfObject>> {
+        let mut dict = HashMap::new();
+        loop {
+            self.skip_whitespace();
+            if self.starts_with(b">>") {
+                self.pos += 2;
+                return Some(dict);
+            }
+            if self.peek()? != b'/' {
+                return None;
+            }
+            self.pos += 1;
+            let key = self.parse_name();
+            let value = self.parse_object(depth + 1)?;
+            dict.insert(key, value);
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<f64> {
+        let token = self.regular_token();
+        std::str::from_utf8(token).ok()?.parse().ok()
+    }
+
+    /// Numbers may be the start of an indirect reference (`12 0 R`).
+    fn parse_number_or_ref(&mut self) -> Option<PdfObject> {
+        let number = self.parse_number()?;
+        let after_number = self.pos;
+        if number.fract() == 0.0 && number >= 0.0 {
+            self.skip_whitespace();
+            if matches!(self.peek(), Some(b'0'..=b'9')) && self.parse_number().is_some() {
+                self.skip_whitespace();
+                if self.peek() == Some(b'R')
+                    && self
+                        .data
+                        .get(self.pos + 1)
+                        .is_none_or(|&b| is_whitespace(b) || is_delimiter(b))
+                {
+                    self.pos += 1;
+                    return Some(PdfObject::Ref(number as u32));
+                }
+            }
+        }
+        self.pos = after_number;
+        Some(PdfObject::Number(number))
+    }
+}
+
+/// Bytes of a PDF file, read on demand.
+enum PdfSource {
+    File(RefCell<File>, u64),
+    /// The whole file, for damaged files that had to be scanned
+    Memory(Vec<u8>),
+}
+
+impl PdfSource {
+    fn len(&self) -> u64 {
+        match self {
+            Self::File(_, len) => *len,
+            Self::Memory(data) => data.len() as u64,
+        }
+    }
+
+    /// Read up to `len` bytes at `offset`; fewer at the end of the file.
+    fn read_at(&self, offset: u64, len: usize) -> Option<Vec<u8>> {
+        let available = self.len().checked_sub(offset).filter(|&n| n > 0)?;
+        let len = len.min(usize::try_from(available).unwrap_or(usize::MAX));
+        match self {
+            Self::File(file, _) => {
+                let mut file = file.borrow_mut();
+                file.seek(SeekFrom::Start(offset)).ok()?;
+                let mut buf = vec![0; len];
+                file.read_exact(&mut buf).ok()?;
+                Some(buf)
+            }
+            Self::Memory(data) => {
+                let start = offset as usize;
+                Some(data[start..start + len].to_vec())
+            }
+        }
+    }
+}
+
+/// Where the cross-reference data puts an object.
+#[derive(Debug, Clone, Copy)]
+enum XrefEntry {
+    /// Offset of the `N G obj` header
+    Offset(u64),
+    /// Number of the object stream holding the object
+    Compressed(u32),
+}
+
+struct PdfDocument {
+    source: PdfSource,
+    xref: HashMap<u32, XrefEntry>,
+    /// Objects of the object streams loaded so far, by stream number
+    object_streams: RefCell<HashMap<u32, HashMap<u32, PdfObject>>>,
+    info_ref: Option<u32>,
+    root_ref: Option<u32>,
+}
+
+impl PdfDocument {
+    /// Open a PDF through its cross-reference data, reading only the parts of the file that
+    /// are needed. Damaged files without usable cross-reference data are read whole and
+    /// scanned for objects instead.
+    fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut document = Self::with_source(PdfSource::File(RefCell::new(file), len));
+        if document.read_xref() && document.root().is_some() {
+            return Ok(document);
+        }
+
+        debug!(
+            "No usable cross-reference data in {:?}, scanning the file",
+            path
+        );
+        Ok(Self::scan(std::fs::read(path)?))
+    }
+
+    fn with_source(source: PdfSource) -> Self {
+        Self {
+            source,
+            xref: HashMap::new(),
+            object_streams: RefCell::new(HashMap::new()),
+            info_ref: None,
+            root_ref: None,
+        }
+    }
+
+    /// Locate objects by scanning for `N G obj` headers (the last definition wins, as with
+    /// incremental updates), and the trailer references by scanning for the last ones.
+    fn scan(data: Vec<u8>) -> Self {
+        let mut xref = HashMap::new();
+        for caps in OBJ_HEADER.captures_iter(&data) {
+            let number = caps.get(1).unwrap();
+            if let Some(n) = std::str::from_utf8(number.as_bytes())
+                .ok()
+                .and_then(|s| s.parse::<u32>().ok())
+            {
+                xref.insert(n, XrefEntry::Offset(number.start() as u64));
+            }
+        }
+
+        let mut info_ref = None;
+        let mut root_ref = None;
+        for caps in TRAILER_REF.captures_iter(&data) {
+            let number = std::str::from_utf8(&caps[2])
+                .ok()
+                .and_then(|s| s.parse::<u32>().ok());
+            match &caps[1] {
+                b"Info" => info_ref = number.or(info_ref),
+                _ => root_ref = number.or(root_ref),
+            }
+        }
+
+        // Objects inside compressed object streams, unless also defined directly
+        let mut streams: Vec<(u32, u64)> = xref
+            .iter()
+            .filter_map(|(&number, entry)| match entry {
+                XrefEntry::Offset(offset) => Some((number, *offset)),
+                XrefEntry::Compressed(_) => None,
+            })
+            .collect();
+        streams.sort_unstable();
+
+        let mut document = Self::with_source(PdfSource::Memory(data));
+        document.xref = xref;
+        document.info_ref = info_ref;
+        document.root_ref = root_ref;
+        for (number, offset) in streams {
+            // Cheap check before parsing the whole object
+            let window = document.source.read_at(offset, 512).unwrap_or_default();
+            if PdfParser::find_bytes(&window, b"/ObjStm").is_none() {
+                continue;
+            }
+            if let Some(objects) = document.load_object_stream(number) {
+                for &contained in objects.keys() {
+                    document
+                        .xref
+                        .entry(contained)
+                        .or_insert(XrefEntry::Compressed(number));
+                }
+                document.object_streams.get_mut().insert(number, objects);
+            }
+        }
+        document
+    }
+
+    /// Read the cross-reference sections from `startxref` back through `/Prev`, keeping the
+    /// newest entry of every object. Returns whether any entries were found.
+    fn read_xref(&mut self) -> bool {
+        let tail_start = self.source.len().saturating_sub(XREF_TAIL_WINDOW);
+        let Some(tail) = self.source.read_at(tail_start, XREF_TAIL_WINDOW as usize) else {
+            return false;
+        };
+        let Some(at) = tail.windows(9).rposition(|w| w == b"startxref") else {
+            return false;
+        };
+        let mut parser = ObjectParser::new(&tail, at + b"startxref".len());
+        parser.skip_whitespace();
+        let mut next = parser.parse_number().map(|n| n as u64);
+
+        let mut visited = HashSet::new();
+        while let Some(offset) = next.take() {
+            if !visited.insert(offset) || visited.len() > MAX_DEPTH {
+                break;
+            }
+            let Some(trailer) = self.read_xref_section(offset) else {
+                break;
+            };
+            self.info_ref = self.info_ref.or(Self::reference(trailer.get("Info")));
+            self.root_ref = self.root_ref.or(Self::reference(trailer.get("Root")));
+            // Hybrid files list their compressed objects in an extra stream
+            if let Some(stream) = trailer.get("XRefStm").and_then(PdfObject::as_number)
+                && visited.insert(stream as u64)
+            {
+                self.read_xref_section(stream as u64);
+            }
+            next = trailer
+                .get("Prev")
+                .and_then(PdfObject::as_number)
+                .map(|n| n as u64);
+        }
+        !self.xref.is_empty()
+    }
+
+    fn reference(object: Option<&PdfObject>) -> Option<u32> {
+        match object? {
+            PdfObject::Ref(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Read a cross-reference table or stream and return its trailer dictionary.
+    fn read_xref_section(&mut self, offset: u64) -> Option<HashMap<String, PdfObject>> {
+        let head = self.source.read_at(offset, 16)?;
+        let mut parser = ObjectParser::new(&head, 0);
+        parser.skip_whitespace();
+        if parser.starts_with(b"xref") {
+            self.read_xref_table(offset + (parser.pos + b"xref".len()) as u64)
+        } else {
+            self.read_xref_stream(offset)
+        }
+    }
+
+    /// Read the subsections of a classic `xref` table up to its trailer.
+    fn read_xref_table(&mut self, mut pos: u64) -> Option<HashMap<String, PdfObject>> {
+        loop {
+            let head = self.source.read_at(pos, 64)?;
+            let mut parser = ObjectParser::new(&head, 0);
+            parser.skip_whitespace();
+            if parser.starts_with(b"trailer") {
+                let at = pos + (parser.pos + b"trailer".len()) as u64;
+                let (trailer, _) = self.parse_at(at, |parser| parser.parse_object(0))?;
+                return trailer.as_dict().cloned();
+            }
+
+            let first = parser.parse_number()? as u32;
+            parser.skip_whitespace();
+            let count = parser.parse_number()? as usize;
+            let entries_at = pos + parser.pos as u64;
+
+            // Entries are `offset generation n|f`, nominally 20 bytes each
+            let table = self
+                .source
+                .read_at(entries_at, count.saturating_mul(20).saturating_add(32))?;
+            let mut entries = ObjectParser::new(&table, 0);
+            for i in 0..count {
+                entries.skip_whitespace();
+                let offset = entries.parse_number()?;
+                entries.skip_whitespace();
+                entries.parse_number()?;
+                entries.skip_whitespace();
+                if entries.regular_token() == b"n" && offset > 0.0 {
+                    self.xref
+                        .entry(first.saturating_add(i as u32))
+                        .or_insert(XrefEntry::Offset(offset as u64));
+                }
+            }
+            pos = entries_at + entries.pos as u64;
+        }
+    }
+
+    /// Read a cross-reference stream (PDF 1.5+), whose dictionary is also the trailer.
+    fn read_xref_stream(&mut self, offset: u64) -> Option<HashMap<String, PdfObject>> {
+        let (_, stream) = self.read_indirect(offset)?;
+        let dict = stream.as_dict()?.clone();
+        if dict.get("Type").and_then(PdfObject::as_name) != Some("XRef") {
+            return None;
+        }
+        let data = self.decode_stream(&stream)?;
+
+        let numbers = |key: &str| match dict.get(key) {
+            Some(PdfObject::Array(values)) => Some(
+                values
+                    .iter()
+                    .map(|v| v.as_number().unwrap_or(0.0) as u64)
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        let widths = numbers("W")?;
+        if widths.len() != 3 || widths.iter().any(|&w| w > 8) {
+            return None;
+        }
+        let size = dict
+            .get("Size")
+            .and_then(PdfObject::as_number)
+            .unwrap_or(0.0) as u64;
+        let index = numbers("Index").unwrap_or_else(|| vec![0, size]);
+        let row_len = widths.iter().sum::<u64>() as usize;
+        if row_len == 0 {
+            return None;
+        }
+
+        let mut rows = data.chunks_exact(row_len);
+        for range in index.chunks_exact(2) {
+            for number in range[0]..range[0].saturating_add(range[1]) {
+                let Some(row) = rows.next() else {
+                    break;
+                };
+                let mut fields = [0u64; 3];
+                let mut at = 0;
+                for (field, &width) in fields.iter_mut().zip(&widths) {
+                    let width = width as usize;
+                    *field = row[at..at + width]
+                        .iter()
+                        .fold(0, |value, &b| (value << 8) | u64::from(b));
+                    at += width;
+                }
+                // The type defaults to 1 when its field is left out
+                let kind = if widths[0] == 0 { 1 } else { fields[0] };
+                let entry = match kind {
+                    1 if fields[1] > 0 => XrefEntry::Offset(fields[1]),
+                    2 => XrefEntry::Compressed(fields[1] as u32),
+                    _ => continue,
+                };
+                self.xref.entry(number as u32).or_insert(entry);
+            }
+        }
+        Some(dict)
+    }
+
+    /// Run `parse` on the bytes from `offset`, reading more of the file while what was
+    /// read so far is too short. Returns the result and the offset after it.
+    fn parse_at<T>(
+        &self,
+        offset: u64,
+        parse: impl Fn(&mut ObjectParser) -> Option<T>,
+    ) -> Option<(T, u64)> {
+        let mut window = OBJECT_WINDOW;
+        loop {
+            let data = self.source.read_at(offset, window)?;
+            let at_end = data.len() < window;
+            let mut parser = ObjectParser::new(&data, 0);
+            // Leave room for a keyword after the object, such as `stream`
+            match parse(&mut parser) {
+                Some(value) if at_end || parser.pos + 32 <= data.len() => {
+                    return Some((value, offset + parser.pos as u64));
+                }
+                _ if at_end || window >= MAX_OBJECT_WINDOW => return None,
+                _ => window *= 4,
+            }
+        }
+    }
+
+    /// Read the indirect object whose `N G obj` header is at `offset`, with the data of
+    /// streams. Returns the object number and the object.
+    fn read_indirect(&self, offset: u64) -> Option<(u32, PdfObject)> {
+        let ((number, object), end) = self.parse_at(offset, |parser| {
+            parser.skip_whitespace();
+            let number = parser.parse_number()?;
+            parser.skip_whitespace();
+            parser.parse_number()?;
+            parser.skip_whitespace();
+            if parser.regular_token() != b"obj" {
+                return None;
+            }
+            Some((number as u32, parser.parse_object(0)?))
+        })?;
+        let PdfObject::Dict(dict) = object else {
+            return Some((number, object));
+        };
+
+        let after = self.source.read_at(end, 32).unwrap_or_default();
+        let mut parser = ObjectParser::new(&after, 0);
+        parser.skip_whitespace();
+        if !parser.starts_with(b"stream") {
+            return Some((number, PdfObject::Dict(dict)));
+        }
+        let mut start = parser.pos + b"stream".len();
+        if after.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if after.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+
+        let data = self.stream_data(&dict, end + start as u64)?;
+        Some((number, PdfObject::Stream(dict, data)))
+    }
+
+    /// Raw data of a stream starting at `start`: `/Length` bytes if the stream ends there,
+    /// otherwise everything up to the `endstream` keyword.
+    fn stream_data(&self, dict: &HashMap<String, PdfObject>, start: u64) -> Option<Vec<u8>> {
+        // Lengths are direct objects or plain numbers outside object streams
+        let declared = match dict.get("Length") {
+            Some(PdfObject::Ref(number)) => match self.xref.get(number) {
+                Some(XrefEntry::Offset(offset)) => self
+                    .read_indirect(*offset)
+                    .and_then(|(_, length)| length.as_number()),
+                _ => None,
+            },
+            Some(other) => other.as_number(),
+            None => None,
+        }
+        .filter(|&n| n >= 0.0 && (n as u64) <= self.source.len())
+        .map(|n| n as usize);
+
+        if let Some(len) = declared
+            && let Some(mut data) = self.source.read_at(start, len + 32)
+            && data.len() >= len
+            && data[len..]
+                .iter()
+                .position(|&b| !is_whitespace(b))
+                .is_some_and(|p| data[len + p..].starts_with(b"endstream"))
+        {
+            data.truncate(len);
+            return Some(data);
+        }
+
+        let mut data = Vec::new();
+        let mut pos = start;
+        loop {
+            let chunk = self.source.read_at(pos, OBJECT_WINDOW * 16)?;
+            let search_from = data.len().saturating_sub(b"endstream".len());
+            pos += chunk.len() as u64;
+            data.extend_from_slice(&chunk);
+            if let Some(end) = PdfParser::find_bytes(&data[search_from..], b"endstream") {
+                let mut len = search_from + end;
+                while len > 0 && matches!(data[len - 1], b'\r' | b'\n') {
+                    len -= 1;
+                }
+                data.truncate(len);
+                return Some(data);
+            }
+            if data.len() as u64 > MAX_DECODED_STREAM {
+                return None;
+            }
+        }
+    }
+
+    fn object(&self, number: u32) -> Option<PdfObject> {
+        match *self.xref.get(&number)? {
+            XrefEntry::Offset(offset) => {
+                let (found, object) = self.read_indirect(offset)?;
+                (found == number).then_some(object)
+            }
+            XrefEntry::Compressed(stream) => {
+                if let Some(objects) = self.object_streams.borrow().get(&stream) {
+                    return objects.get(&number).cloned();
+                }
+                let objects = self.load_object_stream(stream).unwrap_or_default();
+                let object = objects.get(&number).cloned();
+                self.object_streams.borrow_mut().insert(stream, objects);
+                object
+            }
+        }
+    }
+
+    /// Parse all objects of the object stream `stream`.
+    fn load_object_stream(&self, stream: u32) -> Option<HashMap<u32, PdfObject>> {
+        // Object streams are never stored inside other object streams
+        let XrefEntry::Offset(offset) = *self.xref.get(&stream)? else {
+            return None;
+        };
+        let (_, stream) = self.read_indirect(offset)?;
+        let dict = stream.as_dict()?;
+        if dict.get("Type").and_then(PdfObject::as_name) != Some("ObjStm") {
+            return None;
+        }
+        let count = dict.get("N").and_then(PdfObject::as_number).unwrap_or(0.0) as usize;
+        let first = dict
+            .get("First")
+            .and_then(PdfObject::as_number)
+            .unwrap_or(0.0) as usize;
+        let decoded = self.decode_stream(&stream)?;
+
+        let mut objects = HashMap::new();
+        let mut header = ObjectParser::new(&decoded, 0);
+        for _ in 0..count {
+            header.skip_whitespace();
+            let Some(num) = header.parse_number() else {
+                break;
+            };
+            header.skip_whitespace();
+            let Some(rel) = header.parse_number() else {
+                break;
+            };
+            let mut parser = ObjectParser::new(&decoded, first + rel as usize);
+            if let Some(object) = parser.parse_object(0) {
+                objects.entry(num as u32).or_insert(object);
+            }
+        }
+        Some(objects)
+    }
+
+    /// Follow indirect references until a direct object is reached.
+    fn resolve(&self, object: &PdfObject) -> PdfObject {
+        let mut current = object.clone();
+        for _ in 0..MAX_DEPTH {
+            match current {
+                PdfObject::Ref(number) => {
+                    current = self.object(number).unwrap_or(PdfObject::Null);
+                }
+                _ => return current,
+            }
+        }
+        PdfObject::Null
+    }
+
+    fn resolve_dict(&self, object: Option<&PdfObject>) -> Option<HashMap<String, PdfObject>> {
+        match self.resolve(object?) {
+            PdfObject::Dict(dict) | PdfObject::Stream(dict, _) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn filters(&self, dict: &HashMap<String, PdfObject>) -> Vec<String> {
+        match dict.get("Filter").map(|f| self.resolve(f)) {
+            Some(PdfObject::Name(name)) => vec![name],
+            Some(PdfObject::Array(names)) => names
+                .iter()
+                .filter_map(|n| n.as_name().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Apply the filters of a stream up to an image codec, which can only be the last
+    /// filter. Returns the data and the name of that codec, if any.
+    fn decode_filters(
+        &self,
+        dict: &HashMap<String, PdfObject>,
+        raw: &[u8],
+    ) -> Option<(Vec<u8>, Option<String>)> {
+        let filters = self.filters(dict);
+        let parms: Vec<Option<HashMap<String, PdfObject>>> =
+            match dict.get("DecodeParms").map(|p| self.resolve(p)) {
+                Some(PdfObject::Array(parms)) => parms
+                    .iter()
+                    .map(|p| self.resolve(p).as_dict().cloned())
+                    .collect(),
+                Some(PdfObject::Dict(parms)) => vec![Some(parms)],
+                _ => Vec::new(),
+            };
+
+        let mut data = raw.to_vec();
+        for (i, filter) in filters.iter().enumerate() {
+            match filter.as_str() {
+                "FlateDecode" => {
+                    let mut decoded = Vec::new();
+                    ZlibDecoder::new(data.as_slice())
+                        .take(MAX_DECODED_STREAM)
+                        .read_to_end(&mut decoded)
+                        .ok()?;
+                    data = unpredict(decoded, parms.get(i).and_then(Option::as_ref))?;
+                }
+                "DCTDecode" | "JPXDecode" | "CCITTFaxDecode" | "JBIG2Decode"
+                    if i + 1 == filters.len() =>
+                {
+                    return Some((data, Some(filter.clone())));
+                }
+                _ => return None,
+            }
+        }
+        Some((data, None))
+    }
+
+    /// Decode a stream that is unfiltered or Flate-compressed (with or without predictor).
+    fn decode_stream(&self, stream: &PdfObject) -> Option<Vec<u8>> {
+        let PdfObject::Stream(dict, raw) = stream else {
+            return None;
+        };
+        match self.decode_filters(dict, raw)? {
+            (data, None) => Some(data),
+            (_, Some(_)) => None,
+        }
+    }
+
+    fn info(&self) -> Option<HashMap<String, PdfObject>> {
+        let info = self.object(self.info_ref?)?;
+        info.as_dict().cloned()
+    }
+
+    fn root(&self) -> Option<HashMap<String, PdfObject>> {
+        let root = self.object(self.root_ref?)?;
+        root.as_dict().cloned()
+    }
+
+    fn xmp(&self) -> Option<String> {
+        let root = self.root()?;
+        let metadata = self.resolve(root.get("Metadata")?);
+        let decoded = self.decode_stream(&metadata)?;
+        Some(String::from_utf8_lossy(&decoded).into_owned())
+    }
+
+    fn page_count(&self) -> Option<u32> {
+        let pages = self.resolve_dict(self.root()?.get("Pages"))?;
+        let count = self.resolve(pages.get("Count")?).as_number()?;
+        (count >= 1.0).then_some(count as u32)
+    }
+
+    /// Find the first page, collecting inherited resources on the way down the page tree.
+    fn first_page(&self) -> Option<HashMap<String, PdfObject>> {
+        let mut node = self.resolve_dict(self.root()?.get("Pages"))?;
+        let mut resources = None;
+        for _ in 0..MAX_DEPTH {
+            if let Some(res) = node.get("Resources") {
+                resources = Some(res.clone());
+            }
+            let kids = match node.get("Kids").map(|k| self.resolve(k)) {
+                Some(PdfObject::Array(kids)) => kids,
+                _ => {
+                    if let Some(res) = resources {
+                        node.insert("Resources".to_string(), res);
+                    }
+                    return Some(node);
+                }
+            };
+            node = self.resolve_dict(kids.first())?;
+        }
+        None
+    }
+
+    /// The largest image drawn on the first page, as encoded image bytes and MIME type.
+    fn first_page_cover(&self) -> Option<(Vec<u8>, &'static str)> {
+        let page = self.first_page()?;
+        let resources = self.resolve_dict(page.get("Resources"))?;
+        let xobjects = self.resolve_dict(resources.get("XObject"))?;
+
+        let mut names: Vec<&String> = xobjects.keys().collect();
+        names.sort();
+        let mut best: Option<(f64, PdfObject)> = None;
+        for name in names {
+            let image = self.resolve(&xobjects[name]);
+            let Some(dict) = image.as_dict() else {
+                continue;
+            };
+            if dict.get("Subtype").and_then(PdfObject::as_name) != Some("Image") {
+                continue;
+            }
+            let dimension = |key: &str| {
+                dict.get(key)
+                    .and_then(|v| self.resolve(v).as_number())
+                    .unwrap_or(0.0)
+            };
+            let area = dimension("Width") * dimension("Height");
+            if best.as_ref().is_none_or(|(best_area, _)| area > *best_area) {
+                best = Some((area, image));
+            }
+        }
+
+        self.encode_image(&best?.1)
+    }
+
+    fn encode_image(&self, image: &PdfObject) -> Option<(Vec<u8>, &'static str)> {
+        let PdfObject::Stream(dict, raw) = image else {
+            return None;
+        };
+        let (samples, codec) = self.decode_filters(dict, raw)?;
+        match codec.as_deref() {
+            Some("DCTDecode") => return Some((samples, "image/jpeg")),
+            // JPEG 2000, CCITT fax and JBIG2 images are not decoded
+            Some(_) => return None,
+            None => {}
+        }
+
+        let number = |key: &str| {
+            dict.get(key)
+                .and_then(|v| self.resolve(v).as_number())
+                .unwrap_or(0.0) as u32
+        };
+        let (width, height) = (number("Width") as usize, number("Height") as usize);
+        // Stencil masks are 1-bit images painted in the fill color, normally black
+        let (space, bits) = if dict.get("ImageMask") == Some(&PdfObject::Bool(true)) {
+            (ColorSpace::Gray, 1)
+        } else {
+            let space = self.color_space(dict.get("ColorSpace")?)?;
+            (space, number("BitsPerComponent") as usize)
+        };
+        if width == 0 || height == 0 || !matches!(bits, 1 | 2 | 4 | 8 | 16) {
+            return None;
+        }
+        let inverted = match dict.get("Decode").map(|d| self.resolve(d)) {
+            Some(PdfObject::Array(decode)) => {
+                decode.first().and_then(PdfObject::as_number) == Some(1.0)
+            }
+            _ => false,
+        };
+
+        // Sizes come from the file, so they are checked before anything is allocated
+        let components = space.components();
+        let row_len = width
+            .checked_mul(components)?
+            .checked_mul(bits)?
+            .div_ceil(8);
+        let pixels_len =
+            width
+                .checked_mul(height)?
+                .checked_mul(if space.is_gray() { 1 } else { 3 })?;
+        if samples.len() < row_len.checked_mul(height)? || pixels_len as u64 > MAX_DECODED_STREAM {
+            return None;
+        }
+        // 16-bit samples are cut down to their high byte
+        let max = (1u32 << bits.min(8)) - 1;
+        let mut pixels = Vec::with_capacity(pixels_len);
+        for row in samples.chunks_exact(row_len).take(height) {
+            let sample = |index: usize| -> u32 {
+                match bits {
+                    8 => u32::from(row[index]),
+                    16 => u32::from(row[index * 2]),
+                    _ => {
+                        let bit = index * bits;
+                        u32::from(row[bit / 8] >> (8 - bits - bit % 8)) & max
+                    }
+                }
+            };
+            let scaled = |index: usize| (sample(index) * 255 / max) as u8;
+            for x in 0..width {
+                let first = x * components;
+                match &space {
+                    ColorSpace::Gray => {
+                        let value = scaled(first);
+                        pixels.push(if inverted { 255 - value } else { value });
+                    }
+                    ColorSpace::Rgb => pixels.extend((0..3).map(|c| scaled(first + c))),
+                    ColorSpace::Cmyk => pixels.extend(cmyk_to_rgb([
+                        scaled(first),
+                        scaled(first + 1),
+                        scaled(first + 2),
+                        scaled(first + 3),
+                    ])),
+                    ColorSpace::Indexed { base, palette } => {
+                        pixels.extend(base.palette_color(palette, sample(first) as usize))
+                    }
+                }
+            }
+        }
+
+        let (width, height) = (width as u32, height as u32);
+        let dynamic = if space.is_gray() {
+            image::DynamicImage::ImageLuma8(image::GrayImage::from_raw(width, height, pixels)?)
+        } else {
+            image::DynamicImage::ImageRgb8(image::RgbImage::from_raw(width, height, pixels)?)
+        };
+        let mut png = std::io::Cursor::new(Vec::new());
+        dynamic.write_to(&mut png, image::ImageFormat::Png).ok()?;
+        Some((png.into_inner(), "image/png"))
+    }
+
+    /// The color spaces raw image samples are converted from.
+    fn color_space(&self, object: &PdfObject) -> Option<ColorSpace> {
+        match self.resolve(object) {
+            PdfObject::Name(name) => match name.as_str() {
+                "DeviceGray" | "CalGray" => Some(ColorSpace::Gray),
+                "DeviceRGB" | "CalRGB" => Some(ColorSpace::Rgb),
+                "DeviceCMYK" => Some(ColorSpace::Cmyk),
+                _ => None,
+            },
+            PdfObject::Array(parts) => match parts.first().and_then(PdfObject::as_name)? {
+                "CalGray" => Some(ColorSpace::Gray),
+                "CalRGB" => Some(ColorSpace::Rgb),
+                "ICCBased" => {
+                    let profile = self.resolve_dict(parts.get(1))?;
+                    match profile.get("N").and_then(PdfObject::as_number)? as u32 {
+                        1 => Some(ColorSpace::Gray),
+                        3 => Some(ColorSpace::Rgb),
+                        4 => Some(ColorSpace::Cmyk),
+                        _ => None,
+                    }
+                }
+                "Indexed" => {
+                    let base = self.color_space(parts.get(1)?)?;
+                    if matches!(base, ColorSpace::Indexed { .. }) {
+                        return None;
+                    }
+                    let palette = match self.resolve(parts.get(3)?) {
+                        PdfObject::String(bytes) => bytes,
+                        stream @ PdfObject::Stream(..) => self.decode_stream(&stream)?,
+                        _ => return None,
+                    };
+                    Some(ColorSpace::Indexed {
+                        base: Box::new(base),
+                        palette,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Color space of raw image samples.
+enum ColorSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+    /// One-byte-per-component colors of the base space, looked up by sample value
+    Indexed {
+        base: Box<ColorSpace>,
+        palette: Vec<u8>,
+    },
+}
+
+impl ColorSpace {
+    fn components(&self) -> usize {
+        match self {
+            Self::Gray | Self::Indexed { .. } => 1,
+            Self::Rgb => 3,
+            Self::Cmyk => 4,
+        }
+    }
+
+    fn is_gray(&self) -> bool {
+        matches!(self, Self::Gray)
+    }
+
+    /// RGB color of palette entry `index` (black for entries past the end).
+    fn palette_color(&self, palette: &[u8], index: usize) -> [u8; 3] {
+        let n = self.components();
+        let Some(entry) = palette.get(index * n..index * n + n) else {
+            return [0; 3];
+        };
+        match self {
+            Self::Rgb => [entry[0], entry[1], entry[2]],
+            Self::Cmyk => cmyk_to_rgb([entry[0], entry[1], entry[2], entry[3]]),
+            _ => [entry[0]; 3],
+        }
+    }
+}
+
+/// Naive CMYK to RGB conversion, good enough for a thumbnail.
+fn cmyk_to_rgb([c, m, y, k]: [u8; 4]) -> [u8; 3] {
+    let channel = |value: u8| ((255 - u32::from(value)) * (255 - u32::from(k)) / 255) as u8;
+    [channel(c), channel(m), channel(y)]
+}
+
+/// Undo the PNG or TIFF predictor (`/DecodeParms /Predictor`) of Flate-decoded data.
+fn unpredict(data: Vec<u8>, parms: Option<&HashMap<String, PdfObject>>) -> Option<Vec<u8>> {
+    let param = |key: &str, default: f64| {
+        parms
+            .and_then(|p| p.get(key))
+            .and_then(PdfObject::as_number)
+            .unwrap_or(default) as usize
+    };
+    let predictor = param("Predictor", 1.0);
+    if predictor == 1 {
+        return Some(data);
+    }
+    let colors = param("Colors", 1.0).max(1);
+    let bits = param("BitsPerComponent", 8.0);
+    let columns = param("Columns", 1.0);
+    // Crafted parameters must neither overflow nor size buffers beyond the data
+    let pixel_bits = colors.checked_mul(bits)?;
+    let pixel_len = pixel_bits.div_ceil(8).max(1);
+    let row_len = pixel_bits.checked_mul(columns)?.div_ceil(8);
+    if row_len == 0 || row_len > data.len() {
+        return None;
+    }
+
+    if predictor == 2 {
+        // TIFF horizontal differencing, supported for 8-bit components only
+        if bits != 8 {
+            return None;
+        }
+        let mut data = data;
+        for row in data.chunks_mut(row_len) {
+            for i in pixel_len..row.len() {
+                row[i] = row[i].wrapping_add(row[i - pixel_len]);
+            }
+        }
+        return Some(data);
+    }
+
+    // PNG predictors: every row starts with the type of its filter
+    let mut out = Vec::with_capacity(data.len() / (row_len + 1) * row_len);
+    let mut previous = vec![0u8; row_len];
+    for encoded in data.chunks(row_len + 1) {
+        let (&kind, encoded) = encoded.split_first()?;
+        let mut row = encoded.to_vec();
+        row.resize(row_len, 0);
+        for i in 0..row_len {
+            let left = if i >= pixel_len {
+                row[i - pixel_len]
+            } else {
+                0
+            };
+            let up = previous[i];
+            let up_left = if i >= pixel_len {
+                previous[i - pixel_len]
+            } else {
+                0
+            };
+            row[i] = row[i].wrapping_add(match kind {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            });
+        }
+        out.extend_from_slice(&row);
+        previous = row;
+    }
+    Some(out)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let distance = |value: u8| (estimate - i16::from(value)).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// The Dublin Core fields of an XMP packet that map onto `BookInfo`.
+#[derive(Debug, Default)]
+struct XmpMetadata {
+    title: Option<String>,
+    creators: Vec<String>,
+    subjects: Vec<String>,
+    description: Option<String>,
+    language: Option<String>,
+    publisher: Option<String>,
+}
+
+impl XmpMetadata {
+    fn parse(xml: &str) -> Self {
+        let first = |tag: &str| Self::values(xml, tag).into_iter().next();
+        Self {
+            title: first("dc:title"),
+            creators: Self::values(xml, "dc:creator"),
+            subjects: Self::values(xml, "dc:subject"),
+            description: first("dc:description"),
+            language: first("dc:language"),
+            publisher: first("dc:publisher"),
+        }
+    }
+
+    /// Values of a Dublin Core property: the `rdf:li` items of its container, or its
+    /// text content for simple properties.
+    fn values(xml: &str, tag: &str) -> Vec<String> {
+        let Ok(element) = regex::Regex::new(&format!(
+            r"(?s)<{tag}(?:\s[^>]*)?>(.*?)</{tag}>",
+            tag = regex::escape(tag)
+        )) else {
+            return Vec::new();
+        };
+        static LIST_ITEM: LazyLock<regex::Regex> =
+            LazyLock::new(|| regex::Regex::new(r"(?s)<rdf:li(?:\s[^>]*)?>(.*?)</rdf:li>").unwrap());
+
+        let Some(content) = element.captures(xml).map(|c| c.get(1).unwrap().as_str()) else {
+            return Vec::new();
+        };
+        let raw: Vec<&str> = if content.contains("<rdf:li") {
+            LIST_ITEM
+                .captures_iter(content)
+                .map(|c| c.get(1).unwrap().as_str())
+                .collect()
+        } else {
+            vec![content]
+        };
+        raw.into_iter()
+            .filter_map(|value| {
+                let value = quick_xml::escape::unescape(value.trim())
+                    .map(|v| v.into_owned())
+                    .unwrap_or_else(|_| value.trim().to_string());
+                (!value.is_empty()).then_some(value)
+            })
+            .collect()
+    }
+}
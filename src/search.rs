@@ -0,0 +1,265 @@
+//! Full-text search over highlights, notes, review notes, descriptions and subjects.
+//!
+//! The index is written as JSON shards below `/assets/json/search/` for static sites and
+//! kept in memory with each library snapshot for the web server's `/api/v1/search`. The client-side search in
+//! `assets/ts/components/full-text-search.ts` mirrors the tokenizer, the shard keys and the
+//! snippets, so both modes find the same results.
+
+use crate::models::{ContentType, LibraryItem};
+use crate::utils::strip_tags;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Shortest term (in characters) that is indexed.
+pub const MIN_TERM_LENGTH: usize = 2;
+
+/// Number of documents per `documents/<n>.json` chunk of the static index.
+pub const DOCUMENTS_PER_CHUNK: usize = 500;
+
+/// Characters of context shown before the first match in a snippet.
+const SNIPPET_BEFORE: usize = 60;
+/// Total characters of a snippet (without ellipses).
+const SNIPPET_LENGTH: usize = 180;
+
+/// Part of an item a search document was taken from. Results are ranked in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Highlight,
+    Note,
+    ReviewNote,
+    Subject,
+    Description,
+}
+
+impl SearchField {
+    /// Translation key of the field's label.
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            SearchField::Highlight => "search-field.highlight",
+            SearchField::Note => "search-field.note",
+            SearchField::ReviewNote => "search-field.review-note",
+            SearchField::Subject => "search-field.subject",
+            SearchField::Description => "search-field.description",
+        }
+    }
+}
+
+/// A searchable piece of text of a library item.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchDocument {
+    pub item_id: String,
+    pub title: String,
+    pub field: SearchField,
+    /// Detail page of the item (below the site root), with the anchor of the highlight,
+    /// note or section the text appears in
+    pub href: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    pub text: String,
+}
+
+/// A search result with a snippet of the matched text.
+#[derive(Debug, Serialize)]
+pub struct SearchHit<'a> {
+    pub item_id: &'a str,
+    pub title: &'a str,
+    pub field: SearchField,
+    pub href: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    pub snippet: String,
+}
+
+/// Inverted index over the search documents of a library.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Documents in rank order: by field (highlights first), then in library order
+    pub documents: Vec<SearchDocument>,
+    /// Terms mapped to the ascending indices of the documents containing them
+    pub terms: BTreeMap<String, Vec<usize>>,
+}
+
+impl SearchIndex {
+    pub fn build(items: &[LibraryItem]) -> Self {
+        let mut index = SearchIndex::default();
+        for item in items {
+            let page = match item.content_type() {
+                ContentType::Book => format!("/books/{}/", item.id),
+                ContentType::Comic => format!("/comics/{}/", item.id),
+            };
+            let mut add = |field: SearchField, anchor: &str, page_no: Option<u32>, text: &str| {
+                let text = text.trim();
+                if text.is_empty() {
+                    return;
+                }
+                index.documents.push(SearchDocument {
+                    item_id: item.id.clone(),
+                    title: item.book_info.title.clone(),
+                    field,
+                    href: format!("{}#{}", page, anchor),
+                    page: page_no,
+                    text: text.to_string(),
+                });
+            };
+
+            // Anchors match the element IDs of the item detail page
+            for (i, annotation) in item.annotations().iter().enumerate() {
+                let anchor = format!("annotation-{}", i);
+                if annotation.is_highlight()
+                    && let Some(text) = &annotation.text
+                {
+                    add(SearchField::Highlight, &anchor, annotation.pageno, text);
+                }
+                if let Some(note) = &annotation.note {
+                    add(SearchField::Note, &anchor, annotation.pageno, note);
+                }
+            }
+            if let Some(note) = item.review_note() {
+                add(SearchField::ReviewNote, "review-note", None, note);
+            }
+            for subject in item.subjects() {
+                add(SearchField::Subject, "book-overview", None, subject);
+            }
+            if let Some(description) = &item.book_info.description {
+                add(
+                    SearchField::Description,
+                    "book-overview",
+                    None,
+                    &strip_tags(description),
+                );
+            }
+        }
+
+        // Ranking is then the order of the document indices, so clients only need to load
+        // the chunks of the documents they show
+        index.documents.sort_by_key(|document| document.field);
+
+        for (i, document) in index.documents.iter().enumerate() {
+            for term in tokenize(&document.text) {
+                let postings = index.terms.entry(term).or_default();
+                if postings.last() != Some(&i) {
+                    postings.push(i);
+                }
+            }
+        }
+        index
+    }
+
+    /// Documents matching every term of `query`, each term matching as a prefix. Ranked by
+    /// field (highlights first), then in library order.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit<'_>> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Option<BTreeSet<usize>> = None;
+        for term in &query_terms {
+            let docs: BTreeSet<usize> = self
+                .terms
+                .range(term.clone()..)
+                .take_while(|(candidate, _)| candidate.starts_with(term.as_str()))
+                .flat_map(|(_, postings)| postings.iter().copied())
+                .collect();
+            matches = Some(match matches {
+                Some(previous) => previous.intersection(&docs).copied().collect(),
+                None => docs,
+            });
+        }
+
+        matches
+            .unwrap_or_default()
+            .into_iter()
+            .take(limit)
+            .map(|i| {
+                let document = &self.documents[i];
+                SearchHit {
+                    item_id: &document.item_id,
+                    title: &document.title,
+                    field: document.field,
+                    href: document.href.clone(),
+                    page: document.page,
+                    snippet: snippet(&document.text, &query_terms),
+                }
+            })
+            .collect()
+    }
+
+    /// Documents split into chunks of [`DOCUMENTS_PER_CHUNK`]; document `i` is in chunk
+    /// `i / DOCUMENTS_PER_CHUNK`.
+    pub fn document_chunks(&self) -> std::slice::Chunks<'_, SearchDocument> {
+        self.documents.chunks(DOCUMENTS_PER_CHUNK)
+    }
+
+    /// Terms grouped into shards by [`shard_key`], so a client only loads the shards of
+    /// the terms it looks up.
+    pub fn shards(&self) -> BTreeMap<String, BTreeMap<&str, &[usize]>> {
+        let mut shards: BTreeMap<String, BTreeMap<&str, &[usize]>> = BTreeMap::new();
+        for (term, postings) in &self.terms {
+            shards
+                .entry(shard_key(term))
+                .or_default()
+                .insert(term.as_str(), postings.as_slice());
+        }
+        shards
+    }
+}
+
+/// Lowercase words of `text` with at least [`MIN_TERM_LENGTH`] characters.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_LENGTH)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Shard of a term: its first character when that is an ASCII letter or digit, otherwise
+/// `u` followed by the character's hex code point (keeps shard file names portable).
+pub fn shard_key(term: &str) -> String {
+    match term.chars().next() {
+        Some(c) if c.is_ascii_alphanumeric() => c.to_string(),
+        Some(c) => format!("u{:x}", c as u32),
+        None => String::new(),
+    }
+}
+
+/// Excerpt of `text` around the first word starting with one of `terms`, with ellipses
+/// where text was cut.
+pub fn snippet(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let is_word_start = |i: usize| i == 0 || !lower[i - 1].is_alphanumeric();
+    let first_match = (0..lower.len())
+        .filter(|&i| is_word_start(i))
+        .find(|&i| {
+            terms.iter().any(|term| {
+                let term: Vec<char> = term.chars().collect();
+                lower[i..].starts_with(&term)
+            })
+        })
+        .unwrap_or(0);
+
+    let mut start = first_match.saturating_sub(SNIPPET_BEFORE);
+    // Start at a word boundary unless that would drop the whole context
+    while start > 0 && start < first_match && !is_word_start(start) {
+        start += 1;
+    }
+    let end = (start + SNIPPET_LENGTH).min(chars.len());
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    out.extend(&chars[start..end]);
+    let out = out.trim_end().to_string();
+    if end < chars.len() {
+        format!("{}…", out)
+    } else {
+        out
+    }
+}
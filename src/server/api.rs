@@ -0,0 +1,609 @@
+//! Read-only JSON API (`/api/v1`) over the library, annotations and statistics. With reader
+//! profiles, every profile site has its own API below `/u/<profile>/api/v1`.
+//!
+//! All handlers read from the shared [`crate::library::LibrarySnapshot`], so responses always match
+//! the most recently generated HTML.
+
+use actix_web::{HttpResponse, Responder, web};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::GoalsConfig;
+use crate::i18n::Translations;
+use crate::koreader::{
+    BookStatistics, CalendarGenerator, GoalCalculator, StatisticsCalculator, TimelineCalculator,
+};
+use crate::library::{LibrarySnapshot, SharedLibraryState};
+use crate::models::{
+    Annotation, BookStatus, ContentType, GoalProgress, Identifier, LibraryItem, LibraryItemFormat,
+    ReadingStats, StatBook, StatisticsData,
+};
+use crate::search::SearchHit;
+use crate::site_generator::build_year_recap;
+use crate::time_config::TimeConfig;
+
+const DEFAULT_PER_PAGE: usize = 50;
+const MAX_PER_PAGE: usize = 500;
+
+/// Settings the API needs to compute statistics the same way the site generator does.
+#[derive(Clone)]
+pub struct ApiConfig {
+    pub time_config: TimeConfig,
+    pub language: String,
+    /// Output directory of the generated site, whose `assets/covers` holds the `<id>.webp` covers
+    pub site_dir: PathBuf,
+    /// Reading goals reported with the overall statistics and recaps
+    pub goals: GoalsConfig,
+    /// APIs of the reader profile sites by profile slug. When set, each is served below
+    /// `/u/<slug>/api/v1` instead of the root `/api/v1`.
+    pub profiles: Vec<(String, ApiConfig)>,
+}
+
+/// The site a scope serves: the root site or a reader profile's site.
+struct ApiSite {
+    library: SharedLibraryState,
+    /// Reader profile slug, `None` for the root site
+    profile: Option<String>,
+    /// Directory holding the generated `<id>.webp` covers
+    covers_dir: PathBuf,
+}
+
+impl ApiSite {
+    /// Current snapshot of the site.
+    fn load(&self) -> Arc<LibrarySnapshot> {
+        self.library.load_profile(self.profile.as_deref())
+    }
+
+    /// URL prefix of the site's pages.
+    fn root(&self) -> String {
+        self.profile
+            .as_ref()
+            .map(|slug| format!("/u/{}", slug))
+            .unwrap_or_default()
+    }
+}
+
+/// Register the `/api/v1` routes, or those of every reader profile.
+///
+/// Must be called from inside the `HttpServer` factory: translations are not `Send`,
+/// so each worker loads its own copy.
+pub fn configure(
+    cfg: &mut web::ServiceConfig,
+    api_config: &ApiConfig,
+    library: &SharedLibraryState,
+) {
+    if api_config.profiles.is_empty() {
+        configure_site(cfg, api_config, library, None);
+    }
+    for (slug, profile_config) in &api_config.profiles {
+        configure_site(cfg, profile_config, library, Some(slug));
+    }
+}
+
+fn configure_site(
+    cfg: &mut web::ServiceConfig,
+    api_config: &ApiConfig,
+    library: &SharedLibraryState,
+    profile: Option<&str>,
+) {
+    let translations = Translations::load(&api_config.language)
+        .or_else(|_| Translations::load("en_US"))
+        .expect("English translations must exist");
+    let site = ApiSite {
+        library: library.clone(),
+        profile: profile.map(str::to_string),
+        covers_dir: api_config.site_dir.join("assets").join("covers"),
+    };
+
+    cfg.service(
+        web::scope(&format!("{}/api/v1", site.root()))
+            .app_data(web::Data::new(api_config.time_config.clone()))
+            .app_data(web::Data::new(translations))
+            .app_data(web::Data::new(api_config.goals))
+            .app_data(web::Data::new(site))
+            .route("/items", web::get().to(list_items))
+            .route("/items/{id}", web::get().to(get_item))
+            .route("/search", web::get().to(search))
+            .route("/stats", web::get().to(get_stats))
+            .route("/stats/daily", web::get().to(get_daily_stats))
+            .route("/stats/weekly", web::get().to(get_weekly_stats))
+            .route("/calendar", web::get().to(list_calendar_months))
+            .route("/calendar/{month}", web::get().to(get_calendar_month))
+            .route("/recap", web::get().to(list_recap_years))
+            .route("/recap/{year}", web::get().to(get_recap_year)),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Response types
+// ---------------------------------------------------------------------------
+
+/// `/stats` response: the reading stats, plus goal progress for unfiltered requests.
+#[derive(Serialize)]
+struct StatsResponse {
+    #[serde(flatten)]
+    reading_stats: ReadingStats,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    goals: Vec<GoalProgress>,
+}
+
+/// `/search` response: matches in highlights, notes, review notes, subjects and descriptions.
+#[derive(Serialize)]
+struct SearchResponse<'a> {
+    query: &'a str,
+    results: Vec<SearchHit<'a>>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+fn not_found(message: impl Into<String>) -> HttpResponse {
+    HttpResponse::NotFound().json(ErrorResponse {
+        error: message.into(),
+    })
+}
+
+fn no_statistics() -> HttpResponse {
+    not_found("No statistics database configured")
+}
+
+#[derive(Serialize)]
+struct Page<T> {
+    items: Vec<T>,
+    total: usize,
+    page: usize,
+    per_page: usize,
+    total_pages: usize,
+}
+
+impl<T> Page<T> {
+    /// Slice `all` into the requested 1-based page.
+    fn paginate(all: Vec<T>, page: Option<usize>, per_page: Option<usize>) -> Self {
+        let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+        let page = page.unwrap_or(1).max(1);
+        let total = all.len();
+        let total_pages = total.div_ceil(per_page);
+        let items = all
+            .into_iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect();
+
+        Self {
+            items,
+            total,
+            page,
+            per_page,
+            total_pages,
+        }
+    }
+
+    /// Convert the items of this page, keeping its position.
+    fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            per_page: self.per_page,
+            total_pages: self.total_pages,
+        }
+    }
+}
+
+/// Compact item representation used in listings.
+#[derive(Serialize)]
+struct ItemSummary<'a> {
+    id: &'a str,
+    title: &'a str,
+    authors: &'a [String],
+    series: Option<&'a String>,
+    series_number: Option<&'a String>,
+    language: Option<&'a String>,
+    format: Option<LibraryItemFormat>,
+    content_type: ContentType,
+    status: BookStatus,
+    progress: Option<f64>,
+    rating: Option<u32>,
+    pages: Option<u32>,
+    highlight_count: usize,
+    bookmark_count: usize,
+    note_count: usize,
+    url: String,
+    cover_url: Option<String>,
+}
+
+impl<'a> ItemSummary<'a> {
+    fn new(item: &'a LibraryItem, site: &ApiSite) -> Self {
+        let root = site.root();
+        let url = match item.content_type() {
+            ContentType::Book => format!("{}/books/{}/", root, item.id),
+            ContentType::Comic => format!("{}/comics/{}/", root, item.id),
+        };
+
+        Self {
+            id: &item.id,
+            title: &item.book_info.title,
+            authors: &item.book_info.authors,
+            series: item.series(),
+            series_number: item.series_number(),
+            language: item.language(),
+            format: item.format(),
+            content_type: item.content_type(),
+            status: item.status(),
+            progress: item.progress_percentage(),
+            rating: item.rating(),
+            pages: item.doc_pages(),
+            highlight_count: item.highlight_count(),
+            bookmark_count: item.bookmark_count(),
+            note_count: item.note_count(),
+            url,
+            // Items reused from the build cache carry no cover bytes, so look at the output instead
+            cover_url: site
+                .covers_dir
+                .join(format!("{}.webp", item.id))
+                .exists()
+                .then(|| format!("{}/assets/covers/{}.webp", root, item.id)),
+        }
+    }
+}
+
+/// Full item representation including annotations and reading statistics.
+#[derive(Serialize)]
+struct ItemDetail<'a> {
+    #[serde(flatten)]
+    summary: ItemSummary<'a>,
+    description: Option<&'a String>,
+    publisher: Option<&'a String>,
+    identifiers: Vec<Identifier>,
+    subjects: &'a [String],
+    review_note: Option<&'a String>,
+    annotations: &'a [Annotation],
+    statistics: Option<ItemStatistics<'a>>,
+}
+
+#[derive(Serialize)]
+struct ItemStatistics<'a> {
+    item_stats: &'a StatBook,
+    session_stats: crate::models::BookSessionStats,
+    completions: Option<&'a crate::models::BookCompletions>,
+    timeline: crate::models::BookTimeline,
+}
+
+// ---------------------------------------------------------------------------
+// Query parameters
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct ItemsQuery {
+    content_type: Option<ContentType>,
+    status: Option<BookStatus>,
+    /// Case-insensitive substring match on title, authors and series
+    q: Option<String>,
+    author: Option<String>,
+    series: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    /// Words to find; each matches words starting with it
+    #[serde(default)]
+    q: String,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ContentTypeQuery {
+    content_type: Option<ContentType>,
+}
+
+#[derive(Deserialize)]
+struct DailyStatsQuery {
+    content_type: Option<ContentType>,
+    year: Option<i32>,
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn contains_ci(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn item_md5(item: &LibraryItem) -> Option<&String> {
+    item.koreader_metadata
+        .as_ref()
+        .and_then(|m| m.partial_md5_checksum.as_ref())
+}
+
+/// Statistics scoped to a content type (or all content when `None`).
+fn scoped_stats(stats_data: &StatisticsData, content_type: Option<ContentType>) -> StatisticsData {
+    match content_type {
+        Some(content_type) => stats_data.filtered_by_content_type(content_type),
+        None => stats_data.clone(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+async fn list_items(state: web::Data<ApiSite>, query: web::Query<ItemsQuery>) -> impl Responder {
+    let snapshot = state.load();
+    let query = query.into_inner();
+
+    let mut items: Vec<&LibraryItem> = snapshot
+        .items
+        .iter()
+        .filter(|item| {
+            query
+                .content_type
+                .is_none_or(|ct| item.content_type() == ct)
+        })
+        .filter(|item| query.status.as_ref().is_none_or(|s| item.status() == *s))
+        .filter(|item| {
+            query.author.as_deref().is_none_or(|author| {
+                item.book_info
+                    .authors
+                    .iter()
+                    .any(|a| a.eq_ignore_ascii_case(author))
+            })
+        })
+        .filter(|item| {
+            query.series.as_deref().is_none_or(|series| {
+                item.series()
+                    .is_some_and(|s| s.eq_ignore_ascii_case(series))
+            })
+        })
+        .filter(|item| {
+            query.q.as_deref().is_none_or(|q| {
+                contains_ci(&item.book_info.title, q)
+                    || item.book_info.authors.iter().any(|a| contains_ci(a, q))
+                    || item.series().is_some_and(|s| contains_ci(s, q))
+            })
+        })
+        .collect();
+
+    items.sort_by(|a, b| a.book_info.title.cmp(&b.book_info.title));
+
+    let page = Page::paginate(items, query.page, query.per_page)
+        .map(|item| ItemSummary::new(item, &state));
+    HttpResponse::Ok().json(page)
+}
+
+async fn search(state: web::Data<ApiSite>, query: web::Query<SearchQuery>) -> impl Responder {
+    let snapshot = state.load();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+    let mut results = snapshot.search_index.search(&query.q, limit);
+    for hit in &mut results {
+        hit.href.insert_str(0, &state.root());
+    }
+    HttpResponse::Ok().json(SearchResponse {
+        query: &query.q,
+        results,
+    })
+}
+
+async fn get_item(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+    translations: web::Data<Translations>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let id = path.into_inner();
+    let Some(item) = snapshot.items.iter().find(|item| item.id == id) else {
+        return not_found(format!("Item not found: {}", id));
+    };
+
+    let statistics = snapshot.stats_data.as_ref().and_then(|stats| {
+        let stat_book = item_md5(item).and_then(|md5| stats.stats_by_md5.get(md5))?;
+        Some(ItemStatistics {
+            item_stats: stat_book,
+            session_stats: stat_book.calculate_session_stats(
+                &stats.sessions,
+                &time_config,
+                &translations,
+            ),
+            completions: stat_book.completions.as_ref(),
+            timeline: TimelineCalculator::for_item(
+                item,
+                stat_book,
+                stats,
+                &time_config,
+                time_config.today_date(),
+            ),
+        })
+    });
+
+    HttpResponse::Ok().json(ItemDetail {
+        summary: ItemSummary::new(item, &state),
+        description: item.book_info.description.as_ref(),
+        publisher: item.publisher(),
+        identifiers: item.identifiers(),
+        subjects: item.subjects(),
+        review_note: item.review_note(),
+        annotations: item.annotations(),
+        statistics,
+    })
+}
+
+async fn get_stats(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+    goals: web::Data<GoalsConfig>,
+    query: web::Query<ContentTypeQuery>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let mut scoped = scoped_stats(stats_data, query.content_type);
+    let reading_stats = StatisticsCalculator::calculate_stats(&mut scoped, &time_config);
+    // Goals count all content, like on the statistics page
+    let goals = match query.content_type {
+        None => GoalCalculator::current(&goals, &scoped, &reading_stats, time_config.today_date()),
+        Some(_) => Vec::new(),
+    };
+    HttpResponse::Ok().json(StatsResponse {
+        reading_stats,
+        goals,
+    })
+}
+
+async fn get_daily_stats(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+    query: web::Query<DailyStatsQuery>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let mut scoped = scoped_stats(stats_data, query.content_type);
+    let reading_stats = StatisticsCalculator::calculate_stats(&mut scoped, &time_config);
+    let year_prefix = query.year.map(|year| format!("{}-", year));
+    let days: Vec<_> = reading_stats
+        .daily_activity
+        .into_iter()
+        .filter(|day| {
+            year_prefix
+                .as_deref()
+                .is_none_or(|prefix| day.date.starts_with(prefix))
+        })
+        .collect();
+
+    HttpResponse::Ok().json(days)
+}
+
+async fn get_weekly_stats(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+    query: web::Query<ContentTypeQuery>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let mut scoped = scoped_stats(stats_data, query.content_type);
+    let reading_stats = StatisticsCalculator::calculate_stats(&mut scoped, &time_config);
+    HttpResponse::Ok().json(reading_stats.weeks)
+}
+
+async fn list_calendar_months(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let months =
+        CalendarGenerator::generate_calendar_months(stats_data, &snapshot.items, &time_config);
+    // Newest first, like available_months.json
+    let available_months: Vec<&String> = months.keys().rev().collect();
+    HttpResponse::Ok().json(serde_json::json!({ "months": available_months }))
+}
+
+async fn get_calendar_month(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let month = path.into_inner();
+    let mut months =
+        CalendarGenerator::generate_calendar_months(stats_data, &snapshot.items, &time_config);
+    match months.remove(&month) {
+        Some(month_data) => HttpResponse::Ok().json(month_data),
+        None => not_found(format!("No reading activity in month: {}", month)),
+    }
+}
+
+async fn list_recap_years(state: web::Data<ApiSite>) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let mut years: Vec<String> = stats_data
+        .books
+        .iter()
+        .filter_map(|book| book.completions.as_ref())
+        .flat_map(|completions| &completions.entries)
+        .filter_map(|completion| completion.end_date.get(0..4).map(str::to_string))
+        .collect();
+    years.sort_by(|a, b| b.cmp(a));
+    years.dedup();
+
+    let years: Vec<i32> = years.iter().filter_map(|y| y.parse().ok()).collect();
+    HttpResponse::Ok().json(serde_json::json!({ "years": years }))
+}
+
+async fn get_recap_year(
+    state: web::Data<ApiSite>,
+    time_config: web::Data<TimeConfig>,
+    goals: web::Data<GoalsConfig>,
+    translations: web::Data<Translations>,
+    path: web::Path<i32>,
+    query: web::Query<ContentTypeQuery>,
+) -> impl Responder {
+    let snapshot = state.load();
+    let Some(stats_data) = snapshot.stats_data.as_ref() else {
+        return no_statistics();
+    };
+
+    let year = path.into_inner();
+    let (years, recap) = build_year_recap(
+        stats_data,
+        &snapshot.items,
+        year,
+        query.content_type,
+        &time_config,
+        &translations,
+    );
+
+    match recap {
+        Some((months, summary)) => {
+            let goals = match query.content_type {
+                None => {
+                    let mut data = stats_data.clone();
+                    let reading_stats =
+                        StatisticsCalculator::calculate_stats(&mut data, &time_config);
+                    GoalCalculator::for_year(
+                        &goals,
+                        &data,
+                        &reading_stats,
+                        year,
+                        time_config.today_date(),
+                    )
+                }
+                Some(_) => Vec::new(),
+            };
+            HttpResponse::Ok().json(serde_json::json!({
+                "year": year,
+                "available_years": years,
+                "summary": summary,
+                "goals": goals,
+                "months": months,
+            }))
+        }
+        None => not_found(format!("No completions in year: {}", year)),
+    }
+}
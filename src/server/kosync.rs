@@ -0,0 +1,234 @@
+//! KOReader progress sync server (the kosync protocol used by KOReader's "Progress sync" plugin).
+//!
+//! Routes are registered at the root, as KOReader expects: `/users/create`, `/users/auth`,
+//! `/syncs/progress` and `/syncs/progress/{document}`. Requests authenticate with the
+//! `x-auth-user` and `x-auth-key` headers.
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use serde::Deserialize;
+use std::sync::Mutex;
+
+use crate::koreader::{KosyncStore, SyncedProgress};
+
+/// Shared state of the sync routes.
+pub struct KosyncState {
+    store: Mutex<KosyncStore>,
+    /// Whether devices may register new accounts
+    open_registration: bool,
+}
+
+impl KosyncState {
+    pub fn new(store: KosyncStore, open_registration: bool) -> Self {
+        Self {
+            store: Mutex::new(store),
+            open_registration,
+        }
+    }
+}
+
+/// Register the sync routes.
+pub fn configure(cfg: &mut web::ServiceConfig, state: web::Data<KosyncState>) {
+    cfg.app_data(state)
+        .route("/healthcheck", web::get().to(healthcheck))
+        .route("/users/create", web::post().to(create_user))
+        .route("/users/auth", web::get().to(auth_user))
+        .route("/syncs/progress", web::put().to(update_progress))
+        .route("/syncs/progress/{document}", web::get().to(get_progress));
+}
+
+// ---------------------------------------------------------------------------
+// Errors (codes as defined by the reference kosync server)
+// ---------------------------------------------------------------------------
+
+enum SyncError {
+    Internal,
+    Unauthorized,
+    UserExists,
+    InvalidRequest,
+    MissingDocument,
+    RegistrationDisabled,
+}
+
+impl SyncError {
+    fn response(self) -> HttpResponse {
+        let (status, code, message) = match self {
+            SyncError::Internal => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                2000,
+                "Unknown server error.",
+            ),
+            SyncError::Unauthorized => (StatusCode::UNAUTHORIZED, 2001, "Unauthorized"),
+            SyncError::UserExists => (
+                StatusCode::PAYMENT_REQUIRED,
+                2002,
+                "Username is already registered.",
+            ),
+            SyncError::InvalidRequest => (StatusCode::FORBIDDEN, 2003, "Invalid request"),
+            SyncError::MissingDocument => (
+                StatusCode::FORBIDDEN,
+                2004,
+                "Field 'document' not provided.",
+            ),
+            SyncError::RegistrationDisabled => (
+                StatusCode::PAYMENT_REQUIRED,
+                2005,
+                "User registration is disabled.",
+            ),
+        };
+        HttpResponse::build(status).json(serde_json::json!({
+            "code": code,
+            "message": message,
+        }))
+    }
+}
+
+impl From<anyhow::Error> for SyncError {
+    fn from(e: anyhow::Error) -> Self {
+        log::error!("Sync database error: {:#}", e);
+        SyncError::Internal
+    }
+}
+
+fn respond(result: Result<HttpResponse, SyncError>) -> HttpResponse {
+    result.unwrap_or_else(SyncError::response)
+}
+
+fn is_valid_field(value: &str) -> bool {
+    !value.is_empty() && !value.contains(':')
+}
+
+/// Check the auth headers and return the username.
+fn authorize(req: &HttpRequest, state: &KosyncState) -> Result<String, SyncError> {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let (Some(username), Some(userkey)) = (header("x-auth-user"), header("x-auth-key")) else {
+        return Err(SyncError::Unauthorized);
+    };
+
+    let store = state.store.lock().map_err(|_| SyncError::Internal)?;
+    if store.authorize(&username, &userkey)? {
+        Ok(username)
+    } else {
+        Err(SyncError::Unauthorized)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct CreateUserRequest {
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProgressRequest {
+    document: Option<String>,
+    progress: Option<serde_json::Value>,
+    percentage: Option<f64>,
+    device: Option<String>,
+    device_id: Option<String>,
+}
+
+async fn healthcheck() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({ "state": "OK" }))
+}
+
+async fn create_user(
+    state: web::Data<KosyncState>,
+    body: web::Json<CreateUserRequest>,
+) -> impl Responder {
+    respond((|| {
+        if !state.open_registration {
+            return Err(SyncError::RegistrationDisabled);
+        }
+        let body = body.into_inner();
+        let (Some(username), Some(password)) = (body.username, body.password) else {
+            return Err(SyncError::InvalidRequest);
+        };
+        if !is_valid_field(&username) || password.is_empty() {
+            return Err(SyncError::InvalidRequest);
+        }
+
+        let store = state.store.lock().map_err(|_| SyncError::Internal)?;
+        if !store.create_user(&username, &password)? {
+            return Err(SyncError::UserExists);
+        }
+        log::info!("Registered sync user {:?}", username);
+        Ok(HttpResponse::Created().json(serde_json::json!({ "username": username })))
+    })())
+}
+
+async fn auth_user(req: HttpRequest, state: web::Data<KosyncState>) -> impl Responder {
+    respond(
+        authorize(&req, &state)
+            .map(|_| HttpResponse::Ok().json(serde_json::json!({ "authorized": "OK" }))),
+    )
+}
+
+async fn update_progress(
+    req: HttpRequest,
+    state: web::Data<KosyncState>,
+    body: web::Json<ProgressRequest>,
+) -> impl Responder {
+    respond((|| {
+        let username = authorize(&req, &state)?;
+        let body = body.into_inner();
+        let document = body
+            .document
+            .filter(|d| is_valid_field(d))
+            .ok_or(SyncError::MissingDocument)?;
+        // KOReader sends page numbers as numbers and xpointers as strings
+        let progress = match body.progress {
+            Some(serde_json::Value::String(s)) => s,
+            Some(serde_json::Value::Number(n)) => n.to_string(),
+            _ => return Err(SyncError::InvalidRequest),
+        };
+        let (Some(percentage), Some(device)) = (body.percentage, body.device) else {
+            return Err(SyncError::InvalidRequest);
+        };
+
+        let progress = SyncedProgress {
+            document,
+            progress,
+            percentage,
+            device,
+            device_id: body.device_id.unwrap_or_default(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        let store = state.store.lock().map_err(|_| SyncError::Internal)?;
+        store.update_progress(&username, &progress)?;
+        Ok(HttpResponse::Ok().json(serde_json::json!({
+            "document": progress.document,
+            "timestamp": progress.timestamp,
+        })))
+    })())
+}
+
+async fn get_progress(
+    req: HttpRequest,
+    state: web::Data<KosyncState>,
+    document: web::Path<String>,
+) -> impl Responder {
+    respond((|| {
+        let username = authorize(&req, &state)?;
+        let document = document.into_inner();
+        if !is_valid_field(&document) {
+            return Err(SyncError::MissingDocument);
+        }
+
+        let store = state.store.lock().map_err(|_| SyncError::Internal)?;
+        // Unknown documents get an empty object, which KOReader treats as "no progress yet"
+        Ok(match store.get_progress(&username, &document)? {
+            Some(progress) => HttpResponse::Ok().json(progress),
+            None => HttpResponse::Ok().json(serde_json::json!({})),
+        })
+    })())
+}
@@ -1,7 +1,12 @@
-//! Web server and version checking.
+//! Web server, OPDS catalog, KOReader progress sync and version checking.
 
+pub mod api;
+pub mod kosync;
+pub mod opds;
 pub mod version;
 pub mod web;
 
+pub use api::ApiConfig;
+pub use kosync::KosyncState;
 pub use version::create_version_notifier;
 pub use web::WebServer;
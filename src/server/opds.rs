@@ -0,0 +1,162 @@
+//! OPDS catalog routes: the generated feeds with OPDS content types, and OpenSearch results.
+
+use actix_web::{HttpResponse, Responder, web};
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+
+use crate::i18n::Translations;
+use crate::library::SharedLibraryState;
+use crate::opds::{
+    OPDS_JSON_TYPE, OPDS_ROOT, OPENSEARCH_TYPE, OpdsFeed, OpdsFeedKind, OpdsLinks, OpdsSite, search,
+};
+use crate::server::ApiConfig;
+
+/// Content type of the Atom feeds; the `kind` parameter is optional.
+const ATOM_TYPE: &str = "application/atom+xml;profile=opds-catalog";
+
+struct OpdsContext {
+    links: OpdsLinks,
+    /// Directory holding the generated `opds/` feeds
+    output_dir: PathBuf,
+}
+
+/// Register the `/opds` routes.
+///
+/// Must be called from inside the `HttpServer` factory (translations are not `Send`).
+pub fn configure(
+    cfg: &mut web::ServiceConfig,
+    api_config: &ApiConfig,
+    output_dir: &Path,
+    library_path: &Path,
+) {
+    let translations = Translations::load(&api_config.language)
+        .or_else(|_| Translations::load("en_US"))
+        .expect("English translations must exist");
+    let context = OpdsContext {
+        links: OpdsLinks {
+            raw_root: Some(library_path.to_path_buf()),
+            sites: opds_sites(api_config),
+        },
+        output_dir: output_dir.to_path_buf(),
+    };
+
+    cfg.service(
+        web::scope(OPDS_ROOT)
+            .app_data(web::Data::new(context))
+            .app_data(web::Data::new(translations))
+            .route("/search.xml", web::get().to(search_atom))
+            .route("/search.json", web::get().to(search_json))
+            .route("/{path:.*}", web::get().to(feed_file)),
+    );
+}
+
+/// Sites the catalog entries link to: the reader profile sites sharing the catalog, or the
+/// root site.
+fn opds_sites(api_config: &ApiConfig) -> Vec<OpdsSite> {
+    if api_config.profiles.is_empty() {
+        return vec![OpdsSite {
+            root: String::new(),
+            dir: api_config.site_dir.clone(),
+        }];
+    }
+    api_config
+        .profiles
+        .iter()
+        .map(|(slug, profile_config)| OpdsSite {
+            root: format!("/u/{}", slug),
+            dir: profile_config.site_dir.clone(),
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    /// OpenSearch `searchTerms` (Atom clients)
+    q: Option<String>,
+    /// OPDS 2.0 templated `query` parameter
+    query: Option<String>,
+}
+
+impl SearchQuery {
+    fn terms(&self) -> &str {
+        self.q.as_deref().or(self.query.as_deref()).unwrap_or("")
+    }
+}
+
+fn search_feed(
+    state: &SharedLibraryState,
+    context: &OpdsContext,
+    translations: &Translations,
+    query: &SearchQuery,
+) -> OpdsFeed {
+    let snapshot = state.load();
+    let terms = query.terms().trim();
+    let mut results = search(&snapshot.items, terms);
+    results.sort_by_key(|item| item.book_info.title.to_lowercase());
+
+    OpdsFeed {
+        href: format!("{}/search", OPDS_ROOT),
+        title: format!("{}: {}", translations.get("opds.search"), terms),
+        updated: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        kind: OpdsFeedKind::Acquisition,
+        navigation: Vec::new(),
+        entries: results
+            .into_iter()
+            .map(|item| context.links.entry(item))
+            .collect(),
+        searchable: true,
+    }
+}
+
+async fn search_atom(
+    state: web::Data<SharedLibraryState>,
+    context: web::Data<OpdsContext>,
+    translations: web::Data<Translations>,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    let feed = search_feed(&state, &context, &translations, &query);
+    match feed.to_atom() {
+        Ok(atom) => HttpResponse::Ok().content_type(ATOM_TYPE).body(atom),
+        Err(e) => {
+            log::error!("Failed to render OPDS search feed: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+async fn search_json(
+    state: web::Data<SharedLibraryState>,
+    context: web::Data<OpdsContext>,
+    translations: web::Data<Translations>,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    let feed = search_feed(&state, &context, &translations, &query);
+    HttpResponse::Ok()
+        .content_type(OPDS_JSON_TYPE)
+        .body(feed.to_json().to_string())
+}
+
+/// Serve a generated feed with its OPDS content type (static hosting falls back to the
+/// generic XML and JSON types).
+async fn feed_file(context: web::Data<OpdsContext>, path: web::Path<String>) -> impl Responder {
+    let path = path.into_inner();
+    let relative = Path::new(if path.is_empty() { "index.xml" } else { &path });
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return HttpResponse::NotFound().finish();
+    }
+
+    let content_type = match relative.extension().and_then(|e| e.to_str()) {
+        Some("xml") if relative == Path::new("opensearch.xml") => OPENSEARCH_TYPE,
+        Some("xml") => ATOM_TYPE,
+        Some("json") => OPDS_JSON_TYPE,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    match tokio::fs::read(context.output_dir.join("opds").join(relative)).await {
+        Ok(bytes) => HttpResponse::Ok().content_type(content_type).body(bytes),
+        Err(_) => HttpResponse::NotFound().finish(),
+    }
+}
@@ -1,28 +1,38 @@
 //! Shared state for version update notifications between file watcher and web server.
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::sync::broadcast;
 
 /// Shared state for notifying clients when the site has been rebuilt.
-#[derive(Clone)]
 pub struct VersionNotifier {
     /// Broadcast channel sender - sends the new version string when site is rebuilt
     sender: broadcast::Sender<String>,
+    /// Most recently published version, sent to clients as soon as they connect
+    latest: RwLock<Option<String>>,
 }
 
 impl VersionNotifier {
     /// Create a new version notifier.
     pub fn new() -> Self {
         let (sender, _) = broadcast::channel(16);
-        Self { sender }
+        Self {
+            sender,
+            latest: RwLock::new(None),
+        }
     }
 
     /// Notify all waiting clients that a new version is available.
     pub fn notify(&self, version: String) {
+        *self.latest.write().unwrap() = Some(version.clone());
         // Ignore errors (no receivers is OK)
         let _ = self.sender.send(version);
     }
 
+    /// The most recently published version, if any.
+    pub fn latest(&self) -> Option<String> {
+        self.latest.read().unwrap().clone()
+    }
+
     /// Subscribe to version update notifications.
     pub fn subscribe(&self) -> broadcast::Receiver<String> {
         self.sender.subscribe()
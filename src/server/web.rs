@@ -1,19 +1,37 @@
 use actix_cors::Cors;
 use actix_files as fs;
-use actix_web::{web, App, HttpServer, middleware, HttpResponse, Responder};
+use actix_web::web::Bytes;
+use actix_web::{App, HttpResponse, HttpServer, Responder, middleware, web};
+use anyhow::Result;
 use std::path::PathBuf;
 use std::sync::Arc;
-use anyhow::Result;
-
-use crate::models::LibraryItem;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::library::SharedLibraryState;
+use crate::models::BookStatus;
+use crate::server::api::{self, ApiConfig};
+use crate::server::kosync::{self, KosyncState};
+use crate::server::opds;
 use crate::server::version::VersionNotifier;
 
+/// Interval between SSE keep-alive comments (keeps proxies from closing idle connections)
+const SSE_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Format a version update as an SSE `version` event.
+fn sse_version_event(version: &str) -> Bytes {
+    Bytes::from(format!("event: version\ndata: {}\n\n", version))
+}
+
 pub struct WebServer {
     output_dir: PathBuf,
     port: u16,
     version_notifier: Arc<VersionNotifier>,
-    library_items: Arc<Vec<LibraryItem>>,
+    library_state: SharedLibraryState,
     library_path: PathBuf,
+    api_config: ApiConfig,
+    /// KOReader progress sync routes, if enabled
+    kosync: Option<web::Data<KosyncState>>,
 }
 
 impl WebServer {
@@ -21,15 +39,19 @@ impl WebServer {
         output_dir: PathBuf,
         port: u16,
         version_notifier: Arc<VersionNotifier>,
-        library_items: Arc<Vec<LibraryItem>>,
+        library_state: SharedLibraryState,
         library_path: PathBuf,
+        api_config: ApiConfig,
+        kosync: Option<KosyncState>,
     ) -> Self {
         Self {
             output_dir,
             port,
             version_notifier,
-            library_items,
+            library_state,
             library_path,
+            api_config,
+            kosync: kosync.map(web::Data::new),
         }
     }
 
@@ -37,40 +59,79 @@ impl WebServer {
         cfg.service(
             web::scope("/api")
                 .route("/stats", web::get().to(Self::get_stats_handler))
-                .route("/events/version", web::get().to(Self::version_events_handler))
+                .route(
+                    "/events/version",
+                    web::get().to(Self::version_events_handler),
+                ),
         );
     }
 
-    async fn version_events_handler() -> impl Responder {
+    /// Server-Sent Events stream of site versions.
+    ///
+    /// Sends the current version on connect, a new `version` event after every rebuild,
+    /// and a keep-alive comment whenever the stream has been idle.
+    async fn version_events_handler(notifier: web::Data<Arc<VersionNotifier>>) -> impl Responder {
+        let receiver = notifier.subscribe();
+        let initial = notifier.latest();
+        let keep_alive = tokio::time::interval_at(
+            tokio::time::Instant::now() + SSE_KEEP_ALIVE_INTERVAL,
+            SSE_KEEP_ALIVE_INTERVAL,
+        );
+
+        let stream = futures::stream::unfold(
+            (receiver, keep_alive, initial),
+            |(mut receiver, mut keep_alive, initial)| async move {
+                if let Some(version) = initial {
+                    let event = sse_version_event(&version);
+                    return Some((
+                        Ok::<_, actix_web::Error>(event),
+                        (receiver, keep_alive, None),
+                    ));
+                }
+
+                loop {
+                    tokio::select! {
+                        result = receiver.recv() => match result {
+                            Ok(version) => {
+                                keep_alive.reset();
+                                let event = sse_version_event(&version);
+                                return Some((Ok(event), (receiver, keep_alive, None)));
+                            }
+                            // Missed some rebuilds; the next recv yields the newest ones
+                            Err(RecvError::Lagged(_)) => continue,
+                            Err(RecvError::Closed) => return None,
+                        },
+                        _ = keep_alive.tick() => {
+                            let comment = Bytes::from_static(b": keep-alive\n\n");
+                            return Some((Ok(comment), (receiver, keep_alive, None)));
+                        }
+                    }
+                }
+            },
+        );
+
         HttpResponse::Ok()
             .insert_header(("Content-Type", "text/event-stream"))
             .insert_header(("Cache-Control", "no-cache"))
-            .body("data: {\"version\": \"1.0.0\"}\n\n")
+            // Disable response buffering in nginx so events are delivered immediately
+            .insert_header(("X-Accel-Buffering", "no"))
+            .streaming(stream)
     }
 
-    async fn get_stats_handler(library: web::Data<Arc<Vec<LibraryItem>>>) -> impl Responder {
+    async fn get_stats_handler(state: web::Data<SharedLibraryState>) -> impl Responder {
+        let snapshot = state.load();
+        let library = &snapshot.items;
         let mut read = 0;
         let mut reading = 0;
         let mut paused = 0;
         let mut unread = 0;
 
         for item in library.iter() {
-            // Pegamos o progresso do metadado
-            let percent = item.koreader_metadata.as_ref()
-                .and_then(|m| m.percent_finished)
-                .unwrap_or(0.0);
-
-            // Verificação de Status Abandoned/Pausado (Case-insensitive)
-            let status_debug = format!("{:?}", item.koreader_metadata).to_lowercase();
-
-            if status_debug.contains("abandoned") {
-                paused += 1;
-            } else if percent >= 1.0 {
-                read += 1;
-            } else if percent > 0.0 {
-                reading += 1;
-            } else {
-                unread += 1;
+            match item.status() {
+                BookStatus::Complete => read += 1,
+                BookStatus::Reading => reading += 1,
+                BookStatus::Abandoned => paused += 1,
+                BookStatus::Unknown => unread += 1,
             }
         }
 
@@ -85,19 +146,17 @@ impl WebServer {
 
     pub async fn run(self) -> Result<()> {
         let output_dir = self.output_dir.clone();
-        let library_items = self.library_items.clone();
+        let library_state = self.library_state.clone();
         let version_notifier = self.version_notifier.clone();
         let library_path = self.library_path.clone();
+        let api_config = self.api_config.clone();
+        let kosync_state = self.kosync.clone();
 
-        // Aguarda a sincronização inicial do Scanner
-        log::info!("Aguardando sincronização da biblioteca...");
-        let mut retry_count = 0;
-        while library_items.is_empty() && retry_count < 10 {
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            retry_count += 1;
-        }
-
-        log::info!("Iniciando servidor na porta {} (Itens: {})", self.port, library_items.len());
+        log::info!(
+            "Starting web server on port {} ({} items)",
+            self.port,
+            library_state.load().items.len()
+        );
 
         HttpServer::new(move || {
             // Logger configurado para silenciar os ruídos do frontend e da versão
@@ -110,9 +169,18 @@ impl WebServer {
             App::new()
                 .wrap(logger)
                 .wrap(Cors::permissive())
-                .app_data(web::Data::new(library_items.clone()))
+                .app_data(web::Data::new(library_state.clone()))
                 .app_data(web::Data::new(version_notifier.clone()))
+                // `/api/v1` must be registered before the `/api` scope, which would shadow it, and
+                // the profile APIs before the static files
+                .configure(|cfg| api::configure(cfg, &api_config, &library_state))
                 .configure(Self::configure_api)
+                .configure(|cfg| opds::configure(cfg, &api_config, &output_dir, &library_path))
+                .configure(|cfg| {
+                    if let Some(state) = &kosync_state {
+                        kosync::configure(cfg, state.clone());
+                    }
+                })
                 // A correção da rota (shadowing) está aqui:
                 .service(fs::Files::new("/raw", library_path.clone()).show_files_listing())
                 .service(fs::Files::new("/settings", library_path.clone()))
@@ -36,6 +36,8 @@ pub struct ShareImageData {
     pub active_days_percentage: u8,
     pub longest_streak: u32,
     pub best_month: Option<String>,
+    /// Yearly books goal, shown next to the books read (optional)
+    pub books_goal: Option<u32>,
 }
 
 /// Available share image formats
@@ -166,10 +168,14 @@ fn fill_template(data: &ShareImageData, format: ShareFormat) -> String {
     let template = format.template();
     let reading_time = format_reading_time(data.reading_time_hours, data.reading_time_days);
     let best_month = data.best_month.as_deref().unwrap_or("-");
+    let books = match data.books_goal {
+        Some(goal) => format!("{}/{}", data.books_read, goal),
+        None => data.books_read.to_string(),
+    };
 
     template
         .replace("{{YEAR}}", &data.year.to_string())
-        .replace("{{BOOKS}}", &data.books_read.to_string())
+        .replace("{{BOOKS}}", &books)
         .replace("{{READING_TIME}}", &reading_time)
         .replace("{{ACTIVE_DAYS}}", &data.active_days.to_string())
         .replace("{{ACTIVE_PCT}}", &data.active_days_percentage.to_string())
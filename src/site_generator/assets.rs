@@ -8,7 +8,7 @@ use indicatif::{ProgressBar, ProgressStyle};
 use log::info;
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Instant, SystemTime};
 
 impl SiteGenerator {
@@ -122,6 +122,8 @@ impl SiteGenerator {
 
         // PWA manifest
         let manifest_content = include_str!("../../assets/manifest.json");
+        // Its URLs are absolute, so they move below the site root along with the site
+        let manifest_content = manifest_content.replace("\"/", &format!("\"{}/", self.site_root));
         self.write_registered_bytes(
             self.output_dir.join("manifest.json"),
             manifest_content.as_bytes(),
@@ -130,9 +132,9 @@ impl SiteGenerator {
         let sw_content = include_str!(concat!(env!("OUT_DIR"), "/service-worker.js"));
         fs::write(self.output_dir.join("service-worker.js"), sw_content)?;
 
-        // Generate version.txt for lightweight polling (plain timestamp, ~25 bytes)
-        let version = chrono::Local::now().to_rfc3339();
-        fs::write(self.output_dir.join("version.txt"), &version)?;
+        // Generate version.txt for lightweight polling (plain timestamp, ~25 bytes).
+        // Uses the manifest version so it matches what the server pushes over SSE.
+        fs::write(self.output_dir.join("version.txt"), self.version())?;
 
         // PWA icons
         let icon_192 = include_bytes!("../../assets/icons/icon-192.png");
@@ -156,9 +158,10 @@ impl SiteGenerator {
         let (progress_tx, progress_rx) = std::sync::mpsc::channel::<()>();
 
         for book in items {
-            if let Some(ref cover_data) = book.book_info.cover_data {
+            if let Some(ref cover_data) = book.book_info.cover_data
+                && let Some(file_path) = book.file_path().map(Path::to_path_buf)
+            {
                 let cover_path = self.covers_dir().join(format!("{}.webp", book.id));
-                let file_path = book.file_path.clone();
                 let cover_data = cover_data.clone();
 
                 // Track all covers for manifest registration
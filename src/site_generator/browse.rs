@@ -0,0 +1,141 @@
+//! Author, series, subject and language pages below `/authors/`, `/series/`, `/subjects/`
+//! and `/languages/`.
+
+use super::SiteGenerator;
+use super::build_cache::PageTracker;
+use super::utils::UiContext;
+use crate::models::{BrowseGroup, BrowseKind};
+use crate::templates::{BrowseGroupTemplate, BrowseIndexTemplate};
+use anyhow::Result;
+use askama::Template;
+use log::{info, warn};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+impl SiteGenerator {
+    /// Write an index page per kind and a page per author, series, subject and language, and
+    /// remove the pages of groups that no longer exist.
+    pub(crate) fn generate_browse_pages(
+        &self,
+        groups: &[BrowseGroup],
+        ui: &UiContext,
+        pages: &PageTracker,
+    ) -> Result<()> {
+        let kinds: Vec<BrowseKind> = BrowseKind::ALL
+            .into_iter()
+            .filter(|kind| groups.iter().any(|group| group.kind == *kind))
+            .collect();
+
+        for kind in BrowseKind::ALL {
+            let kind_dir = self.output_dir.join(kind.path());
+            let kind_groups: Vec<&BrowseGroup> =
+                groups.iter().filter(|group| group.kind == kind).collect();
+            self.cleanup_stale_browse_dirs(&kind_dir, &kind_groups)?;
+            if kind_groups.is_empty() {
+                continue;
+            }
+
+            info!("Generating {} pages...", kind.path());
+            fs::create_dir_all(&kind_dir)?;
+
+            let mut index_inputs: Vec<String> =
+                kinds.iter().map(|k| k.path().to_string()).collect();
+            index_inputs.extend(kind_groups.iter().map(|group| {
+                format!(
+                    "{}:{}:{}:{}:{}:{}",
+                    group.slug,
+                    group.name,
+                    group.entries.first().map_or("", |e| e.item.id.as_str()),
+                    group.entries.len(),
+                    group.read_time,
+                    group.completions,
+                )
+            }));
+            let index_path = kind_dir.join("index.html");
+            if pages.needs_render(&format!("{}/index", kind.path()), index_inputs, &index_path) {
+                let template = BrowseIndexTemplate {
+                    site_title: self.site_title.clone(),
+                    site_root: self.site_root.clone(),
+                    kind,
+                    kinds: kinds.clone(),
+                    groups: kind_groups.iter().map(|group| (*group).clone()).collect(),
+                    version: self.get_version(),
+                    last_updated: self.get_last_updated(),
+                    navbar_items: self.create_navbar_items_with_recap(
+                        "browse",
+                        ui.recap_latest_href.as_deref(),
+                        ui.nav,
+                    ),
+                    translation: self.t(),
+                };
+                self.write_minify_html(index_path, &template.render()?)?;
+            }
+
+            for group in kind_groups {
+                let group_dir = kind_dir.join(&group.slug);
+                let mut inputs = vec![group.name.clone()];
+                inputs.extend(group.entries.iter().map(|entry| {
+                    format!(
+                        "{}:{}:{}",
+                        pages.item_key(&entry.item),
+                        entry.read_time,
+                        entry.completions
+                    )
+                }));
+                let page = format!("{}/{}", kind.path(), group.slug);
+                if !pages.needs_render(&page, inputs, &group_dir.join("index.html")) {
+                    continue;
+                }
+
+                let template = BrowseGroupTemplate {
+                    site_title: self.site_title.clone(),
+                    site_root: self.site_root.clone(),
+                    group: group.clone(),
+                    version: self.get_version(),
+                    last_updated: self.get_last_updated(),
+                    navbar_items: self.create_navbar_items_with_recap(
+                        "browse",
+                        ui.recap_latest_href.as_deref(),
+                        ui.nav,
+                    ),
+                    translation: self.t(),
+                };
+                fs::create_dir_all(&group_dir)?;
+                self.write_minify_html(group_dir.join("index.html"), &template.render()?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the pages of authors, series or subjects not in `groups` (all of them when
+    /// the library has none of this kind).
+    fn cleanup_stale_browse_dirs(&self, kind_dir: &Path, groups: &[&BrowseGroup]) -> Result<()> {
+        if !kind_dir.exists() {
+            return Ok(());
+        }
+        if groups.is_empty() {
+            info!("Removing stale directory: {:?}", kind_dir);
+            if let Err(e) = fs::remove_dir_all(kind_dir) {
+                warn!("Failed to remove stale directory {:?}: {}", kind_dir, e);
+            }
+            return Ok(());
+        }
+
+        let current: HashSet<&str> = groups.iter().map(|group| group.slug.as_str()).collect();
+        for entry in fs::read_dir(kind_dir)?.flatten() {
+            let path = entry.path();
+            if path.is_dir()
+                && let Some(name) = path.file_name().and_then(|n| n.to_str())
+                && !current.contains(name)
+            {
+                info!("Removing stale directory: {:?}", path);
+                if let Err(e) = fs::remove_dir_all(&path) {
+                    warn!("Failed to remove stale directory {:?}: {}", path, e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! Persistent build cache for incremental site generation.
+//!
+//! Stored as `.build-cache.json` next to `cache-manifest.json`. It keeps the parsed library
+//! items keyed by file fingerprint (so unchanged books are not reparsed) and a digest of the
+//! inputs of every rendered page (so pages whose inputs did not change are not rendered again).
+
+use crate::library::ScanCache;
+use crate::models::{LibraryItem, StatisticsData};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Bump when the cache layout or the meaning of page digests changes.
+const BUILD_CACHE_FORMAT: u32 = 3;
+
+pub(crate) const BUILD_CACHE_FILE: &str = ".build-cache.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct BuildCache {
+    format: u32,
+    /// KoShelf version that wrote the cache; templates may differ between versions
+    generator_version: String,
+    /// Parsed library items keyed by book file path
+    pub items: ScanCache,
+    /// Input digest of every rendered page, keyed by page id (e.g. `books/<id>`)
+    pub pages: HashMap<String, String>,
+}
+
+impl BuildCache {
+    pub fn new() -> Self {
+        Self {
+            format: BUILD_CACHE_FORMAT,
+            generator_version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Load the cache from the output directory. A missing, unreadable or outdated cache
+    /// yields an empty one, which makes the next build a full rebuild.
+    pub fn load(output_dir: &Path) -> Self {
+        let path = output_dir.join(BUILD_CACHE_FILE);
+        let Ok(content) = fs::read(&path) else {
+            return Self::new();
+        };
+
+        match serde_json::from_slice::<Self>(&content) {
+            Ok(cache)
+                if cache.format == BUILD_CACHE_FORMAT
+                    && cache.generator_version == env!("CARGO_PKG_VERSION") =>
+            {
+                cache
+            }
+            Ok(_) => {
+                debug!("Build cache was written by another version, rebuilding everything");
+                Self::new()
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable build cache {:?}: {}", path, e);
+                Self::new()
+            }
+        }
+    }
+
+    pub fn save(&self, output_dir: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_vec(self)?;
+        fs::write(output_dir.join(BUILD_CACHE_FILE), json)?;
+        Ok(())
+    }
+}
+
+/// Decides which pages of the current build have to be rendered, and records the
+/// input digests for the next build.
+pub(crate) struct PageTracker {
+    previous: HashMap<String, String>,
+    current: RefCell<HashMap<String, String>>,
+    /// Digest of the site-wide inputs every page depends on (configuration, navigation)
+    site_digest: String,
+    /// Fingerprint of every scanned item, keyed by book file path
+    item_fingerprints: HashMap<PathBuf, String>,
+    skipped: Cell<usize>,
+}
+
+impl PageTracker {
+    pub fn new(previous: HashMap<String, String>, site_digest: String, items: &ScanCache) -> Self {
+        let item_fingerprints = items
+            .iter()
+            .map(|(path, cached)| (path.clone(), format!("{:?}", cached.fingerprint)))
+            .collect();
+        Self {
+            previous,
+            current: RefCell::default(),
+            site_digest,
+            item_fingerprints,
+            skipped: Cell::new(0),
+        }
+    }
+
+    /// Identity and file fingerprint of an item, for use as a page input.
+    ///
+    /// Progress is included because synced progress can change without the files changing.
+    pub fn item_key(&self, item: &LibraryItem) -> String {
+        let fingerprint = item
+            .file_path()
+            .and_then(|path| self.item_fingerprints.get(path))
+            .map(String::as_str)
+            .unwrap_or_default();
+        format!(
+            "{}:{}:{:?}:{:?}",
+            item.id,
+            fingerprint,
+            item.progress_percentage(),
+            item.status()
+        )
+    }
+
+    /// Returns false if `page` was rendered from the same inputs by the previous build and
+    /// its output file is still there.
+    pub fn needs_render<I, S>(&self, page: &str, inputs: I, output: &Path) -> bool
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let digest = digest(
+            std::iter::once(self.site_digest.clone())
+                .chain(inputs.into_iter().map(|s| s.as_ref().to_string())),
+        );
+        let fresh = self.previous.get(page) == Some(&digest) && output.exists();
+        self.current.borrow_mut().insert(page.to_string(), digest);
+        if fresh {
+            self.skipped.set(self.skipped.get() + 1);
+        }
+        !fresh
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped.get()
+    }
+
+    pub fn into_pages(self) -> HashMap<String, String> {
+        self.current.into_inner()
+    }
+}
+
+/// Digest of a list of page inputs.
+pub(crate) fn digest<I, S>(parts: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut context = md5::Context::new();
+    for part in parts {
+        context.consume(part.as_ref().as_bytes());
+        // Separator so that ("ab", "c") and ("a", "bc") differ
+        context.consume([0u8]);
+    }
+    format!("{:x}", context.finalize())
+}
+
+/// Per-book digest of the reading statistics shown on detail pages, keyed by statistics
+/// book id. Covers everything that feeds the session statistics of a book.
+pub(crate) fn page_stat_digests(stats: &StatisticsData) -> HashMap<i64, String> {
+    let mut totals: HashMap<i64, (usize, i64, i64, i64)> = HashMap::new();
+    for ps in &stats.page_stats {
+        let entry = totals.entry(ps.id_book).or_default();
+        entry.0 += 1;
+        entry.1 += ps.duration;
+        entry.2 = entry.2.max(ps.start_time);
+        entry.3 += ps.page;
+    }
+    totals
+        .into_iter()
+        .map(|(id, (count, duration, last_start, pages))| {
+            (id, format!("{count}:{duration}:{last_start}:{pages}"))
+        })
+        .collect()
+}
@@ -4,7 +4,7 @@
 //! allowing the service worker to only re-cache files that have actually changed.
 
 use anyhow::Result;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
@@ -12,7 +12,7 @@ use std::path::Path;
 use std::sync::Mutex;
 
 /// Cache manifest containing version and file hashes.
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct CacheManifest {
     /// Build timestamp/version
     pub version: String,
@@ -35,6 +35,35 @@ impl CacheManifestBuilder {
         }
     }
 
+    /// The version this manifest is built for.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Seed the manifest with the entries of a previous build, so files kept from that build
+    /// (instead of being written again) stay listed. A missing or invalid manifest is ignored.
+    pub fn seed_from<P: AsRef<Path>>(&self, manifest_path: P) {
+        if let Ok(content) = fs::read(manifest_path)
+            && let Ok(previous) = serde_json::from_slice::<CacheManifest>(&content)
+        {
+            self.files.lock().unwrap().extend(previous.files);
+        }
+    }
+
+    /// Drop entries whose file no longer exists in the output directory.
+    pub fn retain_existing<P: AsRef<Path>>(&self, output_dir: P) {
+        let output_dir = output_dir.as_ref();
+        self.files.lock().unwrap().retain(|url_path, _| {
+            let relative = url_path.trim_start_matches('/');
+            let file_path = if relative.is_empty() || relative.ends_with('/') {
+                output_dir.join(relative).join("index.html")
+            } else {
+                output_dir.join(relative)
+            };
+            file_path.exists()
+        });
+    }
+
     /// Compute a short hash of the content (first 8 chars of hex-encoded MD5).
     pub fn compute_hash(content: &[u8]) -> String {
         let digest = md5::compute(content);
@@ -21,9 +21,23 @@ impl SiteGenerator {
         info!("Generating calendar page...");
 
         // Generate per-month calendar payloads (events + books + stats)
-        let calendar_months =
+        let mut calendar_months =
             CalendarGenerator::generate_calendar_months(stats_data, books, &self.time_config);
 
+        // Item links are generated for a site at the web root
+        if !self.site_root.is_empty() {
+            for month in calendar_months.values_mut() {
+                for item in month.books.values_mut() {
+                    for path in [&mut item.item_path, &mut item.item_cover]
+                        .into_iter()
+                        .flatten()
+                    {
+                        *path = self.url(path);
+                    }
+                }
+            }
+        }
+
         // ------------------------------------------------------------------
         // Write JSON files --------------------------------------------------
         // ------------------------------------------------------------------
@@ -45,6 +59,7 @@ impl SiteGenerator {
         // Create the template
         let template = CalendarTemplate {
             site_title: self.site_title.clone(),
+            site_root: self.site_root.clone(),
             show_type_filter: ui.nav.show_type_filter(),
             version: self.get_version(),
             last_updated: self.get_last_updated(),
@@ -1,27 +1,31 @@
 //! Content list and detail page generation (books + comics).
 
 use super::SiteGenerator;
-use crate::koreader::BookStatistics;
+use super::build_cache::{PageTracker, page_stat_digests};
+use crate::koreader::{BookStatistics, TimelineCalculator};
 use crate::models::{BookStatus, ContentType, LibraryItem, StatisticsData};
-use crate::templates::{ItemDetailMarkdownTemplate, ItemDetailTemplate, LibraryListTemplate};
+use crate::templates::{
+    ItemDetailMarkdownTemplate, ItemDetailTemplate, LibraryListTemplate, RedirectTemplate,
+};
+use crate::utils::generate_book_id;
 use anyhow::Result;
 use askama::Template;
 use log::{info, warn};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 
 use super::utils::UiContext;
 
 #[derive(Debug, Default)]
-struct StatusBuckets {
-    reading: Vec<LibraryItem>,
-    completed: Vec<LibraryItem>,
-    abandoned: Vec<LibraryItem>,
-    unread: Vec<LibraryItem>,
+pub(super) struct StatusBuckets {
+    pub(super) reading: Vec<LibraryItem>,
+    pub(super) completed: Vec<LibraryItem>,
+    pub(super) abandoned: Vec<LibraryItem>,
+    pub(super) unread: Vec<LibraryItem>,
 }
 
 impl StatusBuckets {
-    fn from_items(items: &[LibraryItem]) -> Self {
+    pub(super) fn from_items(items: &[LibraryItem]) -> Self {
         let mut out = Self::default();
 
         for item in items {
@@ -53,6 +57,20 @@ impl StatusBuckets {
     }
 }
 
+/// Old title-slug IDs of `items` that now point to a different item ID, mapped to the
+/// first item (in scan order) that used the slug. Slugs that are a current ID are skipped.
+fn legacy_redirects(items: &[LibraryItem]) -> HashMap<String, &LibraryItem> {
+    let current_ids: HashSet<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    let mut redirects = HashMap::new();
+    for item in items {
+        let legacy_id = generate_book_id(&item.book_info.title);
+        if !legacy_id.is_empty() && !current_ids.contains(legacy_id.as_str()) {
+            redirects.entry(legacy_id).or_insert(item);
+        }
+    }
+    redirects
+}
+
 impl SiteGenerator {
     fn content_dir(&self, content_type: ContentType) -> std::path::PathBuf {
         match content_type {
@@ -81,8 +99,8 @@ impl SiteGenerator {
                 "id": b.id.clone(),
                 "title": b.book_info.title.clone(),
                 "authors": b.book_info.authors.clone(),
-                "json_path": format!("/{}/{}/details.json", slug, b.id),
-                "html_path":  format!("/{}/{}/index.html",  slug, b.id),
+                "json_path": self.url(&format!("/{}/{}/details.json", slug, b.id)),
+                "html_path":  self.url(&format!("/{}/{}/index.html",  slug, b.id)),
             })
         };
 
@@ -108,7 +126,22 @@ impl SiteGenerator {
         items: &[LibraryItem],
         render_to_root: bool,
         ui: &UiContext,
+        pages: &PageTracker,
     ) -> Result<()> {
+        let output_path = if render_to_root {
+            self.output_dir.join("index.html")
+        } else {
+            self.content_dir(content_type).join("index.html")
+        };
+
+        let mut inputs: Vec<String> = items.iter().map(|item| pages.item_key(item)).collect();
+        inputs.sort();
+        inputs.push(render_to_root.to_string());
+        let page = format!("{}/list", Self::content_slug(content_type));
+        if !pages.needs_render(&page, inputs, &output_path) {
+            return Ok(());
+        }
+
         info!(
             "Generating {} list page...",
             Self::content_slug(content_type)
@@ -119,10 +152,11 @@ impl SiteGenerator {
 
         let template = LibraryListTemplate {
             site_title: self.site_title.clone(),
-            details_base_path: match content_type {
-                ContentType::Book => "/books/".to_string(),
-                ContentType::Comic => "/comics/".to_string(),
-            },
+            site_root: self.site_root.clone(),
+            details_base_path: self.url(match content_type {
+                ContentType::Book => "/books/",
+                ContentType::Comic => "/comics/",
+            }),
             reading_books: buckets.reading,
             completed_books: buckets.completed,
             abandoned_books: buckets.abandoned,
@@ -141,12 +175,6 @@ impl SiteGenerator {
         };
 
         let html = template.render()?;
-
-        let output_path = if render_to_root {
-            self.output_dir.join("index.html")
-        } else {
-            self.content_dir(content_type).join("index.html")
-        };
         self.write_minify_html(output_path, &html)?;
         Ok(())
     }
@@ -157,12 +185,18 @@ impl SiteGenerator {
         items: &[LibraryItem],
         stats_data: &mut Option<StatisticsData>,
         ui: &UiContext,
+        pages: &PageTracker,
     ) -> Result<()> {
         info!(
             "Generating {} detail pages...",
             Self::content_slug(content_type)
         );
 
+        let stat_digests = stats_data
+            .as_ref()
+            .map(page_stat_digests)
+            .unwrap_or_default();
+
         for item in items {
             // Try to find matching statistics by MD5
             let item_stats = stats_data.as_ref().and_then(|stats| {
@@ -173,25 +207,63 @@ impl SiteGenerator {
                     .cloned()
             });
 
+            // Skip items whose file, metadata and reading statistics are unchanged
+            let item_dir = self.content_dir(content_type).join(&item.id);
+            let stats_key = item_stats
+                .as_ref()
+                .map(|stat| {
+                    format!(
+                        "{}:{}",
+                        serde_json::to_string(stat).unwrap_or_default(),
+                        stat_digests
+                            .get(&stat.id)
+                            .map(String::as_str)
+                            .unwrap_or_default()
+                    )
+                })
+                .unwrap_or_default();
+            // The finish estimate of books being read moves with the current date
+            let today = self.time_config.today_date();
+            let estimate_key = match (&item_stats, item.status()) {
+                (Some(_), BookStatus::Reading) => today.to_string(),
+                _ => String::new(),
+            };
+            let page = format!("{}/{}", Self::content_slug(content_type), item.id);
+            if !pages.needs_render(
+                &page,
+                [pages.item_key(item), stats_key, estimate_key],
+                &item_dir.join("index.html"),
+            ) {
+                continue;
+            }
+
             // Calculate session statistics if we have item stats
             let session_stats = match (stats_data.as_ref(), &item_stats) {
                 (Some(stats), Some(stat)) => Some(stat.calculate_session_stats(
-                    &stats.page_stats,
+                    &stats.sessions,
                     &self.time_config,
                     &self.translations,
                 )),
                 _ => None,
             };
+            let timeline = match (stats_data.as_ref(), &item_stats) {
+                (Some(stats), Some(stat)) => Some(TimelineCalculator::for_item(
+                    item,
+                    stat,
+                    stats,
+                    &self.time_config,
+                    today,
+                )),
+                _ => None,
+            };
 
             let template = ItemDetailTemplate {
                 site_title: self.site_title.clone(),
+                site_root: self.site_root.clone(),
                 book: item.clone(),
                 book_stats: item_stats.clone(),
                 session_stats: session_stats.clone(),
-                search_base_path: match content_type {
-                    ContentType::Comic if ui.nav.has_books => "/comics/".to_string(),
-                    _ => "/".to_string(),
-                },
+                timeline: timeline.clone(),
                 version: self.get_version(),
                 last_updated: self.get_last_updated(),
                 navbar_items: self.create_navbar_items_with_recap(
@@ -206,7 +278,6 @@ impl SiteGenerator {
             };
 
             let html = template.render()?;
-            let item_dir = self.content_dir(content_type).join(&item.id);
             fs::create_dir_all(&item_dir)?;
             let item_path = item_dir.join("index.html");
             self.write_minify_html(item_path, &html)?;
@@ -275,6 +346,10 @@ impl SiteGenerator {
                 "subjects".to_string(),
                 serde_json::to_value(item.subjects())?,
             );
+            item_obj.insert(
+                "stats_only".to_string(),
+                serde_json::Value::Bool(item.is_stats_only()),
+            );
             item_obj.insert(
                 "identifiers".to_string(),
                 serde_json::to_value(
@@ -327,6 +402,7 @@ impl SiteGenerator {
                     "item_stats": item_stats,
                     "session_stats": session_stats,
                     "completions": item_stats.as_ref().and_then(|s| s.completions.as_ref()),
+                    "timeline": timeline,
                 }),
             );
             root.insert(
@@ -346,6 +422,34 @@ impl SiteGenerator {
         Ok(())
     }
 
+    /// Write redirect stubs at the old title-slug URLs of items whose ID changed, so
+    /// existing bookmarks keep working.
+    fn generate_redirect_stubs(
+        &self,
+        content_type: ContentType,
+        items: &[LibraryItem],
+        pages: &PageTracker,
+    ) -> Result<()> {
+        let slug = Self::content_slug(content_type);
+        for (legacy_id, item) in legacy_redirects(items) {
+            let stub_dir = self.content_dir(content_type).join(&legacy_id);
+            let target = self.url(&format!("/{}/{}/", slug, item.id));
+            let page = format!("{}/{}", slug, legacy_id);
+            if !pages.needs_render(&page, [target.as_str()], &stub_dir.join("index.html")) {
+                continue;
+            }
+
+            let template = RedirectTemplate {
+                site_title: self.site_title.clone(),
+                target,
+                translation: self.t(),
+            };
+            fs::create_dir_all(&stub_dir)?;
+            self.write_minify_html(stub_dir.join("index.html"), &template.render()?)?;
+        }
+        Ok(())
+    }
+
     /// Clean up book directories for books that no longer exist in the library
     pub(crate) fn cleanup_stale_books(&self, books: &[LibraryItem]) -> Result<()> {
         self.cleanup_stale_content_dirs(self.books_dir(), books, "book")
@@ -403,7 +507,9 @@ impl SiteGenerator {
             return Ok(());
         }
 
-        let current_ids: HashSet<String> = items.iter().map(|b| b.id.clone()).collect();
+        // Redirect stubs for old URLs live next to the item directories
+        let mut current_ids: HashSet<String> = items.iter().map(|b| b.id.clone()).collect();
+        current_ids.extend(legacy_redirects(items).into_keys());
         let entries = fs::read_dir(&content_dir)?;
         for entry in entries.flatten() {
             let path = entry.path();
@@ -430,8 +536,9 @@ impl SiteGenerator {
         &self,
         books: &[LibraryItem],
         ui: &UiContext,
+        pages: &PageTracker,
     ) -> Result<()> {
-        self.generate_content_list(ContentType::Book, books, true, ui)
+        self.generate_content_list(ContentType::Book, books, true, ui, pages)
             .await
     }
 
@@ -440,8 +547,9 @@ impl SiteGenerator {
         comics: &[LibraryItem],
         render_to_root: bool,
         ui: &UiContext,
+        pages: &PageTracker,
     ) -> Result<()> {
-        self.generate_content_list(ContentType::Comic, comics, render_to_root, ui)
+        self.generate_content_list(ContentType::Comic, comics, render_to_root, ui, pages)
             .await
     }
 
@@ -450,9 +558,11 @@ impl SiteGenerator {
         books: &[LibraryItem],
         stats_data: &mut Option<StatisticsData>,
         ui: &UiContext,
+        pages: &PageTracker,
     ) -> Result<()> {
-        self.generate_content_pages(ContentType::Book, books, stats_data, ui)
-            .await
+        self.generate_content_pages(ContentType::Book, books, stats_data, ui, pages)
+            .await?;
+        self.generate_redirect_stubs(ContentType::Book, books, pages)
     }
 
     pub(crate) async fn generate_comic_pages(
@@ -460,8 +570,10 @@ impl SiteGenerator {
         comics: &[LibraryItem],
         stats_data: &mut Option<StatisticsData>,
         ui: &UiContext,
+        pages: &PageTracker,
     ) -> Result<()> {
-        self.generate_content_pages(ContentType::Comic, comics, stats_data, ui)
-            .await
+        self.generate_content_pages(ContentType::Comic, comics, stats_data, ui, pages)
+            .await?;
+        self.generate_redirect_stubs(ContentType::Comic, comics, pages)
     }
 }
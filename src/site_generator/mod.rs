@@ -2,32 +2,50 @@
 //!
 //! This module is split into submodules for maintainability:
 //! - `assets`: Directory creation, static assets, and cover generation
+//! - `build_cache`: Persistent build cache for incremental generation
 //! - `library_pages`: Library list and detail page generation (books + comics)
+//! - `browse`: Author, series, subject and language pages
 //! - `statistics`: Statistics page and JSON export
 //! - `calendar`: Calendar page generation
 //! - `recap`: Yearly recap page generation
 //! - `cache_manifest`: PWA cache manifest generation
+//! - `opds`: OPDS catalog feeds
+//! - `profiles`: Per-reader profile sites and their landing page
+//! - `search`: Full-text search index shards
 //! - `utils`: Utility functions (minification, navbar, version info)
 
 mod assets;
+mod browse;
+mod build_cache;
 mod cache_manifest;
 mod calendar;
 mod library_pages;
+mod opds;
+mod profiles;
 mod recap;
+mod search;
 mod statistics;
-mod utils;
+pub(crate) mod utils;
 
+pub(crate) use build_cache::{BuildCache, PageTracker};
 pub use cache_manifest::CacheManifestBuilder;
+pub(crate) use recap::build_year_recap;
 
-use crate::config::SiteConfig;
+use crate::config::{ItemIdScheme, SiteConfig};
 use crate::i18n::Translations;
-use crate::koreader::{StatisticsCalculator, StatisticsParser, calculate_partial_md5};
-use crate::library::scan_library;
-use crate::models::{BookStatus, ContentType, LibraryItem, StatisticsData};
+use crate::koreader::{KosyncStore, StatisticsCalculator, StatisticsParser, StatisticsSource};
+use crate::library::cache::FileFingerprint;
+use crate::library::{LibraryScan, LibrarySnapshot, ScanCache, scan_library};
+use crate::models::{
+    BookStatus, BrowseGroup, BrowseKind, ContentType, LibraryItem, StatisticsData,
+};
+use crate::search::SearchIndex;
+use crate::utils::unique_item_id;
 use anyhow::Result;
-use log::info;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use log::{info, warn};
+use profiles::SharedScan;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 use utils::{NavContext, UiContext};
@@ -37,9 +55,43 @@ struct GenerationContext {
     all_items: Vec<LibraryItem>,
     books: Vec<LibraryItem>,
     comics: Vec<LibraryItem>,
+    /// Books only known from the statistics databases (with `--include-all-stats`), which
+    /// get detail pages but are not listed, searchable or downloadable
+    stats_only: Vec<LibraryItem>,
     stats_data: Option<StatisticsData>,
+    /// Authors, series, subjects and languages of the listed and statistics-only items
+    browse_groups: Vec<BrowseGroup>,
     recap_latest_href: Option<String>,
     nav: NavContext,
+    scan_cache: ScanCache,
+}
+
+impl GenerationContext {
+    /// Items with a detail page: the listed items followed by the statistics-only ones.
+    fn linked_items(&self) -> Vec<LibraryItem> {
+        self.all_items
+            .iter()
+            .chain(&self.stats_only)
+            .cloned()
+            .collect()
+    }
+
+    /// Items of `content_type` with a detail page.
+    fn detail_items(&self, content_type: ContentType) -> Vec<LibraryItem> {
+        let listed = match content_type {
+            ContentType::Book => &self.books,
+            ContentType::Comic => &self.comics,
+        };
+        listed
+            .iter()
+            .chain(
+                self.stats_only
+                    .iter()
+                    .filter(|item| item.content_type() == content_type),
+            )
+            .cloned()
+            .collect()
+    }
 }
 
 pub struct SiteGenerator {
@@ -61,6 +113,11 @@ impl SiteGenerator {
     pub fn new(config: SiteConfig) -> Self {
         // Create cache manifest with current timestamp as version
         let version = chrono::Local::now().to_rfc3339();
+        Self::with_version(config, version)
+    }
+
+    /// Generator for a build with a given version (shared by the sites of reader profiles).
+    pub(crate) fn with_version(config: SiteConfig, version: String) -> Self {
         let cache_manifest = Arc::new(CacheManifestBuilder::new(version));
 
         // Load translations for the configured language
@@ -80,6 +137,11 @@ impl SiteGenerator {
         }
     }
 
+    /// Build version of this generation run (written to version.txt and cache-manifest.json)
+    pub fn version(&self) -> &str {
+        self.cache_manifest.version()
+    }
+
     /// Get Rc<Translations> for templates to call get()/get_with_num()
     pub(crate) fn t(&self) -> Rc<Translations> {
         Rc::clone(&self.translations)
@@ -125,8 +187,11 @@ impl SiteGenerator {
     pub(crate) fn calendar_json_dir(&self) -> PathBuf {
         self.json_dir().join("calendar")
     }
+    pub(crate) fn search_json_dir(&self) -> PathBuf {
+        self.json_dir().join("search")
+    }
 
-    fn recap_latest_href(stats_data: Option<&StatisticsData>) -> Option<String> {
+    fn recap_latest_href(&self, stats_data: Option<&StatisticsData>) -> Option<String> {
         let sd = stats_data?;
         let mut years: Vec<i32> = Vec::new();
         for b in &sd.books {
@@ -145,32 +210,186 @@ impl SiteGenerator {
             None
         } else {
             years.sort_by(|a, b| b.cmp(a));
-            Some(format!("/recap/{}/", years[0]))
+            Some(self.url(&format!("/recap/{}/", years[0])))
+        }
+    }
+
+    /// Digest of the configuration and navigation every rendered page depends on.
+    fn site_digest(&self, ui: &UiContext) -> String {
+        build_cache::digest([
+            self.site_title.clone(),
+            self.language.clone(),
+            format!("{:?}", self.time_config),
+            format!("{:?}", ui),
+            format!(
+                "{:?} {:?} {:?} {} {} {}",
+                self.heatmap_scale_max,
+                self.min_pages_per_day,
+                self.min_time_per_day,
+                self.include_all_stats,
+                self.is_internal_server,
+                self.session_gap
+            ),
+            format!("{:?}", self.completion),
+            format!("{:?}", self.goals),
+        ])
+    }
+
+    /// Inputs of the statistics, calendar and recap pages: the statistics databases, the
+    /// library items they link to, and today's date (for streaks and the current week).
+    fn statistics_inputs(&self, items: &[LibraryItem], pages: &PageTracker) -> Vec<String> {
+        let mut inputs = vec![self.time_config.today_date().to_string()];
+        for path in &self.statistics_db_paths {
+            inputs.push(format!(
+                "{:?}:{:?}:{:?}",
+                path,
+                FileFingerprint::of(path),
+                self.statistics_db_devices.get(path)
+            ));
+        }
+        let mut item_keys: Vec<String> = items.iter().map(|item| pages.item_key(item)).collect();
+        item_keys.sort();
+        inputs.extend(item_keys);
+        inputs
+    }
+
+    /// Whether a scanned item is part of the generated site.
+    fn is_listed(&self, item: &LibraryItem) -> bool {
+        // Always include items with KoReader metadata
+        if item.koreader_metadata.is_some() {
+            return true;
+        }
+        // For items without metadata, only include if include_unread is true
+        // (globally or for the item's library path) and the item has Unknown status
+        // (which is the case for unread items)
+        item.file_path()
+            .is_some_and(|path| self.include_unread_for(path))
+            && item.status() == BookStatus::Unknown
+    }
+
+    /// Scan the library, reusing unchanged items from `previous`.
+    ///
+    /// Items taken from the cache carry no cover bytes. If the cover of such an item is
+    /// missing (deleted, or the item ID changed), the affected items are scanned again.
+    ///
+    /// Covers already generated for another reader profile are copied instead.
+    async fn scan_library_incremental(
+        &self,
+        previous: &ScanCache,
+        shared: Option<&SharedScan>,
+    ) -> Result<LibraryScan> {
+        let scan = scan_library(
+            &self.library_paths,
+            &self.metadata_location,
+            self.id_scheme,
+            Some(previous),
+        )
+        .await?;
+
+        let covers_dir = self.covers_dir();
+        let missing_covers: Vec<_> = scan
+            .items
+            .iter()
+            .filter(|item| {
+                self.is_listed(item)
+                    && item.book_info.cover_data.is_none()
+                    && item
+                        .file_path()
+                        .and_then(|path| scan.cache.get(path))
+                        .is_some_and(|cached| cached.has_cover)
+                    && !covers_dir.join(format!("{}.webp", item.id)).exists()
+                    && shared
+                        .and_then(|shared| shared.cover(&item.id))
+                        .is_none_or(|source| self.copy_shared_cover(&source, &item.id).is_err())
+            })
+            .filter_map(|item| item.file_path().map(Path::to_path_buf))
+            .collect();
+        if missing_covers.is_empty() {
+            return Ok(scan);
         }
+
+        info!(
+            "Rescanning {} items with missing covers",
+            missing_covers.len()
+        );
+        let mut previous = previous.clone();
+        for path in &missing_covers {
+            previous.remove(path);
+        }
+        scan_library(
+            &self.library_paths,
+            &self.metadata_location,
+            self.id_scheme,
+            Some(&previous),
+        )
+        .await
     }
 
-    async fn build_generation_context(&self) -> Result<GenerationContext> {
-        // Scan all library paths for books and comics
-        // Also returns the set of MD5 hashes for all items (for statistics filtering)
-        let (all_items, library_md5s) = if !self.library_paths.is_empty() {
-            scan_library(&self.library_paths, &self.metadata_location).await?
+    /// Merge progress synced by KOReader devices into the scanned items.
+    ///
+    /// Synced progress wins when it is newer than the item's metadata file, and makes items
+    /// without metadata show up as being read.
+    fn apply_synced_progress(&self, items: &mut [LibraryItem], scan_cache: &ScanCache) {
+        let Some(path) = self.kosync_db_path.as_ref().filter(|path| path.exists()) else {
+            return;
+        };
+        let synced = match KosyncStore::read_latest_progress(path) {
+            Ok(synced) => synced,
+            Err(e) => {
+                warn!("Failed to read sync database {:?}: {:#}", path, e);
+                return;
+            }
+        };
+        if synced.is_empty() {
+            return;
+        }
+
+        let mut applied = 0;
+        for item in items.iter_mut() {
+            let cached = item.file_path().and_then(|path| scan_cache.get(path));
+            let md5 = cached.and_then(|c| c.md5.clone()).or_else(|| {
+                item.koreader_metadata
+                    .as_ref()
+                    .and_then(|m| m.partial_md5_checksum.clone())
+            });
+            let Some(progress) = md5.and_then(|md5| synced.get(&md5)) else {
+                continue;
+            };
+            let metadata_modified = cached.and_then(|c| c.fingerprint.metadata_modified);
+            if progress.apply_to(item, metadata_modified) {
+                applied += 1;
+            }
+        }
+        info!("Applied synced progress to {} items", applied);
+    }
+
+    async fn build_generation_context(
+        &self,
+        previous: &ScanCache,
+        shared: Option<&SharedScan>,
+    ) -> Result<GenerationContext> {
+        // Scan all library paths for books and comics, reusing unchanged items from the
+        // previous build. Also returns the set of MD5 hashes for all items (for statistics filtering)
+        let (all_items, library_md5s, scan_cache) = if !self.library_paths.is_empty() {
+            let scan = self.scan_library_incremental(previous, shared).await?;
+            info!(
+                "Scanned {} items ({} parsed, {} unchanged)",
+                scan.items.len(),
+                scan.parsed,
+                scan.items.len() - scan.parsed
+            );
+            (scan.items, scan.library_md5s, scan.cache)
         } else {
-            (Vec::new(), HashSet::new())
+            (Vec::new(), HashSet::new(), ScanCache::new())
         };
+        let mut all_items = all_items;
+        self.apply_synced_progress(&mut all_items, &scan_cache);
 
         // Filter items based on include_unread setting
         // Items without KoReader metadata (unread) should only be included if include_unread is true
         let all_items: Vec<_> = all_items
             .into_iter()
-            .filter(|item| {
-                // Always include items with KoReader metadata
-                if item.koreader_metadata.is_some() {
-                    return true;
-                }
-                // For items without metadata, only include if include_unread is true
-                // and the item has Unknown status (which is the case for unread items)
-                self.include_unread && item.status() == BookStatus::Unknown
-            })
+            .filter(|item| self.is_listed(item))
             .collect();
 
         // Separate books and comics
@@ -180,86 +399,169 @@ impl SiteGenerator {
         let has_books = !books.is_empty();
         let has_comics = !comics.is_empty();
 
-        // Load statistics if path is provided
-        let mut stats_data = if let Some(ref stats_path) = self.statistics_db_path {
-            if stats_path.exists() {
-                let mut data = StatisticsParser::parse(stats_path)?;
-
-                // Filter statistics if minimums are set
-                if self.min_pages_per_day.is_some() || self.min_time_per_day.is_some() {
-                    StatisticsCalculator::filter_stats(
-                        &mut data,
-                        &self.time_config,
-                        self.min_pages_per_day,
-                        self.min_time_per_day,
-                    );
+        // Load statistics if paths are provided
+        let existing_sources: Vec<_> = self
+            .statistics_db_paths
+            .iter()
+            .filter(|path| {
+                let exists = path.exists();
+                if !exists {
+                    info!("Statistics database not found: {:?}", path);
                 }
-
-                // Filter statistics to library items only (unless --include-all-stats is set)
-                // This is skipped if no library paths provided (can't filter without a library)
-                if !self.include_all_stats && !all_items.is_empty() {
-                    StatisticsCalculator::filter_to_library(&mut data, &library_md5s);
-                }
-
-                StatisticsCalculator::populate_completions(&mut data, &self.time_config);
-                Some(data)
-            } else {
-                info!("Statistics database not found: {:?}", stats_path);
-                None
-            }
-        } else {
-            None
-        };
-
-        let recap_latest_href = Self::recap_latest_href(stats_data.as_ref());
-
-        let nav = NavContext {
-            has_books,
-            has_comics,
-            stats_at_root: stats_data.is_some() && all_items.is_empty(),
-        };
-
-        // Tag statistics entries by content type using MD5 -> LibraryItem lookup
-        if let Some(ref mut sd) = stats_data {
-            let mut md5_to_content_type: HashMap<String, ContentType> = HashMap::new();
+                exists
+            })
+            .map(|path| StatisticsSource {
+                path: path.clone(),
+                device: self.statistics_db_devices.get(path).cloned(),
+            })
+            .collect();
+        // MD5 -> LibraryItem lookup for tagging statistics entries by content type and for
+        // the items KOReader marks as finished
+        let mut md5_to_content_type: HashMap<String, ContentType> = HashMap::new();
+        let mut finished_md5s: HashSet<String> = HashSet::new();
+        if !existing_sources.is_empty() {
             for item in &all_items {
-                // Prefer MD5 from KoReader metadata, but fall back to calculating partial MD5 from file.
+                // Prefer MD5 from KoReader metadata, but fall back to the partial MD5 the scan cached.
                 let md5 = item
                     .koreader_metadata
                     .as_ref()
                     .and_then(|m| m.partial_md5_checksum.as_ref())
                     .cloned()
-                    .or_else(|| calculate_partial_md5(&item.file_path).ok());
+                    .or_else(|| {
+                        item.file_path()
+                            .and_then(|path| scan_cache.get(path))
+                            .and_then(|c| c.md5.clone())
+                    });
 
                 if let Some(md5) = md5 {
+                    if item.status() == BookStatus::Complete {
+                        finished_md5s.insert(md5.clone());
+                    }
                     md5_to_content_type.insert(md5, item.content_type());
                 } else {
                     log::debug!(
                         "Could not determine MD5 for {:?}; stats content_type tagging may be incomplete",
-                        item.file_path
+                        item.file_path()
                     );
                 }
             }
-            sd.tag_content_types(&md5_to_content_type);
         }
 
+        let stats_data = if !existing_sources.is_empty() {
+            let mut data = StatisticsParser::parse_sources(&existing_sources)?;
+
+            // Filter statistics if minimums are set
+            if self.min_pages_per_day.is_some() || self.min_time_per_day.is_some() {
+                StatisticsCalculator::filter_stats(
+                    &mut data,
+                    &self.time_config,
+                    self.min_pages_per_day,
+                    self.min_time_per_day,
+                );
+            }
+
+            // Filter statistics to library items only (unless --include-all-stats is set)
+            // This is skipped if no library paths provided (can't filter without a library)
+            if !self.include_all_stats && !all_items.is_empty() {
+                StatisticsCalculator::filter_to_library(&mut data, &library_md5s);
+            }
+
+            data.tag_content_types(&md5_to_content_type);
+            StatisticsCalculator::populate_sessions(&mut data, self.session_gap);
+            StatisticsCalculator::populate_completions(
+                &mut data,
+                &self.time_config,
+                &self.completion,
+                &finished_md5s,
+            );
+            Some(data)
+        } else {
+            None
+        };
+
+        // Entries for books whose files are gone, so they can still be linked to. Without a
+        // library, statistics are shown on their own and nothing links to items.
+        let stats_only = match &stats_data {
+            Some(data) if self.include_all_stats && !all_items.is_empty() => {
+                stats_only_items(data, &all_items, &library_md5s, self.id_scheme)
+            }
+            _ => Vec::new(),
+        };
+        if !stats_only.is_empty() {
+            info!(
+                "Found {} items only in the statistics database",
+                stats_only.len()
+            );
+        }
+
+        let recap_latest_href = self.recap_latest_href(stats_data.as_ref());
+
+        let linked_items: Vec<LibraryItem> = all_items.iter().chain(&stats_only).cloned().collect();
+        let browse_groups: Vec<BrowseGroup> = BrowseKind::ALL
+            .into_iter()
+            .flat_map(|kind| BrowseGroup::collect(kind, &linked_items, stats_data.as_ref()))
+            .collect();
+
+        let nav = NavContext {
+            has_books,
+            has_comics,
+            stats_at_root: stats_data.is_some() && all_items.is_empty(),
+            browse: browse_groups.first().map(|group| group.kind),
+        };
+
         Ok(GenerationContext {
             all_items,
             books,
             comics,
+            stats_only,
             stats_data,
+            browse_groups,
             recap_latest_href,
             nav,
+            scan_cache,
         })
     }
 
-    pub async fn generate(&self) -> Result<()> {
+    /// Generate the site and return the library/statistics snapshot it was rendered from.
+    ///
+    /// With reader profiles configured, one site per profile is generated below `u/`.
+    pub async fn generate(&self) -> Result<LibrarySnapshot> {
+        if !self.profiles.is_empty() {
+            return self.generate_profiles().await;
+        }
+        let (snapshot, _) = self.generate_site(None).await?;
+        Ok(snapshot)
+    }
+
+    /// Generate a single site, reusing the library items `shared` by the sites of other
+    /// reader profiles. Also returns the scanned items, for the next profile.
+    async fn generate_site(
+        &self,
+        shared: Option<&SharedScan>,
+    ) -> Result<(LibrarySnapshot, ScanCache)> {
         info!("Generating static site in: {:?}", self.output_dir);
-        let mut ctx = self.build_generation_context().await?;
+
+        // Pick up where the previous build left off: unchanged items are not reparsed and
+        // pages whose inputs did not change are kept (along with their manifest entries).
+        let mut build_cache = BuildCache::load(&self.output_dir);
+        self.cache_manifest
+            .seed_from(self.output_dir.join("cache-manifest.json"));
+        if let Some(shared) = shared {
+            shared.seed(&mut build_cache.items);
+        }
+
+        let mut ctx = self
+            .build_generation_context(&build_cache.items, shared)
+            .await?;
         let ui = UiContext {
             recap_latest_href: ctx.recap_latest_href.clone(),
             nav: ctx.nav,
         };
+        let pages = PageTracker::new(
+            std::mem::take(&mut build_cache.pages),
+            self.site_digest(&ui),
+            &ctx.scan_cache,
+        );
 
         // Create output directories based on what we're generating
         self.create_directories(&ctx.all_items, &ctx.stats_data)
@@ -272,21 +574,26 @@ impl SiteGenerator {
         // Generate covers for all items (books and comics)
         self.generate_covers(&ctx.all_items).await?;
 
+        // Books and comics with a detail page, including those only in the statistics
+        let detail_books = ctx.detail_items(ContentType::Book);
+        let detail_comics = ctx.detail_items(ContentType::Comic);
+        let linked_items = ctx.linked_items();
+
         // Clean up stale book directories (for deleted books)
-        self.cleanup_stale_books(&ctx.books)?;
+        self.cleanup_stale_books(&detail_books)?;
 
         // Clean up stale comic directories (for deleted comics)
-        self.cleanup_stale_comics(&ctx.comics)?;
+        self.cleanup_stale_comics(&detail_comics)?;
 
         // Clean up stale covers (for deleted items)
         self.cleanup_stale_covers(&ctx.all_items)?;
 
         // Generate individual book pages
-        self.generate_book_pages(&ctx.books, &mut ctx.stats_data, &ui)
+        self.generate_book_pages(&detail_books, &mut ctx.stats_data, &ui, &pages)
             .await?;
 
         // Generate individual comic pages (always at /comics/<id>/)
-        self.generate_comic_pages(&ctx.comics, &mut ctx.stats_data, &ui)
+        self.generate_comic_pages(&detail_comics, &mut ctx.stats_data, &ui, &pages)
             .await?;
 
         // Generate list pages with conditional routing:
@@ -295,35 +602,105 @@ impl SiteGenerator {
         // - If comics exist AND no books: comic list at /
         if ctx.nav.has_books {
             // Generate book list page at index.html
-            self.generate_book_list(&ctx.books, &ui).await?;
+            self.generate_book_list(&ctx.books, &ui, &pages).await?;
         }
 
         if ctx.nav.has_comics {
             // Generate comic list - at root if no books, otherwise at /comics/
-            self.generate_comic_list(&ctx.comics, !ctx.nav.has_books, &ui)
+            self.generate_comic_list(&ctx.comics, !ctx.nav.has_books, &ui, &pages)
                 .await?;
         }
 
-        if let Some(ref mut stats_data) = ctx.stats_data {
+        // Author, series, subject and language pages (stale ones are removed even when there
+        // are none)
+        self.generate_browse_pages(&ctx.browse_groups, &ui, &pages)?;
+
+        // Full-text search over highlights, notes and descriptions
+        let search_index = SearchIndex::build(&ctx.all_items);
+        self.generate_search_index(&ctx.all_items, &search_index, &pages)?;
+
+        // OPDS catalog (Atom and JSON feeds) for e-reader apps. Its URLs are fixed below
+        // `/opds/`, so reader profile sites (served below a site root) share the one
+        // written at the root by `generate_profiles`.
+        if !ctx.all_items.is_empty() && self.site_root.is_empty() {
+            self.generate_opds_catalog(&ctx.all_items)?;
+        }
+
+        // Statistics, calendar and recap pages only change with the statistics databases or
+        // the library items they link to
+        let stats_changed = ctx.stats_data.is_some()
+            && pages.needs_render(
+                "statistics",
+                self.statistics_inputs(&linked_items, &pages),
+                &self.calendar_dir().join("index.html"),
+            );
+
+        if stats_changed && let Some(ref mut stats_data) = ctx.stats_data {
             // Generate statistics page (render to root if no items at all)
             self.generate_statistics_page(stats_data, ctx.all_items.is_empty(), &ui)
                 .await?;
 
             // Generate calendar page if we have statistics data
-            self.generate_calendar_page(stats_data, &ctx.all_items, &ui)
+            self.generate_calendar_page(stats_data, &linked_items, &ui)
                 .await?;
 
             // Generate recap pages (static yearly)
-            self.generate_recap_pages(stats_data, &ctx.all_items, ctx.nav)
+            self.generate_recap_pages(stats_data, &linked_items, ctx.nav)
                 .await?;
         }
 
+        if pages.skipped() > 0 {
+            info!(
+                "Kept {} unchanged pages from the previous build",
+                pages.skipped()
+            );
+        }
+
         // Write cache manifest for PWA smart caching
+        self.cache_manifest.retain_existing(&self.output_dir);
         self.cache_manifest
             .write(self.output_dir.join("cache-manifest.json"))?;
 
+        // Persist the build cache for the next run
+        let mut next_cache = BuildCache::new();
+        next_cache.items = ctx.scan_cache;
+        next_cache.pages = pages.into_pages();
+        if let Err(e) = next_cache.save(&self.output_dir) {
+            warn!("Failed to write build cache: {}", e);
+        }
+
         info!("Static site generation completed!");
 
-        Ok(())
+        let snapshot = LibrarySnapshot {
+            version: self.version().to_string(),
+            items: ctx.all_items,
+            stats_data: ctx.stats_data,
+            search_index,
+            profiles: BTreeMap::new(),
+        };
+        Ok((snapshot, next_cache.items))
     }
 }
+
+/// Entries for the books in `stats_data` whose files are not in the library (see
+/// [`LibraryItem::from_stat_book`]), with IDs that do not clash with those of `items`.
+/// Books without any recorded reading are left out.
+pub(crate) fn stats_only_items(
+    stats_data: &StatisticsData,
+    items: &[LibraryItem],
+    library_md5s: &HashSet<String>,
+    id_scheme: ItemIdScheme,
+) -> Vec<LibraryItem> {
+    let read: HashSet<i64> = stats_data.page_stats.iter().map(|s| s.id_book).collect();
+    let mut taken: HashSet<String> = items.iter().map(|item| item.id.clone()).collect();
+    stats_data
+        .books
+        .iter()
+        .filter(|book| read.contains(&book.id) && !library_md5s.contains(&book.md5))
+        .map(|book| {
+            let mut item = LibraryItem::from_stat_book(String::new(), book);
+            item.id = unique_item_id(&item, Some(&book.md5), id_scheme, &mut taken);
+            item
+        })
+        .collect()
+}
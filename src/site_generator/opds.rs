@@ -0,0 +1,176 @@
+//! OPDS catalog generation (static Atom and JSON feeds below `/opds/`).
+
+use super::SiteGenerator;
+use super::library_pages::StatusBuckets;
+use crate::models::{
+    BrowseKind, LibraryItem, compare_series_positions, compare_titles, group_by_slug,
+};
+use crate::opds::{
+    OPDS_ROOT, OpdsFeed, OpdsFeedKind, OpdsLinks, OpdsNavEntry, OpdsSite, opensearch_description,
+};
+use anyhow::Result;
+use chrono::SecondsFormat;
+use log::info;
+use std::fs;
+use std::path::PathBuf;
+
+impl SiteGenerator {
+    pub(crate) fn opds_dir(&self) -> PathBuf {
+        self.output_dir.join("opds")
+    }
+
+    /// Links for publication entries: downloads from the first library path, pages and
+    /// covers from this site or, with reader profiles, from the profile sites.
+    pub(crate) fn opds_links(&self) -> OpdsLinks {
+        OpdsLinks {
+            raw_root: self.library_paths.first().cloned(),
+            sites: self
+                .site_dirs()
+                .into_iter()
+                .map(|(root, dir)| OpdsSite { root, dir })
+                .collect(),
+        }
+    }
+
+    /// Write the OPDS catalog: a root navigation feed linking to feeds by status, to all
+    /// items, and to navigation feeds of authors, series, subjects and languages.
+    ///
+    /// Reader profile sites share one catalog at the root. Reading status belongs to a
+    /// reader, so it has no status feeds.
+    pub(crate) fn generate_opds_catalog(&self, items: &[LibraryItem]) -> Result<()> {
+        info!("Generating OPDS catalog...");
+        let t = self.t();
+
+        // Feeds are cheap to write, so the catalog is rebuilt from scratch to drop stale groups
+        let opds_dir = self.opds_dir();
+        if opds_dir.exists() {
+            fs::remove_dir_all(&opds_dir)?;
+        }
+        for group in ["status", "authors", "series", "subjects", "languages"] {
+            fs::create_dir_all(opds_dir.join(group))?;
+        }
+
+        let updated = chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let links = self.opds_links();
+        let feed = |href: String, title: String, kind: OpdsFeedKind| OpdsFeed {
+            href,
+            title,
+            updated: updated.clone(),
+            kind,
+            navigation: Vec::new(),
+            entries: Vec::new(),
+            searchable: self.is_internal_server,
+        };
+        let acquisition = |href: String, title: String, items: &[&LibraryItem]| {
+            let mut feed = feed(href, title, OpdsFeedKind::Acquisition);
+            feed.entries = items.iter().map(|item| links.entry(item)).collect();
+            feed
+        };
+
+        let mut root = feed(
+            format!("{}/index", OPDS_ROOT),
+            self.site_title.clone(),
+            OpdsFeedKind::Navigation,
+        );
+
+        let buckets = if self.profiles.is_empty() {
+            StatusBuckets::from_items(items)
+        } else {
+            StatusBuckets::default()
+        };
+        for (slug, title_key, bucket) in [
+            ("reading", "status.reading", &buckets.reading),
+            ("complete", "status.completed", &buckets.completed),
+            ("abandoned", "status.on-hold", &buckets.abandoned),
+            ("unread", "status.unread", &buckets.unread),
+        ] {
+            if bucket.is_empty() {
+                continue;
+            }
+            let refs: Vec<&LibraryItem> = bucket.iter().collect();
+            let status_feed = acquisition(
+                format!("{}/status/{}", OPDS_ROOT, slug),
+                t.get(title_key),
+                &refs,
+            );
+            root.navigation.push(nav_entry(&status_feed));
+            self.write_opds_feed(&status_feed)?;
+        }
+
+        let mut all: Vec<&LibraryItem> = items.iter().collect();
+        all.sort_by(|a, b| compare_titles(a, b));
+        let all_feed = acquisition(format!("{}/all", OPDS_ROOT), t.get("opds.all"), &all);
+        root.navigation.push(nav_entry(&all_feed));
+        self.write_opds_feed(&all_feed)?;
+
+        for kind in BrowseKind::ALL {
+            let group = kind.path();
+            let title_key = match kind {
+                BrowseKind::Author => "opds.authors",
+                BrowseKind::Series => "series",
+                BrowseKind::Subject => "genres",
+                BrowseKind::Language => "browse.languages",
+            };
+            let mut members = group_by_slug(items, kind);
+            if members.is_empty() {
+                continue;
+            }
+
+            let mut index = feed(
+                format!("{}/{}", OPDS_ROOT, group),
+                t.get(title_key),
+                OpdsFeedKind::Navigation,
+            );
+            let mut names: Vec<_> = members.iter_mut().collect();
+            names.sort_by_key(|(_, (name, _))| name.to_lowercase());
+            for (slug, (name, group_items)) in names {
+                if kind == BrowseKind::Series {
+                    group_items.sort_by(|a, b| compare_series_positions(a, b));
+                } else {
+                    group_items.sort_by(|a, b| compare_titles(a, b));
+                }
+                let href = format!("{}/{}/{}", OPDS_ROOT, group, slug);
+                let group_feed = acquisition(href, name.clone(), group_items);
+                index.navigation.push(nav_entry(&group_feed));
+                self.write_opds_feed(&group_feed)?;
+            }
+            root.navigation.push(OpdsNavEntry {
+                title: index.title.clone(),
+                href: index.href.clone(),
+                count: index.navigation.len(),
+                kind: OpdsFeedKind::Navigation,
+            });
+            self.write_opds_feed(&index)?;
+        }
+
+        self.write_opds_feed(&root)?;
+
+        if self.is_internal_server {
+            fs::write(
+                opds_dir.join("opensearch.xml"),
+                opensearch_description(&self.site_title),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_opds_feed(&self, feed: &OpdsFeed) -> Result<()> {
+        let base = self.output_dir.join(feed.href.trim_start_matches('/'));
+        fs::write(format!("{}.xml", base.display()), feed.to_atom()?)?;
+        fs::write(
+            format!("{}.json", base.display()),
+            serde_json::to_string_pretty(&feed.to_json())?,
+        )?;
+        Ok(())
+    }
+}
+
+fn nav_entry(feed: &OpdsFeed) -> OpdsNavEntry {
+    OpdsNavEntry {
+        title: feed.title.clone(),
+        href: feed.href.clone(),
+        count: feed.entries.len(),
+        kind: feed.kind,
+    }
+}
@@ -0,0 +1,153 @@
+//! Multi-reader sites: one site per reader profile below `/u/<profile>/`, generated from a
+//! shared library scan, and a landing page and OPDS catalog at the root.
+
+use super::SiteGenerator;
+use crate::library::{LibrarySnapshot, ScanCache};
+use crate::models::LibraryItem;
+use crate::templates::ProfilesTemplate;
+use anyhow::Result;
+use askama::Template;
+use log::info;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Library scan shared between the reader profiles of one run. Book files are parsed and
+/// covers generated only once; each profile just reads its own KOReader metadata.
+#[derive(Default)]
+pub(crate) struct SharedScan {
+    items: ScanCache,
+    /// Covers directories of the profile sites generated so far
+    covers_dirs: Vec<PathBuf>,
+}
+
+impl SharedScan {
+    fn add(&mut self, items: ScanCache, covers_dir: PathBuf) {
+        for (path, item) in items {
+            self.items.entry(path).or_insert(item);
+        }
+        self.covers_dirs.push(covers_dir);
+    }
+
+    /// Add the shared items to a profile's previous scan where it has no entry for the
+    /// current state of the book file. The scanner then only reparses the metadata.
+    pub(crate) fn seed(&self, previous: &mut ScanCache) {
+        for (path, shared) in &self.items {
+            let known = previous
+                .get(path)
+                .is_some_and(|own| own.fingerprint.file == shared.fingerprint.file);
+            if !known {
+                previous.insert(path.clone(), shared.clone());
+            }
+        }
+    }
+
+    /// Items of the shared catalog: the scanned book files listed by at least one profile,
+    /// without the reading state of any reader.
+    fn catalog_items(&self, profiles: &BTreeMap<String, Arc<LibrarySnapshot>>) -> Vec<LibraryItem> {
+        let listed: HashSet<&str> = profiles
+            .values()
+            .flat_map(|snapshot| &snapshot.items)
+            .map(|item| item.id.as_str())
+            .collect();
+        let mut items: Vec<LibraryItem> = self
+            .items
+            .values()
+            .filter(|cached| listed.contains(cached.item.id.as_str()))
+            .map(|cached| LibraryItem {
+                koreader_metadata: None,
+                ..cached.item.clone()
+            })
+            .collect();
+        items.sort_by(|a, b| a.id.cmp(&b.id));
+        items
+    }
+
+    /// Cover of item `id` generated for another profile.
+    pub(crate) fn cover(&self, id: &str) -> Option<PathBuf> {
+        self.covers_dirs
+            .iter()
+            .map(|dir| dir.join(format!("{}.webp", id)))
+            .find(|path| path.exists())
+    }
+}
+
+impl SiteGenerator {
+    /// Generate the site of every reader profile, the landing page and the shared OPDS
+    /// catalog. The returned snapshot holds the catalog items and the profile snapshots.
+    pub(crate) async fn generate_profiles(&self) -> Result<LibrarySnapshot> {
+        info!(
+            "Generating {} reader profile sites in: {:?}",
+            self.profiles.len(),
+            self.output_dir
+        );
+        fs::create_dir_all(&self.output_dir)?;
+
+        let mut shared = SharedScan::default();
+        let mut profiles = BTreeMap::new();
+        for profile in &self.profiles {
+            info!("Generating site of reader profile {:?}", profile.name);
+            let generator =
+                SiteGenerator::with_version(self.for_profile(profile), self.version().to_string());
+            let (snapshot, items) = generator.generate_site(Some(&shared)).await?;
+            shared.add(items, generator.covers_dir());
+            profiles.insert(profile.slug.clone(), Arc::new(snapshot));
+        }
+
+        self.cleanup_stale_profiles()?;
+        self.generate_profile_index()?;
+
+        let items = shared.catalog_items(&profiles);
+        if !items.is_empty() {
+            self.generate_opds_catalog(&items)?;
+        }
+
+        Ok(LibrarySnapshot {
+            version: self.version().to_string(),
+            items,
+            profiles,
+            ..Default::default()
+        })
+    }
+
+    /// Copy a cover generated for another profile into this site.
+    pub(crate) fn copy_shared_cover(&self, source: &Path, id: &str) -> Result<()> {
+        fs::create_dir_all(self.covers_dir())?;
+        let bytes = fs::read(source)?;
+        self.write_registered_bytes(self.covers_dir().join(format!("{}.webp", id)), &bytes)
+    }
+
+    /// Remove the sites of profiles that are no longer configured.
+    fn cleanup_stale_profiles(&self) -> Result<()> {
+        let profiles_dir = self.output_dir.join("u");
+        let slugs: HashSet<&str> = self.profiles.iter().map(|p| p.slug.as_str()).collect();
+        for entry in fs::read_dir(&profiles_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if entry.file_type()?.is_dir() && !slugs.contains(name.to_string_lossy().as_ref()) {
+                info!("Removing site of stale reader profile: {:?}", entry.path());
+                fs::remove_dir_all(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the landing page listing the profiles, with the stylesheet it needs.
+    fn generate_profile_index(&self) -> Result<()> {
+        let template = ProfilesTemplate {
+            site_title: self.site_title.clone(),
+            profiles: self
+                .profiles
+                .iter()
+                .map(|p| (p.name.clone(), self.url(&format!("/u/{}/", p.slug))))
+                .collect(),
+            version: self.get_version(),
+            translation: self.t(),
+        };
+        fs::create_dir_all(self.css_dir())?;
+        let css_content = include_str!(concat!(env!("OUT_DIR"), "/compiled_style.css"));
+        fs::write(self.css_dir().join("style.css"), css_content)?;
+        self.write_minify_html(self.output_dir.join("index.html"), &template.render()?)
+    }
+}
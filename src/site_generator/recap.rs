@@ -2,9 +2,10 @@
 
 use super::SiteGenerator;
 use super::utils::{format_day_month, format_duration};
+use crate::koreader::GoalCalculator;
 use crate::models::{
-    ContentType, DailyStats, LibraryItem, MonthRecap, PageStat, ReadingStats, RecapItem,
-    StatisticsData, YearlySummary,
+    ContentType, DailyStats, GoalMetric, GoalProgress, LibraryItem, MonthRecap, ReadingSession,
+    ReadingStats, RecapItem, StatisticsData, YearlySummary,
 };
 use crate::templates::{RecapEmptyTemplate, RecapTemplate};
 use anyhow::Result;
@@ -134,9 +135,11 @@ fn build_md5_to_item(items: &[LibraryItem]) -> HashMap<String, &LibraryItem> {
     md5_to_book
 }
 
+/// Item and cover paths are prefixed with `site_root`.
 fn group_completions_by_year_month(
     stats_data: &StatisticsData,
     md5_to_item: &HashMap<String, &LibraryItem>,
+    site_root: &str,
     translations: &crate::i18n::Translations,
 ) -> (YearMonthItems, Vec<i32>) {
     // Build year -> month (YYYY-MM) -> Vec<RecapItem>
@@ -177,10 +180,13 @@ fn group_completions_by_year_month(
                     let review_note = item.review_note().cloned();
                     let series_display = item.series_display();
                     let item_path = Some(match item.content_type() {
-                        ContentType::Book => format!("/books/{}/index.html", item.id),
-                        ContentType::Comic => format!("/comics/{}/index.html", item.id),
+                        ContentType::Book => format!("{}/books/{}/index.html", site_root, item.id),
+                        ContentType::Comic => {
+                            format!("{}/comics/{}/index.html", site_root, item.id)
+                        }
                     });
-                    let item_cover = Some(format!("/assets/covers/{}.webp", item.id));
+                    let item_cover = (!item.is_stats_only())
+                        .then(|| format!("{}/assets/covers/{}.webp", site_root, item.id));
                     (
                         title,
                         authors,
@@ -271,7 +277,7 @@ fn compute_yearly_summary(
     monthly: &[MonthRecap],
     month_hours: &HashMap<String, i64>,
     reading_stats: &ReadingStats,
-    page_stats: &[PageStat],
+    sessions: &[ReadingSession],
     ids_filter: Option<&HashSet<i64>>,
     translations: &crate::i18n::Translations,
 ) -> YearlySummary {
@@ -283,34 +289,20 @@ fn compute_yearly_summary(
         total_time_seconds += m.hours_read_seconds;
     }
 
-    // 2. Session stats from page_stats (filtered by year and (optional) id set)
-    let year_page_stats: Vec<PageStat> = page_stats
+    // 2. Session stats (filtered by year and (optional) id set)
+    let year_sessions: Vec<&ReadingSession> = sessions
         .iter()
-        .filter(|ps| {
-            if let Some(dt) = chrono::DateTime::from_timestamp(ps.start_time, 0) {
-                if dt.year() != year {
-                    return false;
-                }
-                if let Some(ids) = ids_filter {
-                    ids.contains(&ps.id_book)
-                } else {
-                    true
-                }
-            } else {
-                false
-            }
+        .filter(|session| {
+            chrono::DateTime::from_timestamp(session.start_time, 0)
+                .is_some_and(|dt| dt.year() == year)
+                && ids_filter.is_none_or(|ids| ids.contains(&session.id_book))
         })
-        .cloned()
         .collect();
 
-    let all_sessions = crate::koreader::session::aggregate_session_durations(&year_page_stats);
-    let session_count = all_sessions.len() as i64;
-    let longest_session_duration = all_sessions.iter().max().copied().unwrap_or(0);
-    let average_session_duration = if session_count > 0 {
-        all_sessions.iter().sum::<i64>() / session_count
-    } else {
-        0
-    };
+    let (average_session_duration, longest_session_duration) =
+        crate::koreader::session::session_metrics(year_sessions);
+    let average_session_duration = average_session_duration.unwrap_or(0);
+    let longest_session_duration = longest_session_duration.unwrap_or(0);
 
     // 3. Active days (from daily activity)
     let year_str = format!("{}", year);
@@ -405,8 +397,68 @@ fn compute_yearly_summary(
     }
 }
 
+/// Build the recap of a single year for one scope (`None` = all content).
+///
+/// Returns the years that have completions, and the monthly recaps and summary for
+/// `year` if it is one of them. Used by the JSON API; the recap pages build every
+/// year and scope in one pass instead.
+pub(crate) fn build_year_recap(
+    stats_data: &StatisticsData,
+    items: &[LibraryItem],
+    year: i32,
+    content_type: Option<ContentType>,
+    time_config: &crate::time_config::TimeConfig,
+    translations: &crate::i18n::Translations,
+) -> (Vec<i32>, Option<(Vec<MonthRecap>, YearlySummary)>) {
+    let md5_to_item = build_md5_to_item(items);
+    let (year_month_items, years) =
+        group_completions_by_year_month(stats_data, &md5_to_item, "", translations);
+    let Some(months_map) = year_month_items.get(&year).cloned() else {
+        return (years, None);
+    };
+
+    let mut scoped = match content_type {
+        Some(content_type) => stats_data.filtered_by_content_type(content_type),
+        None => stats_data.clone(),
+    };
+    let reading_stats =
+        crate::koreader::StatisticsParser::calculate_stats(&mut scoped, time_config);
+    let month_hours = month_hours_for(&reading_stats.daily_activity);
+
+    let monthly = build_monthly_recaps(
+        months_map,
+        &month_hours,
+        &month_hours,
+        &month_hours,
+        translations,
+    );
+    let monthly = match content_type {
+        None => monthly.all,
+        Some(ContentType::Book) => monthly.books,
+        Some(ContentType::Comic) => monthly.comics,
+    };
+
+    let ids: HashSet<i64> = scoped.books.iter().map(|b| b.id).collect();
+    let summary = compute_yearly_summary(
+        year,
+        &monthly,
+        &month_hours,
+        &reading_stats,
+        &stats_data.sessions,
+        content_type.map(|_| &ids),
+        translations,
+    );
+
+    (years, Some((monthly, summary)))
+}
+
 impl SiteGenerator {
-    async fn render_share_images_for_year(&self, year: i32, summary: &YearlySummary) -> Result<()> {
+    async fn render_share_images_for_year(
+        &self,
+        year: i32,
+        summary: &YearlySummary,
+        goals: &[GoalProgress],
+    ) -> Result<()> {
         // Generate share images for social media
         let share_data = crate::share::ShareImageData {
             year,
@@ -417,14 +469,19 @@ impl SiteGenerator {
             active_days_percentage: summary.active_days_percentage as u8,
             longest_streak: summary.longest_streak as u32,
             best_month: summary.best_month_name.clone(),
+            books_goal: goals
+                .iter()
+                .find(|goal| goal.metric == GoalMetric::Books)
+                .map(|goal| goal.target),
         };
 
         // Check if we need to regenerate share images (skip if stats DB hasn't changed)
         let stats_db_time = self
-            .statistics_db_path
-            .as_ref()
-            .and_then(|p| fs::metadata(p).ok())
-            .and_then(|m| m.modified().ok())
+            .statistics_db_paths
+            .iter()
+            .filter_map(|p| fs::metadata(p).ok())
+            .filter_map(|m| m.modified().ok())
+            .max()
             .unwrap_or(SystemTime::UNIX_EPOCH);
 
         // Generate all three formats in parallel using spawn_blocking (only if needed)
@@ -575,13 +632,18 @@ impl SiteGenerator {
         let book_ids: HashSet<i64> = books_stats_data.books.iter().map(|b| b.id).collect();
         let comic_ids: HashSet<i64> = comics_stats_data.books.iter().map(|b| b.id).collect();
 
-        let (year_month_items, years) =
-            group_completions_by_year_month(stats_data, &md5_to_book, &self.translations);
+        let (year_month_items, years) = group_completions_by_year_month(
+            stats_data,
+            &md5_to_book,
+            &self.site_root,
+            &self.translations,
+        );
 
         if years.is_empty() {
             // No completions → render empty state page
             let template = RecapEmptyTemplate {
                 site_title: self.site_title.clone(),
+                site_root: self.site_root.clone(),
                 recap_scope: "all".to_string(),
                 show_type_filter,
                 year: None,
@@ -620,7 +682,7 @@ impl SiteGenerator {
             );
 
             // Latest year href for sidebar
-            let latest_href = format!("/recap/{}/", years[0]);
+            let latest_href = self.url(&format!("/recap/{}/", years[0]));
 
             // ------------------------------------------------------------------
             // Calculate Yearly Summary Stats
@@ -630,7 +692,7 @@ impl SiteGenerator {
                 &monthly.all,
                 &month_hours_all,
                 &reading_stats_all,
-                &stats_data.page_stats,
+                &stats_data.sessions,
                 None,
                 &self.translations,
             );
@@ -639,7 +701,7 @@ impl SiteGenerator {
                 &monthly.books,
                 &month_hours_books,
                 &reading_stats_books,
-                &stats_data.page_stats,
+                &stats_data.sessions,
                 Some(&book_ids),
                 &self.translations,
             );
@@ -648,19 +710,29 @@ impl SiteGenerator {
                 &monthly.comics,
                 &month_hours_comics,
                 &reading_stats_comics,
-                &stats_data.page_stats,
+                &stats_data.sessions,
                 Some(&comic_ids),
                 &self.translations,
             );
 
+            let goals = GoalCalculator::for_year(
+                &self.goals,
+                stats_data,
+                &reading_stats_all,
+                *year,
+                self.time_config.today_date(),
+            );
+
             let template_all = RecapTemplate {
                 site_title: self.site_title.clone(),
+                site_root: self.site_root.clone(),
                 recap_scope: "all".to_string(),
                 show_type_filter,
                 year: *year,
                 available_years: years.clone(),
                 monthly: monthly.all.clone(),
                 summary: summary.clone(),
+                goals: goals.clone(),
                 version: self.get_version(),
                 last_updated: self.get_last_updated(),
                 navbar_items: self.create_navbar_items_with_recap(
@@ -687,6 +759,7 @@ impl SiteGenerator {
                 if monthly.books.is_empty() {
                     let t = RecapEmptyTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         recap_scope: "books".to_string(),
                         show_type_filter,
                         year: Some(*year),
@@ -705,12 +778,14 @@ impl SiteGenerator {
                 } else {
                     let t = RecapTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         recap_scope: "books".to_string(),
                         show_type_filter,
                         year: *year,
                         available_years: years.clone(),
                         monthly: monthly.books.clone(),
                         summary: summary_books.clone(),
+                        goals: Vec::new(),
                         version: self.get_version(),
                         last_updated: self.get_last_updated(),
                         navbar_items: self.create_navbar_items_with_recap(
@@ -731,6 +806,7 @@ impl SiteGenerator {
                 if monthly.comics.is_empty() {
                     let t = RecapEmptyTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         recap_scope: "comics".to_string(),
                         show_type_filter,
                         year: Some(*year),
@@ -749,12 +825,14 @@ impl SiteGenerator {
                 } else {
                     let t = RecapTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         recap_scope: "comics".to_string(),
                         show_type_filter,
                         year: *year,
                         available_years: years.clone(),
                         monthly: monthly.comics.clone(),
                         summary: summary_comics.clone(),
+                        goals: Vec::new(),
                         version: self.get_version(),
                         last_updated: self.get_last_updated(),
                         navbar_items: self.create_navbar_items_with_recap(
@@ -769,7 +847,8 @@ impl SiteGenerator {
                 }
             }
 
-            self.render_share_images_for_year(*year, &summary).await?;
+            self.render_share_images_for_year(*year, &summary, &goals)
+                .await?;
         }
 
         Ok(())
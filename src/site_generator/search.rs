@@ -0,0 +1,78 @@
+//! Full-text search index shards below `/assets/json/search/`.
+
+use super::SiteGenerator;
+use super::build_cache::PageTracker;
+use crate::models::LibraryItem;
+use crate::search::{DOCUMENTS_PER_CHUNK, MIN_TERM_LENGTH, SearchIndex};
+use anyhow::Result;
+use log::info;
+use serde_json::json;
+use std::fs;
+
+impl SiteGenerator {
+    /// Write the search index of `items`: `index.json` (shard list), one `terms/<shard>.json`
+    /// per shard mapping terms to document indices, and the searchable texts in chunks of
+    /// `documents/<n>.json`.
+    pub(crate) fn generate_search_index(
+        &self,
+        items: &[LibraryItem],
+        index: &SearchIndex,
+        pages: &PageTracker,
+    ) -> Result<()> {
+        let search_dir = self.search_json_dir();
+        if items.is_empty() {
+            if search_dir.exists() {
+                fs::remove_dir_all(&search_dir)?;
+            }
+            return Ok(());
+        }
+
+        let mut inputs: Vec<String> = items.iter().map(|item| pages.item_key(item)).collect();
+        inputs.sort();
+        if !pages.needs_render("search", inputs, &search_dir.join("index.json")) {
+            return Ok(());
+        }
+
+        info!("Generating search index...");
+
+        // Shards are rebuilt from scratch so terms and chunks that disappeared leave no
+        // stale files
+        let terms_dir = search_dir.join("terms");
+        let documents_dir = search_dir.join("documents");
+        for dir in [&terms_dir, &documents_dir] {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+            fs::create_dir_all(dir)?;
+        }
+        let documents_file = search_dir.join("documents.json");
+        if documents_file.exists() {
+            fs::remove_file(documents_file)?;
+        }
+
+        let shards = index.shards();
+        for (key, terms) in &shards {
+            self.write_registered_string(
+                terms_dir.join(format!("{}.json", key)),
+                &serde_json::to_string(terms)?,
+            )?;
+        }
+        for (chunk, documents) in index.document_chunks().enumerate() {
+            self.write_registered_string(
+                documents_dir.join(format!("{}.json", chunk)),
+                &serde_json::to_string(documents)?,
+            )?;
+        }
+        self.write_registered_json_pretty(
+            search_dir.join("index.json"),
+            &json!({
+                "min_term_length": MIN_TERM_LENGTH,
+                "documents": index.documents.len(),
+                "documents_per_chunk": DOCUMENTS_PER_CHUNK,
+                "shards": shards.keys().collect::<Vec<_>>(),
+                "generated_at": self.get_last_updated(),
+            }),
+        )?;
+        Ok(())
+    }
+}
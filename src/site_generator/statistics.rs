@@ -1,17 +1,44 @@
 //! Statistics page generation and JSON export.
 
 use super::SiteGenerator;
-use crate::koreader::StatisticsParser;
-use crate::models::{ContentType, ReadingStats, StatisticsData};
+use crate::koreader::{GoalCalculator, StatisticsParser};
+use crate::models::{ContentType, ReadingStats, StatBook, StatisticsData};
 use crate::templates::{StatsEmptyTemplate, StatsTemplate};
+use crate::utils::generate_slug;
 use anyhow::Result;
 use askama::Template;
 use log::info;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 use super::utils::UiContext;
 
+/// Payload of `/assets/json/statistics/sessions.json`.
+#[derive(Serialize)]
+struct SessionsExport<'a> {
+    /// Longest pause in seconds within one session
+    session_gap: i64,
+    sessions: Vec<SessionExport<'a>>,
+}
+
+#[derive(Serialize)]
+struct SessionExport<'a> {
+    md5: &'a str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<ContentType>,
+    /// Reading day the session started on (respects `day_start_time`)
+    date: String,
+    start_time: i64,
+    end_time: i64,
+    duration: i64,
+    pages: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<&'a str>,
+}
+
 impl SiteGenerator {
     pub(crate) async fn generate_statistics_page(
         &self,
@@ -20,6 +47,7 @@ impl SiteGenerator {
         ui: &UiContext,
     ) -> Result<()> {
         let show_type_filter = ui.nav.show_type_filter();
+        let devices = self.statistics_devices(stats_data);
         if render_to_root {
             info!("Generating statistics page at root index...");
         } else {
@@ -36,6 +64,21 @@ impl SiteGenerator {
             .export_daily_activity_by_year_to_dir(&reading_stats_all.daily_activity, &all_dir)
             .await?;
         self.export_week_stats_to_dir(&reading_stats_all.weeks, &all_dir)?;
+        self.write_registered_json_pretty(
+            all_dir.join("patterns.json"),
+            &reading_stats_all.patterns,
+        )?;
+
+        let goals = GoalCalculator::current(
+            &self.goals,
+            stats_data,
+            &reading_stats_all,
+            self.time_config.today_date(),
+        );
+        if !goals.is_empty() {
+            self.write_registered_json_pretty(all_dir.join("goals.json"), &goals)?;
+        }
+        self.export_sessions(stats_data)?;
 
         // Only export/render per-type views when we actually have both types in the site.
         // If only one type exists, the "all" view is the only meaningful one.
@@ -70,11 +113,15 @@ impl SiteGenerator {
         if render_to_root {
             let template = StatsTemplate {
                 site_title: self.site_title.clone(),
+                site_root: self.site_root.clone(),
                 stats_scope: "all".to_string(),
                 show_type_filter,
-                stats_json_base_path: "/assets/json/statistics/all".to_string(),
+                devices: devices.clone(),
+                stats_device: None,
+                stats_json_base_path: self.url("/assets/json/statistics/all"),
                 reading_stats: reading_stats_all.clone(),
                 available_years,
+                goals: goals.clone(),
                 version: self.get_version(),
                 last_updated: self.get_last_updated(),
                 navbar_items: self.create_navbar_items_with_recap(
@@ -91,11 +138,15 @@ impl SiteGenerator {
             fs::create_dir_all(&stats_dir)?;
             let template_all = StatsTemplate {
                 site_title: self.site_title.clone(),
+                site_root: self.site_root.clone(),
                 stats_scope: "all".to_string(),
                 show_type_filter,
-                stats_json_base_path: "/assets/json/statistics/all".to_string(),
+                devices: devices.clone(),
+                stats_device: None,
+                stats_json_base_path: self.url("/assets/json/statistics/all"),
                 reading_stats: reading_stats_all.clone(),
                 available_years,
+                goals: goals.clone(),
                 version: self.get_version(),
                 last_updated: self.get_last_updated(),
                 navbar_items: self.create_navbar_items_with_recap(
@@ -117,8 +168,11 @@ impl SiteGenerator {
                 let html_books = if years_books.is_empty() {
                     let template = StatsEmptyTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         stats_scope: "books".to_string(),
                         show_type_filter,
+                        devices: devices.clone(),
+                        stats_device: None,
                         version: self.get_version(),
                         last_updated: self.get_last_updated(),
                         navbar_items: self.create_navbar_items_with_recap(
@@ -132,11 +186,15 @@ impl SiteGenerator {
                 } else {
                     let template = StatsTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         stats_scope: "books".to_string(),
                         show_type_filter,
-                        stats_json_base_path: "/assets/json/statistics/books".to_string(),
+                        devices: devices.clone(),
+                        stats_device: None,
+                        stats_json_base_path: self.url("/assets/json/statistics/books"),
                         reading_stats: reading_stats_books.expect("books stats must exist"),
                         available_years: years_books,
+                        goals: Vec::new(),
                         version: self.get_version(),
                         last_updated: self.get_last_updated(),
                         navbar_items: self.create_navbar_items_with_recap(
@@ -158,8 +216,11 @@ impl SiteGenerator {
                 let html_comics = if years_comics.is_empty() {
                     let template = StatsEmptyTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         stats_scope: "comics".to_string(),
                         show_type_filter,
+                        devices: devices.clone(),
+                        stats_device: None,
                         version: self.get_version(),
                         last_updated: self.get_last_updated(),
                         navbar_items: self.create_navbar_items_with_recap(
@@ -173,11 +234,15 @@ impl SiteGenerator {
                 } else {
                     let template = StatsTemplate {
                         site_title: self.site_title.clone(),
+                        site_root: self.site_root.clone(),
                         stats_scope: "comics".to_string(),
                         show_type_filter,
-                        stats_json_base_path: "/assets/json/statistics/comics".to_string(),
+                        devices: devices.clone(),
+                        stats_device: None,
+                        stats_json_base_path: self.url("/assets/json/statistics/comics"),
                         reading_stats: reading_stats_comics.expect("comics stats must exist"),
                         available_years: years_comics,
+                        goals: Vec::new(),
                         version: self.get_version(),
                         last_updated: self.get_last_updated(),
                         navbar_items: self.create_navbar_items_with_recap(
@@ -193,9 +258,105 @@ impl SiteGenerator {
             }
         }
 
+        // /statistics/devices/<device>/
+        for (device, _) in &devices {
+            info!("Generating statistics page (device {})...", device);
+            let slug = generate_slug(device);
+            let json_dir = self.statistics_json_dir().join("devices").join(&slug);
+            fs::create_dir_all(&json_dir)?;
+
+            let mut device_data = stats_data.filtered_by_device(device);
+            let reading_stats =
+                StatisticsParser::calculate_stats(&mut device_data, &self.time_config);
+            let available_years = self
+                .export_daily_activity_by_year_to_dir(&reading_stats.daily_activity, &json_dir)
+                .await?;
+            self.export_week_stats_to_dir(&reading_stats.weeks, &json_dir)?;
+            self.write_registered_json_pretty(
+                json_dir.join("patterns.json"),
+                &reading_stats.patterns,
+            )?;
+
+            let template = StatsTemplate {
+                site_title: self.site_title.clone(),
+                site_root: self.site_root.clone(),
+                stats_scope: "all".to_string(),
+                show_type_filter,
+                devices: devices.clone(),
+                stats_device: Some(device.clone()),
+                stats_json_base_path: self
+                    .url(&format!("/assets/json/statistics/devices/{}", slug)),
+                reading_stats,
+                available_years,
+                goals: Vec::new(),
+                version: self.get_version(),
+                last_updated: self.get_last_updated(),
+                navbar_items: self.create_navbar_items_with_recap(
+                    "statistics",
+                    ui.recap_latest_href.as_deref(),
+                    ui.nav,
+                ),
+                translation: self.t(),
+            };
+            let html = template.render()?;
+            let page_dir = self.statistics_dir().join("devices").join(&slug);
+            fs::create_dir_all(&page_dir)?;
+            self.write_minify_html(page_dir.join("index.html"), &html)?;
+        }
+
         Ok(())
     }
 
+    /// Devices offered by the statistics device filter, with the href of their page.
+    ///
+    /// The filter is only useful when page stats come from more than one source, so a single
+    /// named device whose database holds all page stats gets no filter.
+    fn statistics_devices(&self, stats_data: &StatisticsData) -> Vec<(String, String)> {
+        let devices = stats_data.devices();
+        let untagged = stats_data.page_stats.iter().any(|ps| ps.device.is_none());
+        if devices.is_empty() || (devices.len() == 1 && !untagged) {
+            return Vec::new();
+        }
+        devices
+            .into_iter()
+            .map(|device| {
+                let href = self.url(&format!("/statistics/devices/{}/", generate_slug(&device)));
+                (device, href)
+            })
+            .collect()
+    }
+
+    /// Export every reading session to `sessions.json` for analysis outside of KoShelf.
+    fn export_sessions(&self, stats_data: &StatisticsData) -> Result<()> {
+        let books: HashMap<i64, &StatBook> = stats_data.books.iter().map(|b| (b.id, b)).collect();
+        let sessions: Vec<SessionExport> = stats_data
+            .sessions
+            .iter()
+            .filter_map(|session| {
+                let book = books.get(&session.id_book)?;
+                Some(SessionExport {
+                    md5: &book.md5,
+                    title: &book.title,
+                    content_type: book.content_type,
+                    date: self.time_config.format_date(session.start_time),
+                    start_time: session.start_time,
+                    end_time: session.end_time,
+                    duration: session.duration,
+                    pages: session.pages,
+                    device: session.device.as_deref(),
+                })
+            })
+            .collect();
+
+        self.write_registered_json_pretty(
+            self.statistics_json_dir().join("sessions.json"),
+            &SessionsExport {
+                session_gap: self.session_gap,
+                sessions,
+            },
+        )
+    }
+
     /// Export daily activity data grouped by year as separate JSON files and return available years
     pub(crate) async fn export_daily_activity_by_year_to_dir(
         &self,
@@ -268,6 +429,7 @@ impl SiteGenerator {
             .export_daily_activity_by_year_to_dir(&reading_stats.daily_activity, &subdir)
             .await?;
         self.export_week_stats_to_dir(&reading_stats.weeks, &subdir)?;
+        self.write_registered_json_pretty(subdir.join("patterns.json"), &reading_stats.patterns)?;
 
         Ok((reading_stats, years))
     }
@@ -3,6 +3,7 @@
 use chrono::Datelike;
 
 use super::SiteGenerator;
+use crate::models::BrowseKind;
 use crate::templates::NavItem;
 use anyhow::Result;
 use minify_html::{Cfg, minify};
@@ -16,6 +17,8 @@ pub(crate) struct NavContext {
     pub has_comics: bool,
     /// When true, the statistics page is rendered to `/` instead of `/statistics/`.
     pub stats_at_root: bool,
+    /// First kind of author, series or subject pages the library has (the browse entry point)
+    pub browse: Option<BrowseKind>,
 }
 
 impl NavContext {
@@ -137,6 +140,11 @@ impl SiteGenerator {
         self.write_registered_string(path, &json)
     }
 
+    /// URL of a path of this site (e.g. `/statistics/`), below the site root.
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.site_root, path)
+    }
+
     /// Create default navbar items
     pub(crate) fn create_navbar_items(&self, current_page: &str, nav: NavContext) -> Vec<NavItem> {
         let mut items = Vec::new();
@@ -145,7 +153,7 @@ impl SiteGenerator {
         if nav.has_books {
             items.push(NavItem {
                 label: self.translations.get("books"),
-                href: "/".to_string(),
+                href: self.url("/"),
                 icon_svg: "M12 6.253v13m0-13C10.832 5.477 9.246 5 7.5 5S4.168 5.477 3 6.253v13C4.168 18.477 5.754 18 7.5 18s3.332.477 4.5 1.253m0-13C13.168 5.477 14.754 5 16.5 5c1.746 0 3.332.477 4.5 1.253v13C19.832 18.477 18.246 18 16.5 18c-1.746 0-3.332.477-4.5 1.253".to_string(),
                 is_active: current_page == "books",
                 id: None,
@@ -157,7 +165,7 @@ impl SiteGenerator {
             let href = if nav.has_books { "/comics/" } else { "/" };
             items.push(NavItem {
                 label: self.translations.get("comics"),
-                href: href.to_string(),
+                href: self.url(href),
                 // Comic book icon (speech bubble)
                 icon_svg: "M8 12h.01M12 12h.01M16 12h.01M21 12c0 4.418-4.03 8-9 8a9.863 9.863 0 01-4.255-.949L3 20l1.395-3.72C3.512 15.042 3 13.574 3 12c0-4.418 4.03-8 9-8s9 3.582 9 8z".to_string(),
                 is_active: current_page == "comics",
@@ -165,14 +173,24 @@ impl SiteGenerator {
             });
         }
 
+        // Author, series and genre pages, when any item has one of them
+        if let Some(kind) = nav.browse {
+            items.push(NavItem {
+                label: self.translations.get("browse"),
+                href: self.url(&format!("/{}/", kind.path())),
+                icon_svg: "M19 11H5m14 0a2 2 0 012 2v6a2 2 0 01-2 2H5a2 2 0 01-2-2v-6a2 2 0 012-2m14 0V9a2 2 0 00-2-2M5 11V9a2 2 0 012-2m0 0V5a2 2 0 012-2h6a2 2 0 012 2v2M7 7h10".to_string(),
+                is_active: current_page == "browse",
+                id: None,
+            });
+        }
+
         // Add stats navigation item if we have a stats database path configured
-        if self.statistics_db_path.is_some() {
-            let stats_href = if nav.stats_at_root {
+        if self.has_statistics_db() {
+            let stats_href = self.url(if nav.stats_at_root {
                 "/"
             } else {
                 "/statistics/"
-            }
-            .to_string();
+            });
 
             items.push(NavItem {
                 label: self.translations.get("statistics"),
@@ -184,10 +202,10 @@ impl SiteGenerator {
         }
 
         // Add calendar navigation item if we have statistics data
-        if self.statistics_db_path.is_some() {
+        if self.has_statistics_db() {
             items.push(NavItem {
                 label: self.translations.get("calendar"),
-                href: "/calendar/".to_string(),  // Calendar always goes to /calendar/
+                href: self.url("/calendar/"),  // Calendar always goes to /calendar/
                 icon_svg: "M8 7V3m8 4V3m-9 8h10M5 21h14a2 2 0 002-2V7a2 2 0 00-2-2H5a2 2 0 00-2 2v12a2 2 0 002 2z".to_string(),
                 is_active: current_page == "calendar",
                 id: None,
@@ -204,11 +222,14 @@ impl SiteGenerator {
         nav: NavContext,
     ) -> Vec<NavItem> {
         let mut items = self.create_navbar_items(current_page, nav);
-        if self.statistics_db_path.is_some() {
-            let href = recap_latest_href.unwrap_or("/recap/");
+        if self.has_statistics_db() {
+            let href = match recap_latest_href {
+                Some(href) => href.to_string(),
+                None => self.url("/recap/"),
+            };
             items.push(NavItem {
                 label: self.translations.get("recap"),
-                href: href.to_string(),
+                href,
                 icon_svg: "M12 8v4l3 3m6-3a9 9 0 11-18 0 9 9 0 0118 0z".to_string(),
                 is_active: current_page == "recap",
                 id: Some("nav-recap".to_string()),
@@ -1,5 +1,8 @@
 use crate::i18n::Translations;
 use crate::models::*;
+use crate::opds::{
+    ATOM_ACQUISITION_TYPE, ATOM_NAVIGATION_TYPE, OPDS_ROOT, OPENSEARCH_TYPE, OpdsFeed, OpdsFeedKind,
+};
 use askama::Template;
 use std::rc::Rc;
 
@@ -17,6 +20,8 @@ pub struct NavItem {
 #[template(path = "library_list/library_list.html", whitespace = "minimize")]
 pub struct LibraryListTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     /// Base path for detail pages (e.g. "/books/" or "/comics/").
     #[allow(dead_code)]
     pub details_base_path: String,
@@ -34,6 +39,8 @@ pub struct LibraryListTemplate {
 #[template(path = "recap/recap_year.html", whitespace = "minimize")]
 pub struct RecapTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     /// "all" | "books" | "comics"
     pub recap_scope: String,
     /// Whether we should show the Books/Comics filter UI (only when both exist).
@@ -42,6 +49,8 @@ pub struct RecapTemplate {
     pub available_years: Vec<i32>,
     pub monthly: Vec<MonthRecap>,
     pub summary: YearlySummary,
+    /// Progress of the yearly reading goals (empty without goals and on per-type pages)
+    pub goals: Vec<GoalProgress>,
     pub version: String,
     pub last_updated: String,
     pub navbar_items: Vec<NavItem>,
@@ -52,6 +61,8 @@ pub struct RecapTemplate {
 #[template(path = "recap/recap_empty.html", whitespace = "minimize")]
 pub struct RecapEmptyTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     /// "all" | "books" | "comics"
     pub recap_scope: String,
     /// Whether we should show the Books/Comics filter UI (only when both exist).
@@ -69,10 +80,12 @@ pub struct RecapEmptyTemplate {
 #[template(path = "item_details/item_details.html", whitespace = "minimize")]
 pub struct ItemDetailTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     pub book: LibraryItem,
     pub book_stats: Option<StatBook>,
     pub session_stats: Option<BookSessionStats>,
-    pub search_base_path: String,
+    pub timeline: Option<BookTimeline>,
     pub version: String,
     pub last_updated: String,
     pub navbar_items: Vec<NavItem>,
@@ -89,18 +102,77 @@ pub struct ItemDetailMarkdownTemplate {
     pub last_updated: String,
 }
 
+/// Index of all authors, series or subjects.
+#[derive(Template)]
+#[template(path = "browse/browse_index.html", whitespace = "minimize")]
+pub struct BrowseIndexTemplate {
+    pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
+    pub kind: BrowseKind,
+    /// Kinds with at least one group, for switching between the index pages
+    pub kinds: Vec<BrowseKind>,
+    pub groups: Vec<BrowseGroup>,
+    pub version: String,
+    pub last_updated: String,
+    pub navbar_items: Vec<NavItem>,
+    pub translation: Rc<Translations>,
+}
+
+/// Page of a single author, series or subject.
+#[derive(Template)]
+#[template(path = "browse/browse_group.html", whitespace = "minimize")]
+pub struct BrowseGroupTemplate {
+    pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
+    pub group: BrowseGroup,
+    pub version: String,
+    pub last_updated: String,
+    pub navbar_items: Vec<NavItem>,
+    pub translation: Rc<Translations>,
+}
+
+/// Redirect stub left at an item's old URL after its ID changed.
+#[derive(Template)]
+#[template(path = "redirect.html", whitespace = "minimize")]
+pub struct RedirectTemplate {
+    pub site_title: String,
+    pub target: String,
+    pub translation: Rc<Translations>,
+}
+
+/// Landing page of a multi-reader site, linking to the site of every reader profile.
+#[derive(Template)]
+#[template(path = "profiles.html", whitespace = "minimize")]
+pub struct ProfilesTemplate {
+    pub site_title: String,
+    /// Reader profiles as (name, href)
+    pub profiles: Vec<(String, String)>,
+    pub version: String,
+    pub translation: Rc<Translations>,
+}
+
 #[derive(Template)]
 #[template(path = "statistics/statistics.html", whitespace = "minimize")]
 pub struct StatsTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     /// "all" | "books" | "comics"
     pub stats_scope: String,
     /// Whether we should show the Books/Comics filter UI (only when both exist).
     pub show_type_filter: bool,
+    /// Devices of the device filter as (name, href); empty when the filter is hidden.
+    pub devices: Vec<(String, String)>,
+    /// Device the page is filtered to (optional)
+    pub stats_device: Option<String>,
     /// Base path for stats JSON (e.g. "/assets/json/statistics", "/assets/json/statistics/books")
     pub stats_json_base_path: String,
     pub reading_stats: ReadingStats,
     pub available_years: Vec<i32>,
+    /// Progress of the current reading goals (empty without goals and on filtered pages)
+    pub goals: Vec<GoalProgress>,
     pub version: String,
     pub last_updated: String,
     pub navbar_items: Vec<NavItem>,
@@ -111,10 +183,16 @@ pub struct StatsTemplate {
 #[template(path = "statistics/statistics_empty.html", whitespace = "minimize")]
 pub struct StatsEmptyTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     /// "all" | "books" | "comics"
     pub stats_scope: String,
     /// Whether we should show the Books/Comics filter UI (only when both exist).
     pub show_type_filter: bool,
+    /// Devices of the device filter as (name, href); empty when the filter is hidden.
+    pub devices: Vec<(String, String)>,
+    /// Device the page is filtered to (optional)
+    pub stats_device: Option<String>,
     pub version: String,
     pub last_updated: String,
     pub navbar_items: Vec<NavItem>,
@@ -125,6 +203,8 @@ pub struct StatsEmptyTemplate {
 #[template(path = "calendar/calendar.html", whitespace = "minimize")]
 pub struct CalendarTemplate {
     pub site_title: String,
+    /// URL path the site is served below (empty at the web root)
+    pub site_root: String,
     /// Whether we should show the Books/Comics filter UI (only when both exist).
     pub show_type_filter: bool,
     pub version: String,
@@ -132,3 +212,9 @@ pub struct CalendarTemplate {
     pub navbar_items: Vec<NavItem>,
     pub translation: Rc<Translations>,
 }
+
+#[derive(Template)]
+#[template(path = "opds/feed.xml")]
+pub struct OpdsAtomTemplate<'a> {
+    pub feed: &'a OpdsFeed,
+}
@@ -0,0 +1,97 @@
+use crate::models::{
+    BookCompletions, BookStatus, BrowseGroup, BrowseKind, LibraryItem, ReadCompletion, StatBook,
+    StatisticsData,
+};
+use crate::tests::{test_item, test_stat_book};
+
+fn item(
+    title: &str,
+    authors: &[&str],
+    series_number: Option<&str>,
+    md5: Option<&str>,
+) -> LibraryItem {
+    let mut item = test_item(
+        &title.to_lowercase(),
+        title,
+        &format!("/library/{}.epub", title),
+    )
+    .authors(authors)
+    .subjects(&["Fantasy"]);
+    if let Some(number) = series_number {
+        item = item.series("The Saga", number);
+    }
+    if let Some(md5) = md5 {
+        item = item
+            .md5(md5)
+            .percent_finished(1.0)
+            .status(BookStatus::Complete);
+    }
+    item.build()
+}
+
+fn stat_book(md5: &str, read_time: i64, completions: usize) -> StatBook {
+    StatBook {
+        total_read_time: Some(read_time),
+        completions: Some(BookCompletions::new(
+            (0..completions)
+                .map(|_| ReadCompletion::new("2024-01-01".into(), "2024-01-02".into(), 60, 1, 10))
+                .collect(),
+        )),
+        ..test_stat_book(1, md5)
+    }
+}
+
+#[test]
+fn test_browse_groups_aggregate_and_order() {
+    let items = vec![
+        item("Part Ten", &["Jane Doe"], Some("10"), None),
+        item("Part Two", &["jane doe", "Ann Other"], Some("2"), Some("b")),
+        item("Part One", &["Jane Doe"], Some("1"), Some("a")),
+    ];
+    let books = vec![stat_book("a", 3600, 2), stat_book("b", 1800, 1)];
+    let stats = StatisticsData {
+        stats_by_md5: books.iter().map(|b| (b.md5.clone(), b.clone())).collect(),
+        books,
+        page_stats: Vec::new(),
+        sessions: Vec::new(),
+    };
+
+    // Authors differing only in case share a group, named by the first spelling
+    let authors = BrowseGroup::collect(BrowseKind::Author, &items, Some(&stats));
+    let names: Vec<_> = authors.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, ["Ann Other", "Jane Doe"]);
+    let jane = &authors[1];
+    assert_eq!(jane.slug, "jane-doe");
+    assert_eq!(jane.entries.len(), 3);
+    assert_eq!(jane.read_time, 5400);
+    assert_eq!(jane.completions, 3);
+    let titles: Vec<_> = jane
+        .entries
+        .iter()
+        .map(|e| e.item.book_info.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Part One", "Part Ten", "Part Two"]);
+
+    // Series are ordered numerically, with read/unread progress
+    let series = BrowseGroup::collect(BrowseKind::Series, &items, Some(&stats));
+    assert_eq!(series.len(), 1);
+    let saga = &series[0];
+    assert_eq!(saga.slug, "the-saga");
+    let titles: Vec<_> = saga
+        .entries
+        .iter()
+        .map(|e| e.item.book_info.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Part One", "Part Two", "Part Ten"]);
+    assert_eq!(saga.finished_count(), 2);
+    assert_eq!(saga.unread_count(), 1);
+    assert_eq!(saga.finished_percentage(), 66);
+
+    // Without statistics, groups have no reading time
+    let subjects = BrowseGroup::collect(BrowseKind::Subject, &items, None);
+    assert_eq!(subjects.len(), 1);
+    assert_eq!(subjects[0].read_time, 0);
+    assert_eq!(subjects[0].completions, 0);
+
+    assert!(BrowseGroup::collect(BrowseKind::Series, &[], Some(&stats)).is_empty());
+}
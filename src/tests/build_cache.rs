@@ -0,0 +1,199 @@
+use super::library_scan::write_epub;
+use super::statistics::write_statistics_db;
+use crate::config::{CompletionSettings, GoalsConfig, ItemIdScheme, SiteConfig};
+use crate::library::{MetadataLocation, ScanCache};
+use crate::site_generator::{BuildCache, CacheManifestBuilder, PageTracker, SiteGenerator};
+use crate::time_config::TimeConfig;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Site configuration for a library of unread books in `dir/library`, written to `dir/site`.
+fn site_config(dir: &Path) -> SiteConfig {
+    SiteConfig {
+        output_dir: dir.join("site"),
+        site_title: "Library".to_string(),
+        include_unread: true,
+        library_paths: vec![dir.join("library")],
+        library_options: HashMap::new(),
+        metadata_location: MetadataLocation::InBookFolder,
+        statistics_db_paths: Vec::new(),
+        statistics_db_devices: HashMap::new(),
+        heatmap_scale_max: None,
+        time_config: TimeConfig::new(None, 0),
+        min_pages_per_day: None,
+        min_time_per_day: None,
+        session_gap: 300,
+        include_all_stats: false,
+        is_internal_server: false,
+        language: "en_US".to_string(),
+        id_scheme: ItemIdScheme::Title,
+        kosync_db_path: None,
+        site_root: String::new(),
+        profiles: Vec::new(),
+        goals: GoalsConfig::default(),
+        completion: CompletionSettings::default(),
+    }
+}
+
+#[test]
+fn test_cache_manifest_keeps_entries_of_files_from_previous_build() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path();
+    fs::create_dir_all(output_dir.join("books/kept")).unwrap();
+    fs::write(output_dir.join("books/kept/index.html"), "kept").unwrap();
+    fs::write(output_dir.join("index.html"), "root").unwrap();
+
+    let previous = CacheManifestBuilder::new("v1".to_string());
+    previous.register("/books/kept/", b"kept");
+    previous.register("/books/removed/", b"removed");
+    previous.register("/", b"old root");
+    previous
+        .write(output_dir.join("cache-manifest.json"))
+        .unwrap();
+
+    let current = CacheManifestBuilder::new("v2".to_string());
+    current.seed_from(output_dir.join("cache-manifest.json"));
+    current.register("/", b"root");
+    current.retain_existing(output_dir);
+    current
+        .write(output_dir.join("cache-manifest.json"))
+        .unwrap();
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(output_dir.join("cache-manifest.json")).unwrap()).unwrap();
+    let files = manifest["files"].as_object().unwrap();
+
+    assert_eq!(manifest["version"], "v2");
+    assert_eq!(files.len(), 2);
+    assert_eq!(
+        files["/books/kept/"],
+        CacheManifestBuilder::compute_hash(b"kept")
+    );
+    assert_eq!(files["/"], CacheManifestBuilder::compute_hash(b"root"));
+}
+
+#[test]
+fn test_cache_manifest_ignores_missing_previous_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let builder = CacheManifestBuilder::new("v1".to_string());
+    builder.seed_from(dir.path().join("cache-manifest.json"));
+    builder.retain_existing(dir.path());
+    builder
+        .write(dir.path().join("cache-manifest.json"))
+        .unwrap();
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(dir.path().join("cache-manifest.json")).unwrap()).unwrap();
+    assert!(manifest["files"].as_object().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_changing_goals_rerenders_pages() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("library")).unwrap();
+    write_epub(&dir.path().join("library/alpha.epub"), "Alpha");
+    let db = dir.path().join("statistics.sqlite3");
+    write_statistics_db(&db, 1, "abc", &[(1, 1_700_000_000), (2, 1_700_000_060)]);
+    let mut config = site_config(dir.path());
+    config.statistics_db_paths = vec![db];
+
+    SiteGenerator::new(config.clone()).generate().await.unwrap();
+    let statistics = config.output_dir.join("statistics/index.html");
+    fs::write(&statistics, "stale").unwrap();
+
+    // Unchanged inputs: the page is not rendered again
+    SiteGenerator::new(config.clone()).generate().await.unwrap();
+    assert_eq!(fs::read_to_string(&statistics).unwrap(), "stale");
+
+    config.goals.yearly.books = Some(12);
+    SiteGenerator::new(config.clone()).generate().await.unwrap();
+    assert_ne!(fs::read_to_string(&statistics).unwrap(), "stale");
+}
+
+#[test]
+fn test_page_tracker_renders_only_pages_with_changed_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("index.html");
+    let items = ScanCache::new();
+
+    // Nothing was rendered before
+    let tracker = PageTracker::new(HashMap::new(), "site".to_string(), &items);
+    assert!(tracker.needs_render("index", ["a", "b"], &output));
+    fs::write(&output, "page").unwrap();
+    let previous = tracker.into_pages();
+
+    let tracker = PageTracker::new(previous.clone(), "site".to_string(), &items);
+    assert!(!tracker.needs_render("index", ["a", "b"], &output));
+    assert!(tracker.needs_render("index", ["a", "c"], &output));
+    // Inputs are not concatenated: ("ab") differs from ("a", "b")
+    assert!(tracker.needs_render("index", ["ab"], &output));
+    assert!(tracker.needs_render("other", ["a", "b"], &output));
+    assert_eq!(tracker.skipped(), 1);
+
+    // The site-wide digest is part of every page's inputs
+    let tracker = PageTracker::new(previous.clone(), "changed".to_string(), &items);
+    assert!(tracker.needs_render("index", ["a", "b"], &output));
+
+    // A deleted output file is rendered again
+    fs::remove_file(&output).unwrap();
+    let tracker = PageTracker::new(previous, "site".to_string(), &items);
+    assert!(tracker.needs_render("index", ["a", "b"], &output));
+}
+
+#[test]
+fn test_build_cache_round_trip_and_invalidation() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // No cache yet
+    assert!(BuildCache::load(dir.path()).pages.is_empty());
+
+    let mut cache = BuildCache::new();
+    cache
+        .pages
+        .insert("books/alpha".to_string(), "digest".to_string());
+    cache.save(dir.path()).unwrap();
+    let loaded = BuildCache::load(dir.path());
+    assert_eq!(loaded.pages, cache.pages);
+
+    // A cache in another format is discarded
+    let path = dir.path().join(".build-cache.json");
+    let mut json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    json["format"] = 0.into();
+    fs::write(&path, serde_json::to_vec(&json).unwrap()).unwrap();
+    assert!(BuildCache::load(dir.path()).pages.is_empty());
+
+    fs::write(&path, "{ not json").unwrap();
+    assert!(BuildCache::load(dir.path()).pages.is_empty());
+}
+
+#[tokio::test]
+async fn test_regenerate_renders_only_pages_of_changed_items() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = dir.path().join("library");
+    fs::create_dir_all(&library).unwrap();
+    write_epub(&library.join("alpha.epub"), "Alpha");
+    write_epub(&library.join("beta.epub"), "Beta");
+    let config = site_config(dir.path());
+
+    SiteGenerator::new(config.clone()).generate().await.unwrap();
+    let alpha = config.output_dir.join("books/alpha/index.html");
+    let beta = config.output_dir.join("books/beta/index.html");
+    for page in [&alpha, &beta] {
+        fs::write(page, "stale").unwrap();
+    }
+
+    // Touch one book
+    fs::File::options()
+        .write(true)
+        .open(library.join("alpha.epub"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    SiteGenerator::new(config.clone()).generate().await.unwrap();
+
+    assert!(fs::read_to_string(&alpha).unwrap().contains("Alpha"));
+    assert_eq!(fs::read_to_string(&beta).unwrap(), "stale");
+}
@@ -0,0 +1,149 @@
+use crate::cli::{Cli, Command, InspectCommand};
+use crate::config::CompletionSettings;
+use crate::config_file::FileConfig;
+use crate::koreader::StatisticsCalculator;
+use crate::koreader::session::DEFAULT_SESSION_GAP_SECONDS;
+use crate::models::{ContentType, PageStat, StatBook, StatisticsData};
+use crate::tests::test_stat_book;
+use crate::time_config::TimeConfig;
+use clap::Parser;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+const DAY: i64 = 86_400;
+
+fn stat_book(id: i64, md5: &str, pages: i64, content_type: ContentType) -> StatBook {
+    StatBook {
+        title: md5.to_string(),
+        pages: Some(pages),
+        content_type: Some(content_type),
+        ..test_stat_book(id, md5)
+    }
+}
+
+/// One minute on each of `pages`, a page per minute starting at `start`.
+fn read(id_book: i64, pages: impl Iterator<Item = i64>, start: i64) -> Vec<PageStat> {
+    pages
+        .enumerate()
+        .map(|(i, page)| PageStat {
+            id_book,
+            page,
+            start_time: start + i as i64 * 60,
+            duration: 60,
+            device: None,
+        })
+        .collect()
+}
+
+fn stats_data(books: &[StatBook], page_stats: &[PageStat]) -> StatisticsData {
+    let mut data = StatisticsData {
+        stats_by_md5: books.iter().map(|b| (b.md5.clone(), b.clone())).collect(),
+        books: books.to_vec(),
+        page_stats: page_stats.to_vec(),
+        sessions: Vec::new(),
+    };
+    StatisticsCalculator::populate_sessions(&mut data, DEFAULT_SESSION_GAP_SECONDS);
+    data
+}
+
+fn completions(data: &StatisticsData, md5: &str) -> usize {
+    data.stats_by_md5[md5]
+        .completions
+        .as_ref()
+        .map_or(0, |c| c.total_completions)
+}
+
+#[test]
+fn test_completion_thresholds_per_content_type_and_trusted_status() {
+    // A novel read from page 1 to 70 of 100, and a comic skipping to its last page
+    let mut page_stats = read(1, 1..=70, 0);
+    page_stats.extend(read(2, (1..=28).chain(40..=40), DAY));
+    let books = vec![
+        stat_book(1, "novel", 100, ContentType::Book),
+        stat_book(2, "comic", 40, ContentType::Comic),
+    ];
+    let new_data = || stats_data(&books, &page_stats);
+    let time_config = TimeConfig::new(None, 0);
+    let finished: HashSet<String> = ["novel".to_string()].into();
+
+    // The default thresholds count neither
+    let mut data = new_data();
+    StatisticsCalculator::populate_completions(
+        &mut data,
+        &time_config,
+        &CompletionSettings::default(),
+        &finished,
+    );
+    assert_eq!(completions(&data, "novel"), 0);
+    assert_eq!(completions(&data, "comic"), 0);
+
+    // Looser comic thresholds only apply to comics
+    let settings = FileConfig::parse("[completion.comics]\nmin_completion_percentage = 0.7")
+        .unwrap()
+        .completion;
+    assert_eq!(settings.books, CompletionSettings::default().books);
+    let mut data = new_data();
+    StatisticsCalculator::populate_completions(&mut data, &time_config, &settings, &finished);
+    assert_eq!(completions(&data, "novel"), 0);
+    assert_eq!(completions(&data, "comic"), 1);
+
+    // Trusting KOReader's status counts the finished novel's latest progression
+    let settings = CompletionSettings {
+        trust_status: true,
+        ..Default::default()
+    };
+    let mut data = new_data();
+    StatisticsCalculator::populate_completions(&mut data, &time_config, &settings, &finished);
+    let novel = data.stats_by_md5["novel"].completions.clone().unwrap();
+    assert_eq!(novel.total_completions, 1);
+    assert_eq!(novel.entries[0].pages_read, 70);
+    assert_eq!(novel.entries[0].reading_time, 70 * 60);
+    assert_eq!(completions(&data, "comic"), 0);
+
+    assert!(FileConfig::parse("[completion.books]\nmin_coverage = 0.5").is_err());
+    let invalid: CompletionSettings =
+        toml::from_str("[books]\nmin_early_percentage = 1.5").unwrap();
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_completion_explain_reports_each_progression() {
+    use crate::koreader::completion::ReadCompletionDetector;
+
+    // An abandoned start, then a full read-through from the beginning
+    let mut page_stats = read(1, 1..=40, 0);
+    page_stats.extend(read(1, 1..=100, 10 * DAY));
+    let book = stat_book(1, "book", 100, ContentType::Book);
+    let detector = ReadCompletionDetector::with_config_and_time(
+        CompletionSettings::default().books,
+        TimeConfig::new(None, 0),
+    );
+
+    let data = stats_data(std::slice::from_ref(&book), &page_stats);
+
+    let reports = detector.explain(&book, &data, false);
+    assert_eq!(reports.len(), 2);
+    assert!(!reports[0].counted);
+    assert_eq!(reports[0].pages_visited, 40);
+    assert!(reports[0].reason.contains("needs 78%"));
+    assert!(reports[0].reason.contains("from page 98 on"));
+    assert!(reports[1].counted);
+    assert_eq!(reports[1].session_count, 1);
+    assert_eq!(reports[1].start_date, "1970-01-11");
+    assert!(reports[1].reason.contains("100.0%"));
+
+    let completions = detector.detect_completions(&book, &data, false);
+    assert_eq!(completions.total_completions, 1);
+
+    // `koshelf inspect completions <book>` next to `koshelf inspect <book>`
+    let inspect = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+        Some(Command::Inspect(args)) => args,
+        other => panic!("unexpected command: {:?}", other),
+    };
+    let args = inspect(&["koshelf", "inspect", "completions", "book.epub"]);
+    assert!(matches!(args.command, Some(InspectCommand::Completions(_))));
+    assert_eq!(args.book(), Some(&PathBuf::from("book.epub")));
+    let args = inspect(&["koshelf", "inspect", "book.epub"]);
+    assert!(args.command.is_none());
+    assert_eq!(args.book(), Some(&PathBuf::from("book.epub")));
+}
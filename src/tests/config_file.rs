@@ -0,0 +1,202 @@
+use crate::cli::Cli;
+use crate::config_file::FileConfig;
+use clap::CommandFactory;
+use std::fs;
+use std::path::PathBuf;
+
+fn parse_with_config(args: &[&str]) -> Cli {
+    let matches = Cli::command()
+        .try_get_matches_from(std::iter::once("koshelf").chain(args.iter().copied()))
+        .expect("valid arguments");
+    Cli::from_matches_with_config(&matches).expect("valid configuration")
+}
+
+#[test]
+fn test_config_file_fills_unset_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("koshelf.toml");
+    fs::write(
+        &config_path,
+        r#"
+title = "My Shelf"
+language = "de_DE"
+include_unread = true
+min_pages_per_day = 5
+statistics_db = ["phone.sqlite3", { path = "/data/ereader.sqlite3", device = "Kobo" }]
+library = ["books", { path = "/data/comics", include_unread = false }]
+"#,
+    )
+    .unwrap();
+
+    let cli = parse_with_config(&["--config", config_path.to_str().unwrap()]);
+
+    assert_eq!(cli.title, "My Shelf");
+    assert_eq!(cli.language, "de_DE");
+    assert!(cli.include_unread);
+    assert_eq!(cli.min_pages_per_day, Some(5));
+    assert_eq!(cli.port, 3000);
+    assert_eq!(
+        cli.statistics_db,
+        vec![
+            dir.path().join("phone.sqlite3"),
+            PathBuf::from("/data/ereader.sqlite3")
+        ]
+    );
+    assert_eq!(
+        cli.library_path,
+        vec![dir.path().join("books"), PathBuf::from("/data/comics")]
+    );
+    assert_eq!(
+        cli.library_options[&PathBuf::from("/data/comics")].include_unread,
+        Some(false)
+    );
+    assert_eq!(
+        cli.statistics_db_devices[&PathBuf::from("/data/ereader.sqlite3")],
+        "Kobo"
+    );
+    assert_eq!(cli.statistics_db_devices.len(), 1);
+}
+
+#[test]
+fn test_cli_flags_override_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("koshelf.toml");
+    fs::write(
+        &config_path,
+        r#"
+title = "From File"
+port = 8080
+library_path = ["/data/books"]
+hashdocsettings_path = "/data/hashdocsettings"
+"#,
+    )
+    .unwrap();
+
+    let cli = parse_with_config(&[
+        "--config",
+        config_path.to_str().unwrap(),
+        "--title",
+        "From CLI",
+        "-i",
+        "/cli/books",
+        "--docsettings-path",
+        "/cli/docsettings",
+    ]);
+
+    assert_eq!(cli.title, "From CLI");
+    assert_eq!(cli.port, 8080);
+    assert_eq!(cli.library_path, vec![PathBuf::from("/cli/books")]);
+    assert_eq!(
+        cli.docsettings_path,
+        Some(PathBuf::from("/cli/docsettings"))
+    );
+    assert_eq!(cli.hashdocsettings_path, None);
+}
+
+#[test]
+fn test_subcommands_select_run_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("koshelf.toml");
+    fs::write(
+        &config_path,
+        r#"
+title = "From File"
+output = "site"
+port = 8080
+watch = true
+"#,
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+
+    // Global options work after the subcommand and still win over the file
+    let cli = parse_with_config(&["generate", "--config", config, "--title", "From CLI"]);
+    assert_eq!(cli.title, "From CLI");
+    assert_eq!(cli.output, Some(dir.path().join("site")));
+    assert!(!cli.watch);
+
+    let cli = parse_with_config(&["--config", config, "watch", "-o", "/srv/shelf"]);
+    assert_eq!(cli.output, Some(PathBuf::from("/srv/shelf")));
+    assert!(cli.watch);
+
+    let cli = parse_with_config(&["--config", config, "serve", "--port", "4000"]);
+    assert_eq!(cli.output, None);
+    assert_eq!(cli.port, 4000);
+
+    // Each subcommand only accepts its own mode flags
+    let parse = |args: &[&str]| {
+        Cli::command().try_get_matches_from(std::iter::once("koshelf").chain(args.iter().copied()))
+    };
+    assert!(parse(&["generate", "--port", "4000"]).is_err());
+    assert!(parse(&["serve", "--output", "/srv/shelf"]).is_err());
+    let matches = parse(&["--output", "/srv/shelf", "serve"]).unwrap();
+    assert!(Cli::from_matches_with_config(&matches).is_err());
+
+    // The flag-only invocation keeps working
+    let cli = parse_with_config(&["--config", config, "--output", "/srv/shelf"]);
+    assert!(cli.command.is_none());
+    assert_eq!(cli.output, Some(PathBuf::from("/srv/shelf")));
+    assert!(cli.watch);
+}
+
+#[test]
+fn test_config_file_rejects_unknown_keys() {
+    assert!(FileConfig::parse("titel = \"typo\"").is_err());
+    assert!(FileConfig::parse("library = [{ path = \"/x\", unknown = 1 }]").is_err());
+    assert!(FileConfig::parse("[goals.yearly]\nbook = 12").is_err());
+}
+
+#[test]
+fn test_config_file_reader_profiles() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("books")).unwrap();
+    fs::create_dir(dir.path().join("anna-docsettings")).unwrap();
+    fs::write(dir.path().join("anna.sqlite3"), "").unwrap();
+    fs::write(dir.path().join("ben.sqlite3"), "").unwrap();
+    let config_path = dir.path().join("koshelf.toml");
+    fs::write(
+        &config_path,
+        r#"
+library = ["books"]
+
+[[profile]]
+name = "Anna"
+docsettings_path = "anna-docsettings"
+statistics_db = { path = "anna.sqlite3", device = "Kobo" }
+timezone = "Europe/Berlin"
+
+[[profile]]
+name = "Ben"
+statistics_db = ["ben.sqlite3"]
+language = "de_DE"
+"#,
+    )
+    .unwrap();
+
+    let cli = parse_with_config(&["--config", config_path.to_str().unwrap()]);
+
+    assert_eq!(cli.profiles.len(), 2);
+    let anna = &cli.profiles[0];
+    assert_eq!(anna.name, "Anna");
+    assert_eq!(
+        anna.docsettings_path,
+        Some(dir.path().join("anna-docsettings"))
+    );
+    assert_eq!(anna.statistics_db, vec![dir.path().join("anna.sqlite3")]);
+    assert_eq!(
+        anna.statistics_db_devices[&dir.path().join("anna.sqlite3")],
+        "Kobo"
+    );
+    assert_eq!(anna.timezone.as_deref(), Some("Europe/Berlin"));
+    let ben = &cli.profiles[1];
+    assert_eq!(ben.docsettings_path, None);
+    assert_eq!(ben.language.as_deref(), Some("de_DE"));
+    // Profile databases are not part of the global statistics
+    assert!(cli.statistics_db.is_empty());
+
+    // Profile names must map to distinct URL slugs
+    cli.validate().expect("valid profiles");
+    let mut duplicate = cli.clone();
+    duplicate.profiles[1].name = "anna".to_string();
+    assert!(duplicate.validate().is_err());
+}
@@ -0,0 +1,398 @@
+use crate::parsers::{DjvuParser, PdfParser};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::fs;
+use std::io::Write;
+
+/// Assemble a PDF from object bodies numbered from 1. The cross-reference table is left
+/// out on purpose; the parser locates objects by scanning.
+fn build_pdf(objects: &[Vec<u8>], trailer: &str) -> Vec<u8> {
+    let mut pdf = b"%PDF-1.7\n".to_vec();
+    for (i, body) in objects.iter().enumerate() {
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    pdf.extend_from_slice(format!("trailer\n{}\n%%EOF\n", trailer).as_bytes());
+    pdf
+}
+
+fn stream(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = format!("<< {} /Length {} >>\nstream\n", dict, data.len()).into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(b"\nendstream");
+    body
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn test_pdf_info_dictionary_page_count_and_jpeg_cover() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fallback.pdf");
+
+    let jpeg = b"\xFF\xD8\xFF\xE0fake-jpeg-data\xFF\xD9".to_vec();
+    // Title as UTF-16BE hex string
+    let objects = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R 4 0 R 4 0 R] /Count 3 /Resources << /XObject << /Im1 5 0 R /Im2 6 0 R >> >> >>".to_vec(),
+        b"<< /Type /Page /Parent 2 0 R >>".to_vec(),
+        b"<< /Type /Page /Parent 2 0 R >>".to_vec(),
+        stream(
+            "/Type /XObject /Subtype /Image /Width 600 /Height 800 /BitsPerComponent 8 /ColorSpace /DeviceRGB /Filter /DCTDecode",
+            &jpeg,
+        ),
+        stream(
+            "/Type /XObject /Subtype /Image /Width 10 /Height 10 /BitsPerComponent 8 /ColorSpace /DeviceGray",
+            &[0u8; 100],
+        ),
+        b"<< /Title <FEFF0043006100660065> /Author (Jane Doe; John \\(JJ\\) Smith) /Subject (A test document) /Keywords (alpha, beta) >>".to_vec(),
+    ];
+    fs::write(
+        &path,
+        build_pdf(&objects, "<< /Size 8 /Root 1 0 R /Info 7 0 R >>"),
+    )
+    .unwrap();
+
+    let info = PdfParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "Cafe");
+    assert_eq!(info.authors, vec!["Jane Doe", "John (JJ) Smith"]);
+    assert_eq!(info.description.as_deref(), Some("A test document"));
+    assert_eq!(info.subjects, vec!["alpha", "beta"]);
+    assert_eq!(info.pages, Some(3));
+    assert_eq!(info.cover_mime_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(info.cover_data, Some(jpeg));
+}
+
+#[tokio::test]
+async fn test_pdf_xmp_metadata_and_compressed_objects() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fallback.pdf");
+
+    let xmp = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:title><rdf:Alt><rdf:li xml:lang="x-default">Rust &amp; PDFs</rdf:li></rdf:Alt></dc:title>
+<dc:creator><rdf:Seq><rdf:li>First Author</rdf:li><rdf:li>Second Author</rdf:li></rdf:Seq></dc:creator>
+<dc:subject><rdf:Bag><rdf:li>Programming</rdf:li></rdf:Bag></dc:subject>
+<dc:language><rdf:Bag><rdf:li>en</rdf:li></rdf:Bag></dc:language>
+</rdf:Description></rdf:RDF></x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    // Page tree objects live in a compressed object stream (PDF 1.5+)
+    let packed = [
+        (6, "<< /Type /Pages /Kids [7 0 R] /Count 1 >>"),
+        (
+            7,
+            "<< /Type /Page /Parent 6 0 R /Resources << /XObject << /Im0 5 0 R >> >> >>",
+        ),
+    ];
+    let mut header = String::new();
+    let mut body = String::new();
+    for (number, object) in packed {
+        header.push_str(&format!("{} {} ", number, body.len()));
+        body.push_str(object);
+        body.push(' ');
+    }
+    let objstm = format!("{}{}", header, body);
+
+    let pixels: Vec<u8> = (0..4 * 2 * 3).map(|i| (i * 10) as u8).collect();
+    let objects = vec![
+        b"<< /Type /Catalog /Pages 6 0 R /Metadata 4 0 R >>".to_vec(),
+        b"<< /Title (Info Title) /Author (Info Author) >>".to_vec(),
+        stream(
+            &format!(
+                "/Type /ObjStm /N 2 /First {} /Filter /FlateDecode",
+                header.len()
+            ),
+            &zlib(objstm.as_bytes()),
+        ),
+        stream("/Type /Metadata /Subtype /XML", xmp.as_bytes()),
+        stream(
+            "/Type /XObject /Subtype /Image /Width 4 /Height 2 /BitsPerComponent 8 /ColorSpace /DeviceRGB /Filter /FlateDecode",
+            &zlib(&pixels),
+        ),
+    ];
+    fs::write(
+        &path,
+        build_pdf(&objects, "<< /Size 8 /Root 1 0 R /Info 2 0 R >>"),
+    )
+    .unwrap();
+
+    let info = PdfParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "Rust & PDFs");
+    assert_eq!(info.authors, vec!["First Author", "Second Author"]);
+    assert_eq!(info.subjects, vec!["Programming"]);
+    assert_eq!(info.language.as_deref(), Some("en"));
+    assert_eq!(info.pages, Some(1));
+    assert_eq!(info.cover_mime_type.as_deref(), Some("image/png"));
+    let cover = image::load_from_memory(&info.cover_data.unwrap()).unwrap();
+    assert_eq!((cover.width(), cover.height()), (4, 2));
+}
+
+#[tokio::test]
+async fn test_pdf_without_metadata_falls_back_to_filename() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Some Scanned Book.pdf");
+    fs::write(&path, b"not a pdf at all").unwrap();
+
+    let info = PdfParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "Some Scanned Book");
+    assert!(info.authors.is_empty());
+    assert!(info.cover_data.is_none());
+}
+
+#[tokio::test]
+async fn test_pdf_oversized_image_parameters_give_no_cover() {
+    let dir = tempfile::tempdir().unwrap();
+    let images = [
+        // Predictor rows wider than any buffer could be
+        stream(
+            "/Type /XObject /Subtype /Image /Width 4 /Height 2 /BitsPerComponent 8 /ColorSpace /DeviceGray /Filter /FlateDecode /DecodeParms << /Predictor 15 /Colors 4 /BitsPerComponent 16 /Columns 9223372036854775807 >>",
+            &zlib(&[0u8; 64]),
+        ),
+        // Dimensions whose sample and pixel counts overflow
+        stream(
+            "/Type /XObject /Subtype /Image /Width 4294967295 /Height 4294967295 /BitsPerComponent 16 /ColorSpace /DeviceCMYK",
+            &[0u8; 64],
+        ),
+    ];
+    for (i, image) in images.into_iter().enumerate() {
+        let path = dir.path().join(format!("oversized-{}.pdf", i));
+        let objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /Resources << /XObject << /Im0 4 0 R >> >> >>".to_vec(),
+            image,
+        ];
+        fs::write(&path, build_pdf(&objects, "<< /Size 5 /Root 1 0 R >>")).unwrap();
+
+        let info = PdfParser::new().parse(&path).await.unwrap();
+        assert_eq!(info.pages, Some(1));
+        assert!(info.cover_data.is_none());
+    }
+}
+
+/// Append object `number` to `pdf` and return its offset.
+fn push_object(pdf: &mut Vec<u8>, number: usize, body: &[u8]) -> usize {
+    let offset = pdf.len();
+    pdf.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+    pdf.extend_from_slice(body);
+    pdf.extend_from_slice(b"\nendobj\n");
+    offset
+}
+
+/// Rows encoded with the PNG `Up` predictor.
+fn png_up(rows: &[Vec<u8>]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut previous = vec![0u8; rows[0].len()];
+    for row in rows {
+        encoded.push(2);
+        encoded.extend(row.iter().zip(&previous).map(|(b, p)| b.wrapping_sub(*p)));
+        previous = row.clone();
+    }
+    encoded
+}
+
+#[tokio::test]
+async fn test_pdf_objects_are_read_through_the_xref_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fallback.pdf");
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let offsets = [
+        push_object(&mut pdf, 1, b"<< /Type /Catalog /Pages 3 0 R >>"),
+        push_object(&mut pdf, 2, b"<< /Title (Current Title) >>"),
+        push_object(&mut pdf, 3, b"<< /Type /Pages /Kids [4 0 R] /Count 1 >>"),
+        push_object(
+            &mut pdf,
+            4,
+            b"<< /Type /Page /Parent 3 0 R /Resources << /XObject << /Im0 5 0 R >> >> >>",
+        ),
+        push_object(
+            &mut pdf,
+            5,
+            &stream(
+                "/Type /XObject /Subtype /Image /Width 8 /Height 2 /ImageMask true",
+                &[0b1010_1010, 0b0000_1111],
+            ),
+        ),
+    ];
+    // An object the table does not point to; scanning the file would pick it up instead
+    push_object(&mut pdf, 2, b"<< /Title (Stale Title) >>");
+
+    let xref_at = pdf.len();
+    pdf.extend_from_slice(b"xref\n0 6\n0000000000 65535 f \n");
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size 6 /Root 1 0 R /Info 2 0 R >>\nstartxref\n{}\n%%EOF\n",
+            xref_at
+        )
+        .as_bytes(),
+    );
+    fs::write(&path, pdf).unwrap();
+
+    let info = PdfParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "Current Title");
+    assert_eq!(info.pages, Some(1));
+    assert_eq!(info.cover_mime_type.as_deref(), Some("image/png"));
+    let cover = image::load_from_memory(&info.cover_data.unwrap())
+        .unwrap()
+        .to_luma8();
+    assert_eq!(cover.dimensions(), (8, 2));
+    // Mask samples of 0 are painted black
+    assert_eq!(cover.get_pixel(0, 0).0, [255]);
+    assert_eq!(cover.get_pixel(1, 0).0, [0]);
+    assert_eq!(cover.get_pixel(7, 1).0, [255]);
+}
+
+#[tokio::test]
+async fn test_pdf_xref_stream_with_predictors_and_indexed_cover() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fallback.pdf");
+
+    let packed = [
+        (2, "<< /Title (Packed Title) >>"),
+        (6, "<< /Type /Pages /Kids [7 0 R] /Count 1 >>"),
+        (
+            7,
+            "<< /Type /Page /Parent 6 0 R /Resources << /XObject << /Im0 4 0 R >> >> >>",
+        ),
+    ];
+    let mut header = String::new();
+    let mut body = String::new();
+    for (number, object) in packed {
+        header.push_str(&format!("{} {} ", number, body.len()));
+        body.push_str(object);
+        body.push(' ');
+    }
+
+    // Two rows of four 2-bit palette indices (0, 1, 2, 3)
+    let samples = png_up(&[vec![0b0001_1011], vec![0b0001_1011]]);
+    let mut pdf = b"%PDF-1.7\n".to_vec();
+    let catalog = push_object(&mut pdf, 1, b"<< /Type /Catalog /Pages 6 0 R >>");
+    let objstm = push_object(
+        &mut pdf,
+        3,
+        &stream(
+            &format!(
+                "/Type /ObjStm /N 3 /First {} /Filter /FlateDecode",
+                header.len()
+            ),
+            &zlib(format!("{}{}", header, body).as_bytes()),
+        ),
+    );
+    let image = push_object(
+        &mut pdf,
+        4,
+        &stream(
+            "/Type /XObject /Subtype /Image /Width 4 /Height 2 /BitsPerComponent 2 \
+             /ColorSpace [/Indexed /DeviceRGB 3 <FF000000FF000000FFFFFFFF>] /Filter /FlateDecode \
+             /DecodeParms << /Predictor 15 /Colors 1 /BitsPerComponent 2 /Columns 4 >>",
+            &zlib(&samples),
+        ),
+    );
+    // Not listed in the cross-reference stream, which puts object 2 in the object stream
+    push_object(&mut pdf, 2, b"<< /Title (Stale Title) >>");
+
+    let xref_at = pdf.len();
+    let entry = |kind: u8, field: usize, index: u8| {
+        let field = field as u16;
+        vec![kind, (field >> 8) as u8, field as u8, index]
+    };
+    let rows = vec![
+        entry(0, 0, 255),
+        entry(1, catalog, 0),
+        entry(2, 3, 0),
+        entry(1, objstm, 0),
+        entry(1, image, 0),
+        entry(1, xref_at, 0),
+        entry(2, 3, 1),
+        entry(2, 3, 2),
+    ];
+    push_object(
+        &mut pdf,
+        5,
+        &stream(
+            "/Type /XRef /Size 8 /W [1 2 1] /Root 1 0 R /Info 2 0 R /Filter /FlateDecode \
+             /DecodeParms << /Predictor 12 /Columns 4 >>",
+            &zlib(&png_up(&rows)),
+        ),
+    );
+    pdf.extend_from_slice(format!("startxref\n{}\n%%EOF\n", xref_at).as_bytes());
+    fs::write(&path, pdf).unwrap();
+
+    let info = PdfParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "Packed Title");
+    assert_eq!(info.pages, Some(1));
+    let cover = image::load_from_memory(&info.cover_data.unwrap())
+        .unwrap()
+        .to_rgb8();
+    assert_eq!(cover.dimensions(), (4, 2));
+    assert_eq!(cover.get_pixel(0, 1).0, [255, 0, 0]);
+    assert_eq!(cover.get_pixel(2, 1).0, [0, 0, 255]);
+    assert_eq!(cover.get_pixel(3, 0).0, [255, 255, 255]);
+}
+
+fn iff_chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+fn djvu_form(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = kind.to_vec();
+    for chunk in chunks {
+        data.extend_from_slice(chunk);
+    }
+    iff_chunk(b"FORM", &data)
+}
+
+#[tokio::test]
+async fn test_djvu_page_count_and_annotation_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fallback.djvu");
+
+    let page = || djvu_form(b"DJVU", &[iff_chunk(b"INFO", &[0; 10])]);
+    let annotations =
+        br#"(metadata (title "An \"Old\" Scan") (author "A. Writer") (publisher "Press"))"#;
+    let shared = djvu_form(b"DJVI", &[iff_chunk(b"ANTa", annotations)]);
+    // Bundled directory with four components (three pages and the shared annotations)
+    let dirm = iff_chunk(b"DIRM", &[0x81, 0x00, 0x04, 0, 0, 0, 0]);
+
+    let mut file = b"AT&T".to_vec();
+    file.extend(djvu_form(b"DJVM", &[dirm, shared, page(), page(), page()]));
+    fs::write(&path, file).unwrap();
+
+    let info = DjvuParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "An \"Old\" Scan");
+    assert_eq!(info.authors, vec!["A. Writer"]);
+    assert_eq!(info.publisher.as_deref(), Some("Press"));
+    assert_eq!(info.pages, Some(3));
+    assert!(info.cover_data.is_none());
+}
+
+#[tokio::test]
+async fn test_djvu_single_page_without_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Single Page.djvu");
+
+    let mut file = b"AT&T".to_vec();
+    file.extend(djvu_form(b"DJVU", &[iff_chunk(b"INFO", &[0; 10])]));
+    fs::write(&path, file).unwrap();
+
+    let info = DjvuParser::new().parse(&path).await.unwrap();
+    assert_eq!(info.title, "Single Page");
+    assert_eq!(info.pages, Some(1));
+}
@@ -0,0 +1,188 @@
+use crate::export::{ExportItem, ExportTarget};
+use crate::models::{Annotation, LibraryItem};
+use crate::tests::test_item;
+use std::fs;
+
+fn highlight(pos: &str, text: &str, note: Option<&str>) -> Annotation {
+    Annotation {
+        chapter: Some("Chapter 1".to_string()),
+        datetime: Some("2025-01-02 10:00:00".to_string()),
+        pageno: Some(7),
+        pos0: Some(format!("{pos}.0")),
+        pos1: Some(format!("{pos}.9")),
+        text: Some(text.to_string()),
+        note: note.map(str::to_string),
+        color: None,
+        drawer: None,
+        datetime_updated: None,
+        page: None,
+    }
+}
+
+fn item(title: &str, annotations: Vec<Annotation>) -> LibraryItem {
+    test_item("dune-1a2b3c4d", title, "/library/Dune.epub")
+        .authors(&["Frank Herbert"])
+        .language("en")
+        .annotations(annotations)
+        .md5("1a2b3c4d")
+        .percent_finished(0.5)
+        .build()
+}
+
+#[test]
+fn test_obsidian_reexport_appends_only_new_highlights() {
+    let vault = tempfile::tempdir().unwrap();
+    let note_path = vault.path().join("Dune Messiah.md");
+
+    let first = vec![item(
+        "Dune: Messiah",
+        vec![highlight("/body/p[1]", "Fear is the mind-killer.", None)],
+    )];
+    let summary = ExportTarget::Obsidian
+        .exporter()
+        .export(&ExportItem::collect(&first), vault.path())
+        .unwrap();
+    assert_eq!((summary.items, summary.highlights), (1, 1));
+
+    let note = fs::read_to_string(&note_path).unwrap();
+    assert!(note.starts_with("---\ntitle: \"Dune: Messiah\"\n"));
+    assert!(note.contains("progress: 50\n"));
+    assert_eq!(note.matches("\n^kos-").count(), 1);
+
+    // The user edits the note; KoReader gets a second highlight and a note on the first
+    let edited = note.replace("tags:", "aliases:\n  - \"Messiah\"\ntags:") + "\nMy thoughts\n";
+    fs::write(&note_path, &edited).unwrap();
+    let second = vec![item(
+        "Dune: Messiah",
+        vec![
+            highlight("/body/p[1]", "Fear is the mind-killer.", Some("Litany")),
+            highlight(
+                "/body/p[5]",
+                "The spice must flow.",
+                Some("Line one\nline two"),
+            ),
+        ],
+    )];
+    let summary = ExportTarget::Obsidian
+        .exporter()
+        .export(&ExportItem::collect(&second), vault.path())
+        .unwrap();
+    assert_eq!(summary.highlights, 1);
+
+    let merged = fs::read_to_string(&note_path).unwrap();
+    assert_eq!(merged.matches("\n^kos-").count(), 2);
+    assert_eq!(merged.matches("Fear is the mind-killer.").count(), 1);
+    assert!(merged.contains("aliases:\n  - \"Messiah\""));
+    assert!(merged.contains("My thoughts\n"));
+    assert!(merged.contains("> **Note:** Line one\n> line two\n"));
+    assert!(merged.contains("> — Chapter 1 · p. 7\n"));
+
+    // Nothing new: the note stays as it is
+    ExportTarget::Obsidian
+        .exporter()
+        .export(&ExportItem::collect(&second), vault.path())
+        .unwrap();
+    assert_eq!(fs::read_to_string(&note_path).unwrap(), merged);
+}
+
+#[test]
+fn test_logseq_blocks_have_stable_uuids() {
+    let graph = tempfile::tempdir().unwrap();
+    let items = vec![item(
+        "Dune",
+        vec![highlight(
+            "/body/p[1]",
+            "Fear is the mind-killer.",
+            Some("Litany"),
+        )],
+    )];
+    let export = ExportItem::collect(&items);
+    ExportTarget::Logseq
+        .exporter()
+        .export(&export, graph.path())
+        .unwrap();
+    let page = fs::read_to_string(graph.path().join("pages").join("Dune.md")).unwrap();
+    assert!(page.starts_with("title:: Dune\nauthor:: [[Frank Herbert]]\n"));
+    assert!(page.contains("- > Fear is the mind-killer.\n"));
+    assert!(page.contains("\t- Litany\n"));
+
+    let summary = ExportTarget::Logseq
+        .exporter()
+        .export(&export, graph.path())
+        .unwrap();
+    assert_eq!(summary.highlights, 0);
+    let again = fs::read_to_string(graph.path().join("pages").join("Dune.md")).unwrap();
+    assert_eq!(again, page);
+    assert_eq!(again.matches("  id:: ").count(), 1);
+}
+
+#[test]
+fn test_readwise_csv_and_anki_tsv_escaping() {
+    let dir = tempfile::tempdir().unwrap();
+    let items = vec![item(
+        "Dune",
+        vec![highlight(
+            "/body/p[1]",
+            "He said \"fear\",\nthen <left>",
+            Some("a\tb"),
+        )],
+    )];
+    let export = ExportItem::collect(&items);
+
+    ExportTarget::Readwise
+        .exporter()
+        .export(&export, dir.path())
+        .unwrap();
+    let csv = fs::read_to_string(dir.path().join("koshelf-readwise.csv")).unwrap();
+    let mut rows = csv.split("\r\n");
+    assert_eq!(
+        rows.next(),
+        Some("Highlight,Title,Author,URL,Note,Location,Location Type,Date")
+    );
+    assert_eq!(
+        rows.next(),
+        Some(
+            "\"He said \"\"fear\"\",\nthen <left>\",Dune,Frank Herbert,,a\tb,7,page,2025-01-02 10:00:00"
+        )
+    );
+
+    let tsv_path = dir.path().join("cards.tsv");
+    ExportTarget::Anki
+        .exporter()
+        .export(&export, &tsv_path)
+        .unwrap();
+    let tsv = fs::read_to_string(tsv_path).unwrap();
+    let card = tsv.lines().find(|line| !line.starts_with('#')).unwrap();
+    let fields: Vec<&str> = card.split('\t').collect();
+    assert_eq!(fields.len(), 4);
+    assert!(fields[0].starts_with("koshelf-"));
+    assert_eq!(fields[1], "He said \"fear\",<br>then &lt;left&gt;");
+    assert!(fields[2].starts_with("a b<br><br><small>Dune — Frank Herbert"));
+    assert_eq!(fields[3], "koshelf koshelf::dune");
+}
+
+#[test]
+fn test_shared_titles_get_stable_note_names() {
+    let mut first = item("Dune", vec![highlight("/body/p[1]", "First", None)]);
+    first.id = "dune-aaaa".to_string();
+    let mut second = item("Dune", vec![highlight("/body/p[1]", "Second", None)]);
+    second.id = "dune-bbbb".to_string();
+
+    // The same notes are written whichever item comes first
+    for items in [
+        vec![first.clone(), second.clone()],
+        vec![second.clone(), first.clone()],
+    ] {
+        let vault = tempfile::tempdir().unwrap();
+        ExportTarget::Obsidian
+            .exporter()
+            .export(&ExportItem::collect(&items), vault.path())
+            .unwrap();
+
+        let note = fs::read_to_string(vault.path().join("Dune (dune-aaaa).md")).unwrap();
+        assert!(note.contains("First"));
+        let note = fs::read_to_string(vault.path().join("Dune (dune-bbbb).md")).unwrap();
+        assert!(note.contains("Second"));
+        assert!(!vault.path().join("Dune.md").exists());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::config::{GoalTargets, GoalsConfig};
+use crate::koreader::{GoalCalculator, StatisticsCalculator};
+use crate::models::{
+    BookCompletions, GoalMetric, GoalPeriod, PageStat, ReadCompletion, StatBook, StatisticsData,
+};
+use crate::time_config::TimeConfig;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+fn completion(end_date: &str) -> ReadCompletion {
+    ReadCompletion::new(end_date.to_string(), end_date.to_string(), 3600, 1, 100)
+}
+
+/// One book read for an hour (60 pages) on 2023-01-10, completed twice in 2023 and once in 2022.
+fn sample_data() -> StatisticsData {
+    let start = 1673352000; // 2023-01-10 12:00:00 UTC
+    let page_stats = (0..60)
+        .map(|page| PageStat {
+            id_book: 1,
+            page,
+            start_time: start + page * 60,
+            duration: 60,
+            device: None,
+        })
+        .collect();
+    StatisticsData {
+        books: vec![StatBook {
+            id: 1,
+            title: "Book 1".to_string(),
+            authors: "Author 1".to_string(),
+            notes: None,
+            last_open: None,
+            highlights: None,
+            pages: None,
+            series: None,
+            language: None,
+            md5: "abc".to_string(),
+            content_type: None,
+            total_read_time: None,
+            total_read_pages: None,
+            completions: Some(BookCompletions::new(vec![
+                completion("2022-12-30"),
+                completion("2023-01-10"),
+                completion("2023-03-01"),
+            ])),
+        }],
+        page_stats,
+        sessions: Vec::new(),
+        stats_by_md5: HashMap::new(),
+    }
+}
+
+#[test]
+fn test_goal_progress_pace_and_projection() {
+    let mut data = sample_data();
+    let reading_stats = StatisticsCalculator::calculate_stats(&mut data, &TimeConfig::new(None, 0));
+    let goals = GoalsConfig {
+        yearly: GoalTargets {
+            books: Some(4),
+            hours: Some(2),
+            pages: Some(50),
+        },
+        monthly: GoalTargets {
+            pages: Some(100),
+            ..Default::default()
+        },
+    };
+
+    // Halfway through 2023 (day 182 of 365)
+    let today = NaiveDate::from_ymd_opt(2023, 7, 1).unwrap();
+    let progress = GoalCalculator::current(&goals, &data, &reading_stats, today);
+    assert_eq!(progress.len(), 4);
+
+    let books = &progress[0];
+    assert_eq!(
+        (books.period, books.metric),
+        (GoalPeriod::Year, GoalMetric::Books)
+    );
+    assert_eq!(books.period_key, "2023");
+    assert_eq!(books.actual, 2.0);
+    assert_eq!(books.percentage, 50.0);
+    assert_eq!((books.elapsed_days, books.total_days), (182, 365));
+    assert!((books.expected - 4.0 * 182.0 / 365.0).abs() < 1e-9);
+    assert!((books.projected - 2.0 * 365.0 / 182.0).abs() < 1e-9);
+    assert!(books.on_track && !books.reached);
+
+    let hours = &progress[1];
+    assert_eq!(hours.metric, GoalMetric::Hours);
+    assert!((hours.actual - 1.0).abs() < 1e-9);
+
+    let pages = &progress[2];
+    assert_eq!(pages.actual, 60.0);
+    assert!(pages.reached && pages.on_track);
+    assert_eq!(pages.bar_percentage(), 100);
+
+    // Nothing was read in July yet
+    let month = &progress[3];
+    assert_eq!(
+        (month.period, month.period_key.as_str()),
+        (GoalPeriod::Month, "2023-07")
+    );
+    assert_eq!(
+        (month.actual, month.elapsed_days, month.total_days),
+        (0.0, 1, 31)
+    );
+
+    // Past years are over: the projection is the final amount
+    let past = GoalCalculator::for_year(&goals, &data, &reading_stats, 2022, today);
+    assert_eq!(past[0].actual, 1.0);
+    assert!(past[0].is_finished());
+    assert_eq!(past[0].projected, 1.0);
+
+    // Future years have no pace yet
+    let future = GoalCalculator::for_year(&goals, &data, &reading_stats, 2024, today);
+    assert_eq!((future[0].elapsed_days, future[0].projected), (0, 0.0));
+
+    // Without targets there is nothing to track
+    let none = GoalCalculator::current(&GoalsConfig::default(), &data, &reading_stats, today);
+    assert!(none.is_empty());
+}
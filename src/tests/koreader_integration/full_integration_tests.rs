@@ -113,3 +113,95 @@ fn test_parse_koreader_generated_metadata() {
     );
     assert!(annotation.is_highlight());
 }
+
+/// Highlight styling and edit fields written by newer KoReader versions, next to an
+/// annotation without them.
+#[test]
+fn test_parse_koreader_generated_highlight_styles() {
+    let koreader_dir = get_koreader_dir();
+    let dump_lua_path = koreader_dir.path().join("frontend/dump.lua");
+
+    if !dump_lua_path.exists() {
+        panic!("KoReader dump.lua not found at {}", dump_lua_path.display());
+    }
+
+    let lua = Lua::new();
+
+    let dump_source = std::fs::read_to_string(&dump_lua_path).expect("Failed to read dump.lua");
+    let dump_fn: mlua::Function = lua
+        .load(&dump_source)
+        .eval()
+        .expect("Failed to load dump.lua");
+
+    let test_table: mlua::Table = lua
+        .load(
+            r#"{
+            annotations = {
+                {
+                    chapter = "Chapter 1: Introduction",
+                    color = "blue",
+                    datetime = "2024-03-15 14:30:00",
+                    datetime_updated = "2024-03-16 09:00:00",
+                    drawer = "underscore",
+                    page = "/body/section[1]/p[3]/text().0",
+                    pageno = 15,
+                    pos0 = "/body/section[1]/p[3]/text().0",
+                    pos1 = "/body/section[1]/p[3]/text().50",
+                    text = "This is a styled highlight",
+                },
+                {
+                    chapter = "Chapter 2: Basics",
+                    datetime = "2024-03-17 10:00:00",
+                    pageno = 30,
+                    pos0 = "/body/section[2]/p[1]/text().0",
+                    pos1 = "/body/section[2]/p[1]/text().20",
+                    text = "A plain highlight",
+                },
+            },
+            doc_pages = 250,
+            partial_md5_checksum = "abc123def456789",
+        }"#,
+        )
+        .eval()
+        .expect("Failed to create test table");
+
+    let serialized: String = dump_fn.call(test_table).expect("Failed to call dump()");
+    let lua_output = format!("return {}", serialized);
+
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("metadata.epub.lua");
+    std::fs::write(&test_file, &lua_output).unwrap();
+
+    let parser = crate::koreader::LuaParser::new();
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let metadata = rt
+        .block_on(parser.parse(&test_file))
+        .expect("Failed to parse KoReader-generated metadata");
+
+    assert_eq!(metadata.annotations.len(), 2);
+
+    let styled = &metadata.annotations[0];
+    assert!(styled.is_highlight());
+    assert_eq!(styled.color_name(), Some("blue"));
+    assert_eq!(
+        styled.drawer,
+        Some(crate::models::HighlightDrawer::Underscore)
+    );
+    assert_eq!(
+        styled.page.as_deref(),
+        Some("/body/section[1]/p[3]/text().0")
+    );
+    assert!(styled.is_edited());
+
+    let plain = &metadata.annotations[1];
+    assert!(plain.is_highlight());
+    assert_eq!(plain.text.as_deref(), Some("A plain highlight"));
+    assert_eq!(plain.color_name(), None);
+    assert_eq!(plain.drawer, None);
+    assert_eq!(
+        plain.drawer_style(),
+        crate::models::HighlightDrawer::Lighten
+    );
+    assert_eq!(plain.page, None);
+    assert!(!plain.is_edited());
+}
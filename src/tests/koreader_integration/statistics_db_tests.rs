@@ -149,6 +149,106 @@ fn test_statistics_totals_query_matches_parser() {
     );
 }
 
+#[test]
+fn test_page_stat_rescaling_matches_koreader_view() {
+    let koreader_dir = get_koreader_dir();
+    let lua = unsafe { Lua::unsafe_new_with(StdLib::ALL, LuaOptions::default()) };
+    let artifacts = load_statistics_artifacts(&lua, koreader_dir.path());
+    let db = TestDatabase::new(&artifacts.statements);
+    seed_sample_statistics(&db.conn);
+
+    let mut stmt = db
+        .conn
+        .prepare(
+            "SELECT id_book, page, start_time, duration FROM page_stat ORDER BY start_time, page",
+        )
+        .expect("Failed to prepare page_stat view query");
+    let expected: Vec<(i64, i64, i64, i64)> = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .expect("KoReader page_stat view query failed")
+        .collect::<rusqlite::Result<_>>()
+        .expect("Failed to read page_stat view rows");
+
+    let stats_data =
+        StatisticsParser::parse(&db.path).expect("Rust parser failed to read statistics DB");
+    let mut actual: Vec<(i64, i64, i64, i64)> = stats_data
+        .page_stats
+        .iter()
+        .map(|ps| (ps.id_book, ps.page, ps.start_time, ps.duration))
+        .collect();
+    actual.sort_by_key(|&(_, page, start_time, _)| (start_time, page));
+
+    assert_eq!(
+        actual, expected,
+        "Rust page rescaling diverged from KoReader's page_stat view"
+    );
+}
+
+#[test]
+fn test_page_stat_rescaling_beyond_numbers_table() {
+    let koreader_dir = get_koreader_dir();
+    let lua = unsafe { Lua::unsafe_new_with(StdLib::ALL, LuaOptions::default()) };
+    let artifacts = load_statistics_artifacts(&lua, koreader_dir.path());
+    let db = TestDatabase::new(&artifacts.statements);
+
+    // A comic recorded at 2 pages that now paginates to 3,000: the second recorded page
+    // covers 1,500 current pages, more than the view's 1,000-row numbers table can expand
+    db.conn
+        .execute(
+            "INSERT INTO book (id, title, authors, pages, md5) VALUES (1, 'Long Comic', 'Artist', 3000, 'long-md5');",
+            [],
+        )
+        .expect("Failed to seed book row");
+    db.conn
+        .execute(
+            "INSERT INTO page_stat_data (id_book, page, start_time, duration, total_pages) VALUES (1, 2, 1000, 3000, 2);",
+            [],
+        )
+        .expect("Failed to seed page_stat_data row");
+
+    let view_rows: i64 = db
+        .conn
+        .query_row("SELECT COUNT(*) FROM page_stat", [], |row| row.get(0))
+        .expect("KoReader page_stat view query failed");
+    assert!(
+        view_rows < 1500,
+        "KoReader view no longer truncates rescaling"
+    );
+
+    let stats_data =
+        StatisticsParser::parse(&db.path).expect("Rust parser failed to read statistics DB");
+    let pages: Vec<i64> = stats_data.page_stats.iter().map(|ps| ps.page).collect();
+    assert_eq!(pages, (1501..=3000).collect::<Vec<_>>());
+    assert!(stats_data.page_stats.iter().all(|ps| ps.duration == 2));
+}
+
+#[test]
+fn test_page_stat_view_fallback_without_data_table() {
+    // Databases without page_stat_data only expose page_stat, which is read as recorded
+    let db = TestDatabase::new(&[
+        "CREATE TABLE book (id INTEGER PRIMARY KEY, title TEXT, authors TEXT, notes INTEGER, last_open INTEGER, highlights INTEGER, pages INTEGER, series TEXT, language TEXT, md5 TEXT, total_read_time INTEGER, total_read_pages INTEGER);".to_string(),
+        "CREATE TABLE page_stat (id_book INTEGER, page INTEGER, start_time INTEGER, duration INTEGER);".to_string(),
+        "INSERT INTO book (id, title, authors, pages, series, language, md5) VALUES (1, 'Old Book', 'Author', 200, 'N/A', 'en', 'old-md5');".to_string(),
+        "INSERT INTO page_stat VALUES (1, 7, 1000, 45), (1, 8, 1045, 30);".to_string(),
+    ]);
+
+    let stats_data =
+        StatisticsParser::parse(&db.path).expect("Rust parser failed to read statistics DB");
+    let rows: Vec<(i64, i64, i64)> = stats_data
+        .page_stats
+        .iter()
+        .map(|ps| (ps.page, ps.start_time, ps.duration))
+        .collect();
+    assert_eq!(rows, vec![(7, 1000, 45), (8, 1045, 30)]);
+
+    // KOReader's "N/A" placeholder is not a series
+    let book = &stats_data.books[0];
+    assert_eq!(book.series, None);
+    assert_eq!(book.language.as_deref(), Some("en"));
+}
+
 fn fetch_table_info(conn: &Connection, table: &str) -> Vec<ColumnDescriptor> {
     let pragma = format!("PRAGMA table_info({})", table);
     let mut stmt = conn
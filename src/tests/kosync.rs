@@ -0,0 +1,176 @@
+use crate::koreader::{KosyncStore, SyncedProgress};
+use crate::models::{BookStatus, LibraryItem};
+use crate::server::KosyncState;
+use crate::server::kosync;
+use crate::tests::test_item;
+use actix_web::test::{TestRequest, call_service, init_service, read_body_json};
+use actix_web::{App, web};
+
+fn progress(document: &str, percentage: f64, timestamp: i64) -> SyncedProgress {
+    SyncedProgress {
+        document: document.to_string(),
+        progress: "/body/DocFragment[3]/body/p[1]/text().0".to_string(),
+        percentage,
+        device: "Kobo".to_string(),
+        device_id: "device-1".to_string(),
+        timestamp,
+    }
+}
+
+fn unread_item() -> LibraryItem {
+    test_item("alpha", "Alpha", "/library/alpha.epub").build()
+}
+
+#[test]
+fn test_store_keeps_latest_progress_per_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sync/kosync.sqlite3");
+    let store = KosyncStore::open(&path).unwrap();
+
+    assert!(store.create_user("reader", "key").unwrap());
+    assert!(!store.create_user("reader", "other").unwrap());
+    assert!(store.authorize("reader", "key").unwrap());
+    assert!(!store.authorize("reader", "other").unwrap());
+    assert!(!store.authorize("nobody", "key").unwrap());
+    assert!(store.create_user("partner", "key2").unwrap());
+
+    store
+        .update_progress("reader", &progress("abc", 0.25, 100))
+        .unwrap();
+    store
+        .update_progress("reader", &progress("abc", 0.5, 200))
+        .unwrap();
+    store
+        .update_progress("partner", &progress("abc", 0.1, 150))
+        .unwrap();
+
+    assert_eq!(
+        store.get_progress("reader", "abc").unwrap(),
+        Some(progress("abc", 0.5, 200))
+    );
+    assert_eq!(store.get_progress("reader", "missing").unwrap(), None);
+
+    // The newest update across all users wins
+    drop(store);
+    let latest = KosyncStore::read_latest_progress(&path).unwrap();
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest["abc"].percentage, 0.5);
+}
+
+#[test]
+fn test_synced_progress_only_overrides_older_metadata() {
+    let mut item = unread_item();
+    assert!(progress("abc", 0.4, 1_000).apply_to(&mut item, None));
+    assert_eq!(item.progress_percentage(), Some(0.4));
+    assert_eq!(item.status(), BookStatus::Reading);
+
+    // Metadata written after the sync is newer and wins
+    let newer_metadata = Some(2_000 * 1_000_000_000);
+    assert!(!progress("abc", 0.6, 1_500).apply_to(&mut item, newer_metadata));
+    assert_eq!(item.progress_percentage(), Some(0.4));
+
+    assert!(progress("abc", 0.6, 2_500).apply_to(&mut item, newer_metadata));
+    assert_eq!(item.progress_percentage(), Some(0.6));
+}
+
+#[actix_web::test]
+async fn test_sync_protocol_routes() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KosyncStore::open(dir.path().join("kosync.sqlite3")).unwrap();
+    let state = web::Data::new(KosyncState::new(store, true));
+    let app = init_service(App::new().configure(|cfg| kosync::configure(cfg, state.clone()))).await;
+
+    let register = |username: &str| {
+        TestRequest::post()
+            .uri("/users/create")
+            .set_json(serde_json::json!({ "username": username, "password": "secret-md5" }))
+            .to_request()
+    };
+    let resp = call_service(&app, register("reader")).await;
+    assert_eq!(resp.status(), 201);
+    let resp = call_service(&app, register("reader")).await;
+    assert_eq!(resp.status(), 402);
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["code"], 2002);
+
+    let authed = |req: TestRequest, key: &str| {
+        req.insert_header(("x-auth-user", "reader"))
+            .insert_header(("x-auth-key", key.to_string()))
+            .to_request()
+    };
+    let resp = call_service(
+        &app,
+        authed(TestRequest::get().uri("/users/auth"), "secret-md5"),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, authed(TestRequest::get().uri("/users/auth"), "wrong")).await;
+    assert_eq!(resp.status(), 401);
+
+    let resp = call_service(
+        &app,
+        authed(TestRequest::get().uri("/syncs/progress/abc"), "secret-md5"),
+    )
+    .await;
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body, serde_json::json!({}));
+
+    let resp = call_service(
+        &app,
+        authed(
+            TestRequest::put()
+                .uri("/syncs/progress")
+                .set_json(serde_json::json!({
+                    "document": "abc",
+                    "progress": 42,
+                    "percentage": 0.42,
+                    "device": "Kobo",
+                    "device_id": "device-1",
+                })),
+            "secret-md5",
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["document"], "abc");
+
+    let resp = call_service(
+        &app,
+        authed(TestRequest::get().uri("/syncs/progress/abc"), "secret-md5"),
+    )
+    .await;
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["progress"], "42");
+    assert_eq!(body["percentage"], 0.42);
+    assert_eq!(body["device"], "Kobo");
+
+    let resp = call_service(
+        &app,
+        authed(
+            TestRequest::put().uri("/syncs/progress").set_json(
+                serde_json::json!({ "progress": "1", "percentage": 0.1, "device": "Kobo" }),
+            ),
+            "secret-md5",
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+}
+
+#[actix_web::test]
+async fn test_sync_registration_can_be_closed() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KosyncStore::open(dir.path().join("kosync.sqlite3")).unwrap();
+    let state = web::Data::new(KosyncState::new(store, false));
+    let app = init_service(App::new().configure(|cfg| kosync::configure(cfg, state.clone()))).await;
+
+    let req = TestRequest::post()
+        .uri("/users/create")
+        .set_json(serde_json::json!({ "username": "reader", "password": "secret-md5" }))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 402);
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["code"], 2005);
+}
@@ -0,0 +1,178 @@
+use crate::koreader::LuaParser;
+use crate::models::{HighlightDrawer, KoReaderMetadata};
+use std::fs;
+
+async fn parse(content: &str) -> KoReaderMetadata {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metadata.epub.lua");
+    fs::write(&path, content).unwrap();
+    LuaParser::new().parse(&path).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_legacy_highlight_and_bookmarks_tables() {
+    let metadata = parse(
+        r#"return {
+    ["bookmarks"] = {
+        [1] = {
+            ["datetime"] = "2021-05-02 10:00:00",
+            ["highlighted"] = true,
+            ["notes"] = "Second quote",
+            ["page"] = "/body/DocFragment[4]/body/p[2]/text().0",
+            ["pos0"] = "/body/DocFragment[4]/body/p[2]/text().0",
+            ["pos1"] = "/body/DocFragment[4]/body/p[2]/text().12",
+            ["text"] = "My own note",
+        },
+        [2] = {
+            ["datetime"] = "2021-05-01 09:00:00",
+            ["page"] = "/body/DocFragment[3]/body/p[1]/text().0",
+            ["text"] = "Page 10 @ 2021-05-01 09:00:00",
+        },
+        [3] = {
+            ["chapter"] = "One",
+            ["datetime"] = "2021-04-30 08:00:00",
+            ["highlighted"] = true,
+            ["notes"] = "First quote",
+            ["page"] = "/body/DocFragment[2]/body/p[1]/text().0",
+            ["pos0"] = "/body/DocFragment[2]/body/p[1]/text().0",
+            ["pos1"] = "/body/DocFragment[2]/body/p[1]/text().11",
+            ["text"] = "Page 5 First quote @ 2021-04-30 08:00:00",
+        },
+    },
+    ["highlight"] = {
+        [21] = {
+            [1] = {
+                ["chapter"] = "Two",
+                ["datetime"] = "2021-05-02 10:00:00",
+                ["drawer"] = "underscore",
+                ["pos0"] = "/body/DocFragment[4]/body/p[2]/text().0",
+                ["pos1"] = "/body/DocFragment[4]/body/p[2]/text().12",
+                ["text"] = "Second quote",
+            },
+        },
+        [5] = {
+            [1] = {
+                ["chapter"] = "One",
+                ["datetime"] = "2021-04-30 08:00:00",
+                ["drawer"] = "lighten",
+                ["pos0"] = "/body/DocFragment[2]/body/p[1]/text().0",
+                ["pos1"] = "/body/DocFragment[2]/body/p[1]/text().11",
+                ["text"] = "First quote",
+            },
+        },
+    },
+}"#,
+    )
+    .await;
+
+    let annotations = &metadata.annotations;
+    assert_eq!(annotations.len(), 3);
+
+    // Ordered by page: the page bookmark has no known page number and comes first
+    assert!(annotations[0].is_bookmark());
+    assert_eq!(annotations[0].note, None);
+
+    assert_eq!(annotations[1].text.as_deref(), Some("First quote"));
+    assert_eq!(annotations[1].pageno, Some(5));
+    assert_eq!(annotations[1].note, None);
+    assert_eq!(annotations[1].drawer, Some(HighlightDrawer::Lighten));
+
+    assert_eq!(annotations[2].text.as_deref(), Some("Second quote"));
+    assert_eq!(annotations[2].pageno, Some(21));
+    assert_eq!(annotations[2].note.as_deref(), Some("My own note"));
+    assert_eq!(annotations[2].drawer, Some(HighlightDrawer::Underscore));
+    assert_eq!(annotations[2].chapter.as_deref(), Some("Two"));
+}
+
+#[tokio::test]
+async fn test_legacy_tables_are_deduplicated_against_annotations() {
+    let metadata = parse(
+        r#"return {
+    ["annotations"] = {
+        [1] = {
+            ["datetime"] = "2021-04-30 08:00:00",
+            ["pageno"] = 5,
+            ["pos0"] = "/body/DocFragment[2]/body/p[1]/text().0",
+            ["pos1"] = "/body/DocFragment[2]/body/p[1]/text().11",
+            ["text"] = "First quote",
+        },
+    },
+    ["highlight"] = {
+        [5] = {
+            [1] = {
+                ["datetime"] = "2021-04-30 08:00:00",
+                ["pos0"] = "/body/DocFragment[2]/body/p[1]/text().0",
+                ["pos1"] = "/body/DocFragment[2]/body/p[1]/text().11",
+                ["text"] = "First quote",
+            },
+        },
+        [9] = {
+            [1] = {
+                ["datetime"] = "2021-05-01 12:00:00",
+                ["pos0"] = { ["page"] = 9, ["x"] = 10, ["y"] = 20 },
+                ["pos1"] = { ["page"] = 9, ["x"] = 200, ["y"] = 40 },
+                ["text"] = "Only in the legacy table",
+            },
+        },
+    },
+}"#,
+    )
+    .await;
+
+    let annotations = &metadata.annotations;
+    assert_eq!(annotations.len(), 2);
+    assert_eq!(annotations[0].text.as_deref(), Some("First quote"));
+    assert_eq!(
+        annotations[1].text.as_deref(),
+        Some("Only in the legacy table")
+    );
+    // PDF positions are tables, which still mark the annotation as a highlight
+    assert!(annotations[1].is_highlight());
+    assert_eq!(annotations[1].pos0.as_deref(), Some("9:10,20"));
+}
+
+#[tokio::test]
+async fn test_merged_annotations_are_in_reading_order() {
+    let metadata = parse(
+        r#"return {
+    ["annotations"] = {
+        [1] = {
+            ["datetime"] = "2021-04-30 08:00:00",
+            ["pageno"] = 40,
+            ["pos0"] = "/body/DocFragment[10]/body/p[2]/text().0",
+            ["pos1"] = "/body/DocFragment[10]/body/p[2]/text().5",
+            ["text"] = "Late quote",
+        },
+    },
+    ["highlight"] = {
+        [40] = {
+            [1] = {
+                ["datetime"] = "2021-05-02 09:00:00",
+                ["pos0"] = "/body/DocFragment[10]/body/p[1]/text().12",
+                ["pos1"] = "/body/DocFragment[10]/body/p[1]/text().20",
+                ["text"] = "Same page, earlier paragraph",
+            },
+        },
+        [3] = {
+            [1] = {
+                ["datetime"] = "2021-05-01 12:00:00",
+                ["pos0"] = "/body/DocFragment[2]/body/p[1]/text().0",
+                ["pos1"] = "/body/DocFragment[2]/body/p[1]/text().9",
+                ["text"] = "Early quote",
+            },
+        },
+    },
+}"#,
+    )
+    .await;
+
+    let texts: Vec<_> = metadata
+        .annotations
+        .iter()
+        .map(|a| a.text.as_deref().unwrap_or_default())
+        .collect();
+    assert_eq!(
+        texts,
+        ["Early quote", "Same page, earlier paragraph", "Late quote"]
+    );
+}
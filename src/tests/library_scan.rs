@@ -0,0 +1,202 @@
+use crate::config::ItemIdScheme;
+use crate::library::{MetadataLocation, scan_library};
+use crate::models::LibraryItemFormat;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+
+pub(super) fn write_epub(path: &Path, title: &str) {
+    let file = fs::File::create(path).unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("mimetype", options).unwrap();
+    zip.write_all(b"application/epub+zip").unwrap();
+    zip.start_file("META-INF/container.xml", options).unwrap();
+    zip.write_all(
+        br#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#,
+    )
+    .unwrap();
+    zip.start_file("content.opf", options).unwrap();
+    write!(
+        zip,
+        r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>{title}</dc:title>
+    <dc:creator>Test Author</dc:creator>
+  </metadata>
+  <manifest/>
+  <spine/>
+</package>"#
+    )
+    .unwrap();
+    zip.finish().unwrap();
+}
+
+#[tokio::test]
+async fn test_scan_library_is_ordered_and_reuses_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = dir.path().join("library");
+    fs::create_dir_all(library.join("nested")).unwrap();
+
+    let titles = ["Delta", "Alpha", "Echo", "Charlie", "Bravo", "Foxtrot"];
+    for (i, title) in titles.iter().enumerate() {
+        let folder = if i % 2 == 0 {
+            library.clone()
+        } else {
+            library.join("nested")
+        };
+        write_epub(
+            &folder.join(format!("{}.epub", title.to_lowercase())),
+            title,
+        );
+    }
+
+    let paths = vec![library.clone()];
+    let location = MetadataLocation::InBookFolder;
+
+    let first = scan_library(&paths, &location, ItemIdScheme::Hash, None)
+        .await
+        .unwrap();
+    let files: Vec<_> = first
+        .items
+        .iter()
+        .map(|i| i.file_path().unwrap().to_path_buf())
+        .collect();
+    let expected: Vec<_> = walkdir::WalkDir::new(&library)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().to_path_buf())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "epub"))
+        .collect();
+    assert_eq!(files, expected);
+    assert_eq!(first.parsed, titles.len());
+    assert_eq!(first.library_md5s.len(), titles.len());
+
+    // Unchanged files come from the cache; a rewritten one is parsed again
+    let changed = library.join("echo.epub");
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    write_epub(&changed, "Echo Revised");
+
+    let second = scan_library(&paths, &location, ItemIdScheme::Hash, Some(&first.cache))
+        .await
+        .unwrap();
+    let files: Vec<_> = second
+        .items
+        .iter()
+        .map(|i| i.file_path().unwrap().to_path_buf())
+        .collect();
+    assert_eq!(files, expected);
+    assert_eq!(second.parsed, 1);
+    let echo = second
+        .items
+        .iter()
+        .find(|item| item.file_path() == Some(changed.as_path()))
+        .unwrap();
+    assert_eq!(echo.book_info.title, "Echo Revised");
+}
+
+fn path_hash(path: &Path) -> String {
+    format!("{:x}", md5::compute(path.to_string_lossy().as_bytes()))[..8].to_string()
+}
+
+#[tokio::test]
+async fn test_scan_library_assigns_unique_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = dir.path().join("library");
+    fs::create_dir_all(library.join("copy")).unwrap();
+    write_epub(&library.join("poems.epub"), "Collected Poems");
+    write_epub(&library.join("other-poems.epub"), "Collected Poems!");
+    fs::copy(library.join("poems.epub"), library.join("copy/poems.epub")).unwrap();
+
+    let paths = vec![library.clone()];
+    let location = MetadataLocation::InBookFolder;
+
+    let scan = scan_library(&paths, &location, ItemIdScheme::Hash, None)
+        .await
+        .unwrap();
+    let ids: Vec<_> = scan.items.iter().map(|item| item.id.as_str()).collect();
+    let md5 = crate::koreader::calculate_partial_md5(&library.join("poems.epub")).unwrap();
+    let poems_id = format!("collected-poems-{}", &md5[..8]);
+
+    // Walk order: copy/poems.epub, other-poems.epub, poems.epub
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids[0], poems_id);
+    assert!(ids[1].starts_with("collected-poems-"));
+    assert_ne!(ids[1], poems_id);
+    assert_eq!(
+        ids[2],
+        format!("{}-{}", poems_id, path_hash(&library.join("poems.epub")))
+    );
+
+    let scan = scan_library(&paths, &location, ItemIdScheme::Title, None)
+        .await
+        .unwrap();
+    let ids: Vec<_> = scan.items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "collected-poems".to_string(),
+            format!(
+                "collected-poems-{}",
+                path_hash(&library.join("other-poems.epub"))
+            ),
+            format!("collected-poems-{}", path_hash(&library.join("poems.epub"))),
+        ]
+    );
+
+    // Suffixes do not depend on the other colliding items
+    fs::remove_file(library.join("other-poems.epub")).unwrap();
+    let scan = scan_library(&paths, &location, ItemIdScheme::Title, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        scan.items[1].id,
+        format!("collected-poems-{}", path_hash(&library.join("poems.epub")))
+    );
+}
+
+#[tokio::test]
+async fn test_djv_files_are_scanned_with_their_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let library = dir.path().join("library");
+    let docsettings = dir.path().join("docsettings");
+    fs::create_dir_all(library.join("Scan.sdr")).unwrap();
+    fs::create_dir_all(docsettings.join("library").join("Scan.sdr")).unwrap();
+
+    // A single-page document without any chunks in its page
+    fs::write(library.join("Scan.djv"), b"AT&TFORM\0\0\0\x04DJVU").unwrap();
+    // KoReader names the metadata file after the book's extension
+    let metadata =
+        r#"return { ["percent_finished"] = 0.5, ["summary"] = { ["status"] = "reading" } }"#;
+    fs::write(library.join("Scan.sdr").join("metadata.djv.lua"), metadata).unwrap();
+    fs::write(
+        docsettings
+            .join("library")
+            .join("Scan.sdr")
+            .join("metadata.djv.lua"),
+        metadata,
+    )
+    .unwrap();
+
+    let paths = vec![library.clone()];
+    for location in [
+        MetadataLocation::InBookFolder,
+        MetadataLocation::DocSettings(docsettings.clone()),
+    ] {
+        let scan = scan_library(&paths, &location, ItemIdScheme::Title, None)
+            .await
+            .unwrap();
+        assert_eq!(scan.items.len(), 1, "{:?}", location);
+        let item = &scan.items[0];
+        assert_eq!(item.format(), Some(LibraryItemFormat::Djvu));
+        assert_eq!(item.book_info.title, "Scan");
+        assert!(item.koreader_metadata.is_some(), "{:?}", location);
+    }
+}
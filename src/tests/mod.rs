@@ -6,3 +6,206 @@ mod koreader_integration;
 
 #[cfg(test)]
 mod i18n;
+
+#[cfg(test)]
+mod config_file;
+
+#[cfg(test)]
+mod build_cache;
+
+#[cfg(test)]
+mod library_scan;
+
+#[cfg(test)]
+mod document_parsers;
+
+#[cfg(test)]
+mod legacy_annotations;
+
+#[cfg(test)]
+mod kosync;
+
+#[cfg(test)]
+mod opds;
+
+#[cfg(test)]
+mod export;
+
+#[cfg(test)]
+mod goals;
+
+#[cfg(test)]
+mod browse;
+
+#[cfg(test)]
+mod search;
+
+#[cfg(test)]
+mod completion;
+
+#[cfg(test)]
+mod sessions;
+
+#[cfg(test)]
+mod patterns;
+
+#[cfg(test)]
+mod timeline;
+
+#[cfg(test)]
+mod stats_only;
+
+#[cfg(test)]
+use crate::models::{
+    Annotation, BookInfo, BookStatus, ItemSource, KoReaderMetadata, LibraryItem, LibraryItemFormat,
+    StatBook, Summary,
+};
+#[cfg(test)]
+use std::path::PathBuf;
+
+/// Builder for library items in tests. Everything not set stays empty.
+#[cfg(test)]
+pub(crate) struct TestItem {
+    item: LibraryItem,
+}
+
+/// Start a file item without KOReader metadata, its format taken from `path`.
+#[cfg(test)]
+pub(crate) fn test_item(id: &str, title: &str, path: &str) -> TestItem {
+    let path = PathBuf::from(path);
+    TestItem {
+        item: LibraryItem {
+            id: id.to_string(),
+            book_info: BookInfo {
+                title: title.to_string(),
+                authors: Vec::new(),
+                description: None,
+                language: None,
+                publisher: None,
+                identifiers: Vec::new(),
+                subjects: Vec::new(),
+                series: None,
+                series_number: None,
+                pages: None,
+                cover_data: None,
+                cover_mime_type: None,
+            },
+            koreader_metadata: None,
+            source: ItemSource::File {
+                format: LibraryItemFormat::from_path(&path).unwrap(),
+                path,
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+impl TestItem {
+    pub(crate) fn authors(mut self, authors: &[&str]) -> Self {
+        self.item.book_info.authors = authors.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    pub(crate) fn description(mut self, description: &str) -> Self {
+        self.item.book_info.description = Some(description.to_string());
+        self
+    }
+
+    pub(crate) fn language(mut self, language: &str) -> Self {
+        self.item.book_info.language = Some(language.to_string());
+        self
+    }
+
+    pub(crate) fn subjects(mut self, subjects: &[&str]) -> Self {
+        self.item.book_info.subjects = subjects.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub(crate) fn series(mut self, name: &str, number: &str) -> Self {
+        self.item.book_info.series = Some(name.to_string());
+        self.item.book_info.series_number = Some(number.to_string());
+        self
+    }
+
+    /// KOReader metadata of the item, created empty on first use.
+    fn metadata(&mut self) -> &mut KoReaderMetadata {
+        self.item
+            .koreader_metadata
+            .get_or_insert_with(|| KoReaderMetadata {
+                annotations: Vec::new(),
+                doc_pages: None,
+                doc_path: None,
+                doc_props: None,
+                partial_md5_checksum: None,
+                percent_finished: None,
+                stats: None,
+                summary: None,
+                text_lang: None,
+            })
+    }
+
+    pub(crate) fn annotations(mut self, annotations: Vec<Annotation>) -> Self {
+        self.metadata().annotations = annotations;
+        self
+    }
+
+    pub(crate) fn md5(mut self, md5: &str) -> Self {
+        self.metadata().partial_md5_checksum = Some(md5.to_string());
+        self
+    }
+
+    pub(crate) fn doc_pages(mut self, doc_pages: u32) -> Self {
+        self.metadata().doc_pages = Some(doc_pages);
+        self
+    }
+
+    pub(crate) fn percent_finished(mut self, percent_finished: f64) -> Self {
+        self.metadata().percent_finished = Some(percent_finished);
+        self
+    }
+
+    /// KOReader summary of the item, created as "reading" on first use.
+    fn summary(&mut self) -> &mut Summary {
+        self.metadata().summary.get_or_insert(Summary {
+            modified: None,
+            note: None,
+            rating: None,
+            status: BookStatus::Reading,
+        })
+    }
+
+    pub(crate) fn status(mut self, status: BookStatus) -> Self {
+        self.summary().status = status;
+        self
+    }
+
+    pub(crate) fn note(mut self, note: &str) -> Self {
+        self.summary().note = Some(note.to_string());
+        self
+    }
+
+    pub(crate) fn build(self) -> LibraryItem {
+        self.item
+    }
+}
+
+/// A statistics book with only its ID and MD5 set; fill in the rest with struct update syntax.
+#[cfg(test)]
+pub(crate) fn test_stat_book(id: i64, md5: &str) -> StatBook {
+    StatBook {
+        id,
+        title: String::new(),
+        authors: String::new(),
+        notes: None,
+        last_open: None,
+        highlights: None,
+        pages: None,
+        series: None,
+        language: None,
+        md5: md5.to_string(),
+        content_type: None,
+        total_read_time: None,
+        total_read_pages: None,
+        completions: None,
+    }
+}
@@ -0,0 +1,152 @@
+use crate::models::LibraryItem;
+use crate::opds::{OpdsFeed, OpdsFeedKind, OpdsLinks, OpdsSite, search};
+use crate::tests::test_item;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use std::fs;
+use std::path::PathBuf;
+
+fn item(id: &str, title: &str, file: &str, series: Option<(&str, &str)>) -> LibraryItem {
+    let item = test_item(id, title, file)
+        .authors(&["Jane Doe"])
+        .description("<p>A <b>short</b> &amp; sweet book</p>")
+        .language("en")
+        .subjects(&["Science Fiction"]);
+    match series {
+        Some((name, number)) => item.series(name, number).build(),
+        None => item.build(),
+    }
+}
+
+fn links() -> OpdsLinks {
+    OpdsLinks {
+        raw_root: Some(PathBuf::from("/library")),
+        sites: vec![OpdsSite {
+            root: String::new(),
+            dir: PathBuf::from("/nonexistent"),
+        }],
+    }
+}
+
+#[test]
+fn test_entry_links_and_plain_text_summary() {
+    let links = links();
+    let entry = links.entry(&item(
+        "dune-1a2b3c4d",
+        "Dune",
+        "/library/Frank Herbert/Dune & Co.epub",
+        Some(("Dune", "1")),
+    ));
+    assert_eq!(
+        entry.acquisition,
+        Some((
+            "/raw/Frank%20Herbert/Dune%20%26%20Co.epub".to_string(),
+            "application/epub+zip".to_string()
+        ))
+    );
+    assert_eq!(entry.summary.as_deref(), Some("A short & sweet book"));
+    assert_eq!(entry.authors[0].href, "/opds/authors/jane-doe");
+    assert_eq!(entry.html_href, "/books/dune-1a2b3c4d/");
+    // No generated cover
+    assert!(entry.cover_href.is_none());
+
+    // Files outside the served library folder cannot be downloaded
+    let elsewhere = links.entry(&item("x", "X", "/comics/x.cbz", None));
+    assert!(elsewhere.acquisition.is_none());
+    assert_eq!(elsewhere.html_href, "/comics/x/");
+}
+
+#[test]
+fn test_shared_catalog_links_to_the_profile_site_with_the_page() {
+    let dir = tempfile::tempdir().unwrap();
+    let site = |slug: &str| OpdsSite {
+        root: format!("/u/{}", slug),
+        dir: dir.path().join("u").join(slug),
+    };
+    let bob = site("bob");
+    fs::create_dir_all(bob.dir.join("books").join("dune")).unwrap();
+    fs::write(bob.dir.join("books").join("dune").join("index.html"), "").unwrap();
+    fs::create_dir_all(bob.dir.join("assets").join("covers")).unwrap();
+    fs::write(bob.dir.join("assets").join("covers").join("dune.webp"), "").unwrap();
+    let links = OpdsLinks {
+        raw_root: Some(PathBuf::from("/library")),
+        sites: vec![site("anna"), bob],
+    };
+
+    let dune = links.entry(&item("dune", "Dune", "/library/Dune.epub", None));
+    assert_eq!(dune.html_href, "/u/bob/books/dune/");
+    assert_eq!(
+        dune.cover_href.as_deref(),
+        Some("/u/bob/assets/covers/dune.webp")
+    );
+    assert_eq!(dune.acquisition.unwrap().0, "/raw/Dune.epub");
+
+    // Without a page anywhere, the entry links to the first site
+    let emma = links.entry(&item("emma", "Emma", "/library/Emma.epub", None));
+    assert_eq!(emma.html_href, "/u/anna/books/emma/");
+    assert!(emma.cover_href.is_none());
+}
+
+#[test]
+fn test_feed_renders_well_formed_atom_and_json() {
+    let links = links();
+    let feed = OpdsFeed {
+        href: "/opds/series/dune".to_string(),
+        title: "Dune <Saga>".to_string(),
+        updated: "2026-01-01T00:00:00Z".to_string(),
+        kind: OpdsFeedKind::Acquisition,
+        navigation: Vec::new(),
+        entries: vec![links.entry(&item(
+            "dune",
+            "Dune",
+            "/library/Dune.epub",
+            Some(("Dune", "1")),
+        ))],
+        searchable: true,
+    };
+
+    let atom = feed.to_atom().unwrap();
+    let mut reader = Reader::from_str(&atom);
+    let mut entries = 0;
+    loop {
+        match reader.read_event().unwrap() {
+            Event::Start(e) if e.name().as_ref() == b"entry" => entries += 1,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(entries, 1);
+    // Text is escaped, so the document parsed above stays well formed
+    assert!(!atom.contains("<Saga>"));
+    assert!(atom.contains(r#"rel="search" href="/opds/opensearch.xml""#));
+
+    let json = feed.to_json();
+    assert_eq!(json["metadata"]["numberOfItems"], 1);
+    let publication = &json["publications"][0];
+    assert_eq!(publication["metadata"]["title"], "Dune");
+    assert_eq!(
+        publication["metadata"]["belongsTo"]["series"]["position"],
+        1.0
+    );
+    assert_eq!(publication["links"][0]["href"], "/raw/Dune.epub");
+    assert_eq!(json["links"][2]["href"], "/opds/search.json{?query}");
+}
+
+#[test]
+fn test_search_matches_all_words_across_fields() {
+    let items = vec![
+        item("dune", "Dune", "/library/Dune.epub", Some(("Dune", "1"))),
+        item("other", "Other Book", "/library/Other.epub", None),
+    ];
+    let titles = |query: &str| -> Vec<String> {
+        search(&items, query)
+            .iter()
+            .map(|item| item.book_info.title.clone())
+            .collect()
+    };
+
+    assert_eq!(titles("dune jane"), vec!["Dune"]);
+    assert_eq!(titles("SCIENCE fiction").len(), 2);
+    assert!(titles("dune missing").is_empty());
+    assert!(titles("  ").is_empty());
+}
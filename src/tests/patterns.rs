@@ -0,0 +1,70 @@
+use crate::koreader::session::build_sessions;
+use crate::koreader::{PatternCalculator, StatisticsCalculator};
+use crate::models::{Chronotype, PageStat, StatisticsData};
+use crate::time_config::TimeConfig;
+
+fn pages(start: i64, count: i64, seconds_per_page: i64) -> Vec<PageStat> {
+    (0..count)
+        .map(|i| PageStat {
+            id_book: 1,
+            page: i + 1,
+            start_time: start + i * seconds_per_page,
+            duration: seconds_per_page,
+            device: None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_reading_patterns_by_hour_and_weekday() {
+    // Monday 23:00 for 20 minutes, one page on Tuesday 01:00 (still Monday with a 03:00
+    // day start) and Wednesday 07:00 for 15 minutes at a faster pace
+    let mut page_stats = pages(1673305200, 20, 60);
+    page_stats.extend(pages(1673312400, 1, 60));
+    page_stats.extend(pages(1673420400, 20, 45));
+    let mut data = StatisticsData {
+        books: Vec::new(),
+        stats_by_md5: Default::default(),
+        sessions: build_sessions(&page_stats, 300),
+        page_stats,
+    };
+    let time_config = TimeConfig::new(Some(chrono_tz::UTC), 3 * 60);
+
+    let patterns = PatternCalculator::calculate(&data, &time_config);
+    assert_eq!(patterns.punch_card[0][23], 1200);
+    assert_eq!(patterns.punch_card[0][1], 60);
+    assert_eq!(patterns.punch_card[1].iter().sum::<i64>(), 0);
+    assert_eq!(patterns.punch_card[2][7], 900);
+    assert_eq!(patterns.weekdays[0].pages_read, 21);
+    assert_eq!(patterns.cell_level(&0, &23), 4);
+    assert_eq!(patterns.cell_level(&0, &1), 1);
+    assert_eq!(patterns.cell_level(&0, &2), 0);
+
+    assert_eq!(patterns.hours[23].session_count, 1);
+    assert_eq!(patterns.hours[23].average_session_duration, Some(1200));
+    assert_eq!(patterns.hours[23].pages_per_hour, Some(60.0));
+    assert_eq!(patterns.hours[7].pages_per_hour, Some(80.0));
+    // Too little reading at 01:00 for a meaningful speed
+    assert_eq!(patterns.hours[1].pages_per_hour, None);
+    assert_eq!(patterns.speed_bar_percent(&23), 75);
+    assert_eq!(patterns.session_bar_percent(&7), 75);
+
+    assert_eq!(patterns.peak_hour, Some(23));
+    assert_eq!(patterns.peak_weekday, Some(0));
+    assert_eq!(patterns.fastest_hour, Some(7));
+    assert_eq!(patterns.chronotype, Some(Chronotype::NightOwl));
+    assert_eq!(patterns.chronotype_percent(), 58);
+
+    let json = serde_json::to_value(&patterns).unwrap();
+    assert_eq!(json["chronotype"], "night_owl");
+    assert_eq!(json["punch_card"].as_array().unwrap().len(), 7);
+
+    // Part of the overall reading stats; no reading means no insights
+    let stats = StatisticsCalculator::calculate_stats(&mut data, &time_config);
+    assert_eq!(stats.patterns.peak_hour, Some(23));
+    data.page_stats.clear();
+    data.sessions.clear();
+    let empty = PatternCalculator::calculate(&data, &time_config);
+    assert_eq!(empty.chronotype, None);
+    assert_eq!(empty.peak_weekday, None);
+}
@@ -0,0 +1,117 @@
+use crate::models::{Annotation, BookStatus, LibraryItem};
+use crate::search::{DOCUMENTS_PER_CHUNK, SearchField, SearchIndex, shard_key, snippet, tokenize};
+use crate::tests::test_item;
+
+fn annotation(pos: Option<&str>, page: u32, text: Option<&str>, note: Option<&str>) -> Annotation {
+    Annotation {
+        chapter: None,
+        datetime: None,
+        pageno: Some(page),
+        pos0: pos.map(|p| format!("{p}.0")),
+        pos1: pos.map(|p| format!("{p}.9")),
+        text: text.map(str::to_string),
+        note: note.map(str::to_string),
+        color: None,
+        drawer: None,
+        datetime_updated: None,
+        page: None,
+    }
+}
+
+fn item(id: &str, file: &str, annotations: Vec<Annotation>, review: Option<&str>) -> LibraryItem {
+    let item = test_item(id, &id.to_uppercase(), file)
+        .description("<p>A story about <b>whales</b> and the sea.</p>")
+        .subjects(&["Sea Stories"])
+        .annotations(annotations)
+        .status(BookStatus::Reading);
+    match review {
+        Some(review) => item.note(review).build(),
+        None => item.build(),
+    }
+}
+
+#[test]
+fn test_search_index_matches_ranks_and_links() {
+    let items = vec![
+        item(
+            "moby",
+            "/library/moby.epub",
+            vec![
+                // Bookmarks are indexed by their note only
+                annotation(None, 3, Some("Bookmark text"), Some("Remember the whale")),
+                annotation(Some("a"), 12, Some("Call me Ishmael. Whales ahead!"), None),
+            ],
+            Some("The whaling chapters drag."),
+        ),
+        item("comic", "/library/comic.cbz", Vec::new(), None),
+    ];
+    let index = SearchIndex::build(&items);
+
+    // Prefix matching, ranked by field, then in library order
+    let hits = index.search("whal", 10);
+    let found: Vec<_> = hits.iter().map(|h| (h.field, h.href.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (SearchField::Highlight, "/books/moby/#annotation-1"),
+            (SearchField::Note, "/books/moby/#annotation-0"),
+            (SearchField::ReviewNote, "/books/moby/#review-note"),
+            (SearchField::Description, "/books/moby/#book-overview"),
+            (SearchField::Description, "/comics/comic/#book-overview"),
+        ]
+    );
+    assert_eq!(hits[0].page, Some(12));
+    assert_eq!(hits[0].title, "MOBY");
+    assert_eq!(hits[3].snippet, "A story about whales and the sea.");
+    assert!(index.search("bookmark", 10).is_empty());
+
+    // Every term has to match; terms are case-insensitive and limited
+    let hits = index.search("SEA stories", 10);
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|h| h.field == SearchField::Subject));
+    assert_eq!(index.search("sea", 1).len(), 1);
+    assert!(index.search("ishmael sea", 10).is_empty());
+    assert!(index.search("a", 10).is_empty());
+
+    // Shards hold every term under its first character
+    let shards = index.shards();
+    assert_eq!(shards["w"]["whales"], [0, 5, 6]);
+    assert!(shards.values().flat_map(|s| s.keys()).all(|t| t.len() >= 2));
+}
+
+#[test]
+fn test_search_tokenize_shards_and_snippets() {
+    assert_eq!(tokenize("Émile's café, 42 a b"), ["émile", "café", "42"]);
+    assert_eq!(shard_key("whale"), "w");
+    assert_eq!(shard_key("42"), "4");
+    assert_eq!(shard_key("émile"), "ue9");
+
+    let text = format!("{} needle {}", "word ".repeat(30), "tail ".repeat(60));
+    let excerpt = snippet(&text, &["needle".to_string()]);
+    assert!(excerpt.starts_with("…word"));
+    assert!(excerpt.ends_with('…'));
+    assert!(excerpt.contains("needle"));
+    assert!(excerpt.chars().count() <= 182);
+
+    // Matches only count at word starts
+    assert_eq!(
+        snippet("short unneeded needle", &["need".to_string()]),
+        "short unneeded needle"
+    );
+}
+
+#[test]
+fn test_search_documents_are_chunked_in_rank_order() {
+    let annotations = (0..DOCUMENTS_PER_CHUNK as u32)
+        .map(|page| annotation(Some("a"), page, Some("Call me Ishmael"), None))
+        .collect();
+    let items = vec![item("moby", "/library/moby.epub", annotations, None)];
+    let index = SearchIndex::build(&items);
+
+    // Highlights fill the first chunk; the subject and description follow in the second
+    let chunks: Vec<_> = index.document_chunks().collect();
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[0].iter().all(|d| d.field == SearchField::Highlight));
+    let fields: Vec<_> = chunks[1].iter().map(|d| d.field).collect();
+    assert_eq!(fields, [SearchField::Subject, SearchField::Description]);
+}
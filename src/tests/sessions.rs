@@ -0,0 +1,119 @@
+use crate::cli::Cli;
+use crate::koreader::CalendarGenerator;
+use crate::koreader::session::{build_sessions, session_metrics};
+use crate::models::{ContentType, PageStat, ReadingSession, StatBook, StatisticsData};
+use crate::time_config::TimeConfig;
+use clap::Parser;
+
+fn page_stat(id_book: i64, start_time: i64, duration: i64, device: Option<&str>) -> PageStat {
+    PageStat {
+        id_book,
+        page: start_time / 60,
+        start_time,
+        duration,
+        device: device.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_build_sessions_splits_on_gap_and_device() {
+    let start = 1673352000; // 2023-01-10 12:00:00 UTC
+    let page_stats = vec![
+        // Book 1 on the Kobo: two pages, a 5 minute pause, another page
+        page_stat(1, start, 60, Some("Kobo")),
+        page_stat(1, start + 60, 60, Some("Kobo")),
+        page_stat(1, start + 420, 30, Some("Kobo")),
+        // ... then on the phone right away
+        page_stat(1, start + 460, 40, Some("Phone")),
+        // Book 2 in between, and a zero-duration page that does not count
+        page_stat(2, start + 100, 50, None),
+        page_stat(2, start + 200, 0, None),
+    ];
+
+    let sessions = build_sessions(&page_stats, 300);
+    assert_eq!(
+        sessions,
+        vec![
+            ReadingSession {
+                id_book: 1,
+                start_time: start,
+                end_time: start + 450,
+                pages: 3,
+                duration: 150,
+                device: Some("Kobo".to_string()),
+            },
+            ReadingSession {
+                id_book: 2,
+                start_time: start + 100,
+                end_time: start + 150,
+                pages: 1,
+                duration: 50,
+                device: None,
+            },
+            ReadingSession {
+                id_book: 1,
+                start_time: start + 460,
+                end_time: start + 500,
+                pages: 1,
+                duration: 40,
+                device: Some("Phone".to_string()),
+            },
+        ]
+    );
+    assert_eq!(session_metrics(&sessions), (Some(80), Some(150)));
+    assert_eq!(session_metrics(&[]), (None, None));
+
+    // A shorter gap splits the Kobo session at the pause
+    let sessions = build_sessions(&page_stats, 120);
+    assert_eq!(sessions.len(), 4);
+    assert_eq!(sessions[2].start_time, start + 420);
+
+    let book = |id: i64, md5: &str| StatBook {
+        id,
+        title: md5.to_string(),
+        authors: String::new(),
+        notes: None,
+        last_open: None,
+        highlights: None,
+        pages: Some(100),
+        series: None,
+        language: None,
+        md5: md5.to_string(),
+        content_type: Some(if id == 1 {
+            ContentType::Book
+        } else {
+            ContentType::Comic
+        }),
+        total_read_time: None,
+        total_read_pages: None,
+        completions: None,
+    };
+    let books = vec![book(1, "book"), book(2, "comic")];
+    let data = StatisticsData {
+        stats_by_md5: books.iter().map(|b| (b.md5.clone(), b.clone())).collect(),
+        books,
+        sessions: build_sessions(&page_stats, 300),
+        page_stats,
+    };
+
+    // Filtered views keep the sessions of what they keep
+    let phone = data.filtered_by_device("Phone");
+    assert_eq!(phone.sessions.len(), 1);
+    assert_eq!(phone.sessions[0].start_time, start + 460);
+    let comics = data.filtered_by_content_type(ContentType::Comic);
+    assert_eq!(comics.sessions.len(), 1);
+    assert_eq!(comics.sessions[0].id_book, 2);
+
+    // The calendar event of book 1 counts both of its sessions
+    let months = CalendarGenerator::generate_calendar_months(&data, &[], &TimeConfig::new(None, 0));
+    let event = months["2023-01"]
+        .events
+        .iter()
+        .find(|e| e.item_id == "book")
+        .unwrap();
+    assert_eq!(event.session_count, 2);
+
+    let cli = Cli::try_parse_from(["koshelf", "--session-gap", "10m"]).unwrap();
+    assert_eq!(cli.session_gap, "10m");
+    assert_eq!(Cli::try_parse_from(["koshelf"]).unwrap().session_gap, "5m");
+}
@@ -1,7 +1,10 @@
 use crate::koreader::statistics::StatisticsCalculator;
+use crate::koreader::{StatisticsParser, StatisticsSource};
 use crate::models::{PageStat, StatBook, StatisticsData};
 use crate::time_config::TimeConfig;
+use rusqlite::{Connection, params};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[test]
 fn test_filter_stats_per_book_per_day() {
@@ -23,6 +26,7 @@ fn test_filter_stats_per_book_per_day() {
             page: i,
             start_time: day1_ts + i * 60,
             duration: 60,
+            device: None,
         });
     }
 
@@ -33,6 +37,7 @@ fn test_filter_stats_per_book_per_day() {
             page: i,
             start_time: day1_ts + i * 60,
             duration: 60,
+            device: None,
         });
     }
 
@@ -43,6 +48,7 @@ fn test_filter_stats_per_book_per_day() {
             page: i + 10,
             start_time: day2_ts + i * 1800,
             duration: 1800,
+            device: None,
         });
     }
 
@@ -53,6 +59,7 @@ fn test_filter_stats_per_book_per_day() {
             page: i + 5,
             start_time: day2_ts + i * 10,
             duration: 13,
+            device: None,
         });
     }
 
@@ -63,6 +70,7 @@ fn test_filter_stats_per_book_per_day() {
             page: i + 12,
             start_time: day3_ts + i * 30,
             duration: 33,
+            device: None,
         });
     }
 
@@ -73,6 +81,7 @@ fn test_filter_stats_per_book_per_day() {
             page: i + 20,
             start_time: day3_ts + i * 100,
             duration: 125,
+            device: None,
         });
     }
 
@@ -86,6 +95,8 @@ fn test_filter_stats_per_book_per_day() {
                 last_open: None,
                 highlights: None,
                 pages: None,
+                series: None,
+                language: None,
                 md5: "abc".to_string(),
                 content_type: None,
                 total_read_time: None,
@@ -100,6 +111,8 @@ fn test_filter_stats_per_book_per_day() {
                 last_open: None,
                 highlights: None,
                 pages: None,
+                series: None,
+                language: None,
                 md5: "def".to_string(),
                 content_type: None,
                 total_read_time: None,
@@ -108,6 +121,7 @@ fn test_filter_stats_per_book_per_day() {
             },
         ],
         page_stats,
+        sessions: Vec::new(),
         stats_by_md5: HashMap::new(),
     };
 
@@ -158,3 +172,119 @@ fn test_filter_stats_per_book_per_day() {
     );
     assert_eq!(book2_day3, 0, "Book 2 Day 3: filtered (fails both)");
 }
+
+/// Write a minimal statistics database with one book and the given (page, start_time) rows.
+pub(super) fn write_statistics_db(path: &Path, book_id: i64, md5: &str, rows: &[(i64, i64)]) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE book (id INTEGER PRIMARY KEY, title TEXT, authors TEXT, notes INTEGER,
+            last_open INTEGER, highlights INTEGER, pages INTEGER, series TEXT, language TEXT, md5 TEXT,
+            total_read_time INTEGER, total_read_pages INTEGER);
+         CREATE TABLE page_stat (id_book INTEGER, page INTEGER, start_time INTEGER,
+            duration INTEGER);",
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO book VALUES (?1, 'Book', 'Author', 0, 0, 0, 100, 'N/A', 'en', ?2, ?3, ?4)",
+        params![book_id, md5, rows.len() as i64 * 60, rows.len() as i64],
+    )
+    .unwrap();
+    for (page, start_time) in rows {
+        conn.execute(
+            "INSERT INTO page_stat VALUES (?1, ?2, ?3, 60)",
+            params![book_id, page, start_time],
+        )
+        .unwrap();
+    }
+}
+
+fn source(path: PathBuf, device: &str) -> StatisticsSource {
+    StatisticsSource {
+        path,
+        device: Some(device.to_string()),
+    }
+}
+
+#[test]
+fn test_merge_skips_duplicate_page_stats_and_tags_devices() {
+    let dir = tempfile::tempdir().unwrap();
+    let kobo = dir.path().join("kobo.sqlite3");
+    let copy = dir.path().join("kobo-copy.sqlite3");
+    let phone = dir.path().join("phone.sqlite3");
+    write_statistics_db(&kobo, 1, "abc", &[(1, 1000), (2, 1060)]);
+    // A synced copy of the same database
+    write_statistics_db(&copy, 1, "abc", &[(1, 1000), (2, 1060)]);
+    // Another device that read the same book (with a different local ID) further on
+    write_statistics_db(&phone, 7, "abc", &[(2, 1060), (3, 5000), (2, 6000)]);
+
+    let data = StatisticsParser::parse_sources(&[
+        source(kobo, "Kobo"),
+        source(copy, "Kobo copy"),
+        source(phone, "Phone"),
+    ])
+    .unwrap();
+
+    assert_eq!(data.books.len(), 1);
+    assert_eq!(data.page_stats.len(), 4);
+    assert!(data.page_stats.iter().all(|ps| ps.id_book == 1));
+
+    // Only the two new page stats from the phone count towards the totals
+    let book = &data.stats_by_md5["abc"];
+    assert_eq!(book.total_read_time, Some(4 * 60));
+    assert_eq!(book.total_read_pages, Some(3));
+
+    // Duplicates keep the device of the first database; the copy contributes nothing
+    assert_eq!(
+        data.devices(),
+        vec!["Kobo".to_string(), "Phone".to_string()]
+    );
+    let phone_only = data.filtered_by_device("Phone");
+    assert_eq!(phone_only.page_stats.len(), 2);
+    assert_eq!(phone_only.books.len(), 1);
+    assert!(data.filtered_by_device("Kobo copy").books.is_empty());
+}
+
+/// Pages and durations of `page` (with 600 seconds of reading) recorded at `total_pages`,
+/// rescaled to a book that now has `pages` pages.
+fn rescaled(page: i64, total_pages: Option<i64>, pages: Option<i64>) -> Vec<(i64, i64)> {
+    let stat = PageStat {
+        id_book: 1,
+        page,
+        start_time: 1000,
+        duration: 600,
+        device: Some("Kobo".to_string()),
+    };
+    let mut page_stats = Vec::new();
+    StatisticsParser::push_rescaled(&mut page_stats, stat, total_pages, pages);
+    assert!(page_stats.iter().all(|ps| ps.id_book == 1
+        && ps.start_time == 1000
+        && ps.device.as_deref() == Some("Kobo")));
+    page_stats.iter().map(|ps| (ps.page, ps.duration)).collect()
+}
+
+#[test]
+fn test_page_stat_rescaling() {
+    // Pagination grew: a recorded page covers several current pages
+    assert_eq!(
+        rescaled(2, Some(100), Some(300)),
+        vec![(4, 200), (5, 200), (6, 200)]
+    );
+    // Pagination shrank: several recorded pages map onto the same current page
+    assert_eq!(rescaled(2, Some(300), Some(100)), vec![(1, 600)]);
+    assert_eq!(rescaled(3, Some(300), Some(100)), vec![(1, 600)]);
+    assert_eq!(rescaled(4, Some(300), Some(100)), vec![(2, 600)]);
+    // Unchanged pagination
+    assert_eq!(rescaled(7, Some(100), Some(100)), vec![(7, 600)]);
+
+    // Without a usable page count, rows are kept as recorded
+    assert_eq!(rescaled(7, Some(0), Some(100)), vec![(7, 600)]);
+    assert_eq!(rescaled(7, None, Some(100)), vec![(7, 600)]);
+    assert_eq!(rescaled(7, Some(100), None), vec![(7, 600)]);
+    assert_eq!(rescaled(7, Some(100), Some(0)), vec![(7, 600)]);
+
+    // More than the 1,000 pages KOReader's view can expand a row into
+    let pages = rescaled(2, Some(2), Some(3000));
+    assert_eq!(pages.len(), 1500);
+    assert_eq!(pages.first(), Some(&(1501, 0)));
+    assert_eq!(pages.last(), Some(&(3000, 0)));
+}
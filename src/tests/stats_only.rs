@@ -0,0 +1,116 @@
+use crate::config::ItemIdScheme;
+use crate::models::{
+    BookCompletions, BookStatus, BrowseGroup, BrowseKind, ContentType, LibraryItem, PageStat,
+    ReadCompletion, StatBook, StatisticsData,
+};
+use crate::site_generator::stats_only_items;
+use crate::tests::test_stat_book;
+use std::collections::HashSet;
+
+fn stat_book(id: i64, title: &str, md5: &str) -> StatBook {
+    StatBook {
+        title: title.to_string(),
+        authors: "Ann Author\nBob Writer".to_string(),
+        notes: Some(1),
+        highlights: Some(3),
+        pages: Some(240),
+        series: Some("The Saga #2".to_string()),
+        language: Some("de".to_string()),
+        total_read_time: Some(600),
+        total_read_pages: Some(10),
+        ..test_stat_book(id, md5)
+    }
+}
+
+fn page_stat(id_book: i64) -> PageStat {
+    PageStat {
+        id_book,
+        page: 1,
+        start_time: 1_700_000_000,
+        duration: 60,
+        device: None,
+    }
+}
+
+#[test]
+fn test_stats_only_item_from_stat_book() {
+    let mut book = stat_book(1, "Lost Book", "abcdef0123456789");
+    let item = LibraryItem::from_stat_book("lost-book".to_string(), &book);
+
+    assert!(item.is_stats_only());
+    assert_eq!(item.file_path(), None);
+    assert_eq!(item.format(), None);
+    assert!(item.is_book());
+    assert_eq!(item.book_info.authors, ["Ann Author", "Bob Writer"]);
+    assert_eq!(item.series().map(String::as_str), Some("The Saga"));
+    assert_eq!(item.series_number().map(String::as_str), Some("2"));
+    assert_eq!(item.language().map(String::as_str), Some("de"));
+    assert_eq!(item.doc_pages(), Some(240));
+    assert_eq!(item.note_count(), 1);
+    assert_eq!(
+        item.koreader_metadata
+            .as_ref()
+            .and_then(|m| m.partial_md5_checksum.as_deref()),
+        Some("abcdef0123456789")
+    );
+    // No completion detected, so no status to show
+    assert_eq!(item.status(), BookStatus::Unknown);
+
+    book.content_type = Some(ContentType::Comic);
+    book.series = Some("Standalone".to_string());
+    book.completions = Some(BookCompletions::new(vec![ReadCompletion::new(
+        "2024-01-01".into(),
+        "2024-01-02".into(),
+        600,
+        1,
+        240,
+    )]));
+    let item = LibraryItem::from_stat_book("lost-book".to_string(), &book);
+    assert!(item.is_comic());
+    assert_eq!(item.series().map(String::as_str), Some("Standalone"));
+    assert_eq!(item.series_number(), None);
+    assert_eq!(item.status(), BookStatus::Complete);
+}
+
+#[test]
+fn test_stats_only_items_skip_library_and_unread_books() {
+    let library_book = stat_book(1, "Shelved", "1111111111111111");
+    let library_item = LibraryItem::from_stat_book("lost-book".to_string(), &library_book);
+    let books = vec![
+        library_book,
+        stat_book(2, "Lost Book", "2222222222222222"),
+        stat_book(3, "Never Read", "3333333333333333"),
+    ];
+    let data = StatisticsData {
+        stats_by_md5: books.iter().map(|b| (b.md5.clone(), b.clone())).collect(),
+        books,
+        page_stats: vec![page_stat(1), page_stat(2)],
+        sessions: Vec::new(),
+    };
+    let library_md5s: HashSet<String> = ["1111111111111111".to_string()].into();
+
+    // IDs must not clash with those of the library items
+    let items = stats_only_items(
+        &data,
+        std::slice::from_ref(&library_item),
+        &library_md5s,
+        ItemIdScheme::Title,
+    );
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].book_info.title, "Lost Book");
+    assert_eq!(
+        items[0].id,
+        format!("lost-book-{:x}", md5::compute("2222222222222222"))[..18]
+    );
+
+    let items = stats_only_items(&data, &[], &library_md5s, ItemIdScheme::Hash);
+    assert_eq!(items[0].id, "lost-book-22222222");
+
+    // Linked from the series and language pages, with their reading time
+    let series = BrowseGroup::collect(BrowseKind::Series, &items, Some(&data));
+    assert_eq!(series[0].name, "The Saga");
+    assert_eq!(series[0].read_time, 600);
+    let languages = BrowseGroup::collect(BrowseKind::Language, &items, Some(&data));
+    assert_eq!(languages[0].slug, "de");
+    assert_eq!(languages[0].entries.len(), 1);
+}
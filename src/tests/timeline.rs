@@ -0,0 +1,90 @@
+use crate::koreader::TimelineCalculator;
+use crate::koreader::session::build_sessions;
+use crate::models::{BookStatus, LibraryItem, PageStat, StatBook, StatisticsData};
+use crate::tests::{test_item, test_stat_book};
+use crate::time_config::TimeConfig;
+use chrono::NaiveDate;
+
+fn item(status: BookStatus, percent_finished: f64) -> LibraryItem {
+    test_item("novel", "Novel", "/library/novel.epub")
+        .doc_pages(100)
+        .md5("novel")
+        .percent_finished(percent_finished)
+        .status(status)
+        .build()
+}
+
+fn read(pages: std::ops::RangeInclusive<i64>, start: i64, seconds_per_page: i64) -> Vec<PageStat> {
+    pages
+        .enumerate()
+        .map(|(i, page)| PageStat {
+            id_book: 1,
+            page,
+            start_time: start + i as i64 * seconds_per_page,
+            duration: seconds_per_page,
+            device: None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_book_timeline_and_finish_estimate() {
+    // Pages 1-20 on March 1st at 10:00, pages 15-40 on March 3rd at 20:00 at a slower pace
+    let mut page_stats = read(1..=20, 1677664800, 60);
+    page_stats.extend(read(15..=40, 1677873600, 90));
+    let stat_book = StatBook {
+        title: "Novel".to_string(),
+        pages: Some(100),
+        ..test_stat_book(1, "novel")
+    };
+    let data = StatisticsData {
+        books: vec![stat_book.clone()],
+        stats_by_md5: Default::default(),
+        sessions: build_sessions(&page_stats, 300),
+        page_stats,
+    };
+    let time_config = TimeConfig::new(Some(chrono_tz::UTC), 0);
+    let today = NaiveDate::from_ymd_opt(2023, 3, 10).unwrap();
+    let reading = item(BookStatus::Reading, 0.4);
+
+    let timeline = TimelineCalculator::for_item(&reading, &stat_book, &data, &time_config, today);
+    assert_eq!(timeline.days.len(), 2);
+    assert_eq!(timeline.days[0].date, "2023-03-01");
+    assert_eq!(timeline.days[0].pages_covered, 20);
+    assert_eq!(timeline.days[0].pages_per_hour, Some(60.0));
+    assert_eq!(timeline.days[1].pages_covered, 40);
+    assert_eq!(timeline.days[1].progress, Some(0.4));
+    assert_eq!(timeline.days[1].pages_per_hour, Some(40.0));
+    assert_eq!(timeline.progress_points(), "0,40 0.0,32.0 100.0,24.0");
+    assert_eq!(timeline.speed_bar_percent(&timeline.days[1]), 67);
+
+    // Newest session first, with local times
+    assert_eq!(timeline.sessions.len(), 2);
+    assert_eq!(timeline.sessions[0].date, "2023-03-03");
+    assert_eq!(timeline.sessions[0].start, "20:00");
+    assert_eq!(timeline.sessions[0].end, "20:39");
+    assert_eq!(timeline.sessions[0].pages, 26);
+
+    // 60 pages left at 46 pages in 59 minutes, and 46 pages in the last 30 days
+    let estimate = timeline.estimate.unwrap();
+    assert_eq!(estimate.remaining_pages, 60);
+    assert_eq!(estimate.remaining_read_time, 4617);
+    assert_eq!(estimate.pages_per_day, 1.5);
+    assert_eq!(estimate.days_to_finish, Some(40));
+    assert_eq!(estimate.finish_date.as_deref(), Some("2023-04-19"));
+
+    // Not read lately: still a reading time estimate, but no finish date
+    let later = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    let estimate = TimelineCalculator::for_item(&reading, &stat_book, &data, &time_config, later)
+        .estimate
+        .unwrap();
+    assert_eq!(estimate.remaining_read_time, 4617);
+    assert_eq!(estimate.days_to_finish, None);
+    assert_eq!(estimate.finish_date, None);
+
+    // Finished books get no estimate
+    let finished = item(BookStatus::Complete, 1.0);
+    let timeline = TimelineCalculator::for_item(&finished, &stat_book, &data, &time_config, today);
+    assert!(timeline.estimate.is_none());
+    assert_eq!(timeline.days.len(), 2);
+}
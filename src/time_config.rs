@@ -1,5 +1,5 @@
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 
 #[derive(Clone, Debug)]
@@ -72,6 +72,16 @@ impl TimeConfig {
         }
     }
 
+    /// Local wall-clock time of a Unix timestamp in the configured timezone (no day-start adjustment).
+    pub fn local_datetime(&self, timestamp: i64) -> NaiveDateTime {
+        let utc_dt = DateTime::<Utc>::from_timestamp(timestamp, 0)
+            .unwrap_or_else(|| DateTime::<Utc>::from_timestamp(0, 0).unwrap());
+        match self.timezone {
+            Some(tz) => utc_dt.with_timezone(&tz).naive_local(),
+            None => utc_dt.with_timezone(&Local).naive_local(),
+        }
+    }
+
     /// Format a timestamp as YYYY-MM-DD under configured timezone/day-start.
     pub fn format_date(&self, timestamp: i64) -> String {
         self.date_for_timestamp(timestamp)
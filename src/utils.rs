@@ -3,6 +3,10 @@ use quick_xml::escape::unescape;
 use std::borrow::Cow;
 use std::collections::HashSet;
 
+use crate::config::ItemIdScheme;
+use crate::models::LibraryItem;
+use log::warn;
+
 /// Generates a URL-friendly ID from a book title
 pub fn generate_book_id(title: &str) -> String {
     title
@@ -15,6 +19,103 @@ pub fn generate_book_id(title: &str) -> String {
         .to_lowercase()
 }
 
+/// File-name safe slug of a name such as an author, series or device.
+///
+/// Names without letters or digits (e.g. only punctuation) get a short hash instead.
+pub fn generate_slug(name: &str) -> String {
+    let slug = generate_book_id(name);
+    if slug.is_empty() {
+        format!("{:x}", md5::compute(name.as_bytes()))[..8].to_string()
+    } else {
+        slug
+    }
+}
+
+/// Number of partial MD5 hex digits appended to the title slug by `ItemIdScheme::Hash`.
+const ID_HASH_LENGTH: usize = 8;
+
+/// Generates the ID of a library item according to `scheme`.
+///
+/// Falls back to the plain title slug when the item has no MD5, and to `"item"`
+/// when the title has no usable characters.
+pub fn generate_item_id(title: &str, md5: Option<&str>, scheme: ItemIdScheme) -> String {
+    let slug = generate_book_id(title);
+    let slug = if slug.is_empty() {
+        "item".to_string()
+    } else {
+        slug
+    };
+
+    match (scheme, md5) {
+        (ItemIdScheme::Hash, Some(md5)) => {
+            let hash: String = md5.chars().take(ID_HASH_LENGTH).collect();
+            format!("{}-{}", slug, hash.to_lowercase())
+        }
+        _ => slug,
+    }
+}
+
+/// Derive the ID of an item and disambiguate it against the IDs already taken by appending
+/// a short hash of the item's file path (or of its MD5 for items without a file), so the
+/// ID of an item does not depend on how many other items share its title. Books and comics
+/// share one namespace since covers are stored by ID.
+pub fn unique_item_id(
+    item: &LibraryItem,
+    md5: Option<&str>,
+    scheme: ItemIdScheme,
+    taken: &mut HashSet<String>,
+) -> String {
+    let base = generate_item_id(&item.book_info.title, md5, scheme);
+    if taken.insert(base.clone()) {
+        return base;
+    }
+
+    let key = match item.file_path() {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => md5.unwrap_or(&item.book_info.title).to_string(),
+    };
+    let hash = format!("{:x}", md5::compute(key.as_bytes()));
+    let disambiguated = format!("{}-{}", base, &hash[..ID_HASH_LENGTH]);
+    let mut id = disambiguated.clone();
+    let mut suffix = 2;
+    while !taken.insert(id.clone()) {
+        id = format!("{}-{}", disambiguated, suffix);
+        suffix += 1;
+    }
+    warn!(
+        "Item ID {:?} is already taken, using {:?} for {:?}",
+        base,
+        id,
+        item.file_path()
+    );
+    id
+}
+
+/// Plain text of an HTML description (for OPDS summaries and the search index).
+pub fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 /// Sanitize HTML content, keeping only safe formatting tags.
 /// Used for book descriptions/annotations from EPUB and FB2 files.
 pub fn sanitize_html(input: &str) -> String {